@@ -1,9 +1,15 @@
-use crate::database::{
-    DbPool,
-    secrets::{delete_excess_secret_versions, delete_scheduled_secrets},
+use crate::{
+    database::{
+        DbPool,
+        kms::delete_scheduled_kms_keys,
+        secrets::{delete_excess_secret_versions, delete_scheduled_secrets},
+        session_credentials::delete_expired_session_credentials,
+    },
+    rotation::{function::RotationFunctions, rotate_scheduled_secrets},
 };
 use chrono::Utc;
 use futures::StreamExt;
+use std::sync::Arc;
 use tokio_simple_fixed_scheduler::{SchedulerEventStream, SchedulerQueueEvent};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -14,9 +20,18 @@ pub enum BackgroundEvent {
     /// Task to prune the secrets with versions in excess of 100 versions that are
     /// over 24h old
     PurgeExcessSecrets,
+
+    /// Task to rotate secrets that have a scheduled rotation due
+    PerformScheduledRotations,
+
+    /// Task to purge temporary session credentials that have expired
+    PurgeExpiredSessionCredentials,
+
+    /// Task to purge KMS keys that have passed their scheduled deletion date
+    PurgeDeletedKeys,
 }
 
-pub async fn perform_background_tasks(db: DbPool) {
+pub async fn perform_background_tasks(db: DbPool, rotation_functions: Arc<RotationFunctions>) {
     let events = vec![
         SchedulerQueueEvent {
             event: BackgroundEvent::PurgeDeletedSecrets,
@@ -26,6 +41,18 @@ pub async fn perform_background_tasks(db: DbPool) {
             event: BackgroundEvent::PurgeExcessSecrets,
             interval: 60 * 60,
         },
+        SchedulerQueueEvent {
+            event: BackgroundEvent::PerformScheduledRotations,
+            interval: 60,
+        },
+        SchedulerQueueEvent {
+            event: BackgroundEvent::PurgeExpiredSessionCredentials,
+            interval: 60 * 60,
+        },
+        SchedulerQueueEvent {
+            event: BackgroundEvent::PurgeDeletedKeys,
+            interval: 60 * 60,
+        },
     ];
 
     let mut events = SchedulerEventStream::new(events);
@@ -49,6 +76,30 @@ pub async fn perform_background_tasks(db: DbPool) {
                     )
                 }
             }
+
+            BackgroundEvent::PerformScheduledRotations => {
+                tracing::debug!("performing background scheduled secret rotations");
+                let now = Utc::now();
+                if let Err(error) = rotate_scheduled_secrets(&db, &rotation_functions, now).await {
+                    tracing::error!(?error, "failed to perform scheduled secret rotations")
+                }
+            }
+
+            BackgroundEvent::PurgeExpiredSessionCredentials => {
+                tracing::debug!("performing background purge for expired session credentials");
+                let now = Utc::now();
+                if let Err(error) = delete_expired_session_credentials(&db, now).await {
+                    tracing::error!(?error, "failed to purge expired session credentials")
+                }
+            }
+
+            BackgroundEvent::PurgeDeletedKeys => {
+                tracing::debug!("performing background purge for scheduled kms key deletions");
+                let now = Utc::now();
+                if let Err(error) = delete_scheduled_kms_keys(&db, now).await {
+                    tracing::error!(?error, "failed to purge scheduled kms key deletions")
+                }
+            }
         }
     }
 }
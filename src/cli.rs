@@ -0,0 +1,221 @@
+use crate::{
+    config::Config,
+    database::{
+        self, DbPool,
+        access_keys::{
+            CreateAccessKey, create_access_key, delete_access_key, get_access_key, get_access_keys,
+            update_access_key_enabled, update_access_key_policy,
+        },
+    },
+    handlers::models::Arn,
+    middleware::aws_sig_v4::default_principal_arn,
+    policy::validate_identity_policy,
+    utils::access_key::{generate_access_key_id, generate_access_key_secret},
+};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use std::{error::Error, path::PathBuf, str::FromStr};
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage the access keys stored in the database
+    #[command(subcommand)]
+    AccessKeys(AccessKeysCommand),
+}
+
+#[derive(Subcommand)]
+pub enum AccessKeysCommand {
+    /// Create a new access key, the generated secret is only shown once
+    Create {
+        /// Name identifying the access key
+        name: String,
+        /// ARN of the principal requests signed with the access key are made as
+        #[arg(long)]
+        principal_arn: Option<String>,
+        /// RFC 3339 date after which the access key can no longer be used
+        #[arg(long)]
+        expires_at: Option<DateTime<Utc>>,
+        /// Path to a JSON identity policy restricting what the access key can do
+        #[arg(long)]
+        policy: Option<PathBuf>,
+    },
+    /// List the access keys
+    List,
+    /// Enable a disabled access key
+    Enable { access_key_id: String },
+    /// Disable an access key, requests signed with it are rejected
+    Disable { access_key_id: String },
+    /// Delete an access key
+    Delete { access_key_id: String },
+    /// Show when an access key was last used
+    LastUsed { access_key_id: String },
+    /// Attach a JSON identity policy to an access key, replacing any existing policy
+    PutPolicy {
+        access_key_id: String,
+        /// Path to the JSON identity policy
+        policy: PathBuf,
+    },
+    /// Remove the identity policy from an access key
+    DeletePolicy { access_key_id: String },
+}
+
+/// Run a CLI `command` against the database from the config
+pub async fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    let config = Config::from_env()?;
+    let db = database::create_database(config.encryption_key, config.database_path).await?;
+
+    match command {
+        Command::AccessKeys(command) => run_access_keys(&db, &config.account_id, command).await,
+    }
+}
+
+/// Run an access key `command`, access keys without a principal are created
+/// within the `account_id`
+async fn run_access_keys(
+    db: &DbPool,
+    account_id: &str,
+    command: AccessKeysCommand,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        AccessKeysCommand::Create {
+            name,
+            principal_arn,
+            expires_at,
+            policy,
+        } => {
+            if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+                return Err("access key expiry date must be in the future".into());
+            }
+
+            let principal_arn = match principal_arn {
+                Some(principal_arn) => {
+                    if !Arn::from_str(&principal_arn).is_ok_and(|arn| arn.has_account_id()) {
+                        return Err("principal arn must contain a 12-digit account id".into());
+                    }
+
+                    principal_arn
+                }
+                None => default_principal_arn(account_id, &name),
+            };
+
+            let policy = match policy {
+                Some(path) => Some(read_identity_policy(path).await?),
+                None => None,
+            };
+
+            let access_key = create_access_key(
+                db,
+                CreateAccessKey {
+                    access_key_id: generate_access_key_id(),
+                    access_key_secret: generate_access_key_secret(),
+                    name,
+                    principal_arn: Some(principal_arn),
+                    expires_at,
+                    policy,
+                },
+            )
+            .await?;
+
+            println!("Access key ID:     {}", access_key.access_key_id);
+            println!("Access key secret: {}", access_key.access_key_secret);
+        }
+        AccessKeysCommand::List => {
+            println!("ACCESS KEY ID\tNAME\tSTATUS\tPRINCIPAL ARN\tCREATED\tEXPIRES\tLAST USED");
+
+            for access_key in get_access_keys(db).await? {
+                let principal_arn = access_key
+                    .principal_arn
+                    .unwrap_or_else(|| default_principal_arn(account_id, &access_key.name));
+
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    access_key.access_key_id,
+                    access_key.name,
+                    if access_key.enabled {
+                        "Active"
+                    } else {
+                        "Inactive"
+                    },
+                    principal_arn,
+                    access_key.created_at.to_rfc3339(),
+                    format_optional_date(access_key.expires_at),
+                    format_optional_date(access_key.last_used_at),
+                );
+            }
+        }
+        AccessKeysCommand::Enable { access_key_id } => {
+            require_access_key(db, &access_key_id).await?;
+            update_access_key_enabled(db, &access_key_id, true).await?;
+            println!("Enabled access key {access_key_id}");
+        }
+        AccessKeysCommand::Disable { access_key_id } => {
+            require_access_key(db, &access_key_id).await?;
+            update_access_key_enabled(db, &access_key_id, false).await?;
+            println!("Disabled access key {access_key_id}");
+        }
+        AccessKeysCommand::Delete { access_key_id } => {
+            require_access_key(db, &access_key_id).await?;
+            delete_access_key(db, &access_key_id).await?;
+            println!("Deleted access key {access_key_id}");
+        }
+        AccessKeysCommand::LastUsed { access_key_id } => {
+            let access_key = require_access_key(db, &access_key_id).await?;
+            match access_key.last_used_at {
+                Some(last_used_at) => println!("{}", last_used_at.to_rfc3339()),
+                None => println!("Never used"),
+            }
+        }
+        AccessKeysCommand::PutPolicy {
+            access_key_id,
+            policy,
+        } => {
+            require_access_key(db, &access_key_id).await?;
+            let policy = read_identity_policy(policy).await?;
+            update_access_key_policy(db, &access_key_id, Some(&policy)).await?;
+            println!("Attached policy to access key {access_key_id}");
+        }
+        AccessKeysCommand::DeletePolicy { access_key_id } => {
+            require_access_key(db, &access_key_id).await?;
+            update_access_key_policy(db, &access_key_id, None).await?;
+            println!("Removed policy from access key {access_key_id}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the access key with the provided `access_key_id` failing when the
+/// access key does not exist
+async fn require_access_key(
+    db: &DbPool,
+    access_key_id: &str,
+) -> Result<database::access_keys::StoredAccessKey, Box<dyn Error>> {
+    get_access_key(db, access_key_id)
+        .await?
+        .ok_or_else(|| format!("access key {access_key_id} does not exist").into())
+}
+
+/// Read and validate the identity policy stored at `path`
+async fn read_identity_policy(path: PathBuf) -> Result<String, Box<dyn Error>> {
+    let policy = tokio::fs::read_to_string(&path).await?;
+
+    if let Err(errors) = validate_identity_policy(&policy) {
+        let messages: Vec<String> = errors.into_iter().map(|error| error.message).collect();
+        return Err(format!("invalid policy {}: {}", path.display(), messages.join(", ")).into());
+    }
+
+    Ok(policy)
+}
+
+fn format_optional_date(value: Option<DateTime<Utc>>) -> String {
+    value
+        .map(|value| value.to_rfc3339())
+        .unwrap_or_else(|| "-".to_string())
+}
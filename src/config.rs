@@ -1,4 +1,13 @@
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use crate::{
+    handlers::models::Arn,
+    rotation::function::DEFAULT_ROTATION_FUNCTION_TIMEOUT,
+    utils::arn::{DEFAULT_ACCOUNT_ID, is_valid_account_id},
+};
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    str::FromStr,
+    time::Duration,
+};
 use thiserror::Error;
 
 /// Default server address when not specified (HTTP)
@@ -9,6 +18,19 @@ const DEFAULT_SERVER_ADDRESS_HTTP: SocketAddr =
 const DEFAULT_SERVER_ADDRESS_HTTPS: SocketAddr =
     SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 8443));
 
+/// Default region the compatibility endpoints read and store secrets in
+const DEFAULT_ENDPOINT_REGION: &str = "us-east-1";
+
+/// Default duration the Lambda extension caches secrets for, matches the
+/// default of the real extension
+const DEFAULT_LAMBDA_EXTENSION_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Default number of secrets the Lambda extension caches
+const DEFAULT_LAMBDA_EXTENSION_CACHE_SIZE: usize = 1000;
+
+/// Default path the Vault KV v2 secrets engine is mounted at
+const DEFAULT_VAULT_MOUNT: &str = "secret";
+
 pub struct Config {
     /// Encryption key to encrypt and decrypt the database
     pub encryption_key: String,
@@ -25,10 +47,61 @@ pub struct Config {
     /// Path to the HTTPS private key file
     pub private_key_path: String,
 
-    /// Access key ID for AWS SigV4
-    pub access_key_id: String,
-    /// Access key secret for AWS SigV4
-    pub access_key_secret: String,
+    /// Account ID for access keys that don't belong to a specific account
+    pub account_id: String,
+
+    /// Access key ID and secret for AWS SigV4
+    pub access_key: Option<(String, String)>,
+    /// ARN of the principal requests signed with the access key are made as
+    pub access_key_principal_arn: String,
+    /// Path to the JSON file containing additional access keys
+    pub access_keys_path: Option<String>,
+
+    /// Whether to enforce the resource policies of secrets
+    pub enforce_resource_policies: bool,
+
+    /// Whether to enforce the Secrets Manager service quotas and rate limits
+    pub strict_quotas: bool,
+
+    /// Path to the JSON file mapping rotation function ARNs to local
+    /// rotation functions
+    pub rotation_functions_path: Option<String>,
+    /// Duration each invocation of a rotation function can take
+    pub rotation_function_timeout: Duration,
+
+    /// Path to the JSON file containing the fault injection rules to start
+    /// with
+    pub fault_injection_rules_path: Option<String>,
+
+    /// Configuration for the optional Lambda extension compatible endpoint
+    pub lambda_extension: Option<LambdaExtensionConfig>,
+
+    /// Configuration for the optional Vault KV v2 compatible endpoint
+    pub vault: Option<VaultConfig>,
+}
+
+pub struct LambdaExtensionConfig {
+    /// Address the Lambda extension endpoint binds against
+    pub server_address: SocketAddr,
+    /// Token requests must provide in the `X-Aws-Parameters-Secrets-Token` header
+    pub token: String,
+    /// Region secrets are read from
+    pub region: String,
+    /// Duration secret values are cached for
+    pub cache_ttl: Duration,
+    /// Maximum number of secret values to cache
+    pub cache_size: usize,
+}
+
+pub struct VaultConfig {
+    /// Address the Vault endpoint binds against
+    pub server_address: SocketAddr,
+    /// Token requests must provide in the `X-Vault-Token` header
+    pub token: String,
+    /// Region secrets are stored in
+    pub region: String,
+    /// Path the KV v2 secrets engine is mounted at
+    pub mount: String,
 }
 
 #[derive(Debug, Error)]
@@ -36,14 +109,49 @@ pub enum ConfigError {
     #[error("Must specify SM_ENCRYPTION_KEY environment variable")]
     MissingEncryptionKey,
 
-    #[error("Must specify SM_ACCESS_KEY_ID environment variable")]
+    #[error("Must specify SM_ACCESS_KEY_ID environment variable when SM_ACCESS_KEY_SECRET is set")]
     MissingAccessKeyId,
 
-    #[error("Must specify SM_ACCESS_KEY_SECRET environment variable")]
+    #[error("Must specify SM_ACCESS_KEY_SECRET environment variable when SM_ACCESS_KEY_ID is set")]
     MissingAccessKeySecret,
 
     #[error("SM_USE_HTTPS must be either true or false")]
     InvalidUseHttps,
+
+    #[error("SM_ENFORCE_RESOURCE_POLICIES must be either true or false")]
+    InvalidEnforceResourcePolicies,
+
+    #[error("SM_STRICT_QUOTAS must be either true or false")]
+    InvalidStrictQuotas,
+
+    #[error("SM_ROTATION_FUNCTION_TIMEOUT must be a number of seconds")]
+    InvalidRotationFunctionTimeout,
+
+    #[error("SM_ACCOUNT_ID must be a 12-digit account ID")]
+    InvalidAccountId,
+
+    #[error("SM_ACCESS_KEY_PRINCIPAL_ARN must be an ARN containing a 12-digit account ID")]
+    InvalidAccessKeyPrincipalArn,
+
+    #[error("SM_LAMBDA_EXTENSION_ADDRESS must be a socket address")]
+    InvalidLambdaExtensionAddress,
+
+    #[error(
+        "Must specify SM_LAMBDA_EXTENSION_TOKEN environment variable when SM_LAMBDA_EXTENSION_ADDRESS is set"
+    )]
+    MissingLambdaExtensionToken,
+
+    #[error("SM_LAMBDA_EXTENSION_CACHE_TTL must be a number of seconds")]
+    InvalidLambdaExtensionCacheTtl,
+
+    #[error("SM_LAMBDA_EXTENSION_CACHE_SIZE must be a number")]
+    InvalidLambdaExtensionCacheSize,
+
+    #[error("SM_VAULT_ADDRESS must be a socket address")]
+    InvalidVaultAddress,
+
+    #[error("Must specify SM_VAULT_TOKEN environment variable when SM_VAULT_ADDRESS is set")]
+    MissingVaultToken,
 }
 
 impl Config {
@@ -52,11 +160,36 @@ impl Config {
         let encryption_key =
             std::env::var("SM_ENCRYPTION_KEY").map_err(|_| ConfigError::MissingEncryptionKey)?;
 
-        let access_key_id =
-            std::env::var("SM_ACCESS_KEY_ID").map_err(|_| ConfigError::MissingAccessKeyId)?;
+        let access_key = match (
+            std::env::var("SM_ACCESS_KEY_ID"),
+            std::env::var("SM_ACCESS_KEY_SECRET"),
+        ) {
+            (Ok(access_key_id), Ok(access_key_secret)) => Some((access_key_id, access_key_secret)),
+            (Ok(_), Err(_)) => return Err(ConfigError::MissingAccessKeySecret),
+            (Err(_), Ok(_)) => return Err(ConfigError::MissingAccessKeyId),
+            (Err(_), Err(_)) => None,
+        };
 
-        let access_key_secret = std::env::var("SM_ACCESS_KEY_SECRET")
-            .map_err(|_| ConfigError::MissingAccessKeySecret)?;
+        let access_keys_path = std::env::var("SM_ACCESS_KEYS_PATH").ok();
+
+        let account_id =
+            std::env::var("SM_ACCOUNT_ID").unwrap_or_else(|_| DEFAULT_ACCOUNT_ID.to_string());
+
+        if !is_valid_account_id(&account_id) {
+            return Err(ConfigError::InvalidAccountId);
+        }
+
+        // Access key defaults to the root of the account
+        let access_key_principal_arn = match std::env::var("SM_ACCESS_KEY_PRINCIPAL_ARN") {
+            Ok(value) => {
+                if !Arn::from_str(&value).is_ok_and(|arn| arn.has_account_id()) {
+                    return Err(ConfigError::InvalidAccessKeyPrincipalArn);
+                }
+
+                value
+            }
+            Err(_) => Arn::iam_root(&account_id).to_string(),
+        };
 
         let database_path =
             std::env::var("SM_DATABASE_PATH").unwrap_or_else(|_| "secrets.db".to_string());
@@ -87,6 +220,35 @@ impl Config {
             Err(_) => "sm.key.pem".to_string(),
         };
 
+        let rotation_functions_path = std::env::var("SM_ROTATION_FUNCTIONS_PATH").ok();
+
+        let rotation_function_timeout = match std::env::var("SM_ROTATION_FUNCTION_TIMEOUT") {
+            Ok(value) => value
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| ConfigError::InvalidRotationFunctionTimeout)?,
+            Err(_) => DEFAULT_ROTATION_FUNCTION_TIMEOUT,
+        };
+
+        let fault_injection_rules_path = std::env::var("SM_FAULT_INJECTION_RULES_PATH").ok();
+
+        let enforce_resource_policies = match std::env::var("SM_ENFORCE_RESOURCE_POLICIES") {
+            Ok(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidEnforceResourcePolicies)?,
+            Err(_) => false,
+        };
+
+        let strict_quotas = match std::env::var("SM_STRICT_QUOTAS") {
+            Ok(value) => value
+                .parse::<bool>()
+                .map_err(|_| ConfigError::InvalidStrictQuotas)?,
+            Err(_) => false,
+        };
+
+        let lambda_extension = LambdaExtensionConfig::from_env()?;
+        let vault = VaultConfig::from_env()?;
+
         Ok(Config {
             encryption_key,
             database_path,
@@ -94,8 +256,87 @@ impl Config {
             server_address,
             certificate_path,
             private_key_path,
-            access_key_id,
-            access_key_secret,
+            account_id,
+            access_key,
+            access_key_principal_arn,
+            access_keys_path,
+            enforce_resource_policies,
+            strict_quotas,
+            rotation_functions_path,
+            rotation_function_timeout,
+            fault_injection_rules_path,
+            lambda_extension,
+            vault,
         })
     }
 }
+
+impl LambdaExtensionConfig {
+    /// Load the Lambda extension config from the environment variables, the
+    /// endpoint is only enabled when SM_LAMBDA_EXTENSION_ADDRESS is set
+    fn from_env() -> Result<Option<LambdaExtensionConfig>, ConfigError> {
+        let server_address = match std::env::var("SM_LAMBDA_EXTENSION_ADDRESS") {
+            Ok(value) => value
+                .parse::<SocketAddr>()
+                .map_err(|_| ConfigError::InvalidLambdaExtensionAddress)?,
+            Err(_) => return Ok(None),
+        };
+
+        let token = std::env::var("SM_LAMBDA_EXTENSION_TOKEN")
+            .map_err(|_| ConfigError::MissingLambdaExtensionToken)?;
+
+        let region = std::env::var("SM_LAMBDA_EXTENSION_REGION")
+            .unwrap_or_else(|_| DEFAULT_ENDPOINT_REGION.to_string());
+
+        let cache_ttl = match std::env::var("SM_LAMBDA_EXTENSION_CACHE_TTL") {
+            Ok(value) => value
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| ConfigError::InvalidLambdaExtensionCacheTtl)?,
+            Err(_) => DEFAULT_LAMBDA_EXTENSION_CACHE_TTL,
+        };
+
+        let cache_size = match std::env::var("SM_LAMBDA_EXTENSION_CACHE_SIZE") {
+            Ok(value) => value
+                .parse::<usize>()
+                .map_err(|_| ConfigError::InvalidLambdaExtensionCacheSize)?,
+            Err(_) => DEFAULT_LAMBDA_EXTENSION_CACHE_SIZE,
+        };
+
+        Ok(Some(LambdaExtensionConfig {
+            server_address,
+            token,
+            region,
+            cache_ttl,
+            cache_size,
+        }))
+    }
+}
+
+impl VaultConfig {
+    /// Load the Vault config from the environment variables, the endpoint is
+    /// only enabled when SM_VAULT_ADDRESS is set
+    fn from_env() -> Result<Option<VaultConfig>, ConfigError> {
+        let server_address = match std::env::var("SM_VAULT_ADDRESS") {
+            Ok(value) => value
+                .parse::<SocketAddr>()
+                .map_err(|_| ConfigError::InvalidVaultAddress)?,
+            Err(_) => return Ok(None),
+        };
+
+        let token = std::env::var("SM_VAULT_TOKEN").map_err(|_| ConfigError::MissingVaultToken)?;
+
+        let region = std::env::var("SM_VAULT_REGION")
+            .unwrap_or_else(|_| DEFAULT_ENDPOINT_REGION.to_string());
+
+        let mount =
+            std::env::var("SM_VAULT_MOUNT").unwrap_or_else(|_| DEFAULT_VAULT_MOUNT.to_string());
+
+        Ok(Some(VaultConfig {
+            server_address,
+            token,
+            region,
+            mount,
+        }))
+    }
+}
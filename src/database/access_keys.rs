@@ -0,0 +1,129 @@
+use crate::database::{DbExecutor, DbResult};
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+#[derive(Clone, FromRow)]
+pub struct StoredAccessKey {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    pub name: String,
+    pub principal_arn: Option<String>,
+    pub enabled: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub policy: Option<String>,
+}
+
+pub struct CreateAccessKey {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    pub name: String,
+    pub principal_arn: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub policy: Option<String>,
+}
+
+/// Store a new access key
+pub async fn create_access_key(
+    db: impl DbExecutor<'_>,
+    create: CreateAccessKey,
+) -> DbResult<StoredAccessKey> {
+    sqlx::query_as(
+        r#"
+        INSERT INTO "access_keys" (
+            "access_key_id",
+            "access_key_secret",
+            "name",
+            "principal_arn",
+            "expires_at",
+            "policy",
+            "created_at"
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(create.access_key_id)
+    .bind(create.access_key_secret)
+    .bind(create.name)
+    .bind(create.principal_arn)
+    .bind(create.expires_at)
+    .bind(create.policy)
+    .bind(Utc::now())
+    .fetch_one(db)
+    .await
+}
+
+/// Get all the access keys stored in the database
+pub async fn get_access_keys(db: impl DbExecutor<'_>) -> DbResult<Vec<StoredAccessKey>> {
+    sqlx::query_as(r#"SELECT * FROM "access_keys" ORDER BY "created_at""#)
+        .fetch_all(db)
+        .await
+}
+
+/// Get an access key by its `access_key_id`
+pub async fn get_access_key(
+    db: impl DbExecutor<'_>,
+    access_key_id: &str,
+) -> DbResult<Option<StoredAccessKey>> {
+    sqlx::query_as(r#"SELECT * FROM "access_keys" WHERE "access_key_id" = ?"#)
+        .bind(access_key_id)
+        .fetch_optional(db)
+        .await
+}
+
+/// Set whether an access key is enabled
+pub async fn update_access_key_enabled(
+    db: impl DbExecutor<'_>,
+    access_key_id: &str,
+    enabled: bool,
+) -> DbResult<()> {
+    sqlx::query(r#"UPDATE "access_keys" SET "enabled" = ? WHERE "access_key_id" = ?"#)
+        .bind(enabled)
+        .bind(access_key_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Set the identity policy of an access key, [None] removes the policy
+pub async fn update_access_key_policy(
+    db: impl DbExecutor<'_>,
+    access_key_id: &str,
+    policy: Option<&str>,
+) -> DbResult<()> {
+    sqlx::query(r#"UPDATE "access_keys" SET "policy" = ? WHERE "access_key_id" = ?"#)
+        .bind(policy)
+        .bind(access_key_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Record that an access key was used to sign a request
+pub async fn update_access_key_last_used(
+    db: impl DbExecutor<'_>,
+    access_key_id: &str,
+    last_used_at: DateTime<Utc>,
+) -> DbResult<()> {
+    sqlx::query(r#"UPDATE "access_keys" SET "last_used_at" = ? WHERE "access_key_id" = ?"#)
+        .bind(last_used_at)
+        .bind(access_key_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Delete an access key
+pub async fn delete_access_key(db: impl DbExecutor<'_>, access_key_id: &str) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "access_keys" WHERE "access_key_id" = ?"#)
+        .bind(access_key_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,315 @@
+use crate::database::{DbExecutor, DbResult};
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+/// State of a KMS key that can be used for cryptographic operations
+pub const KEY_STATE_ENABLED: &str = "Enabled";
+
+/// State of a KMS key that has been disabled
+pub const KEY_STATE_DISABLED: &str = "Disabled";
+
+/// State of a KMS key that is scheduled to be deleted
+pub const KEY_STATE_PENDING_DELETION: &str = "PendingDeletion";
+
+#[derive(Clone, FromRow)]
+pub struct StoredKmsKey {
+    pub key_id: String,
+    pub arn: String,
+    pub region: String,
+    //
+    pub description: String,
+    pub key_usage: String,
+    pub key_spec: String,
+    pub key_state: String,
+    pub key_material: Vec<u8>,
+    //
+    pub created_at: DateTime<Utc>,
+    pub deletion_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub struct StoredKmsAlias {
+    pub region: String,
+    pub alias_name: String,
+    pub arn: String,
+    pub target_key_id: String,
+    //
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+pub struct CreateKmsKey {
+    pub key_id: String,
+    pub arn: String,
+    pub region: String,
+    pub description: String,
+    pub key_usage: String,
+    pub key_spec: String,
+    pub key_material: Vec<u8>,
+}
+
+pub struct CreateKmsAlias {
+    pub region: String,
+    pub alias_name: String,
+    pub arn: String,
+    pub target_key_id: String,
+}
+
+/// Create a new enabled KMS key
+pub async fn create_kms_key(
+    db: impl DbExecutor<'_>,
+    create: CreateKmsKey,
+) -> DbResult<StoredKmsKey> {
+    sqlx::query_as(
+        r#"
+        INSERT INTO "kms_keys" (
+            "key_id",
+            "arn",
+            "region",
+            "description",
+            "key_usage",
+            "key_spec",
+            "key_state",
+            "key_material",
+            "created_at"
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(create.key_id)
+    .bind(create.arn)
+    .bind(create.region)
+    .bind(create.description)
+    .bind(create.key_usage)
+    .bind(create.key_spec)
+    .bind(KEY_STATE_ENABLED)
+    .bind(create.key_material)
+    .bind(Utc::now())
+    .fetch_one(db)
+    .await
+}
+
+/// Get a KMS key in `region` where the key ID, key ARN, alias name or alias ARN
+/// matches the `key_id`
+pub async fn get_kms_key(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    key_id: &str,
+) -> DbResult<Option<StoredKmsKey>> {
+    sqlx::query_as(
+        r#"
+        SELECT "key".*
+        FROM "kms_keys" "key"
+        LEFT JOIN "kms_aliases" "alias" ON "alias"."target_key_id" = "key"."key_id"
+        WHERE "key"."region" = ?
+            AND ("key"."key_id" = ? OR "key"."arn" = ?
+                OR "alias"."alias_name" = ? OR "alias"."arn" = ?)
+        LIMIT 1
+        "#,
+    )
+    .bind(region)
+    .bind(key_id)
+    .bind(key_id)
+    .bind(key_id)
+    .bind(key_id)
+    .fetch_optional(db)
+    .await
+}
+
+/// Get a KMS key by its unique `key_id` in any region
+pub async fn get_kms_key_by_id(
+    db: impl DbExecutor<'_>,
+    key_id: &str,
+) -> DbResult<Option<StoredKmsKey>> {
+    sqlx::query_as(r#"SELECT * FROM "kms_keys" WHERE "key_id" = ?"#)
+        .bind(key_id)
+        .fetch_optional(db)
+        .await
+}
+
+/// Get a page of the KMS keys in `region` ordered by creation date
+pub async fn get_kms_keys_page(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    limit: i64,
+    offset: i64,
+) -> DbResult<Vec<StoredKmsKey>> {
+    sqlx::query_as(
+        r#"
+        SELECT * FROM "kms_keys"
+        WHERE "region" = ?
+        ORDER BY "created_at" ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(region)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await
+}
+
+/// Get the total number of KMS keys in `region`
+pub async fn count_kms_keys(db: impl DbExecutor<'_>, region: &str) -> DbResult<i64> {
+    let (count,): (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM "kms_keys" WHERE "region" = ?"#)
+        .bind(region)
+        .fetch_one(db)
+        .await?;
+    Ok(count)
+}
+
+/// Update the state of a KMS key, the `deletion_date` is only set for keys
+/// that are pending deletion
+pub async fn update_kms_key_state(
+    db: impl DbExecutor<'_>,
+    key_id: &str,
+    key_state: &str,
+    deletion_date: Option<DateTime<Utc>>,
+) -> DbResult<()> {
+    sqlx::query(r#"UPDATE "kms_keys" SET "key_state" = ?, "deletion_date" = ? WHERE "key_id" = ?"#)
+        .bind(key_state)
+        .bind(deletion_date)
+        .bind(key_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Delete KMS keys that were scheduled to be deleted before the provided date
+pub async fn delete_scheduled_kms_keys(
+    db: impl DbExecutor<'_>,
+    before: DateTime<Utc>,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM "kms_keys"
+        WHERE "key_state" = ? AND "deletion_date" < ?
+        "#,
+    )
+    .bind(KEY_STATE_PENDING_DELETION)
+    .bind(before)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Create a new alias for a KMS key
+pub async fn create_kms_alias(db: impl DbExecutor<'_>, create: CreateKmsAlias) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO "kms_aliases" ("region", "alias_name", "arn", "target_key_id", "created_at")
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(create.region)
+    .bind(create.alias_name)
+    .bind(create.arn)
+    .bind(create.target_key_id)
+    .bind(Utc::now())
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the alias `alias_name` in `region`
+pub async fn get_kms_alias(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    alias_name: &str,
+) -> DbResult<Option<StoredKmsAlias>> {
+    sqlx::query_as(r#"SELECT * FROM "kms_aliases" WHERE "region" = ? AND "alias_name" = ?"#)
+        .bind(region)
+        .bind(alias_name)
+        .fetch_optional(db)
+        .await
+}
+
+/// Change the key the alias `alias_name` in `region` refers to
+pub async fn update_kms_alias_target(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    alias_name: &str,
+    target_key_id: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE "kms_aliases"
+        SET "target_key_id" = ?, "updated_at" = ?
+        WHERE "region" = ? AND "alias_name" = ?
+        "#,
+    )
+    .bind(target_key_id)
+    .bind(Utc::now())
+    .bind(region)
+    .bind(alias_name)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete the alias `alias_name` in `region`
+pub async fn delete_kms_alias(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    alias_name: &str,
+) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "kms_aliases" WHERE "region" = ? AND "alias_name" = ?"#)
+        .bind(region)
+        .bind(alias_name)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Get a page of the aliases in `region` ordered by name, only includes the
+/// aliases of the key `target_key_id` when provided
+pub async fn get_kms_aliases_page(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    target_key_id: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> DbResult<Vec<StoredKmsAlias>> {
+    sqlx::query_as(
+        r#"
+        SELECT * FROM "kms_aliases"
+        WHERE "region" = ? AND (? IS NULL OR "target_key_id" = ?)
+        ORDER BY "alias_name" ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(region)
+    .bind(target_key_id)
+    .bind(target_key_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(db)
+    .await
+}
+
+/// Get the total number of aliases in `region`, only includes the aliases of
+/// the key `target_key_id` when provided
+pub async fn count_kms_aliases(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    target_key_id: Option<&str>,
+) -> DbResult<i64> {
+    let (count,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM "kms_aliases"
+        WHERE "region" = ? AND (? IS NULL OR "target_key_id" = ?)
+        "#,
+    )
+    .bind(region)
+    .bind(target_key_id)
+    .bind(target_key_id)
+    .fetch_one(db)
+    .await?;
+    Ok(count)
+}
@@ -0,0 +1,62 @@
+CREATE TABLE IF NOT EXISTS "kms_keys" (
+    -- Unique key ID (UUID) and the ARN of the key
+    "key_id" TEXT PRIMARY KEY NOT NULL,
+    "arn" TEXT NOT NULL,
+
+    -- Region the key belongs to
+    "region" TEXT NOT NULL,
+
+    -- Metadata
+    "description" TEXT NOT NULL,
+    "key_usage" TEXT NOT NULL,
+    "key_spec" TEXT NOT NULL,
+
+    -- Current state of the key (Enabled, Disabled, PendingDeletion)
+    "key_state" TEXT NOT NULL,
+
+    -- 256-bit symmetric key material
+    "key_material" BLOB NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+
+    -- Date the key is scheduled to be deleted when pending deletion
+    "deletion_date" TEXT NULL,
+
+    -- ARN must be unique
+    UNIQUE ("arn")
+);
+
+-- Fast lookups of keys within a region
+CREATE INDEX IF NOT EXISTS "idx_kms_keys_region" ON "kms_keys"("region");
+
+CREATE TABLE IF NOT EXISTS "kms_aliases" (
+    -- Region the alias belongs to
+    "region" TEXT NOT NULL,
+
+    -- Name of the alias (alias/example) and the ARN of the alias
+    "alias_name" TEXT NOT NULL,
+    "arn" TEXT NOT NULL,
+
+    -- Key the alias refers to
+    "target_key_id" TEXT NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Composite primary key
+    PRIMARY KEY ("region", "alias_name"),
+
+    -- Foreign key to "kms_keys"
+    FOREIGN KEY ("target_key_id") REFERENCES "kms_keys"("key_id") ON DELETE CASCADE
+);
+
+-- Fast lookups of the aliases of a key
+CREATE INDEX IF NOT EXISTS "idx_kms_aliases_target_key_id" ON "kms_aliases"("target_key_id");
+
+-- KMS key the secret values are encrypted with, as provided when the secret was created or updated
+ALTER TABLE "secrets" ADD COLUMN "kms_key_id" TEXT NULL;
+
+-- Data key the secret value was encrypted with, encrypted by the KMS key of the secret
+ALTER TABLE "secrets_versions" ADD COLUMN "encrypted_data_key" TEXT NULL;
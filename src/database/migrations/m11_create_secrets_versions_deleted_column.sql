@@ -0,0 +1,3 @@
+-- Date a version was soft deleted through the Vault KV API, deleted versions are
+-- restored by clearing the date
+ALTER TABLE "secrets_versions" ADD COLUMN "deleted_at" TEXT NULL;
@@ -0,0 +1,3 @@
+-- JSON array of the identity policies of the caller that assumed the role, the
+-- temporary credentials are restricted by each of them along with the session policy
+ALTER TABLE "session_credentials" ADD COLUMN "source_policies" TEXT NULL;
@@ -0,0 +1,14 @@
+-- Whether automatic rotation is enabled for the secret
+ALTER TABLE "secrets" ADD COLUMN "rotation_enabled" BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ARN of the function responsible for rotating the secret
+ALTER TABLE "secrets" ADD COLUMN "rotation_lambda_arn" TEXT NULL;
+
+-- Rotation rules
+ALTER TABLE "secrets" ADD COLUMN "rotation_automatically_after_days" INTEGER NULL;
+ALTER TABLE "secrets" ADD COLUMN "rotation_duration" TEXT NULL;
+ALTER TABLE "secrets" ADD COLUMN "rotation_schedule_expression" TEXT NULL;
+
+-- Datetime the secret was last rotated and the datetime of the next scheduled rotation
+ALTER TABLE "secrets" ADD COLUMN "last_rotated_at" TEXT NULL;
+ALTER TABLE "secrets" ADD COLUMN "next_rotation_at" TEXT NULL;
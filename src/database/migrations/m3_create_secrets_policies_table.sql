@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS "secrets_policies" (
+    -- Secret the policy is attached to
+    "secret_arn" TEXT PRIMARY KEY NOT NULL,
+
+    -- Resource policy document, stored verbatim
+    "policy" TEXT NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets"("arn") ON DELETE CASCADE
+);
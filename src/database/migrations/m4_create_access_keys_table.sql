@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS "access_keys" (
+    -- Access key ID used in the SigV4 credential scope
+    "access_key_id" TEXT PRIMARY KEY NOT NULL,
+
+    -- Secret used to sign requests
+    "access_key_secret" TEXT NOT NULL,
+
+    -- Name identifying the access key
+    "name" TEXT NOT NULL,
+
+    -- ARN of the principal requests are made as, defaults to a user
+    -- named after the access key when not specified
+    "principal_arn" TEXT NULL,
+
+    -- Whether the access key can be used
+    "enabled" BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Date after which the access key can no longer be used
+    "expires_at" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Date the access key was last used to sign a request
+ALTER TABLE "access_keys" ADD COLUMN "last_used_at" TEXT NULL;
@@ -0,0 +1,2 @@
+-- Identity policy document restricting what the access key can do, stored verbatim
+ALTER TABLE "access_keys" ADD COLUMN "policy" TEXT NULL;
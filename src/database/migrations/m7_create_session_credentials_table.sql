@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS "session_credentials" (
+    -- Temporary access key ID used in the SigV4 credential scope
+    "access_key_id" TEXT PRIMARY KEY NOT NULL,
+
+    -- Secret used to sign requests
+    "access_key_secret" TEXT NOT NULL,
+
+    -- Token that must be provided in the X-Amz-Security-Token header
+    "session_token" TEXT NOT NULL,
+
+    -- ARN of the role that was assumed
+    "role_arn" TEXT NOT NULL,
+
+    -- Name of the role session provided when assuming the role
+    "session_name" TEXT NOT NULL,
+
+    -- Access key ID of the caller that assumed the role
+    "source_access_key_id" TEXT NOT NULL,
+
+    -- Session policy restricting the temporary credentials
+    "policy" TEXT NULL,
+
+    -- Date after which the temporary credentials can no longer be used
+    "expires_at" TEXT NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL
+);
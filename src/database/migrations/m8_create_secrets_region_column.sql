@@ -0,0 +1,170 @@
+-- Secret names are unique within a region rather than globally. SQLite can't drop the
+-- existing UNIQUE constraint so the secrets tables are rebuilt, the tables referencing
+-- "secrets" are rebuilt alongside so dropping the old tables doesn't cascade onto their data
+
+CREATE TABLE "secrets_migrated" (
+    -- Secret ARN
+    "arn" TEXT PRIMARY KEY NOT NULL,
+
+    -- Region the secret belongs to
+    "region" TEXT NOT NULL,
+
+    -- Metadata
+    "name" TEXT NOT NULL,
+    "description" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Datetime the resource was marked for deletion and the datetime its scheduled to be deleted by
+    "deleted_at" TEXT NULL,
+    "scheduled_delete_at" TEXT NULL,
+
+    -- Rotation configuration
+    "rotation_enabled" BOOLEAN NOT NULL DEFAULT FALSE,
+    "rotation_lambda_arn" TEXT NULL,
+    "rotation_automatically_after_days" INTEGER NULL,
+    "rotation_duration" TEXT NULL,
+    "rotation_schedule_expression" TEXT NULL,
+    "last_rotated_at" TEXT NULL,
+    "next_rotation_at" TEXT NULL,
+
+    -- Name must be unique within the region
+    UNIQUE ("region", "name")
+);
+
+-- Existing secrets were all created with the arn:aws:secretsmanager: prefix, the region
+-- is the segment that follows it
+INSERT INTO "secrets_migrated" (
+    "arn", "region", "name", "description", "created_at", "updated_at", "deleted_at",
+    "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn",
+    "rotation_automatically_after_days", "rotation_duration", "rotation_schedule_expression",
+    "last_rotated_at", "next_rotation_at"
+)
+SELECT
+    "arn",
+    substr(substr("arn", 24), 1, instr(substr("arn", 24), ':') - 1),
+    "name", "description", "created_at", "updated_at", "deleted_at",
+    "scheduled_delete_at", "rotation_enabled", "rotation_lambda_arn",
+    "rotation_automatically_after_days", "rotation_duration", "rotation_schedule_expression",
+    "last_rotated_at", "next_rotation_at"
+FROM "secrets";
+
+CREATE TABLE "secrets_versions_migrated" (
+    -- Secret details
+    "secret_arn" TEXT NOT NULL,
+    "version_id" TEXT NOT NULL,
+
+    -- Secret Value
+    "secret_string" TEXT NULL,
+    "secret_binary" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "last_accessed_at" TEXT NULL,
+
+    -- Composite primary key
+    PRIMARY KEY ("secret_arn", "version_id"),
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets_migrated"("arn") ON DELETE CASCADE
+);
+
+INSERT INTO "secrets_versions_migrated" SELECT
+    "secret_arn", "version_id", "secret_string", "secret_binary", "created_at", "last_accessed_at"
+FROM "secrets_versions";
+
+CREATE TABLE "secret_version_stages_migrated" (
+    -- Version details
+    "secret_arn" TEXT NOT NULL,
+    "version_id" TEXT NOT NULL,
+
+    -- Stage value
+    "value" TEXT NOT NULL,
+
+    "created_at" TEXT NOT NULL,
+
+    -- Each version can have multiple stages but each stage value should be unique per version
+    PRIMARY KEY ("secret_arn", "version_id", "value"),
+
+    -- Stage value must be unique for each secret
+    UNIQUE ("secret_arn", "value"),
+
+    -- Foreign key referencing secrets_versions composite key
+    FOREIGN KEY ("secret_arn", "version_id")
+        REFERENCES "secrets_versions_migrated"("secret_arn", "version_id")
+        ON DELETE CASCADE
+);
+
+INSERT INTO "secret_version_stages_migrated" SELECT
+    "secret_arn", "version_id", "value", "created_at"
+FROM "secret_version_stages";
+
+CREATE TABLE "secrets_tags_migrated" (
+    -- Secret details
+    "secret_arn" TEXT NOT NULL,
+
+    -- Tag data
+    "key" TEXT NOT NULL,
+    "value" TEXT NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Composite primary key
+    PRIMARY KEY ("secret_arn", "key"),
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets_migrated"("arn") ON DELETE CASCADE
+);
+
+INSERT INTO "secrets_tags_migrated" SELECT
+    "secret_arn", "key", "value", "created_at", "updated_at"
+FROM "secrets_tags";
+
+CREATE TABLE "secrets_policies_migrated" (
+    -- Secret the policy is attached to
+    "secret_arn" TEXT PRIMARY KEY NOT NULL,
+
+    -- Resource policy document, stored verbatim
+    "policy" TEXT NOT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets_migrated"("arn") ON DELETE CASCADE
+);
+
+INSERT INTO "secrets_policies_migrated" SELECT
+    "secret_arn", "policy", "created_at", "updated_at"
+FROM "secrets_policies";
+
+-- Drop the old tables, dependent tables first
+DROP TABLE "secrets_policies";
+DROP TABLE "secrets_tags";
+DROP TABLE "secret_version_stages";
+DROP TABLE "secrets_versions";
+DROP TABLE "secrets";
+
+-- Renaming also updates the foreign keys referencing the renamed tables
+ALTER TABLE "secrets_migrated" RENAME TO "secrets";
+ALTER TABLE "secrets_versions_migrated" RENAME TO "secrets_versions";
+ALTER TABLE "secret_version_stages_migrated" RENAME TO "secret_version_stages";
+ALTER TABLE "secrets_tags_migrated" RENAME TO "secrets_tags";
+ALTER TABLE "secrets_policies_migrated" RENAME TO "secrets_policies";
+
+-- Fast lookups by name within a region
+CREATE INDEX IF NOT EXISTS "idx_secrets_region_name" ON "secrets"("region", "name");
+
+-- Fast lookups by ARN + Version ID
+CREATE INDEX IF NOT EXISTS "idx_secrets_versions_version_id" ON "secrets_versions"("secret_arn", "version_id");
+
+-- Fast lookups by ARN + Version ID + Value
+CREATE INDEX IF NOT EXISTS "idx_secrets_versions_stages_value" ON "secret_version_stages"("secret_arn", "version_id", "value");
+
+-- Fast lookups by ARN
+CREATE INDEX IF NOT EXISTS "idx_secrets_tags_secret_arn" ON "secrets_tags"("secret_arn");
@@ -0,0 +1,30 @@
+-- ARN of the primary secret when the secret is a replica of another secret
+ALTER TABLE "secrets" ADD COLUMN "primary_arn" TEXT NULL REFERENCES "secrets"("arn") ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS "secrets_replicas" (
+    -- Primary secret the replica belongs to
+    "secret_arn" TEXT NOT NULL,
+
+    -- Region the secret is replicated to
+    "region" TEXT NOT NULL,
+
+    -- KMS key used to encrypt the replica
+    "kms_key_id" TEXT NULL,
+
+    -- Replication status (InSync, Failed, InProgress) and the reason for a failure
+    "status" TEXT NOT NULL,
+    "status_message" TEXT NULL,
+
+    -- Timestamps
+    "created_at" TEXT NOT NULL,
+    "updated_at" TEXT NULL,
+
+    -- Composite primary key
+    PRIMARY KEY ("secret_arn", "region"),
+
+    -- Foreign key to "secrets"
+    FOREIGN KEY ("secret_arn") REFERENCES "secrets"("arn") ON DELETE CASCADE
+);
+
+-- Fast lookups of the replicas of a primary secret
+CREATE INDEX IF NOT EXISTS "idx_secrets_primary_arn" ON "secrets"("primary_arn");
@@ -5,10 +5,56 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::prelude::FromRow;
 
-pub const MIGRATIONS: &[(&str, &str)] = &[(
-    "m1_create_secrets_tables",
-    include_str!("./m1_create_secrets_tables.sql"),
-)];
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "m1_create_secrets_tables",
+        include_str!("./m1_create_secrets_tables.sql"),
+    ),
+    (
+        "m2_create_secrets_rotation_columns",
+        include_str!("./m2_create_secrets_rotation_columns.sql"),
+    ),
+    (
+        "m3_create_secrets_policies_table",
+        include_str!("./m3_create_secrets_policies_table.sql"),
+    ),
+    (
+        "m4_create_access_keys_table",
+        include_str!("./m4_create_access_keys_table.sql"),
+    ),
+    (
+        "m5_create_access_keys_last_used_column",
+        include_str!("./m5_create_access_keys_last_used_column.sql"),
+    ),
+    (
+        "m6_create_access_keys_policy_column",
+        include_str!("./m6_create_access_keys_policy_column.sql"),
+    ),
+    (
+        "m7_create_session_credentials_table",
+        include_str!("./m7_create_session_credentials_table.sql"),
+    ),
+    (
+        "m8_create_secrets_region_column",
+        include_str!("./m8_create_secrets_region_column.sql"),
+    ),
+    (
+        "m9_create_secrets_replicas_table",
+        include_str!("./m9_create_secrets_replicas_table.sql"),
+    ),
+    (
+        "m10_create_kms_tables",
+        include_str!("./m10_create_kms_tables.sql"),
+    ),
+    (
+        "m11_create_secrets_versions_deleted_column",
+        include_str!("./m11_create_secrets_versions_deleted_column.sql"),
+    ),
+    (
+        "m12_create_session_credentials_source_policies_column",
+        include_str!("./m12_create_session_credentials_source_policies_column.sql"),
+    ),
+];
 
 const MIGRATIONS_SETUP_SQL: &str = include_str!("./m0_create_migrations_table.sql");
 
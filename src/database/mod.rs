@@ -8,8 +8,13 @@ use tokio::fs::File;
 
 use crate::database::migrations::{apply_migrations, setup_migrations};
 
+pub mod access_keys;
+pub mod kms;
 pub mod migrations;
+pub mod policies;
+pub mod replicas;
 pub mod secrets;
+pub mod session_credentials;
 
 /// Type of the database connection pool
 pub type DbPool = SqlitePool;
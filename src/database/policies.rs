@@ -0,0 +1,53 @@
+use crate::database::{DbExecutor, DbResult};
+use chrono::Utc;
+
+/// Set the resource policy attached to a secret, replaces any existing policy
+pub async fn put_secret_policy(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    policy: &str,
+) -> DbResult<()> {
+    let now = Utc::now();
+
+    sqlx::query(
+        r#"
+        INSERT INTO "secrets_policies" ("secret_arn", "policy", "created_at")
+        VALUES (?, ?, ?)
+        ON CONFLICT("secret_arn")
+        DO UPDATE SET
+            "policy" = "excluded"."policy",
+            "updated_at" = "excluded"."created_at"
+        "#,
+    )
+    .bind(secret_arn)
+    .bind(policy)
+    .bind(now)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the resource policy attached to a secret
+pub async fn get_secret_policy(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+) -> DbResult<Option<String>> {
+    let result: Option<(String,)> =
+        sqlx::query_as(r#"SELECT "policy" FROM "secrets_policies" WHERE "secret_arn" = ?"#)
+            .bind(secret_arn)
+            .fetch_optional(db)
+            .await?;
+
+    Ok(result.map(|(policy,)| policy))
+}
+
+/// Remove the resource policy attached to a secret
+pub async fn delete_secret_policy(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "secrets_policies" WHERE "secret_arn" = ?"#)
+        .bind(secret_arn)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
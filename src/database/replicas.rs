@@ -0,0 +1,90 @@
+use crate::database::{DbExecutor, DbResult};
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+/// Replication status of a secret replica that is up to date with its primary
+pub const REPLICA_STATUS_IN_SYNC: &str = "InSync";
+
+/// Replication status of a secret replica that could not be created
+pub const REPLICA_STATUS_FAILED: &str = "Failed";
+
+#[derive(Clone, FromRow)]
+pub struct StoredSecretReplica {
+    pub secret_arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
+    pub status: String,
+    pub status_message: Option<String>,
+    //
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+pub struct PutSecretReplica {
+    pub secret_arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
+    pub status: String,
+    pub status_message: Option<String>,
+}
+
+/// Set the replication status of a secret for a region, replaces the existing
+/// status for the region
+pub async fn put_secret_replica(db: impl DbExecutor<'_>, put: PutSecretReplica) -> DbResult<()> {
+    let now = Utc::now();
+
+    sqlx::query(
+        r#"
+        INSERT INTO "secrets_replicas" ("secret_arn", "region", "kms_key_id", "status", "status_message", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT("secret_arn", "region")
+        DO UPDATE SET
+            "kms_key_id" = "excluded"."kms_key_id",
+            "status" = "excluded"."status",
+            "status_message" = "excluded"."status_message",
+            "updated_at" = "excluded"."created_at"
+        "#,
+    )
+    .bind(put.secret_arn)
+    .bind(put.region)
+    .bind(put.kms_key_id)
+    .bind(put.status)
+    .bind(put.status_message)
+    .bind(now)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the replication status of all the regions a secret is replicated to
+pub async fn get_secret_replicas(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+) -> DbResult<Vec<StoredSecretReplica>> {
+    sqlx::query_as(
+        r#"
+        SELECT * FROM "secrets_replicas"
+        WHERE "secret_arn" = ?
+        ORDER BY "created_at" ASC
+        "#,
+    )
+    .bind(secret_arn)
+    .fetch_all(db)
+    .await
+}
+
+/// Remove the replication status of a secret for a region
+pub async fn delete_secret_replica(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    region: &str,
+) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "secrets_replicas" WHERE "secret_arn" = ? AND "region" = ?"#)
+        .bind(secret_arn)
+        .bind(region)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
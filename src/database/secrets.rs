@@ -1,16 +1,20 @@
 use crate::{
     database::{DbErr, DbExecutor, DbResult},
-    handlers::models::Filter,
+    handlers::models::{Arn, Filter},
     utils::filter::split_search_terms,
 };
 use chrono::{DateTime, Days, Utc};
 use serde::Deserialize;
-use sqlx::prelude::FromRow;
+use sqlx::{Sqlite, prelude::FromRow, query::QueryAs, sqlite::SqliteArguments};
+use std::str::FromStr;
 
 #[derive(Clone, FromRow)]
 pub struct StoredSecret {
     pub arn: String,
+    pub region: String,
     pub name: String,
+    pub primary_arn: Option<String>,
+    pub kms_key_id: Option<String>,
     //
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -24,10 +28,19 @@ pub struct StoredSecret {
     pub description: Option<String>,
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_data_key: Option<String>,
     //
     pub version_created_at: DateTime<Utc>,
     pub version_last_accessed_at: Option<DateTime<Utc>>,
     //
+    pub rotation_enabled: bool,
+    pub rotation_lambda_arn: Option<String>,
+    pub rotation_automatically_after_days: Option<i64>,
+    pub rotation_duration: Option<String>,
+    pub rotation_schedule_expression: Option<String>,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_rotation_at: Option<DateTime<Utc>>,
+    //
     #[sqlx(json)]
     pub version_tags: Vec<StoredVersionTags>,
 }
@@ -35,7 +48,12 @@ pub struct StoredSecret {
 #[derive(Clone, FromRow)]
 pub struct StoredSecretWithVersionStages {
     pub arn: String,
+    pub region: String,
     pub name: String,
+    pub primary_arn: Option<String>,
+    pub kms_key_id: Option<String>,
+    /// Region of the primary secret, only present for replicated secrets
+    pub primary_region: Option<String>,
     //
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -49,10 +67,19 @@ pub struct StoredSecretWithVersionStages {
     pub description: Option<String>,
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_data_key: Option<String>,
     //
     pub version_created_at: DateTime<Utc>,
     pub version_last_accessed_at: Option<DateTime<Utc>>,
     //
+    pub rotation_enabled: bool,
+    pub rotation_lambda_arn: Option<String>,
+    pub rotation_automatically_after_days: Option<i64>,
+    pub rotation_duration: Option<String>,
+    pub rotation_schedule_expression: Option<String>,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_rotation_at: Option<DateTime<Utc>>,
+    //
     #[sqlx(json)]
     pub version_tags: Vec<StoredVersionTags>,
     //
@@ -78,9 +105,27 @@ pub struct SecretVersion {
     //
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    pub encrypted_data_key: Option<String>,
     //
     pub created_at: DateTime<Utc>,
     pub last_accessed_at: Option<DateTime<Utc>>,
+    /// Date the version was soft deleted through the Vault KV API
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub struct StoredSecretResource {
+    pub arn: String,
+    pub name: String,
+    #[sqlx(json)]
+    pub version_tags: Vec<StoredVersionTags>,
+}
+
+#[derive(Clone, FromRow)]
+pub struct StoredReplicaSecret {
+    pub arn: String,
+    pub region: String,
+    pub kms_key_id: Option<String>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -93,8 +138,13 @@ pub struct StoredVersionTags {
 
 pub struct CreateSecret {
     pub arn: String,
+    pub region: String,
     pub name: String,
     pub description: Option<String>,
+    /// ARN of the primary secret when creating a replica
+    pub primary_arn: Option<String>,
+    /// KMS key to encrypt the secret values with
+    pub kms_key_id: Option<String>,
 }
 
 /// Create a new "secret" with no versions
@@ -103,12 +153,16 @@ pub async fn create_secret(db: impl DbExecutor<'_>, create: CreateSecret) -> DbR
 
     sqlx::query(
         r#"
-        INSERT INTO "secrets" ("arn", "name", "description", "created_at") VALUES (?, ?, ?, ?)
+        INSERT INTO "secrets" ("arn", "region", "name", "description", "primary_arn", "kms_key_id", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?, ?)
     "#,
     )
     .bind(create.arn)
+    .bind(create.region)
     .bind(create.name)
     .bind(create.description)
+    .bind(create.primary_arn)
+    .bind(create.kms_key_id)
     .bind(created_at)
     .execute(db)
     .await?;
@@ -136,6 +190,26 @@ pub async fn update_secret_description(
     Ok(())
 }
 
+/// Updates the KMS key that new versions of a secret are encrypted with
+pub async fn update_secret_kms_key_id(
+    db: impl DbExecutor<'_>,
+    arn: &str,
+    kms_key_id: Option<&str>,
+) -> DbResult<()> {
+    let updated_at = Utc::now();
+
+    sqlx::query(
+        r#"UPDATE "secrets" SET "kms_key_id" = ?, "updated_at" = ? WHERE "secrets"."arn" = ?"#,
+    )
+    .bind(kms_key_id)
+    .bind(updated_at)
+    .bind(arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 /// Remove a secret
 pub async fn delete_secret(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
     sqlx::query(r#"DELETE FROM "secrets" WHERE "arn" = ?"#)
@@ -146,6 +220,163 @@ pub async fn delete_secret(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResul
     Ok(())
 }
 
+/// Get the replica secrets of the primary secret `secret_arn`
+pub async fn get_secret_replica_secrets(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+) -> DbResult<Vec<StoredReplicaSecret>> {
+    sqlx::query_as(r#"SELECT "arn", "region", "kms_key_id" FROM "secrets" WHERE "primary_arn" = ?"#)
+        .bind(secret_arn)
+        .fetch_all(db)
+        .await
+}
+
+/// Promote a replica secret to a standalone secret that is no longer
+/// replicated from its primary
+pub async fn promote_replica_secret(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
+    let updated_at = Utc::now();
+
+    sqlx::query(r#"UPDATE "secrets" SET "primary_arn" = NULL, "updated_at" = ? WHERE "arn" = ?"#)
+        .bind(updated_at)
+        .bind(secret_arn)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Copy the description and rotation configuration of the secret `source_arn`
+/// onto the secret `target_arn`
+pub async fn copy_secret_metadata(
+    db: impl DbExecutor<'_>,
+    source_arn: &str,
+    target_arn: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE "secrets"
+        SET (
+            "description",
+            "updated_at",
+            "rotation_enabled",
+            "rotation_lambda_arn",
+            "rotation_automatically_after_days",
+            "rotation_duration",
+            "rotation_schedule_expression",
+            "last_rotated_at",
+            "next_rotation_at"
+        ) = (
+            SELECT
+                "source"."description",
+                "source"."updated_at",
+                "source"."rotation_enabled",
+                "source"."rotation_lambda_arn",
+                "source"."rotation_automatically_after_days",
+                "source"."rotation_duration",
+                "source"."rotation_schedule_expression",
+                "source"."last_rotated_at",
+                "source"."next_rotation_at"
+            FROM "secrets" "source"
+            WHERE "source"."arn" = ?
+        )
+        WHERE "arn" = ?
+        "#,
+    )
+    .bind(source_arn)
+    .bind(target_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove all versions of a secret along with their version stages
+pub async fn delete_secret_versions(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "secrets_versions" WHERE "secret_arn" = ?"#)
+        .bind(secret_arn)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Copy all the versions of the secret `source_arn` onto the secret `target_arn`
+pub async fn copy_secret_versions(
+    db: impl DbExecutor<'_>,
+    source_arn: &str,
+    target_arn: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "secret_binary", "encrypted_data_key", "created_at", "last_accessed_at", "deleted_at")
+        SELECT ?, "version_id", "secret_string", "secret_binary", "encrypted_data_key", "created_at", "last_accessed_at", "deleted_at"
+        FROM "secrets_versions"
+        WHERE "secret_arn" = ?
+        "#,
+    )
+    .bind(target_arn)
+    .bind(source_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Copy all the version stages of the secret `source_arn` onto the secret `target_arn`,
+/// the versions must have already been copied
+pub async fn copy_secret_version_stages(
+    db: impl DbExecutor<'_>,
+    source_arn: &str,
+    target_arn: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO "secret_version_stages" ("secret_arn", "version_id", "value", "created_at")
+        SELECT ?, "version_id", "value", "created_at"
+        FROM "secret_version_stages"
+        WHERE "secret_arn" = ?
+        "#,
+    )
+    .bind(target_arn)
+    .bind(source_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove all the tags from a secret
+pub async fn delete_secret_tags(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "secrets_tags" WHERE "secret_arn" = ?"#)
+        .bind(secret_arn)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Copy all the tags of the secret `source_arn` onto the secret `target_arn`
+pub async fn copy_secret_tags(
+    db: impl DbExecutor<'_>,
+    source_arn: &str,
+    target_arn: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO "secrets_tags" ("secret_arn", "key", "value", "created_at", "updated_at")
+        SELECT ?, "key", "value", "created_at", "updated_at"
+        FROM "secrets_tags"
+        WHERE "secret_arn" = ?
+        "#,
+    )
+    .bind(target_arn)
+    .bind(source_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 /// Get the ARN's of all the secrets that are scheduled for deletion
 ///
 /// Not used by the actual application, only used within tests to ensure
@@ -224,6 +455,137 @@ pub async fn cancel_delete_secret(db: impl DbExecutor<'_>, secret_arn: &str) ->
     Ok(())
 }
 
+pub struct UpdateSecretRotation {
+    pub rotation_enabled: bool,
+    pub rotation_lambda_arn: Option<String>,
+    pub rotation_automatically_after_days: Option<i64>,
+    pub rotation_duration: Option<String>,
+    pub rotation_schedule_expression: Option<String>,
+    pub next_rotation_at: Option<DateTime<Utc>>,
+}
+
+/// Updates the rotation configuration of a secret
+pub async fn update_secret_rotation(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    update: UpdateSecretRotation,
+) -> DbResult<()> {
+    let updated_at = Utc::now();
+
+    sqlx::query(
+        r#"
+        UPDATE "secrets"
+        SET
+            "rotation_enabled" = ?,
+            "rotation_lambda_arn" = ?,
+            "rotation_automatically_after_days" = ?,
+            "rotation_duration" = ?,
+            "rotation_schedule_expression" = ?,
+            "next_rotation_at" = ?,
+            "updated_at" = ?
+        WHERE "arn" = ?
+        "#,
+    )
+    .bind(update.rotation_enabled)
+    .bind(update.rotation_lambda_arn)
+    .bind(update.rotation_automatically_after_days)
+    .bind(update.rotation_duration)
+    .bind(update.rotation_schedule_expression)
+    .bind(update.next_rotation_at)
+    .bind(updated_at)
+    .bind(secret_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Disables automatic rotation for a secret, the rotation rules and function
+/// are kept so that rotation can be re-enabled later
+pub async fn cancel_secret_rotation(db: impl DbExecutor<'_>, secret_arn: &str) -> DbResult<()> {
+    let updated_at = Utc::now();
+
+    sqlx::query(
+        r#"
+        UPDATE "secrets"
+        SET
+            "rotation_enabled" = FALSE,
+            "next_rotation_at" = NULL,
+            "updated_at" = ?
+        WHERE "arn" = ?
+        "#,
+    )
+    .bind(updated_at)
+    .bind(secret_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Records that a secret has been rotated at `rotated_at` and sets the date of
+/// its next rotation
+pub async fn update_secret_rotated(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    rotated_at: DateTime<Utc>,
+    next_rotation_at: Option<DateTime<Utc>>,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE "secrets"
+        SET
+            "last_rotated_at" = ?,
+            "next_rotation_at" = ?
+        WHERE "arn" = ?
+        "#,
+    )
+    .bind(rotated_at)
+    .bind(next_rotation_at)
+    .bind(secret_arn)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Sets the date of the next rotation for a secret
+pub async fn update_secret_next_rotation(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    next_rotation_at: Option<DateTime<Utc>>,
+) -> DbResult<()> {
+    sqlx::query(r#"UPDATE "secrets" SET "next_rotation_at" = ? WHERE "arn" = ?"#)
+        .bind(next_rotation_at)
+        .bind(secret_arn)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Get the ARN's and regions of all the secrets with rotation enabled where the next
+/// rotation date is at or before `now`, excludes secrets that are scheduled for deletion
+/// and replicas which are rotated through their primary
+pub async fn get_secrets_due_rotation(
+    db: impl DbExecutor<'_>,
+    now: DateTime<Utc>,
+) -> DbResult<Vec<(String, String)>> {
+    sqlx::query_as(
+        r#"
+        SELECT "arn", "region" FROM "secrets"
+        WHERE "rotation_enabled" = TRUE
+            AND "next_rotation_at" IS NOT NULL
+            AND "next_rotation_at" <= ?
+            AND "scheduled_delete_at" IS NULL
+            AND "primary_arn" IS NULL
+        "#,
+    )
+    .bind(now)
+    .fetch_all(db)
+    .await
+}
+
 /// Set a tag on a secret
 pub async fn put_secret_tag(
     db: impl DbExecutor<'_>,
@@ -274,6 +636,8 @@ pub struct CreateSecretVersion {
     //
     pub secret_string: Option<String>,
     pub secret_binary: Option<String>,
+    /// Data key the value was encrypted with, encrypted by the KMS key of the secret
+    pub encrypted_data_key: Option<String>,
 }
 
 /// Creates a new version of a secret
@@ -285,14 +649,15 @@ pub async fn create_secret_version(
 
     sqlx::query(
         r#"
-        INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "secret_binary", "created_at")
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "secret_binary", "encrypted_data_key", "created_at")
+        VALUES (?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(create.secret_arn)
     .bind(create.version_id)
     .bind(create.secret_string)
     .bind(create.secret_binary)
+    .bind(create.encrypted_data_key)
     .bind(now)
     .execute(db)
     .await?;
@@ -300,6 +665,33 @@ pub async fn create_secret_version(
     Ok(())
 }
 
+/// Replace the stored value of a secret version, used when the value is
+/// re-encrypted with a different KMS key
+pub async fn update_secret_version_value(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    version_id: &str,
+    secret_string: Option<String>,
+    secret_binary: Option<String>,
+    encrypted_data_key: Option<String>,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE "secrets_versions"
+        SET "secret_string" = ?, "secret_binary" = ?, "encrypted_data_key" = ?
+        WHERE "secret_arn" = ? AND "version_id" = ?"#,
+    )
+    .bind(secret_string)
+    .bind(secret_binary)
+    .bind(encrypted_data_key)
+    .bind(secret_arn)
+    .bind(version_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 /// Updates the last access date of a secret version
 pub async fn update_secret_version_last_accessed(
     db: impl DbExecutor<'_>,
@@ -323,6 +715,29 @@ pub async fn update_secret_version_last_accessed(
     Ok(())
 }
 
+/// Updates the soft deletion date of a secret version, clearing the date
+/// restores the version
+pub async fn update_secret_version_deleted(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    version_id: &str,
+    deleted_at: Option<DateTime<Utc>>,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE "secrets_versions"
+        SET "deleted_at" = ?
+        WHERE "secret_arn" = ? AND "version_id" = ?"#,
+    )
+    .bind(deleted_at)
+    .bind(secret_arn)
+    .bind(version_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 /// Add a secret version stage to a specific secret version
 pub async fn add_secret_version_stage(
     db: impl DbExecutor<'_>,
@@ -388,52 +803,124 @@ pub async fn remove_secret_version_stage_any(
     Ok(result.rows_affected())
 }
 
-/// Get the current version of a secret where the name OR arn matches the `secret_id`
+/// Get the current version of a secret in `region` that the `secret_id` refers to
 pub async fn get_secret_latest_version(
     db: impl DbExecutor<'_>,
+    region: &str,
     secret_id: &str,
 ) -> DbResult<Option<StoredSecret>> {
-    get_secret_by_version_stage(db, secret_id, "AWSCURRENT").await
+    get_secret_by_version_stage(db, region, secret_id, "AWSCURRENT").await
+}
+
+/// Get the ARN, name and tags of the secret in `region` that the `secret_id`
+/// refers to, the secret is found regardless of the state of its versions
+pub async fn get_secret_resource(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    secret_id: &str,
+) -> DbResult<Option<StoredSecretResource>> {
+    let query = format!(
+        r#"
+        SELECT
+            "secret"."arn",
+            "secret"."name",
+            COALESCE((
+                SELECT json_group_array(
+                    json_object(
+                        'key', "secret_tag"."key",
+                        'value', "secret_tag"."value",
+                        'created_at', "secret_tag"."created_at",
+                        'updated_at', "secret_tag"."updated_at"
+                    )
+                )
+                FROM "secrets_tags" "secret_tag"
+                WHERE "secret_tag"."secret_arn" = "secret"."arn"
+            ), '[]') AS "version_tags"
+        FROM "secrets" "secret"
+        WHERE "secret"."arn" = ({RESOLVE_SECRET_ARN});
+    "#
+    );
+
+    let query = sqlx::query_as(&query);
+
+    bind_resolve_secret_arn(query, region, secret_id)
+        .fetch_optional(db)
+        .await
 }
 
-/// Check if the value is a partial arn
-fn is_partial_arn(value: &str) -> bool {
-    let is_arn_like = value.starts_with("arn:") && value.split(':').count() >= 6;
-    is_arn_like && (value.contains('*') || value.contains('?'))
+/// Subquery resolving the ARN of the secret in a region that a secret ID refers
+/// to, parameters are bound using [bind_resolve_secret_arn]
+///
+/// Secret IDs are matched with the same precedence as AWS, an exact ARN is
+/// preferred over an exact name which is preferred over a partial ARN (An ARN
+/// without the hyphen and 6 random characters at the end)
+const RESOLVE_SECRET_ARN: &str = r#"
+    SELECT "candidate"."arn"
+    FROM "secrets" "candidate"
+    WHERE "candidate"."region" = ?
+        AND ("candidate"."arn" = ? OR "candidate"."name" = ? OR "candidate"."arn" GLOB ?)
+    ORDER BY
+        CASE
+            WHEN "candidate"."arn" = ? THEN 0
+            WHEN "candidate"."name" = ? THEN 1
+            ELSE 2
+        END,
+        "candidate"."created_at" ASC
+    LIMIT 1
+"#;
+
+/// Bind the parameters of the [RESOLVE_SECRET_ARN] subquery for the `secret_id`
+/// in `region`
+fn bind_resolve_secret_arn<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    region: &'q str,
+    secret_id: &'q str,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query
+        .bind(region)
+        .bind(secret_id)
+        .bind(secret_id)
+        .bind(partial_arn_glob(secret_id))
+        .bind(secret_id)
+        .bind(secret_id)
 }
 
-/// Create a partial arn acceptable for a LIKE query
-fn make_partial_arn_like_query(value: &str) -> Option<String> {
-    if is_partial_arn(value) {
-        Some(
-            value
-                // Escape underscores
-                .replace('_', r"\_")
-                // Replace the placeholders with SQLite LIKE equivalents
-                .replace('*', "%")
-                .replace('?', "_"),
-        )
-    } else {
-        None
+/// Create the GLOB pattern matching the ARNs a partial ARN `secret_id` could
+/// refer to, provides [None] when the `secret_id` isn't an ARN
+///
+/// ARNs containing `*` or `?` wildcards are matched as a pattern, otherwise
+/// the pattern matches the ARN followed by the random suffix of a secret ARN
+fn partial_arn_glob(secret_id: &str) -> Option<String> {
+    if Arn::from_str(secret_id).is_err() {
+        return None;
+    }
+
+    // Escape the character class syntax, leaving the wildcards intact
+    let pattern = secret_id.replace('[', "[[]");
+
+    if pattern.contains(['*', '?']) {
+        return Some(pattern);
     }
+
+    Some(format!("{pattern}-??????"))
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
-/// with the version ID of `version_id`
+/// Get a secret in `region` that the `secret_id` refers to and there
+/// is a version with the version ID of `version_id`
 pub async fn get_secret_by_version_id(
     db: impl DbExecutor<'_>,
+    region: &str,
     secret_id: &str,
     version_id: &str,
 ) -> DbResult<Option<StoredSecret>> {
-    let partial_arn = make_partial_arn_like_query(secret_id);
-
-    sqlx::query_as(
+    let query = format!(
         r#"
         SELECT
             "secret".*,
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."encrypted_data_key",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -458,36 +945,34 @@ pub async fn get_secret_by_version_id(
         JOIN "secrets_versions" "secret_version"
             ON "secret_version"."secret_arn" = "secret"."arn"
             AND "secret_version"."version_id" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."arn" = ({RESOLVE_SECRET_ARN})
         LIMIT 1;
-    "#,
-    )
-    .bind(version_id)
-    .bind(secret_id)
-    .bind(secret_id)
-    .bind(partial_arn.is_some())
-    .bind(partial_arn)
-    .fetch_optional(db)
-    .await
+    "#
+    );
+
+    let query = sqlx::query_as(&query).bind(version_id);
+
+    bind_resolve_secret_arn(query, region, secret_id)
+        .fetch_optional(db)
+        .await
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
-/// in `version_stage`
+/// Get a secret in `region` that the `secret_id` refers to and there
+/// is a version in `version_stage`
 pub async fn get_secret_by_version_stage(
     db: impl DbExecutor<'_>,
+    region: &str,
     secret_id: &str,
     version_stage: &str,
 ) -> DbResult<Option<StoredSecret>> {
-    let partial_arn = make_partial_arn_like_query(secret_id);
-
-    sqlx::query_as(
+    let query = format!(
         r#"
         SELECT
             "secret".*,
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."encrypted_data_key",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -515,38 +1000,36 @@ pub async fn get_secret_by_version_stage(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."arn" = ({RESOLVE_SECRET_ARN})
         ORDER BY "secret_version"."created_at" DESC
         LIMIT 1;
-    "#,
-    )
-    .bind(version_stage)
-    .bind(secret_id)
-    .bind(secret_id)
-    .bind(partial_arn.is_some())
-    .bind(partial_arn)
-    .fetch_optional(db)
-    .await
+    "#
+    );
+
+    let query = sqlx::query_as(&query).bind(version_stage);
+
+    bind_resolve_secret_arn(query, region, secret_id)
+        .fetch_optional(db)
+        .await
 }
 
-/// Get a secret where the name OR arn matches the `secret_id` and there is a version
-/// in `version_stage` with the version ID `version_id`
+/// Get a secret in `region` that the `secret_id` refers to and there
+/// is a version in `version_stage` with the version ID `version_id`
 pub async fn get_secret_by_version_stage_and_id(
     db: impl DbExecutor<'_>,
+    region: &str,
     secret_id: &str,
     version_id: &str,
     version_stage: &str,
 ) -> DbResult<Option<StoredSecret>> {
-    let partial_arn = make_partial_arn_like_query(secret_id);
-
-    sqlx::query_as(
+    let query = format!(
         r#"
         SELECT
             "secret".*,
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."encrypted_data_key",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -574,25 +1057,29 @@ pub async fn get_secret_by_version_stage_and_id(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = ?
-        WHERE "secret"."name" = ? OR "secret"."arn" = ?
-            OR (? = TRUE AND "secret"."arn" LIKE ?)
+        WHERE "secret"."arn" = ({RESOLVE_SECRET_ARN})
         LIMIT 1;
-    "#,
-    )
-    .bind(version_id)
-    .bind(version_stage)
-    .bind(secret_id)
-    .bind(secret_id)
-    .bind(partial_arn.is_some())
-    .bind(partial_arn)
-    .fetch_optional(db)
-    .await
+    "#
+    );
+
+    let query = sqlx::query_as(&query).bind(version_id).bind(version_stage);
+
+    bind_resolve_secret_arn(query, region, secret_id)
+        .fetch_optional(db)
+        .await
 }
 
+/// Expression for the region of the primary of a secret, replicas use the region
+/// of their primary secret and all other secrets are the primary in their own region
+const PRIMARY_REGION_EXPRESSION: &str = r#"COALESCE((
+    SELECT "primary"."region" FROM "secrets" "primary"
+    WHERE "primary"."arn" = "secret"."primary_arn"
+), "secret"."region")"#;
+
 /// Generates the WHERE portion of a filtered query appending it to `query` returning
 /// a list of parameters that need to be bound to the query
 ///
-/// Assumes `query` has already specified the WHERE and at least one clause like 1=1
+/// Assumes `query` has already specified the WHERE and at least one clause
 fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<String> {
     let mut bound_values: Vec<String> = Vec::new();
 
@@ -738,6 +1225,17 @@ fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<Strin
                 query.push_str("))");
             }
 
+            "primary-region" => {
+                query.push_str(" AND (");
+                write_condition_cs(
+                    query,
+                    &mut bound_values,
+                    PRIMARY_REGION_EXPRESSION,
+                    &filter.values,
+                );
+                query.push(')');
+            }
+
             "tag-value" => {
                 query.push_str(
                     r#" AND EXISTS (
@@ -762,25 +1260,28 @@ fn push_secret_filter_where(filters: &[Filter], query: &mut String) -> Vec<Strin
     bound_values
 }
 
-/// Get secrets filtered using the provided `filters`, will only include secrets planned for deletion
-/// if `include_planned_deletions` is true.
+/// Get secrets in `region` filtered using the provided `filters`, will only include secrets planned
+/// for deletion if `include_planned_deletions` is true.
 ///
 /// Paginated using the provided `limit` and `offset` use `asc` to order the results by creation date
 /// in ascending order, false to order descending
 pub async fn get_secrets_by_filter(
     db: impl DbExecutor<'_>,
+    region: &str,
     filters: &[Filter],
     include_planned_deletions: bool,
     limit: i64,
     offset: i64,
     asc: bool,
 ) -> DbResult<Vec<StoredSecretWithVersionStages>> {
-    let mut query = r#"
+    let mut query = format!(
+        r#"
         SELECT
             "secret".*,
             "secret_version"."version_id",
             "secret_version"."secret_string",
             "secret_version"."secret_binary",
+            "secret_version"."encrypted_data_key",
             "secret_version"."created_at" AS "version_created_at",
             "secret_version"."last_accessed_at" AS "version_last_accessed_at",
             COALESCE((
@@ -821,16 +1322,23 @@ pub async fn get_secrets_by_filter(
                     AND "version_stage"."version_id" = "secret_version"."version_id"
                     AND "version_stage"."value" = 'AWSCURRENT'
                 WHERE "secret_version"."secret_arn" = "secret"."arn"
-            ), '[]') AS "versions"
+            ), '[]') AS "versions",
+            CASE
+                WHEN "secret"."primary_arn" IS NOT NULL OR EXISTS (
+                    SELECT 1 FROM "secrets_replicas" "replica"
+                    WHERE "replica"."secret_arn" = "secret"."arn"
+                )
+                THEN {PRIMARY_REGION_EXPRESSION}
+            END AS "primary_region"
         FROM "secrets" "secret"
         JOIN "secrets_versions" "secret_version" ON "secret_version"."secret_arn" = "secret"."arn"
         JOIN "secret_version_stages" "version_stage"
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = 'AWSCURRENT'
-        WHERE 1=1
+        WHERE "secret"."region" = ?
     "#
-    .to_string();
+    );
 
     if !include_planned_deletions {
         query.push_str(r#" AND "secret"."scheduled_delete_at" IS NULL "#);
@@ -848,7 +1356,7 @@ pub async fn get_secrets_by_filter(
     // Apply pagination
     query.push_str(r#"LIMIT ? OFFSET ?"#);
 
-    let mut query = sqlx::query_as(&query);
+    let mut query = sqlx::query_as(&query).bind(region);
 
     for bound in bound_values {
         query = query.bind(bound);
@@ -857,10 +1365,11 @@ pub async fn get_secrets_by_filter(
     query.bind(limit).bind(offset).fetch_all(db).await
 }
 
-/// Get the total number of secrets filtered using the provided `filters`, will only include secrets planned for deletion
-/// if `include_planned_deletions` is true.
+/// Get the total number of secrets in `region` filtered using the provided `filters`, will only include
+/// secrets planned for deletion if `include_planned_deletions` is true.
 pub async fn get_secrets_count_by_filter(
     db: impl DbExecutor<'_>,
+    region: &str,
     filters: &[Filter],
     include_planned_deletions: bool,
 ) -> DbResult<i64> {
@@ -872,7 +1381,7 @@ pub async fn get_secrets_count_by_filter(
             ON "version_stage"."secret_arn" = "secret_version"."secret_arn"
             AND "version_stage"."version_id" = "secret_version"."version_id"
             AND "version_stage"."value" = 'AWSCURRENT'
-        WHERE 1=1
+        WHERE "secret"."region" = ?
     "#
     .to_string();
 
@@ -881,7 +1390,7 @@ pub async fn get_secrets_count_by_filter(
     }
 
     let bound_values = push_secret_filter_where(filters, &mut query);
-    let mut query = sqlx::query_as(&query);
+    let mut query = sqlx::query_as(&query).bind(region);
 
     for bound in bound_values {
         query = query.bind(bound);
@@ -891,6 +1400,32 @@ pub async fn get_secrets_count_by_filter(
     Ok(count)
 }
 
+/// Get the names of the secrets in `region` that start with `prefix`,
+/// excluding secrets scheduled for deletion
+pub async fn get_secret_names_by_prefix(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    prefix: &str,
+) -> DbResult<Vec<String>> {
+    let names: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT "secret"."name"
+        FROM "secrets" "secret"
+        WHERE "secret"."region" = ?
+            AND substr("secret"."name", 1, length(?)) = ?
+            AND "secret"."scheduled_delete_at" IS NULL
+        ORDER BY "secret"."name" ASC
+    "#,
+    )
+    .bind(region)
+    .bind(prefix)
+    .bind(prefix)
+    .fetch_all(db)
+    .await?;
+
+    Ok(names.into_iter().map(|(name,)| name).collect())
+}
+
 /// Get all versions of a secret
 pub async fn get_secret_versions(
     db: impl DbExecutor<'_>,
@@ -951,6 +1486,82 @@ pub async fn count_secret_versions(
     Ok(count)
 }
 
+/// Get the number of versions for the secret that can't be purged by
+/// [delete_excess_secret_versions], these are versions that either have an
+/// attached version stage or were created within the last 24h
+pub async fn count_retained_secret_versions(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+) -> DbResult<i64> {
+    let cutoff = version_purge_cutoff()?;
+
+    let (count,): (i64,) = sqlx::query_as(
+        r#"
+           SELECT COUNT(*)
+           FROM "secrets_versions" "secret_version"
+           WHERE "secret_version"."secret_arn" = ?
+               AND (
+                   "secret_version"."created_at" >= ?
+                   OR EXISTS (
+                       SELECT 1
+                       FROM "secret_version_stages" "version_stage"
+                       WHERE "version_stage"."secret_arn" = "secret_version"."secret_arn"
+                           AND "version_stage"."version_id" = "secret_version"."version_id"
+                   )
+               )
+       "#,
+    )
+    .bind(secret_arn)
+    .bind(cutoff)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count)
+}
+
+/// Get the total number of secrets owned by `account_id` across all regions,
+/// including secrets scheduled for deletion
+pub async fn count_account_secrets(db: impl DbExecutor<'_>, account_id: &str) -> DbResult<i64> {
+    let (count,): (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM "secrets" WHERE "arn" LIKE ?"#)
+        .bind(format!("arn:%:secretsmanager:%:{account_id}:secret:%"))
+        .fetch_one(db)
+        .await?;
+
+    Ok(count)
+}
+
+/// Get the number of version stages attached to a version of a secret
+pub async fn count_secret_version_stages(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+    version_id: &str,
+) -> DbResult<i64> {
+    let (count,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*)
+        FROM "secret_version_stages"
+        WHERE "secret_arn" = ? AND "version_id" = ?
+        "#,
+    )
+    .bind(secret_arn)
+    .bind(version_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(count)
+}
+
+/// Get the keys of the tags attached to a secret
+pub async fn get_secret_tag_keys(
+    db: impl DbExecutor<'_>,
+    secret_arn: &str,
+) -> DbResult<Vec<String>> {
+    sqlx::query_scalar(r#"SELECT "key" FROM "secrets_tags" WHERE "secret_arn" = ?"#)
+        .bind(secret_arn)
+        .fetch_all(db)
+        .await
+}
+
 /// Get a versions page for a secret
 ///
 /// Does not include versions without at least one attached version stage
@@ -1011,12 +1622,7 @@ pub async fn get_secret_versions_page(
 ///
 /// Only allowed to delete versions that don't have a stage
 pub async fn delete_excess_secret_versions(db: impl DbExecutor<'_>) -> DbResult<()> {
-    let now = Utc::now();
-    let cutoff = now.checked_sub_days(Days::new(1)).ok_or_else(|| {
-        DbErr::Encode(Box::new(std::io::Error::other(
-            "failed to create a future timestamp",
-        )))
-    })?;
+    let cutoff = version_purge_cutoff()?;
 
     sqlx::query(
         r#"
@@ -1027,9 +1633,9 @@ pub async fn delete_excess_secret_versions(db: impl DbExecutor<'_>) -> DbResult<
                     PARTITION BY "secret_version"."secret_arn"
                     ORDER BY "secret_version"."created_at" DESC
                 ) AS "row_number"
-            FROM "secret_versions" "secret_version"
+            FROM "secrets_versions" "secret_version"
         )
-        DELETE FROM "secret_versions" "secret_version"
+        DELETE FROM "secrets_versions" AS "secret_version"
         WHERE ("secret_arn", "version_id") IN (
             SELECT "secret_arn", "version_id"
             FROM "ranked_versions"
@@ -1050,3 +1656,13 @@ pub async fn delete_excess_secret_versions(db: impl DbExecutor<'_>) -> DbResult<
 
     Ok(())
 }
+
+/// Versions created before this timestamp without a stage are eligible to be
+/// purged by [delete_excess_secret_versions]
+fn version_purge_cutoff() -> DbResult<DateTime<Utc>> {
+    Utc::now().checked_sub_days(Days::new(1)).ok_or_else(|| {
+        DbErr::Encode(Box::new(std::io::Error::other(
+            "failed to create a future timestamp",
+        )))
+    })
+}
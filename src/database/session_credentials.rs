@@ -0,0 +1,90 @@
+use crate::database::{DbExecutor, DbResult};
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+
+#[derive(Clone, FromRow)]
+pub struct StoredSessionCredential {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    pub session_token: String,
+    pub role_arn: String,
+    pub session_name: String,
+    pub source_access_key_id: String,
+    pub policy: Option<String>,
+    pub source_policies: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct CreateSessionCredential {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    pub session_token: String,
+    pub role_arn: String,
+    pub session_name: String,
+    pub source_access_key_id: String,
+    pub policy: Option<String>,
+    pub source_policies: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Store new temporary session credentials
+pub async fn create_session_credential(
+    db: impl DbExecutor<'_>,
+    create: CreateSessionCredential,
+) -> DbResult<StoredSessionCredential> {
+    sqlx::query_as(
+        r#"
+        INSERT INTO "session_credentials" (
+            "access_key_id",
+            "access_key_secret",
+            "session_token",
+            "role_arn",
+            "session_name",
+            "source_access_key_id",
+            "policy",
+            "source_policies",
+            "expires_at",
+            "created_at"
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(create.access_key_id)
+    .bind(create.access_key_secret)
+    .bind(create.session_token)
+    .bind(create.role_arn)
+    .bind(create.session_name)
+    .bind(create.source_access_key_id)
+    .bind(create.policy)
+    .bind(create.source_policies)
+    .bind(create.expires_at)
+    .bind(Utc::now())
+    .fetch_one(db)
+    .await
+}
+
+/// Get session credentials by their temporary `access_key_id`
+pub async fn get_session_credential(
+    db: impl DbExecutor<'_>,
+    access_key_id: &str,
+) -> DbResult<Option<StoredSessionCredential>> {
+    sqlx::query_as(r#"SELECT * FROM "session_credentials" WHERE "access_key_id" = ?"#)
+        .bind(access_key_id)
+        .fetch_optional(db)
+        .await
+}
+
+/// Delete session credentials that expired before the provided date
+pub async fn delete_expired_session_credentials(
+    db: impl DbExecutor<'_>,
+    before: DateTime<Utc>,
+) -> DbResult<()> {
+    sqlx::query(r#"DELETE FROM "session_credentials" WHERE "expires_at" < ?"#)
+        .bind(before)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
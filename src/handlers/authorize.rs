@@ -0,0 +1,281 @@
+use crate::{
+    database::{
+        DbPool,
+        policies::get_secret_policy,
+        secrets::{
+            StoredSecret, StoredSecretResource, StoredSecretWithVersionStages, StoredVersionTags,
+            get_secret_resource,
+        },
+    },
+    handlers::{
+        error::{AccessDeniedException, AwsErrorResponse, InternalServiceError},
+        models::Arn,
+    },
+    middleware::aws_sig_v4::CallerIdentity,
+    policy::evaluate::{
+        ConditionContext, PRINCIPAL_ARN_KEY, PolicyDecision, PolicyRequest,
+        RESOURCE_TAG_KEY_PREFIX, SECRET_NAME_KEY, evaluate_policy,
+    },
+};
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+
+/// Authorizes requests against the identity policy of the calling access key
+/// and the resource policy of the secret the request targets
+#[derive(Default)]
+pub struct Authorizer {
+    /// Whether the resource policies of secrets are enforced
+    pub enforce_resource_policies: bool,
+}
+
+impl Authorizer {
+    /// Authorize the `caller` performing the `action` on the secret `secret_id`,
+    /// requests that don't target a secret are authorized against the `*`
+    /// resource
+    ///
+    /// Any explicit deny rejects the request. Callers with an identity policy
+    /// must be allowed by either their identity policy or the resource policy,
+    /// callers without one must be allowed by the resource policy when the
+    /// secret has a resource policy that is enforced
+    pub async fn authorize(
+        &self,
+        db: &DbPool,
+        caller: &CallerIdentity,
+        action: &str,
+        secret_id: Option<&str>,
+    ) -> Result<(), Response> {
+        let has_identity_policy = caller.identity_policies().next().is_some();
+        let enforce_resource_policy = self.enforce_resource_policies && secret_id.is_some();
+
+        // Nothing to evaluate for the request
+        if !has_identity_policy && !enforce_resource_policy {
+            return Ok(());
+        }
+
+        let mut context = ConditionContext::default();
+        context.insert(PRINCIPAL_ARN_KEY, caller.principal_arn.as_str());
+
+        let mut resource = "*".to_string();
+
+        if let Some(secret_id) = secret_id {
+            let secret = get_secret_resource(db, &caller.region, secret_id)
+                .await
+                .map_err(|error| {
+                    tracing::error!(?error, "failed to get secret");
+                    AwsErrorResponse(InternalServiceError).into_response()
+                })?;
+
+            match secret {
+                Some(secret) => {
+                    let allowed = self
+                        .is_secret_allowed(db, caller, action, SecretResource::from(&secret))
+                        .await?;
+
+                    if !allowed {
+                        return Err(AwsErrorResponse(AccessDeniedException).into_response());
+                    }
+
+                    return Ok(());
+                }
+                // Secret doesn't exist (yet), authorize against the secret
+                // that the request would create or target
+                None if secret_id.starts_with("arn:") => {
+                    resource = secret_id.to_string();
+                }
+                None => {
+                    context.insert(SECRET_NAME_KEY, secret_id);
+                    resource = Arn::new(
+                        "secretsmanager",
+                        &caller.region,
+                        &caller.account_id,
+                        &format!("secret:{secret_id}"),
+                    )
+                    .to_string();
+                }
+            }
+        }
+
+        if !is_allowed(caller, action, &resource, &context, None) {
+            return Err(AwsErrorResponse(AccessDeniedException).into_response());
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the `caller` is allowed to perform the `action` on the
+    /// existing `secret`, used by requests that read multiple secrets to
+    /// authorize each secret separately
+    ///
+    /// Policies are evaluated in the same way as [Authorizer::authorize],
+    /// failures unrelated to the policies are provided as the error
+    pub async fn is_secret_allowed(
+        &self,
+        db: &DbPool,
+        caller: &CallerIdentity,
+        action: &str,
+        secret: SecretResource<'_>,
+    ) -> Result<bool, Response> {
+        // Nothing to evaluate for the secret
+        if caller.identity_policies().next().is_none() && !self.enforce_resource_policies {
+            return Ok(true);
+        }
+
+        let resource_policy = match self.enforce_resource_policies {
+            true => get_resource_policy(db, secret.arn).await?,
+            false => None,
+        };
+
+        let mut context = ConditionContext::default();
+        context.insert(PRINCIPAL_ARN_KEY, caller.principal_arn.as_str());
+        context.insert(SECRET_NAME_KEY, secret.name);
+        for tag in secret.tags {
+            context.insert(
+                &format!("{RESOURCE_TAG_KEY_PREFIX}{}", tag.key),
+                tag.value.as_str(),
+            );
+        }
+
+        Ok(is_allowed(
+            caller,
+            action,
+            secret.arn,
+            &context,
+            resource_policy.as_ref(),
+        ))
+    }
+}
+
+/// Secret that a request is authorized against
+#[derive(Clone, Copy)]
+pub struct SecretResource<'a> {
+    pub arn: &'a str,
+    pub name: &'a str,
+    pub tags: &'a [StoredVersionTags],
+}
+
+impl<'a> From<&'a StoredSecret> for SecretResource<'a> {
+    fn from(secret: &'a StoredSecret) -> Self {
+        Self {
+            arn: &secret.arn,
+            name: &secret.name,
+            tags: &secret.version_tags,
+        }
+    }
+}
+
+impl<'a> From<&'a StoredSecretResource> for SecretResource<'a> {
+    fn from(secret: &'a StoredSecretResource) -> Self {
+        Self {
+            arn: &secret.arn,
+            name: &secret.name,
+            tags: &secret.version_tags,
+        }
+    }
+}
+
+impl<'a> From<&'a StoredSecretWithVersionStages> for SecretResource<'a> {
+    fn from(secret: &'a StoredSecretWithVersionStages) -> Self {
+        Self {
+            arn: &secret.arn,
+            name: &secret.name,
+            tags: &secret.version_tags,
+        }
+    }
+}
+
+/// Evaluate the identity policy of the `caller` and the `resource_policy` for
+/// the `caller` performing the `action` on the `resource`
+fn is_allowed(
+    caller: &CallerIdentity,
+    action: &str,
+    resource: &str,
+    context: &ConditionContext,
+    resource_policy: Option<&Value>,
+) -> bool {
+    let request = PolicyRequest {
+        principal_arn: &caller.principal_arn,
+        action,
+        resource,
+        context,
+    };
+
+    let identity_decision = identity_decision(caller, &request);
+    let resource_decision = resource_policy.map(|policy| evaluate_policy(policy, &request));
+
+    let allowed = match (identity_decision, resource_decision) {
+        (Some(PolicyDecision::Deny), _) | (_, Some(PolicyDecision::Deny)) => false,
+        (Some(identity), Some(resource)) => {
+            identity == PolicyDecision::Allow || resource == PolicyDecision::Allow
+        }
+        (Some(decision), None) | (None, Some(decision)) => decision == PolicyDecision::Allow,
+        (None, None) => true,
+    };
+
+    if !allowed {
+        tracing::debug!(
+            ?identity_decision,
+            ?resource_decision,
+            principal_arn = %caller.principal_arn,
+            action,
+            resource,
+            "policy denied request"
+        );
+    }
+
+    allowed
+}
+
+/// Check whether the identity policies of the `caller` allow the `action` on
+/// the `resource`, callers without an identity policy are not restricted
+pub fn identity_policy_allows(caller: &CallerIdentity, action: &str, resource: &str) -> bool {
+    let mut context = ConditionContext::default();
+    context.insert(PRINCIPAL_ARN_KEY, caller.principal_arn.as_str());
+
+    let request = PolicyRequest {
+        principal_arn: &caller.principal_arn,
+        action,
+        resource,
+        context: &context,
+    };
+
+    identity_decision(caller, &request).is_none_or(|decision| decision == PolicyDecision::Allow)
+}
+
+/// Evaluate the identity policies of the `caller` for the `request`, the
+/// request is only allowed when each of the policies allows it
+///
+/// Sessions are restricted by both the session policy and the identity
+/// policies of the caller that created the session
+fn identity_decision(
+    caller: &CallerIdentity,
+    request: &PolicyRequest<'_>,
+) -> Option<PolicyDecision> {
+    caller
+        .identity_policies()
+        .map(|policy| evaluate_policy(policy, request))
+        .reduce(|decision, next| match (decision, next) {
+            (PolicyDecision::Deny, _) | (_, PolicyDecision::Deny) => PolicyDecision::Deny,
+            (PolicyDecision::Allow, PolicyDecision::Allow) => PolicyDecision::Allow,
+            _ => PolicyDecision::NotApplicable,
+        })
+}
+
+/// Get the parsed resource policy attached to the secret `secret_arn`
+async fn get_resource_policy(db: &DbPool, secret_arn: &str) -> Result<Option<Value>, Response> {
+    let policy = match get_secret_policy(db, secret_arn).await {
+        Ok(Some(value)) => value,
+        Ok(None) => return Ok(None),
+        Err(error) => {
+            tracing::error!(?error, "failed to get secret policy");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+    };
+
+    match serde_json::from_str(&policy) {
+        Ok(value) => Ok(Some(value)),
+        Err(error) => {
+            tracing::error!(?error, "failed to parse stored secret policy");
+            Err(AwsErrorResponse(InternalServiceError).into_response())
+        }
+    }
+}
@@ -8,21 +8,32 @@ use crate::{
     },
     handlers::{
         Handler,
+        authorize::{Authorizer, SecretResource},
         error::{
-            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            AccessDeniedException, AwsError, AwsErrorResponse, DecryptionFailure,
+            InternalServiceError, InvalidNextTokenException, InvalidRequestException,
             ResourceNotFoundException,
         },
-        models::{APIErrorType, Filter, PaginationToken},
+        models::{APIErrorType, Filter, PaginationToken, serialize_secret_binary},
     },
-    utils::date::datetime_to_f64,
+    kms::{KmsError, decrypt_secret_value},
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::join;
 
+/// Policy action each secret read by the batch is authorized for
+const GET_SECRET_VALUE_ACTION: &str = "secretsmanager:GetSecretValue";
+
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_BatchGetSecretValue.html
-pub struct BatchGetSecretValueHandler;
+pub struct BatchGetSecretValueHandler {
+    /// Authorizer for reading each of the secrets in the batch
+    pub authorizer: Arc<Authorizer>,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct BatchGetSecretValueRequest {
@@ -58,12 +69,12 @@ struct SecretValueEntry {
     #[serde(rename = "ARN")]
     arn: String,
     #[serde(rename = "CreatedDate")]
-    created_date: f64,
+    created_date: Timestamp,
     #[serde(rename = "Name")]
     name: String,
     #[serde(rename = "SecretString")]
     secret_string: Option<String>,
-    #[serde(rename = "SecretBinary")]
+    #[serde(rename = "SecretBinary", serialize_with = "serialize_secret_binary")]
     secret_binary: Option<String>,
     #[serde(rename = "VersionId")]
     version_id: String,
@@ -87,7 +98,12 @@ impl Handler for BatchGetSecretValueHandler {
     type Response = BatchGetSecretValueResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let mut errors: Vec<APIErrorType> = Vec::new();
         let mut secret_values: Vec<SecretValueEntry> = Vec::new();
         let mut next_token: Option<String> = None;
@@ -104,11 +120,19 @@ impl Handler for BatchGetSecretValueHandler {
 
                 let (limit, offset) = pagination_token
                     .as_query_parts()
-                    .ok_or_else(|| AwsErrorResponse(InvalidRequestException).into_response())?;
+                    .ok_or_else(|| AwsErrorResponse(InvalidNextTokenException).into_response())?;
 
                 let (secrets, count) = join!(
-                    get_secrets_by_filter(db, &filters, false, limit, offset, false),
-                    get_secrets_count_by_filter(db, &filters, false),
+                    get_secrets_by_filter(
+                        db,
+                        &context.caller.region,
+                        &filters,
+                        false,
+                        limit,
+                        offset,
+                        false
+                    ),
+                    get_secrets_count_by_filter(db, &context.caller.region, &filters, false),
                 );
 
                 let secrets = secrets.map_err(|error| {
@@ -125,10 +149,40 @@ impl Handler for BatchGetSecretValueHandler {
                     .get_next_page(count)
                     .map(|value| value.to_string());
 
-                for secret in secrets {
+                for mut secret in secrets {
+                    let allowed = self
+                        .authorizer
+                        .is_secret_allowed(
+                            db,
+                            &context.caller,
+                            GET_SECRET_VALUE_ACTION,
+                            SecretResource::from(&secret),
+                        )
+                        .await?;
+
+                    if !allowed {
+                        errors.push(access_denied_error(secret.arn));
+                        continue;
+                    }
+
+                    if let Err(error) = decrypt_secret_value(
+                        db,
+                        secret.encrypted_data_key.as_deref(),
+                        &mut secret.secret_string,
+                        &mut secret.secret_binary,
+                    )
+                    .await
+                    {
+                        errors.push(
+                            decryption_error(error, secret.arn)
+                                .map_err(IntoResponse::into_response)?,
+                        );
+                        continue;
+                    }
+
                     secret_values.push(SecretValueEntry {
                         arn: secret.arn,
-                        created_date: datetime_to_f64(secret.created_at),
+                        created_date: Timestamp(secret.created_at),
                         name: secret.name,
                         secret_string: secret.secret_string,
                         secret_binary: secret.secret_binary,
@@ -141,15 +195,18 @@ impl Handler for BatchGetSecretValueHandler {
             // Finding secrets from a list of ARNs / names
             (None, Some(secret_id_list)) => {
                 for secret_id in secret_id_list {
-                    let secret = match get_secret_latest_version(db, &secret_id).await {
-                        Ok(value) => value,
-                        Err(error) => {
-                            tracing::error!(?error, %secret_id, "failed to load secret");
-                            return Err(AwsErrorResponse(InternalServiceError).into_response());
-                        }
-                    };
+                    let secret =
+                        match get_secret_latest_version(db, &context.caller.region, &secret_id)
+                            .await
+                        {
+                            Ok(value) => value,
+                            Err(error) => {
+                                tracing::error!(?error, %secret_id, "failed to load secret");
+                                return Err(AwsErrorResponse(InternalServiceError).into_response());
+                            }
+                        };
 
-                    let secret = match secret {
+                    let mut secret = match secret {
                         Some(value) => value,
                         None => {
                             errors.push(APIErrorType {
@@ -161,6 +218,21 @@ impl Handler for BatchGetSecretValueHandler {
                         }
                     };
 
+                    let allowed = self
+                        .authorizer
+                        .is_secret_allowed(
+                            db,
+                            &context.caller,
+                            GET_SECRET_VALUE_ACTION,
+                            SecretResource::from(&secret),
+                        )
+                        .await?;
+
+                    if !allowed {
+                        errors.push(access_denied_error(secret_id));
+                        continue;
+                    }
+
                     if let Err(error) =
                         update_secret_version_last_accessed(db, &secret.arn, &secret.version_id)
                             .await
@@ -169,9 +241,24 @@ impl Handler for BatchGetSecretValueHandler {
                         return Err(AwsErrorResponse(InternalServiceError).into_response());
                     }
 
+                    if let Err(error) = decrypt_secret_value(
+                        db,
+                        secret.encrypted_data_key.as_deref(),
+                        &mut secret.secret_string,
+                        &mut secret.secret_binary,
+                    )
+                    .await
+                    {
+                        errors.push(
+                            decryption_error(error, secret_id)
+                                .map_err(IntoResponse::into_response)?,
+                        );
+                        continue;
+                    }
+
                     secret_values.push(SecretValueEntry {
                         arn: secret.arn,
-                        created_date: datetime_to_f64(secret.created_at),
+                        created_date: Timestamp(secret.created_at),
                         name: secret.name,
                         secret_string: secret.secret_string,
                         secret_binary: secret.secret_binary,
@@ -184,7 +271,9 @@ impl Handler for BatchGetSecretValueHandler {
             // Must only specify one or the other and not both
             // and cannot pick neither
             (Some(_), Some(_)) | (None, None) => {
-                return Err(AwsErrorResponse(InvalidRequestException).into_response());
+                let error = InvalidRequestException
+                    .with_message("You must provide either SecretIdList or Filters, but not both.");
+                return Err(AwsErrorResponse(error).into_response());
             }
         }
 
@@ -195,3 +284,32 @@ impl Handler for BatchGetSecretValueHandler {
         })
     }
 }
+
+/// Create the error entry for a secret `secret_id` the caller is not allowed to read
+fn access_denied_error(secret_id: String) -> APIErrorType {
+    APIErrorType {
+        error_code: Some(AccessDeniedException::TYPE.to_string()),
+        message: Some(AccessDeniedException::MESSAGE.to_string()),
+        secret_id: Some(secret_id),
+    }
+}
+
+/// Create the error entry for a secret `secret_id` that failed to decrypt, failing
+/// the whole request when the failure was caused by the database
+fn decryption_error(
+    error: KmsError,
+    secret_id: String,
+) -> Result<APIErrorType, AwsErrorResponse<InternalServiceError>> {
+    if let KmsError::Db(error) = error {
+        tracing::error!(?error, %secret_id, "failed to decrypt secret value");
+        return Err(AwsErrorResponse(InternalServiceError));
+    }
+
+    tracing::debug!(?error, %secret_id, "failed to decrypt secret value");
+
+    Ok(APIErrorType {
+        error_code: Some(DecryptionFailure::TYPE.to_string()),
+        message: Some(DecryptionFailure::MESSAGE.to_string()),
+        secret_id: Some(secret_id),
+    })
+}
@@ -0,0 +1,110 @@
+use crate::{
+    database::{
+        DbPool,
+        secrets::{cancel_secret_rotation, get_secret_by_version_stage, get_secret_latest_version},
+    },
+    handlers::{
+        Handler,
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response, scheduled_for_deletion_response,
+        },
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_CancelRotateSecret.html
+pub struct CancelRotateSecretHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CancelRotateSecretRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct CancelRotateSecretResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "VersionId")]
+    version_id: Option<String>,
+}
+
+impl Handler for CancelRotateSecretHandler {
+    type Request = CancelRotateSecretRequest;
+    type Response = CancelRotateSecretResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // Secret is scheduled for deletion
+        if secret.scheduled_delete_at.is_some() {
+            return Err(scheduled_for_deletion_response());
+        }
+
+        // Version of any rotation that was still in progress
+        let pending = match get_secret_by_version_stage(
+            db,
+            &context.caller.region,
+            &secret.arn,
+            "AWSPENDING",
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get pending secret version");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        if let Err(error) = cancel_secret_rotation(db, &secret.arn).await {
+            tracing::error!(?error, "failed to cancel secret rotation");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
+        Ok(CancelRotateSecretResponse {
+            arn: secret.arn,
+            name: secret.name,
+            version_id: pending.map(|pending| pending.version_id),
+        })
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    database::{
+        DbPool,
+        access_keys::{CreateAccessKey, create_access_key},
+    },
+    handlers::{
+        Handler,
+        error::{
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidParameterException,
+            malformed_policy_response,
+        },
+        models::{AccessKeyStatus, Arn},
+    },
+    middleware::aws_sig_v4::RequestContext,
+    middleware::aws_sig_v4::default_principal_arn,
+    policy::validate_identity_policy,
+    utils::{
+        access_key::{generate_access_key_id, generate_access_key_secret},
+        date::{Timestamp, f64_to_datetime},
+    },
+};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_CreateAccessKey.html
+pub struct CreateAccessKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateAccessKeyRequest {
+    #[serde(rename = "Name")]
+    #[garde(length(min = 1, max = 128))]
+    name: String,
+
+    #[serde(rename = "PrincipalArn")]
+    #[garde(inner(length(min = 20, max = 2048)))]
+    principal_arn: Option<String>,
+
+    #[serde(rename = "ExpirationDate")]
+    #[garde(skip)]
+    expiration_date: Option<f64>,
+
+    #[serde(rename = "Policy")]
+    #[garde(inner(length(min = 1, max = 20480)))]
+    policy: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateAccessKeyResponse {
+    #[serde(rename = "AccessKey")]
+    access_key: AccessKey,
+}
+
+#[derive(Serialize)]
+pub struct AccessKey {
+    #[serde(rename = "AccessKeyId")]
+    access_key_id: String,
+    #[serde(rename = "SecretAccessKey")]
+    secret_access_key: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "PrincipalArn")]
+    principal_arn: String,
+    #[serde(rename = "Status")]
+    status: AccessKeyStatus,
+    #[serde(rename = "CreateDate")]
+    create_date: Timestamp,
+    #[serde(rename = "ExpirationDate")]
+    expiration_date: Option<Timestamp>,
+    #[serde(rename = "Policy")]
+    policy: Option<String>,
+}
+
+impl Handler for CreateAccessKeyHandler {
+    type Request = CreateAccessKeyRequest;
+    type Response = CreateAccessKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(name = %request.name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let expires_at = match request.expiration_date {
+            Some(value) => {
+                let expires_at = f64_to_datetime(value).ok_or_else(|| {
+                    let error = InvalidParameterException
+                        .with_message("ExpirationDate is not a valid date.");
+                    AwsErrorResponse(error).into_response()
+                })?;
+
+                // Access key would already be expired
+                if expires_at <= Utc::now() {
+                    let error = InvalidParameterException
+                        .with_message("ExpirationDate must be in the future.");
+                    return Err(AwsErrorResponse(error).into_response());
+                }
+
+                Some(expires_at)
+            }
+            None => None,
+        };
+
+        // Access keys are created within the account of the caller unless the
+        // principal belongs to another account
+        let principal_arn = match request.principal_arn {
+            Some(principal_arn) => {
+                if !Arn::from_str(&principal_arn).is_ok_and(|arn| arn.has_account_id()) {
+                    let error = InvalidParameterException
+                        .with_message("PrincipalArn must be an ARN containing an account ID.");
+                    return Err(AwsErrorResponse(error).into_response());
+                }
+
+                principal_arn
+            }
+            None => default_principal_arn(&context.caller.account_id, &request.name),
+        };
+
+        if let Some(policy) = &request.policy {
+            validate_identity_policy(policy).map_err(malformed_policy_response)?;
+        }
+
+        let access_key = create_access_key(
+            db,
+            CreateAccessKey {
+                access_key_id: generate_access_key_id(),
+                access_key_secret: generate_access_key_secret(),
+                name: request.name,
+                principal_arn: Some(principal_arn),
+                expires_at,
+                policy: request.policy,
+            },
+        )
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to create access key");
+            AwsErrorResponse(InternalServiceError).into_response()
+        })?;
+
+        let principal_arn = access_key
+            .principal_arn
+            .unwrap_or_else(|| default_principal_arn(&context.caller.account_id, &access_key.name));
+
+        Ok(CreateAccessKeyResponse {
+            access_key: AccessKey {
+                access_key_id: access_key.access_key_id,
+                secret_access_key: access_key.access_key_secret,
+                name: access_key.name,
+                principal_arn,
+                status: AccessKeyStatus::from_enabled(access_key.enabled),
+                create_date: Timestamp(access_key.created_at),
+                expiration_date: access_key.expires_at.map(Timestamp),
+                policy: access_key.policy,
+            },
+        })
+    }
+}
@@ -1,19 +1,29 @@
 use crate::{
     database::{
         DbPool,
+        replicas::get_secret_replicas,
         secrets::{
             CreateSecret, CreateSecretVersion, add_secret_version_stage, create_secret,
-            create_secret_version, get_secret_by_version_id, put_secret_tag,
+            create_secret_version, get_secret_by_version_id, get_secret_latest_version,
+            put_secret_tag,
         },
     },
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
-            ResourceExistsException,
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidParameterException,
+            InvalidRequestException, ResourceExistsException, decryption_failure_response,
+            encryption_failure_response,
         },
-        models::{ClientRequestToken, SecretBinary, SecretName, SecretString, Tag},
+        models::{
+            Arn, ClientRequestToken, ReplicaRegionType, ReplicationStatusType, SecretBinary,
+            SecretName, SecretString, Tag,
+        },
+        quotas::Quotas,
     },
+    kms::{decrypt_stored_secret, encrypt_secret_value},
+    middleware::aws_sig_v4::RequestContext,
+    replication::{ReplicaRegion, replicate_secret_to_regions},
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -22,7 +32,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_CreateSecret.html
-pub struct CreateSecretHandler;
+pub struct CreateSecretHandler {
+    /// Service quotas checked when creating the secret
+    pub quotas: Quotas,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct CreateSecretRequest {
@@ -34,6 +47,10 @@ pub struct CreateSecretRequest {
     #[garde(inner(length(max = 2048)))]
     description: Option<String>,
 
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    kms_key_id: Option<String>,
+
     #[serde(rename = "ClientRequestToken")]
     #[garde(dive)]
     client_request_token: Option<ClientRequestToken>,
@@ -49,6 +66,15 @@ pub struct CreateSecretRequest {
     #[serde(rename = "Tags")]
     #[garde(dive)]
     tags: Option<Vec<Tag>>,
+
+    #[serde(rename = "AddReplicaRegions")]
+    #[garde(dive)]
+    add_replica_regions: Option<Vec<ReplicaRegionType>>,
+
+    #[serde(rename = "ForceOverwriteReplicaSecret")]
+    #[serde(default)]
+    #[garde(skip)]
+    force_overwrite_replica_secret: bool,
 }
 
 #[derive(Serialize)]
@@ -59,60 +85,121 @@ pub struct CreateSecretResponse {
     name: String,
     #[serde(rename = "VersionId")]
     version_id: String,
+    #[serde(rename = "ReplicationStatus")]
+    replication_status: Option<Vec<ReplicationStatusType>>,
 }
 
 /// Generate a new secret ARN
 ///
-/// Uses the mock prefix arn:aws:secretsmanager:us-east-1:1:secret:
-/// and provides a randomly generated suffix as is done by the
+/// Uses the `region` the request was signed for and the `account_id` of
+/// the caller, and provides a randomly generated suffix as is done by the
 /// official implementation
-fn create_secret_arn(name: &str) -> String {
+fn create_secret_arn(region: &str, account_id: &str, name: &str) -> String {
     let random_suffix: String = rand::rng()
         .sample_iter(&Alphanumeric)
         .take(6)
         .map(char::from)
         .collect();
 
-    format!("arn:aws:secretsmanager:us-east-1:1:secret:{name}-{random_suffix}")
+    Arn::new(
+        "secretsmanager",
+        region,
+        account_id,
+        &format!("secret:{name}-{random_suffix}"),
+    )
+    .to_string()
 }
 
 impl Handler for CreateSecretHandler {
     type Request = CreateSecretRequest;
     type Response = CreateSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.name.0)
+    }
+
     #[tracing::instrument(skip_all, fields(name = %request.name))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let SecretName(name) = request.name;
         let ClientRequestToken(version_id) = request.client_request_token.unwrap_or_default();
 
-        let arn = create_secret_arn(&name);
+        let arn = create_secret_arn(&context.caller.region, &context.caller.account_id, &name);
 
         let tags = request.tags.unwrap_or_default();
+        let replica_regions: Vec<ReplicaRegion> = request
+            .add_replica_regions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|replica_region| ReplicaRegion {
+                region: replica_region.region.0,
+                kms_key_id: replica_region.kms_key_id,
+            })
+            .collect();
+
+        // Secrets can't be replicated into their own region
+        if replica_regions
+            .iter()
+            .any(|replica_region| replica_region.region == context.caller.region)
+        {
+            let error = InvalidParameterException
+                .with_message("A secret can't be replicated to its own Region.");
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
         let secret_string = request.secret_string.map(SecretString::into_inner);
         let secret_binary = request.secret_binary.map(SecretBinary::into_inner);
 
         // Must only specify one of the two
         if secret_string.is_some() && secret_binary.is_some() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            let error = InvalidRequestException
+                .with_message("You can't specify both SecretString and SecretBinary.");
+            return Err(AwsErrorResponse(error).into_response());
         }
 
         // Must specify at least one
         if secret_string.is_none() && secret_binary.is_none() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            let error = InvalidRequestException
+                .with_message("You must provide either SecretString or SecretBinary.");
+            return Err(AwsErrorResponse(error).into_response());
         }
 
+        let kms_key_id = request.kms_key_id;
+
+        // Encrypt the value using the KMS key of the secret
+        let value = encrypt_secret_value(
+            db,
+            &context.caller.region,
+            kms_key_id.as_deref(),
+            secret_string.clone(),
+            secret_binary.clone(),
+        )
+        .await
+        .map_err(encryption_failure_response)?;
+
         let mut t = db.begin().await.map_err(|error| {
             tracing::error!(?error, "failed to begin transaction");
             AwsErrorResponse(InternalServiceError).into_response()
         })?;
 
+        self.quotas
+            .check_secret_count(t.deref_mut(), &context.caller.account_id)
+            .await?;
+
         // Create the secret
         if let Err(error) = create_secret(
             t.deref_mut(),
             CreateSecret {
                 arn: arn.clone(),
+                region: context.caller.region.clone(),
                 name: name.clone(),
                 description: request.description,
+                primary_arn: None,
+                kms_key_id,
             },
         )
         .await
@@ -126,28 +213,40 @@ impl Handler for CreateSecretHandler {
                 }
 
                 // Check if the secret has been created
-                let secret = match get_secret_by_version_id(db, &name, &version_id).await {
-                    Ok(value) => value,
-                    Err(error) => {
-                        tracing::error!(?error, "failed to determine existing version");
-                        return Err(AwsErrorResponse(InternalServiceError).into_response());
-                    }
-                };
-
-                let secret = match secret {
+                let secret =
+                    match get_secret_by_version_id(db, &context.caller.region, &name, &version_id)
+                        .await
+                    {
+                        Ok(value) => value,
+                        Err(error) => {
+                            tracing::error!(?error, "failed to determine existing version");
+                            return Err(AwsErrorResponse(InternalServiceError).into_response());
+                        }
+                    };
+
+                let mut secret = match secret {
                     Some(value) => value,
                     None => {
                         // This version we tried to store was not created so this is an already exists error
-                        return Err(AwsErrorResponse(ResourceExistsException).into_response());
+                        let error = ResourceExistsException
+                            .with_message(format!("The secret {name} already exists."));
+                        return Err(AwsErrorResponse(error).into_response());
                     }
                 };
 
+                decrypt_stored_secret(db, &mut secret)
+                    .await
+                    .map_err(decryption_failure_response)?;
+
                 // If the stored version data doesn't match this is an error that
                 // the resource already exists
                 if secret.secret_string.ne(&secret_string)
                     || secret.secret_binary.ne(&secret_binary)
                 {
-                    return Err(AwsErrorResponse(ResourceExistsException).into_response());
+                    let error = ResourceExistsException.with_message(
+                        "A version with this ClientRequestToken already exists with a different value.",
+                    );
+                    return Err(AwsErrorResponse(error).into_response());
                 }
 
                 // Request has already been fulfilled
@@ -155,6 +254,7 @@ impl Handler for CreateSecretHandler {
                     arn: secret.arn,
                     name,
                     version_id,
+                    replication_status: None,
                 });
             }
 
@@ -168,8 +268,9 @@ impl Handler for CreateSecretHandler {
             CreateSecretVersion {
                 secret_arn: arn.clone(),
                 version_id: version_id.clone(),
-                secret_string: secret_string.clone(),
-                secret_binary: secret_binary.clone(),
+                secret_string: value.secret_string,
+                secret_binary: value.secret_binary,
+                encrypted_data_key: value.encrypted_data_key,
             },
         )
         .await
@@ -183,15 +284,18 @@ impl Handler for CreateSecretHandler {
                 }
 
                 // Check if the secret has been created
-                let secret = match get_secret_by_version_id(db, &arn, &version_id).await {
-                    Ok(value) => value,
-                    Err(error) => {
-                        tracing::error!(?error, "failed to determine existing version");
-                        return Err(AwsErrorResponse(InternalServiceError).into_response());
-                    }
-                };
-
-                let secret = match secret {
+                let secret =
+                    match get_secret_by_version_id(db, &context.caller.region, &arn, &version_id)
+                        .await
+                    {
+                        Ok(value) => value,
+                        Err(error) => {
+                            tracing::error!(?error, "failed to determine existing version");
+                            return Err(AwsErrorResponse(InternalServiceError).into_response());
+                        }
+                    };
+
+                let mut secret = match secret {
                     Some(value) => value,
                     None => {
                         // Shouldn't be possible if we hit the unique violation
@@ -199,12 +303,19 @@ impl Handler for CreateSecretHandler {
                     }
                 };
 
+                decrypt_stored_secret(db, &mut secret)
+                    .await
+                    .map_err(decryption_failure_response)?;
+
                 // If the stored version data doesn't match this is an error that
                 // the resource already exists
                 if secret.secret_string.ne(&secret_string)
                     || secret.secret_binary.ne(&secret_binary)
                 {
-                    return Err(AwsErrorResponse(ResourceExistsException).into_response());
+                    let error = ResourceExistsException.with_message(
+                        "A version with this ClientRequestToken already exists with a different value.",
+                    );
+                    return Err(AwsErrorResponse(error).into_response());
                 }
 
                 // Request has already been fulfilled
@@ -212,6 +323,7 @@ impl Handler for CreateSecretHandler {
                     arn,
                     name,
                     version_id,
+                    replication_status: None,
                 });
             }
 
@@ -236,6 +348,10 @@ impl Handler for CreateSecretHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        self.quotas
+            .check_tag_count(t.deref_mut(), &arn, tags.iter().map(|tag| tag.key.as_str()))
+            .await?;
+
         // Attach all the secrets
         for tag in tags {
             if let Err(error) = put_secret_tag(t.deref_mut(), &arn, &tag.key, &tag.value).await {
@@ -253,10 +369,61 @@ impl Handler for CreateSecretHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        let replication_status = if replica_regions.is_empty() {
+            None
+        } else {
+            Some(
+                replicate_created_secret(
+                    db,
+                    &context.caller.region,
+                    &arn,
+                    &replica_regions,
+                    request.force_overwrite_replica_secret,
+                )
+                .await?,
+            )
+        };
+
         Ok(CreateSecretResponse {
             arn,
             name,
             version_id,
+            replication_status,
         })
     }
 }
+
+/// Replicate the newly created secret `arn` to the `replica_regions` providing
+/// the resulting replication status of each region
+async fn replicate_created_secret(
+    db: &DbPool,
+    region: &str,
+    arn: &str,
+    replica_regions: &[ReplicaRegion],
+    force_overwrite: bool,
+) -> Result<Vec<ReplicationStatusType>, Response> {
+    let secret = get_secret_latest_version(db, region, arn)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to get secret");
+            AwsErrorResponse(InternalServiceError).into_response()
+        })?
+        .ok_or_else(|| AwsErrorResponse(InternalServiceError).into_response())?;
+
+    if let Err(error) =
+        replicate_secret_to_regions(db, &secret, replica_regions, force_overwrite).await
+    {
+        tracing::error!(?error, "failed to replicate secret");
+        return Err(AwsErrorResponse(InternalServiceError).into_response());
+    }
+
+    let replicas = get_secret_replicas(db, arn).await.map_err(|error| {
+        tracing::error!(?error, "failed to get secret replicas");
+        AwsErrorResponse(InternalServiceError).into_response()
+    })?;
+
+    Ok(replicas
+        .into_iter()
+        .map(ReplicationStatusType::from_stored)
+        .collect())
+}
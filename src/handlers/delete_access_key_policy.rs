@@ -0,0 +1,60 @@
+use crate::{
+    database::{
+        DbPool,
+        access_keys::{get_access_key, update_access_key_policy},
+    },
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        models::AccessKeyId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_DeleteUserPolicy.html
+pub struct DeleteAccessKeyPolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DeleteAccessKeyPolicyRequest {
+    #[serde(rename = "AccessKeyId")]
+    #[garde(dive)]
+    access_key_id: AccessKeyId,
+}
+
+#[derive(Serialize)]
+pub struct DeleteAccessKeyPolicyResponse {}
+
+impl Handler for DeleteAccessKeyPolicyHandler {
+    type Request = DeleteAccessKeyPolicyRequest;
+    type Response = DeleteAccessKeyPolicyResponse;
+
+    #[tracing::instrument(skip_all, fields(access_key_id = %request.access_key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AccessKeyId(access_key_id) = request.access_key_id;
+
+        get_access_key(db, &access_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get access key");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        if let Err(error) = update_access_key_policy(db, &access_key_id, None).await {
+            tracing::error!(?error, "failed to delete access key policy");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(DeleteAccessKeyPolicyResponse {})
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{
+    database::{DbPool, policies::delete_secret_policy, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response, scheduled_for_deletion_response,
+        },
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_DeleteResourcePolicy.html
+pub struct DeleteResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DeleteResourcePolicyRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct DeleteResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+impl Handler for DeleteResourcePolicyHandler {
+    type Request = DeleteResourcePolicyRequest;
+    type Response = DeleteResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // Secret is scheduled for deletion
+        if secret.scheduled_delete_at.is_some() {
+            return Err(scheduled_for_deletion_response());
+        }
+
+        if let Err(error) = delete_secret_policy(db, &secret.arn).await {
+            tracing::error!(?error, "failed to delete secret policy");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(DeleteResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+        })
+    }
+}
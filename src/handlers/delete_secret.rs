@@ -1,14 +1,19 @@
 use crate::{
     database::{
         DbPool,
+        replicas::get_secret_replicas,
         secrets::{delete_secret, get_secret_latest_version, schedule_delete_secret},
     },
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        error::{
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            ResourceNotFoundException, replica_secret_response,
+        },
         models::SecretId,
     },
-    utils::date::datetime_to_f64,
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
 };
 use axum::response::{IntoResponse, Response};
 use chrono::Utc;
@@ -42,7 +47,7 @@ pub struct DeleteSecretResponse {
     #[serde(rename = "Name")]
     name: String,
     #[serde(rename = "DeletionDate")]
-    deletion_date: f64,
+    deletion_date: Timestamp,
 }
 
 fn default_recovery_window_days() -> i32 {
@@ -53,8 +58,17 @@ impl Handler for DeleteSecretHandler {
     type Request = DeleteSecretRequest;
     type Response = DeleteSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let DeleteSecretRequest {
             force_delete_without_recovery,
             recovery_window_in_days,
@@ -63,7 +77,7 @@ impl Handler for DeleteSecretHandler {
 
         let SecretId(secret_id) = secret_id;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -76,12 +90,33 @@ impl Handler for DeleteSecretHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        let replicas = match get_secret_replicas(db, &secret.arn).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get secret replicas");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        // Replicas must be removed before the primary secret can be deleted
+        if !replicas.is_empty() {
+            let error = InvalidRequestException.with_message(
+                "You can't delete a primary secret that has replicas, remove the replica Regions first.",
+            );
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
         // Secret is already scheduled for deletion
         if let Some(scheduled_deletion_date) = secret.scheduled_delete_at {
             return Ok(DeleteSecretResponse {
                 arn: secret.arn,
                 name: secret.name,
-                deletion_date: datetime_to_f64(scheduled_deletion_date),
+                deletion_date: Timestamp(scheduled_deletion_date),
             });
         }
 
@@ -106,7 +141,7 @@ impl Handler for DeleteSecretHandler {
         Ok(DeleteSecretResponse {
             arn: secret.arn,
             name: secret.name,
-            deletion_date: datetime_to_f64(deletion_date),
+            deletion_date: Timestamp(deletion_date),
         })
     }
 }
@@ -1,19 +1,21 @@
 use crate::{
     database::{
         DbPool,
+        replicas::get_secret_replicas,
         secrets::{get_secret_latest_version, get_secret_versions},
     },
     handlers::{
         Handler,
         error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
-        models::{SecretId, Tag},
+        models::{Arn, ReplicationStatusType, RotationRules, SecretId, Tag},
     },
-    utils::date::datetime_to_f64,
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_DescribeSecret.html
 pub struct DescribeSecretHandler;
@@ -32,33 +34,33 @@ pub struct DescribeSecretResponse {
     #[serde(rename = "Description")]
     description: Option<String>,
     #[serde(rename = "CreatedDate")]
-    created_date: f64,
+    created_date: Timestamp,
     #[serde(rename = "DeletedDate")]
-    deleted_date: Option<f64>,
+    deleted_date: Option<Timestamp>,
     #[serde(rename = "KmsKeyId")]
     kms_key_id: Option<String>,
     #[serde(rename = "LastAccessedDate")]
-    last_accessed_date: Option<f64>,
+    last_accessed_date: Option<Timestamp>,
     #[serde(rename = "LastChangedDate")]
-    last_changed_date: Option<f64>,
+    last_changed_date: Option<Timestamp>,
     #[serde(rename = "LastRotatedDate")]
-    last_rotated_date: Option<f64>,
+    last_rotated_date: Option<Timestamp>,
     #[serde(rename = "Name")]
     name: String,
     #[serde(rename = "NextRotationDate")]
-    next_rotation_date: Option<f64>,
+    next_rotation_date: Option<Timestamp>,
     #[serde(rename = "OwningService")]
     owning_service: Option<String>,
     #[serde(rename = "PrimaryRegion")]
     primary_region: Option<String>,
     #[serde(rename = "ReplicationStatus")]
-    replication_status: Option<Vec<serde_json::Value>>,
+    replication_status: Option<Vec<ReplicationStatusType>>,
     #[serde(rename = "RotationEnabled")]
     rotation_enabled: bool,
     #[serde(rename = "RotationLambdaARN")]
     rotation_lambda_arn: Option<String>,
     #[serde(rename = "RotationRules")]
-    rotation_rules: Option<serde_json::Value>,
+    rotation_rules: Option<RotationRules>,
     #[serde(rename = "Tags")]
     tags: Vec<Tag>,
     #[serde(rename = "VersionIdsToStages")]
@@ -69,11 +71,20 @@ impl Handler for DescribeSecretHandler {
     type Request = DescribeSecretRequest;
     type Response = DescribeSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let SecretId(secret_id) = request.secret_id;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -94,6 +105,32 @@ impl Handler for DescribeSecretHandler {
             }
         };
 
+        let replicas = match get_secret_replicas(db, &secret.arn).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get secret replicas");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        // Replicas report the region of their primary, primaries only report
+        // their own region once they have been replicated
+        let primary_region = match &secret.primary_arn {
+            Some(primary_arn) => Arn::from_str(primary_arn)
+                .ok()
+                .map(|arn| arn.region)
+                .filter(|region| !region.is_empty()),
+            None if !replicas.is_empty() => Some(secret.region.clone()),
+            None => None,
+        };
+
+        let replication_status = (!replicas.is_empty()).then(|| {
+            replicas
+                .into_iter()
+                .map(ReplicationStatusType::from_stored)
+                .collect()
+        });
+
         let most_recently_used = versions
             .iter()
             .filter_map(|version| version.last_accessed_at)
@@ -116,20 +153,24 @@ impl Handler for DescribeSecretHandler {
         Ok(DescribeSecretResponse {
             arn: secret.arn,
             description: secret.description,
-            created_date: datetime_to_f64(secret.created_at),
-            deleted_date: secret.deleted_at.map(datetime_to_f64),
-            kms_key_id: None,
-            last_accessed_date: most_recently_used.map(datetime_to_f64),
-            last_changed_date: last_changed_date.map(datetime_to_f64),
-            last_rotated_date: None,
+            created_date: Timestamp(secret.created_at),
+            deleted_date: secret.deleted_at.map(Timestamp),
+            kms_key_id: secret.kms_key_id,
+            last_accessed_date: most_recently_used.map(Timestamp),
+            last_changed_date: last_changed_date.map(Timestamp),
+            last_rotated_date: secret.last_rotated_at.map(Timestamp),
             name: secret.name,
-            next_rotation_date: None,
+            next_rotation_date: secret.next_rotation_at.map(Timestamp),
             owning_service: None,
-            primary_region: None,
-            replication_status: None,
-            rotation_enabled: false,
-            rotation_lambda_arn: None,
-            rotation_rules: None,
+            primary_region,
+            replication_status,
+            rotation_enabled: secret.rotation_enabled,
+            rotation_lambda_arn: secret.rotation_lambda_arn,
+            rotation_rules: RotationRules::from_stored(
+                secret.rotation_automatically_after_days,
+                secret.rotation_duration,
+                secret.rotation_schedule_expression,
+            ),
             tags: secret
                 .version_tags
                 .into_iter()
@@ -1,14 +1,43 @@
+use crate::{kms::KmsError, policy::PolicyValidationError};
 use axum::{
     Json,
     http::{HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use serde_json::json;
+use std::marker::PhantomData;
 
 pub trait AwsError {
     const STATUS_CODE: StatusCode = StatusCode::BAD_REQUEST;
     const TYPE: &str;
     const MESSAGE: &str;
+
+    /// Message describing the error, defaults to the generic [AwsError::MESSAGE]
+    fn message(&self) -> &str {
+        Self::MESSAGE
+    }
+
+    /// Replace the generic message of the error with a `message` describing
+    /// the specific cause of the error
+    fn with_message(self, message: impl Into<String>) -> WithMessage<Self>
+    where
+        Self: Sized,
+    {
+        WithMessage(PhantomData, message.into())
+    }
+}
+
+/// [AwsError] with a message describing the specific cause of the error
+pub struct WithMessage<A: AwsError>(PhantomData<A>, String);
+
+impl<A: AwsError> AwsError for WithMessage<A> {
+    const STATUS_CODE: StatusCode = A::STATUS_CODE;
+    const TYPE: &str = A::TYPE;
+    const MESSAGE: &str = A::MESSAGE;
+
+    fn message(&self) -> &str {
+        &self.1
+    }
 }
 
 pub struct AwsErrorResponse<A: AwsError>(pub A);
@@ -17,7 +46,7 @@ impl<A: AwsError> IntoResponse for AwsErrorResponse<A> {
     fn into_response(self) -> axum::response::Response {
         let body = json!({
             "__type": A::TYPE,
-            "message": A::MESSAGE
+            "message": self.0.message()
         });
 
         let mut response = (A::STATUS_CODE, Json(body)).into_response();
@@ -45,6 +74,14 @@ impl AwsError for SignatureDoesNotMatch {
     const MESSAGE: &str = "The request signature we calculated does not match the signature you provided. Check your AWS Secret Access Key and signing method. Consult the service documentation for details.";
 }
 
+pub struct ExpiredTokenException;
+
+impl AwsError for ExpiredTokenException {
+    const STATUS_CODE: StatusCode = StatusCode::FORBIDDEN;
+    const TYPE: &str = "ExpiredTokenException";
+    const MESSAGE: &str = "The security token included in the request is expired";
+}
+
 pub struct MissingAuthenticationToken;
 
 impl AwsError for MissingAuthenticationToken {
@@ -87,16 +124,163 @@ impl AwsError for ResourceExistsException {
     const MESSAGE: &str = "A resource with the ID you requested already exists.";
 }
 
+pub struct MalformedPolicyDocumentException;
+
+impl AwsError for MalformedPolicyDocumentException {
+    const TYPE: &str = "MalformedPolicyDocumentException";
+    const MESSAGE: &str = "The resource policy has syntax errors.";
+}
+
+pub struct PublicPolicyException;
+
+impl AwsError for PublicPolicyException {
+    const TYPE: &str = "PublicPolicyException";
+    const MESSAGE: &str = "The BlockPublicPolicy parameter is set to true, and the resource policy did not prevent broad access to the secret.";
+}
+
+pub struct AccessDeniedException;
+
+impl AwsError for AccessDeniedException {
+    const TYPE: &str = "AccessDeniedException";
+    const MESSAGE: &str = "User is not authorized to perform this action on the resource because no policy allows it.";
+}
+
 pub struct NotImplemented;
 
 impl AwsError for NotImplemented {
-    const TYPE: &str = "ResourceExistsException";
+    const TYPE: &str = "NotImplemented";
     const MESSAGE: &str = "This operation is not implemented in this server";
 }
 
+pub struct LimitExceededException;
+
+impl AwsError for LimitExceededException {
+    const TYPE: &str = "LimitExceededException";
+    const MESSAGE: &str =
+        "The request failed because it would exceed one of the Secrets Manager quotas.";
+}
+
+pub struct InvalidNextTokenException;
+
+impl AwsError for InvalidNextTokenException {
+    const TYPE: &str = "InvalidNextTokenException";
+    const MESSAGE: &str = "The NextToken value is invalid.";
+}
+
 pub struct InternalServiceError;
 
 impl AwsError for InternalServiceError {
     const TYPE: &str = "InternalServiceError";
     const MESSAGE: &str = "An error occurred on the server side.";
 }
+
+pub struct ThrottlingException;
+
+impl AwsError for ThrottlingException {
+    const TYPE: &str = "ThrottlingException";
+    const MESSAGE: &str = "Rate exceeded";
+}
+
+pub struct ServiceUnavailable;
+
+impl AwsError for ServiceUnavailable {
+    const STATUS_CODE: StatusCode = StatusCode::SERVICE_UNAVAILABLE;
+    const TYPE: &str = "ServiceUnavailable";
+    const MESSAGE: &str = "The service is unavailable. Please try again later.";
+}
+
+pub struct DecryptionFailure;
+
+impl AwsError for DecryptionFailure {
+    const TYPE: &str = "DecryptionFailure";
+    const MESSAGE: &str =
+        "Secrets Manager can't decrypt the protected secret text using the provided KMS key.";
+}
+
+pub struct EncryptionFailure;
+
+impl AwsError for EncryptionFailure {
+    const TYPE: &str = "EncryptionFailure";
+    const MESSAGE: &str = "Secrets Manager can't encrypt the protected secret text using the provided KMS key. Check that the KMS key is available, enabled, and not in an invalid state.";
+}
+
+/// Create the message describing the validation errors within the `report`
+/// (i.e 1 validation error detected: Value at 'name' failed to satisfy constraint: length is lower than 1)
+pub fn validation_message(report: &garde::Report) -> String {
+    let errors: Vec<String> = report
+        .iter()
+        .map(|(path, error)| {
+            let path = path.to_string();
+            if path.is_empty() {
+                format!("Value failed to satisfy constraint: {error}")
+            } else {
+                format!("Value at '{path}' failed to satisfy constraint: {error}")
+            }
+        })
+        .collect();
+
+    let plural = if errors.len() == 1 { "" } else { "s" };
+    format!(
+        "{} validation error{plural} detected: {}",
+        errors.len(),
+        errors.join("; ")
+    )
+}
+
+/// Create the message describing the `errors` found in a policy document
+pub fn policy_validation_message(errors: &[PolicyValidationError]) -> String {
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    messages.join("; ")
+}
+
+/// Create the response for a policy document with syntax `errors`
+pub fn malformed_policy_response(errors: Vec<PolicyValidationError>) -> Response {
+    let message = policy_validation_message(&errors);
+    AwsErrorResponse(MalformedPolicyDocumentException.with_message(message)).into_response()
+}
+
+/// Create the response for a request that changes a replica secret, replicas
+/// can only be changed through their primary secret
+pub fn replica_secret_response() -> Response {
+    let error = InvalidRequestException.with_message(
+        "Operation not permitted on a replica secret. Call must be made in primary secret's region.",
+    );
+    AwsErrorResponse(error).into_response()
+}
+
+/// Create the response for a request targeting a secret that is scheduled
+/// for deletion
+pub fn scheduled_for_deletion_response() -> Response {
+    let error = InvalidRequestException.with_message(
+        "You can't perform this operation on the secret because it was marked for deletion.",
+    );
+    AwsErrorResponse(error).into_response()
+}
+
+/// Create the response for a failure to decrypt a secret value
+pub fn decryption_failure_response(error: KmsError) -> Response {
+    match error {
+        KmsError::Db(error) => {
+            tracing::error!(?error, "failed to decrypt secret value");
+            AwsErrorResponse(InternalServiceError).into_response()
+        }
+        error => {
+            tracing::debug!(?error, "failed to decrypt secret value");
+            AwsErrorResponse(DecryptionFailure).into_response()
+        }
+    }
+}
+
+/// Create the response for a failure to encrypt a secret value
+pub fn encryption_failure_response(error: KmsError) -> Response {
+    match error {
+        KmsError::Db(error) => {
+            tracing::error!(?error, "failed to encrypt secret value");
+            AwsErrorResponse(InternalServiceError).into_response()
+        }
+        error => {
+            tracing::debug!(?error, "failed to encrypt secret value");
+            AwsErrorResponse(EncryptionFailure).into_response()
+        }
+    }
+}
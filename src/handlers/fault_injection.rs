@@ -0,0 +1,421 @@
+//! Fault injection for testing the resilience of clients, rules matching the
+//! operation and secret of a request can delay the request, fail it with one
+//! of the retryable errors AWS returns or drop the connection
+//!
+//! Rules are loaded at startup and replaced at runtime using the
+//! `loker.PutFaultInjectionRules` operation
+
+use crate::{
+    handlers::{
+        error::{AwsErrorResponse, InternalServiceError, ServiceUnavailable, ThrottlingException},
+        protocol::Protocol,
+    },
+    policy::evaluate::wildcard_match,
+};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use garde::Validate;
+use http_body_util::BodyExt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    mem::swap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tower::{Layer, Service};
+
+/// Targets of the operations managing the fault injection rules, faults are
+/// never injected into these operations so rules can always be replaced
+const FAULT_INJECTION_TARGETS: &[&str] = &[
+    "loker.PutFaultInjectionRules",
+    "loker.GetFaultInjectionRules",
+];
+
+/// Rule describing a fault to inject into matching requests
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct FaultRule {
+    /// Pattern matching the target of requests (i.e secretsmanager.GetSecretValue),
+    /// matches all operations when not provided
+    #[serde(rename = "Operation", default, skip_serializing_if = "Option::is_none")]
+    #[garde(length(min = 1, max = 256))]
+    pub operation: Option<String>,
+
+    /// Pattern matching the `SecretId` or `Name` of requests, matches all
+    /// requests when not provided
+    #[serde(rename = "SecretId", default, skip_serializing_if = "Option::is_none")]
+    #[garde(length(min = 1, max = 2048))]
+    pub secret_id: Option<String>,
+
+    /// Fault to respond with, requests are only delayed when not provided
+    #[serde(rename = "Fault", default, skip_serializing_if = "Option::is_none")]
+    #[garde(skip)]
+    pub fault: Option<Fault>,
+
+    /// Probability of the rule applying to a matching request, always
+    /// applies when not provided
+    #[serde(
+        rename = "Probability",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[garde(range(min = 0.0, max = 1.0))]
+    pub probability: Option<f64>,
+
+    /// Only apply the rule to the Nth matching request
+    #[serde(rename = "NthCall", default, skip_serializing_if = "Option::is_none")]
+    #[garde(range(min = 1))]
+    pub nth_call: Option<u64>,
+
+    /// Latency in milliseconds to add before responding
+    #[serde(rename = "LatencyMs", default, skip_serializing_if = "Option::is_none")]
+    #[garde(range(max = 300000))]
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fault {
+    ThrottlingException,
+    InternalServiceError,
+    ServiceUnavailable,
+    /// Abort the connection while the response is being sent
+    DropConnection,
+}
+
+#[derive(Debug, Error)]
+pub enum LoadFaultRulesError {
+    #[error("failed to read fault injection rules: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse fault injection rules: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid fault injection rules: {0}")]
+    Validation(#[from] garde::Report),
+}
+
+/// Shared set of fault injection rules along with the number of requests
+/// each rule has matched
+#[derive(Default)]
+pub struct FaultInjector {
+    rules: Mutex<Vec<FaultRuleState>>,
+}
+
+struct FaultRuleState {
+    rule: FaultRule,
+    /// Number of requests the rule has matched
+    calls: u64,
+}
+
+impl FaultInjector {
+    pub fn new(rules: Vec<FaultRule>) -> Self {
+        let injector = Self::default();
+        injector.set_rules(rules);
+        injector
+    }
+
+    /// Load the fault injection rules from the JSON file at `path`
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, LoadFaultRulesError> {
+        let data = tokio::fs::read(path).await?;
+        let rules: Vec<FaultRule> = serde_json::from_slice(&data)?;
+        rules.validate()?;
+        Ok(Self::new(rules))
+    }
+
+    /// Get the current rules
+    pub fn rules(&self) -> Vec<FaultRule> {
+        let rules = self.rules.lock().expect("fault injection rules poisoned");
+        rules.iter().map(|state| state.rule.clone()).collect()
+    }
+
+    /// Replace the current rules with `rules`, resetting the call counts
+    pub fn set_rules(&self, rules: Vec<FaultRule>) {
+        let mut current = self.rules.lock().expect("fault injection rules poisoned");
+        *current = rules
+            .into_iter()
+            .map(|rule| FaultRuleState { rule, calls: 0 })
+            .collect();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rules
+            .lock()
+            .expect("fault injection rules poisoned")
+            .is_empty()
+    }
+
+    /// Find the first rule that applies to a request for the `target` operation
+    /// on the `secret_id`, every matching rule counts the request as a call
+    /// up until the rule that applies
+    fn next_fault(&self, target: &str, secret_id: Option<&str>) -> Option<FaultRule> {
+        let mut rules = self.rules.lock().expect("fault injection rules poisoned");
+        let mut rng = rand::rng();
+
+        rules.iter_mut().find_map(|state| {
+            let rule = &state.rule;
+
+            let operation_matches = rule
+                .operation
+                .as_deref()
+                .is_none_or(|pattern| wildcard_match(pattern, target, true));
+            let secret_matches = match rule.secret_id.as_deref() {
+                Some(pattern) => {
+                    secret_id.is_some_and(|value| wildcard_match(pattern, value, false))
+                }
+                None => true,
+            };
+
+            if !operation_matches || !secret_matches {
+                return None;
+            }
+
+            state.calls += 1;
+
+            if rule
+                .nth_call
+                .is_some_and(|nth_call| nth_call != state.calls)
+            {
+                return None;
+            }
+
+            if rule
+                .probability
+                .is_some_and(|probability| !rng.random_bool(probability))
+            {
+                return None;
+            }
+
+            Some(rule.clone())
+        })
+    }
+}
+
+/// Secret a request targets, used for matching the secret patterns of rules
+#[derive(Deserialize)]
+struct FaultRequestSecret {
+    #[serde(rename = "SecretId")]
+    secret_id: Option<String>,
+    #[serde(rename = "Name")]
+    name: Option<String>,
+}
+
+/// Layer injecting faults into requests before they reach the handlers, must
+/// be applied to the [HandlerRouterService](super::HandlerRouterService)
+#[derive(Clone)]
+pub struct FaultInjectionLayer {
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultInjectionLayer {
+    pub fn new(injector: Arc<FaultInjector>) -> Self {
+        Self { injector }
+    }
+}
+
+impl<S> Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjectionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FaultInjectionService {
+            inner,
+            injector: self.injector.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FaultInjectionService<S> {
+    inner: S,
+    injector: Arc<FaultInjector>,
+}
+
+impl<S> Service<Request<Body>> for FaultInjectionService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        let injector = self.injector.clone();
+
+        Box::pin(async move {
+            if injector.is_empty() {
+                return inner.call(req).await;
+            }
+
+            let protocol = Protocol::from_headers(req.headers());
+            let target = match protocol.target(req.uri(), req.headers()) {
+                Some(value) if !FAULT_INJECTION_TARGETS.contains(&value.as_str()) => value,
+                _ => return inner.call(req).await,
+            };
+
+            // The body is buffered to find the secret of the request then
+            // provided to the handlers as is
+            let (parts, body) = req.into_parts();
+            let body = match body.collect().await {
+                Ok(value) => value.to_bytes(),
+                Err(error) => {
+                    tracing::error!(?error, "failed to collect bytes");
+                    let response = AwsErrorResponse(InternalServiceError).into_response();
+                    return Ok(protocol.error_response(response).await);
+                }
+            };
+
+            let secret_id = protocol
+                .deserialize::<FaultRequestSecret>(&body)
+                .ok()
+                .and_then(|request| request.secret_id.or(request.name));
+
+            let rule = injector.next_fault(&target, secret_id.as_deref());
+            let req = Request::from_parts(parts, Body::from(body));
+
+            let Some(rule) = rule else {
+                return inner.call(req).await;
+            };
+
+            tracing::debug!(?rule, "injecting fault");
+
+            if let Some(latency_ms) = rule.latency_ms {
+                tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+            }
+
+            let response = match rule.fault {
+                Some(Fault::ThrottlingException) => {
+                    AwsErrorResponse(ThrottlingException).into_response()
+                }
+                Some(Fault::InternalServiceError) => {
+                    // Handlers report internal errors with a 400 status while
+                    // AWS uses a 500 status which clients treat as retryable
+                    let mut response = AwsErrorResponse(InternalServiceError).into_response();
+                    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                    response
+                }
+                Some(Fault::ServiceUnavailable) => {
+                    AwsErrorResponse(ServiceUnavailable).into_response()
+                }
+                Some(Fault::DropConnection) => return Ok(drop_connection_response()),
+                None => return inner.call(req).await,
+            };
+
+            Ok(protocol.error_response(response).await)
+        })
+    }
+}
+
+/// Create a response whose body fails while it is being sent, the server
+/// aborts the connection when the body fails
+fn drop_connection_response() -> Response {
+    let body = futures::stream::once(async {
+        Err::<Bytes, _>(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+    });
+
+    Response::new(Body::from_stream(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(operation: Option<&str>, secret_id: Option<&str>) -> FaultRule {
+        FaultRule {
+            operation: operation.map(str::to_string),
+            secret_id: secret_id.map(str::to_string),
+            fault: Some(Fault::ThrottlingException),
+            probability: None,
+            nth_call: None,
+            latency_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let injector = FaultInjector::new(vec![rule(Some("secretsmanager.Get*"), Some("prod/*"))]);
+
+        assert!(
+            injector
+                .next_fault("secretsmanager.GetSecretValue", Some("prod/db"))
+                .is_some()
+        );
+        assert!(
+            injector
+                .next_fault("secretsmanager.GetSecretValue", Some("dev/db"))
+                .is_none()
+        );
+        assert!(
+            injector
+                .next_fault("secretsmanager.GetSecretValue", None)
+                .is_none()
+        );
+        assert!(
+            injector
+                .next_fault("secretsmanager.PutSecretValue", Some("prod/db"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rule_nth_call() {
+        let injector = FaultInjector::new(vec![FaultRule {
+            nth_call: Some(2),
+            ..rule(None, None)
+        }]);
+
+        let faults: Vec<bool> = (0..3)
+            .map(|_| {
+                injector
+                    .next_fault("secretsmanager.ListSecrets", None)
+                    .is_some()
+            })
+            .collect();
+        assert_eq!(faults, [false, true, false]);
+
+        // Replacing the rules resets the call counts
+        injector.set_rules(injector.rules());
+        assert!(
+            injector
+                .next_fault("secretsmanager.ListSecrets", None)
+                .is_none()
+        );
+        assert!(
+            injector
+                .next_fault("secretsmanager.ListSecrets", None)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_rule_probability() {
+        let injector = FaultInjector::new(vec![FaultRule {
+            probability: Some(0.0),
+            ..rule(None, None)
+        }]);
+
+        assert!(
+            injector
+                .next_fault("secretsmanager.ListSecrets", None)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    database::{DbPool, access_keys::get_access_key},
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        models::AccessKeyId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_GetAccessKeyLastUsed.html
+pub struct GetAccessKeyLastUsedHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct GetAccessKeyLastUsedRequest {
+    #[serde(rename = "AccessKeyId")]
+    #[garde(dive)]
+    access_key_id: AccessKeyId,
+}
+
+#[derive(Serialize)]
+pub struct GetAccessKeyLastUsedResponse {
+    #[serde(rename = "AccessKeyId")]
+    access_key_id: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "LastUsedDate")]
+    last_used_date: Option<Timestamp>,
+}
+
+impl Handler for GetAccessKeyLastUsedHandler {
+    type Request = GetAccessKeyLastUsedRequest;
+    type Response = GetAccessKeyLastUsedResponse;
+
+    #[tracing::instrument(skip_all, fields(access_key_id = %request.access_key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AccessKeyId(access_key_id) = request.access_key_id;
+
+        let access_key = get_access_key(db, &access_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get access key");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        Ok(GetAccessKeyLastUsedResponse {
+            access_key_id: access_key.access_key_id,
+            name: access_key.name,
+            last_used_date: access_key.last_used_at.map(Timestamp),
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    database::DbPool,
+    handlers::{
+        Handler,
+        fault_injection::{FaultInjector, FaultRule},
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::Response;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Provides the current fault injection rules
+pub struct GetFaultInjectionRulesHandler {
+    pub fault_injector: Arc<FaultInjector>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct GetFaultInjectionRulesRequest {}
+
+#[derive(Serialize)]
+pub struct GetFaultInjectionRulesResponse {
+    #[serde(rename = "Rules")]
+    rules: Vec<FaultRule>,
+}
+
+impl Handler for GetFaultInjectionRulesHandler {
+    type Request = GetFaultInjectionRulesRequest;
+    type Response = GetFaultInjectionRulesResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        _db: &DbPool,
+        _context: &RequestContext,
+        _request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        Ok(GetFaultInjectionRulesResponse {
+            rules: self.fault_injector.rules(),
+        })
+    }
+}
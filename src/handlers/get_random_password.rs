@@ -2,8 +2,9 @@ use crate::{
     database::DbPool,
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InvalidRequestException},
+        error::{AwsError, AwsErrorResponse, InvalidRequestException},
     },
+    middleware::aws_sig_v4::RequestContext,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -67,7 +68,12 @@ impl Handler for GetRandomPasswordHandler {
     type Response = GetRandomPasswordResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(_db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        _db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let GetRandomPasswordRequest {
             exclude_characters,
             exclude_lowercase,
@@ -90,8 +96,21 @@ impl Handler for GetRandomPasswordHandler {
             require_each_included_type,
         }) {
             Ok(value) => value,
-            Err(_error) => {
-                return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            Err(error) => {
+                let message = match error {
+                    RandomPasswordError::EmptyCharSet => {
+                        "All of the possible characters have been excluded."
+                    }
+                    RandomPasswordError::EmptyTypeSet => {
+                        "All of the characters of an included type have been excluded."
+                    }
+                    RandomPasswordError::InvalidLength => {
+                        "PasswordLength is too short to include a character of each included type."
+                    }
+                };
+                return Err(
+                    AwsErrorResponse(InvalidRequestException.with_message(message)).into_response(),
+                );
             }
         };
 
@@ -105,7 +124,7 @@ const NUMBERS: &str = "0123456789";
 const PUNCTUATION: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
 
 #[derive(Debug)]
-struct PasswordOptions {
+pub struct PasswordOptions {
     pub exclude_characters: String,
     pub exclude_lowercase: bool,
     pub exclude_numbers: bool,
@@ -116,8 +135,23 @@ struct PasswordOptions {
     pub require_each_included_type: bool,
 }
 
+impl Default for PasswordOptions {
+    fn default() -> Self {
+        Self {
+            exclude_characters: String::new(),
+            exclude_lowercase: false,
+            exclude_numbers: false,
+            exclude_punctuation: false,
+            exclude_uppercase: false,
+            include_space: false,
+            password_length: default_password_length() as usize,
+            require_each_included_type: false,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
-enum RandomPasswordError {
+pub enum RandomPasswordError {
     /// All of the possible characters are excluded
     #[error("empty char set")]
     EmptyCharSet,
@@ -133,7 +167,7 @@ enum RandomPasswordError {
 }
 
 /// Generate a random password from the provided options
-fn get_random_password(opts: PasswordOptions) -> Result<String, RandomPasswordError> {
+pub fn get_random_password(opts: PasswordOptions) -> Result<String, RandomPasswordError> {
     // Take the input charset string and provide a collection of chars
     // that aren't present in the excluded list
     fn filter_allowed(set: &str, excluded: &str) -> Vec<char> {
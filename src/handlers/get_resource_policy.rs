@@ -0,0 +1,75 @@
+use crate::{
+    database::{DbPool, policies::get_secret_policy, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_GetResourcePolicy.html
+pub struct GetResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct GetResourcePolicyRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct GetResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "ResourcePolicy")]
+    resource_policy: Option<String>,
+}
+
+impl Handler for GetResourcePolicyHandler {
+    type Request = GetResourcePolicyRequest;
+    type Response = GetResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        let resource_policy = match get_secret_policy(db, &secret.arn).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get secret policy");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        Ok(GetResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+            resource_policy,
+        })
+    }
+}
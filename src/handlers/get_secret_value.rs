@@ -10,12 +10,14 @@ use crate::{
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
-            ResourceNotFoundException,
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            decryption_failure_response, scheduled_for_deletion_response,
         },
-        models::{SecretId, VersionId},
+        models::{SecretId, VersionId, serialize_secret_binary},
     },
-    utils::date::datetime_to_f64,
+    kms::decrypt_stored_secret,
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -44,12 +46,12 @@ pub struct GetSecretValueResponse {
     #[serde(rename = "ARN")]
     arn: String,
     #[serde(rename = "CreatedDate")]
-    created_date: f64,
+    created_date: Timestamp,
     #[serde(rename = "Name")]
     name: String,
     #[serde(rename = "SecretString")]
     secret_string: Option<String>,
-    #[serde(rename = "SecretBinary")]
+    #[serde(rename = "SecretBinary", serialize_with = "serialize_secret_binary")]
     secret_binary: Option<String>,
     #[serde(rename = "VersionId")]
     version_id: String,
@@ -61,20 +63,39 @@ impl Handler for GetSecretValueHandler {
     type Request = GetSecretValueRequest;
     type Response = GetSecretValueResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let SecretId(secret_id) = request.secret_id;
         let version_id = request.version_id.map(VersionId::into_inner);
         let version_stage = request.version_stage;
 
         let secret = match (&version_id, &version_stage) {
-            (None, None) => get_secret_latest_version(db, &secret_id).await,
+            (None, None) => get_secret_latest_version(db, &context.caller.region, &secret_id).await,
             (Some(version_id), Some(version_stage)) => {
-                get_secret_by_version_stage_and_id(db, &secret_id, version_id, version_stage).await
+                get_secret_by_version_stage_and_id(
+                    db,
+                    &context.caller.region,
+                    &secret_id,
+                    version_id,
+                    version_stage,
+                )
+                .await
+            }
+            (Some(version_id), None) => {
+                get_secret_by_version_id(db, &context.caller.region, &secret_id, version_id).await
             }
-            (Some(version_id), None) => get_secret_by_version_id(db, &secret_id, version_id).await,
             (None, Some(version_stage)) => {
-                get_secret_by_version_stage(db, &secret_id, version_stage).await
+                get_secret_by_version_stage(db, &context.caller.region, &secret_id, version_stage)
+                    .await
             }
         };
 
@@ -86,16 +107,20 @@ impl Handler for GetSecretValueHandler {
             }
         };
 
-        let secret = match secret {
+        let mut secret = match secret {
             Some(value) => value,
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
         // Secret is scheduled for deletion
         if secret.scheduled_delete_at.is_some() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            return Err(scheduled_for_deletion_response());
         }
 
+        decrypt_stored_secret(db, &mut secret)
+            .await
+            .map_err(decryption_failure_response)?;
+
         if let Err(error) =
             update_secret_version_last_accessed(db, &secret.arn, &secret.version_id).await
         {
@@ -111,7 +136,7 @@ impl Handler for GetSecretValueHandler {
 
         Ok(GetSecretValueResponse {
             arn: secret.arn,
-            created_date: datetime_to_f64(created_at),
+            created_date: Timestamp(created_at),
             name: secret.name,
             secret_string: secret.secret_string,
             secret_binary: secret.secret_binary,
@@ -0,0 +1,72 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{KEY_STATE_DISABLED, KEY_STATE_PENDING_DELETION, get_kms_key, update_kms_key_state},
+    },
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, KMSInvalidStateException, NotFoundException},
+            models::KeyId,
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_CancelKeyDeletion.html
+pub struct CancelKeyDeletionHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CancelKeyDeletionRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct CancelKeyDeletionResponse {
+    #[serde(rename = "KeyId")]
+    key_id: String,
+}
+
+impl Handler for CancelKeyDeletionHandler {
+    type Request = CancelKeyDeletionRequest;
+    type Response = CancelKeyDeletionResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let KeyId(key_id) = request.key_id;
+
+        let key = get_kms_key(db, &context.caller.region, &key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        // Key is not scheduled for deletion
+        if key.key_state != KEY_STATE_PENDING_DELETION {
+            return Err(AwsErrorResponse(KMSInvalidStateException).into_response());
+        }
+
+        // Keys remain disabled after their deletion is cancelled
+        if let Err(error) = update_kms_key_state(db, &key.key_id, KEY_STATE_DISABLED, None).await {
+            tracing::error!(?error, "failed to cancel kms key deletion");
+            return Err(AwsErrorResponse(KMSInternalException).into_response());
+        }
+
+        Ok(CancelKeyDeletionResponse { key_id: key.arn })
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{
+            CreateKmsAlias, KEY_STATE_PENDING_DELETION, create_kms_alias, get_kms_alias,
+            get_kms_key,
+        },
+    },
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{
+                AlreadyExistsException, KMSInternalException, KMSInvalidStateException,
+                NotFoundException,
+            },
+            models::{AliasName, KeyId},
+        },
+        models::Arn,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_CreateAlias.html
+pub struct CreateAliasHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateAliasRequest {
+    #[serde(rename = "AliasName")]
+    #[garde(dive)]
+    alias_name: AliasName,
+
+    #[serde(rename = "TargetKeyId")]
+    #[garde(dive)]
+    target_key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct CreateAliasResponse {}
+
+impl Handler for CreateAliasHandler {
+    type Request = CreateAliasRequest;
+    type Response = CreateAliasResponse;
+
+    #[tracing::instrument(skip_all, fields(alias_name = %request.alias_name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AliasName(alias_name) = request.alias_name;
+        let KeyId(target_key_id) = request.target_key_id;
+
+        let key = get_kms_key(db, &context.caller.region, &target_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        // Keys pending deletion can't be given new aliases
+        if key.key_state == KEY_STATE_PENDING_DELETION {
+            return Err(AwsErrorResponse(KMSInvalidStateException).into_response());
+        }
+
+        let existing = get_kms_alias(db, &context.caller.region, &alias_name)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms alias");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?;
+
+        if existing.is_some() {
+            return Err(AwsErrorResponse(AlreadyExistsException).into_response());
+        }
+
+        let arn = Arn::new(
+            "kms",
+            &context.caller.region,
+            &context.caller.account_id,
+            &alias_name,
+        )
+        .to_string();
+
+        if let Err(error) = create_kms_alias(
+            db,
+            CreateKmsAlias {
+                region: context.caller.region.clone(),
+                alias_name,
+                arn,
+                target_key_id: key.key_id,
+            },
+        )
+        .await
+        {
+            if let Some(error) = error.as_database_error()
+                && error.is_unique_violation()
+            {
+                return Err(AwsErrorResponse(AlreadyExistsException).into_response());
+            }
+
+            tracing::error!(?error, "failed to create kms alias");
+            return Err(AwsErrorResponse(KMSInternalException).into_response());
+        }
+
+        Ok(CreateAliasResponse {})
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{CreateKmsKey, create_kms_key},
+    },
+    handlers::{
+        Handler,
+        error::{AwsError, AwsErrorResponse},
+        kms::{
+            error::{KMSInternalException, ValidationException},
+            models::{KEY_SPEC_SYMMETRIC_DEFAULT, KEY_USAGE_ENCRYPT_DECRYPT, KeyMetadata},
+        },
+        models::Arn,
+    },
+    kms::{KEY_MATERIAL_LENGTH, generate_key_material},
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_CreateKey.html
+pub struct CreateKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateKeyRequest {
+    #[serde(rename = "Description")]
+    #[garde(inner(length(max = 8192)))]
+    description: Option<String>,
+
+    #[serde(rename = "KeyUsage")]
+    #[garde(skip)]
+    key_usage: Option<String>,
+
+    #[serde(rename = "KeySpec")]
+    #[garde(skip)]
+    key_spec: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateKeyResponse {
+    #[serde(rename = "KeyMetadata")]
+    key_metadata: KeyMetadata,
+}
+
+impl Handler for CreateKeyHandler {
+    type Request = CreateKeyRequest;
+    type Response = CreateKeyResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        // Only symmetric encryption keys are supported
+        if request
+            .key_usage
+            .is_some_and(|key_usage| key_usage != KEY_USAGE_ENCRYPT_DECRYPT)
+            || request
+                .key_spec
+                .is_some_and(|key_spec| key_spec != KEY_SPEC_SYMMETRIC_DEFAULT)
+        {
+            let error = ValidationException.with_message(
+                "Only ENCRYPT_DECRYPT keys with the SYMMETRIC_DEFAULT key spec are supported.",
+            );
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
+        let key_id = Uuid::new_v4().to_string();
+        let arn = Arn::new(
+            "kms",
+            &context.caller.region,
+            &context.caller.account_id,
+            &format!("key/{key_id}"),
+        )
+        .to_string();
+
+        let key = create_kms_key(
+            db,
+            CreateKmsKey {
+                key_id,
+                arn,
+                region: context.caller.region.clone(),
+                description: request.description.unwrap_or_default(),
+                key_usage: KEY_USAGE_ENCRYPT_DECRYPT.to_string(),
+                key_spec: KEY_SPEC_SYMMETRIC_DEFAULT.to_string(),
+                key_material: generate_key_material(KEY_MATERIAL_LENGTH),
+            },
+        )
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to create kms key");
+            AwsErrorResponse(KMSInternalException).into_response()
+        })?;
+
+        Ok(CreateKeyResponse {
+            key_metadata: KeyMetadata::from_stored(key),
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    database::{DbPool, kms::get_kms_key},
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{
+                IncorrectKeyException, InvalidCiphertextException, KMSInternalException,
+                NotFoundException, kms_error_response,
+            },
+            models::{Blob, ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT, KeyId, is_valid_ciphertext},
+        },
+    },
+    kms::{EncryptionContext, decrypt},
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_Decrypt.html
+pub struct DecryptHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DecryptRequest {
+    #[serde(rename = "CiphertextBlob")]
+    #[garde(custom(is_valid_ciphertext))]
+    ciphertext_blob: Blob,
+
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: Option<KeyId>,
+
+    #[serde(rename = "EncryptionContext")]
+    #[serde(default)]
+    #[garde(skip)]
+    encryption_context: EncryptionContext,
+}
+
+#[derive(Serialize)]
+pub struct DecryptResponse {
+    #[serde(rename = "EncryptionAlgorithm")]
+    encryption_algorithm: String,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+    #[serde(rename = "Plaintext")]
+    plaintext: Blob,
+}
+
+impl Handler for DecryptHandler {
+    type Request = DecryptRequest;
+    type Response = DecryptResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let Blob(ciphertext_blob) = request.ciphertext_blob;
+
+        let (key, plaintext) = decrypt(db, &ciphertext_blob, &request.encryption_context)
+            .await
+            .map_err(kms_error_response)?;
+
+        // Ciphertext must have been created by a key in the same region
+        if key.region != context.caller.region {
+            return Err(AwsErrorResponse(InvalidCiphertextException).into_response());
+        }
+
+        // Ciphertext must have been created by the requested key
+        if let Some(KeyId(key_id)) = request.key_id {
+            let expected_key = get_kms_key(db, &context.caller.region, &key_id)
+                .await
+                //
+                .map_err(|error| {
+                    tracing::error!(?error, "failed to get kms key");
+                    AwsErrorResponse(KMSInternalException).into_response()
+                })?
+                //
+                .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+            if expected_key.key_id != key.key_id {
+                return Err(AwsErrorResponse(IncorrectKeyException).into_response());
+            }
+        }
+
+        Ok(DecryptResponse {
+            encryption_algorithm: ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT.to_string(),
+            key_id: key.arn,
+            plaintext: Blob(plaintext),
+        })
+    }
+}
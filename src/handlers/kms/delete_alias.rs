@@ -0,0 +1,63 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{delete_kms_alias, get_kms_alias},
+    },
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, NotFoundException},
+            models::AliasName,
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_DeleteAlias.html
+pub struct DeleteAliasHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DeleteAliasRequest {
+    #[serde(rename = "AliasName")]
+    #[garde(dive)]
+    alias_name: AliasName,
+}
+
+#[derive(Serialize)]
+pub struct DeleteAliasResponse {}
+
+impl Handler for DeleteAliasHandler {
+    type Request = DeleteAliasRequest;
+    type Response = DeleteAliasResponse;
+
+    #[tracing::instrument(skip_all, fields(alias_name = %request.alias_name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AliasName(alias_name) = request.alias_name;
+
+        get_kms_alias(db, &context.caller.region, &alias_name)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms alias");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        if let Err(error) = delete_kms_alias(db, &context.caller.region, &alias_name).await {
+            tracing::error!(?error, "failed to delete kms alias");
+            return Err(AwsErrorResponse(KMSInternalException).into_response());
+        }
+
+        Ok(DeleteAliasResponse {})
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    database::{DbPool, kms::get_kms_key},
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, NotFoundException},
+            models::{KeyId, KeyMetadata},
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_DescribeKey.html
+pub struct DescribeKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct DescribeKeyRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct DescribeKeyResponse {
+    #[serde(rename = "KeyMetadata")]
+    key_metadata: KeyMetadata,
+}
+
+impl Handler for DescribeKeyHandler {
+    type Request = DescribeKeyRequest;
+    type Response = DescribeKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let KeyId(key_id) = request.key_id;
+
+        let key = get_kms_key(db, &context.caller.region, &key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        Ok(DescribeKeyResponse {
+            key_metadata: KeyMetadata::from_stored(key),
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    database::{DbPool, kms::get_kms_key},
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, NotFoundException, kms_error_response},
+            models::{Blob, ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT, KeyId, is_valid_plaintext},
+        },
+    },
+    kms::{EncryptionContext, encrypt},
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_Encrypt.html
+pub struct EncryptHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct EncryptRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+
+    #[serde(rename = "Plaintext")]
+    #[garde(custom(is_valid_plaintext))]
+    plaintext: Blob,
+
+    #[serde(rename = "EncryptionContext")]
+    #[serde(default)]
+    #[garde(skip)]
+    encryption_context: EncryptionContext,
+}
+
+#[derive(Serialize)]
+pub struct EncryptResponse {
+    #[serde(rename = "CiphertextBlob")]
+    ciphertext_blob: Blob,
+    #[serde(rename = "EncryptionAlgorithm")]
+    encryption_algorithm: String,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+}
+
+impl Handler for EncryptHandler {
+    type Request = EncryptRequest;
+    type Response = EncryptResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let KeyId(key_id) = request.key_id;
+        let Blob(plaintext) = request.plaintext;
+
+        let key = get_kms_key(db, &context.caller.region, &key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        let ciphertext_blob =
+            encrypt(&key, &plaintext, &request.encryption_context).map_err(kms_error_response)?;
+
+        Ok(EncryptResponse {
+            ciphertext_blob: Blob(ciphertext_blob),
+            encryption_algorithm: ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT.to_string(),
+            key_id: key.arn,
+        })
+    }
+}
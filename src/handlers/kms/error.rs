@@ -0,0 +1,82 @@
+use crate::{
+    handlers::error::{AwsError, AwsErrorResponse},
+    kms::KmsError,
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+pub struct NotFoundException;
+
+impl AwsError for NotFoundException {
+    const TYPE: &str = "NotFoundException";
+    const MESSAGE: &str =
+        "The request was rejected because the specified entity or resource could not be found.";
+}
+
+pub struct AlreadyExistsException;
+
+impl AwsError for AlreadyExistsException {
+    const TYPE: &str = "AlreadyExistsException";
+    const MESSAGE: &str =
+        "The request was rejected because it attempted to create a resource that already exists.";
+}
+
+pub struct DisabledException;
+
+impl AwsError for DisabledException {
+    const TYPE: &str = "DisabledException";
+    const MESSAGE: &str = "The request was rejected because the specified KMS key is not enabled.";
+}
+
+pub struct KMSInvalidStateException;
+
+impl AwsError for KMSInvalidStateException {
+    const TYPE: &str = "KMSInvalidStateException";
+    const MESSAGE: &str = "The request was rejected because the state of the specified resource is not valid for this request.";
+}
+
+pub struct InvalidCiphertextException;
+
+impl AwsError for InvalidCiphertextException {
+    const TYPE: &str = "InvalidCiphertextException";
+    const MESSAGE: &str = "The request was rejected because the specified ciphertext, or additional authenticated data incorporated into the ciphertext, such as the encryption context, is corrupted, missing, or otherwise invalid.";
+}
+
+pub struct IncorrectKeyException;
+
+impl AwsError for IncorrectKeyException {
+    const TYPE: &str = "IncorrectKeyException";
+    const MESSAGE: &str =
+        "The request was rejected because the specified KMS key cannot decrypt the data.";
+}
+
+pub struct ValidationException;
+
+impl AwsError for ValidationException {
+    const TYPE: &str = "ValidationException";
+    const MESSAGE: &str = "The input fails to satisfy the constraints specified by the service.";
+}
+
+pub struct KMSInternalException;
+
+impl AwsError for KMSInternalException {
+    const STATUS_CODE: StatusCode = StatusCode::INTERNAL_SERVER_ERROR;
+    const TYPE: &str = "KMSInternalException";
+    const MESSAGE: &str = "The request was rejected because an internal exception occurred.";
+}
+
+/// Create the response for a failed cryptographic operation
+pub fn kms_error_response(error: KmsError) -> Response {
+    match error {
+        KmsError::Db(error) => {
+            tracing::error!(?error, "failed to perform kms operation");
+            AwsErrorResponse(KMSInternalException).into_response()
+        }
+        KmsError::KeyNotFound => AwsErrorResponse(NotFoundException).into_response(),
+        KmsError::KeyDisabled => AwsErrorResponse(DisabledException).into_response(),
+        KmsError::KeyPendingDeletion => AwsErrorResponse(KMSInvalidStateException).into_response(),
+        KmsError::InvalidCiphertext => AwsErrorResponse(InvalidCiphertextException).into_response(),
+    }
+}
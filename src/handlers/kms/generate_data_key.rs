@@ -0,0 +1,119 @@
+use crate::{
+    database::{DbPool, kms::get_kms_key},
+    handlers::{
+        Handler,
+        error::{AwsError, AwsErrorResponse},
+        kms::{
+            error::{
+                KMSInternalException, NotFoundException, ValidationException, kms_error_response,
+            },
+            models::{Blob, KeyId},
+        },
+    },
+    kms::{EncryptionContext, encrypt, generate_key_material},
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_GenerateDataKey.html
+pub struct GenerateDataKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct GenerateDataKeyRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+
+    #[serde(rename = "KeySpec")]
+    #[garde(inner(custom(is_valid_data_key_spec)))]
+    key_spec: Option<String>,
+
+    #[serde(rename = "NumberOfBytes")]
+    #[garde(inner(range(min = 1, max = 1024)))]
+    number_of_bytes: Option<usize>,
+
+    #[serde(rename = "EncryptionContext")]
+    #[serde(default)]
+    #[garde(skip)]
+    encryption_context: EncryptionContext,
+}
+
+#[derive(Serialize)]
+pub struct GenerateDataKeyResponse {
+    #[serde(rename = "CiphertextBlob")]
+    ciphertext_blob: Blob,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+    #[serde(rename = "Plaintext")]
+    plaintext: Blob,
+}
+
+/// Checks if the provided value is a valid data key spec
+fn is_valid_data_key_spec(value: &str, _context: &()) -> garde::Result {
+    if data_key_spec_length(value).is_none() {
+        return Err(garde::Error::new(
+            "unknown key spec expected one of: AES_256, AES_128",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the length in bytes of a data key with the provided `key_spec`
+fn data_key_spec_length(key_spec: &str) -> Option<usize> {
+    match key_spec {
+        "AES_256" => Some(32),
+        "AES_128" => Some(16),
+        _ => None,
+    }
+}
+
+impl Handler for GenerateDataKeyHandler {
+    type Request = GenerateDataKeyRequest;
+    type Response = GenerateDataKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let KeyId(key_id) = request.key_id;
+
+        // Must specify exactly one of the key spec or number of bytes
+        let length = match (request.key_spec.as_deref(), request.number_of_bytes) {
+            (Some(key_spec), None) => data_key_spec_length(key_spec),
+            (None, Some(number_of_bytes)) => Some(number_of_bytes),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            let error = ValidationException.with_message(
+                "Specify either KeySpec or NumberOfBytes with a value, but not both.",
+            );
+            AwsErrorResponse(error).into_response()
+        })?;
+
+        let key = get_kms_key(db, &context.caller.region, &key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        let plaintext = generate_key_material(length);
+        let ciphertext_blob =
+            encrypt(&key, &plaintext, &request.encryption_context).map_err(kms_error_response)?;
+
+        Ok(GenerateDataKeyResponse {
+            ciphertext_blob: Blob(ciphertext_blob),
+            key_id: key.arn,
+            plaintext: Blob(plaintext),
+        })
+    }
+}
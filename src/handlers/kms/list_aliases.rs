@@ -0,0 +1,133 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{count_kms_aliases, get_kms_aliases_page, get_kms_key},
+    },
+    handlers::{
+        Handler,
+        error::{AwsError, AwsErrorResponse},
+        kms::{
+            error::{KMSInternalException, NotFoundException, ValidationException},
+            models::{AliasListEntry, KeyId},
+        },
+        models::PaginationToken,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use tokio::join;
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_ListAliases.html
+pub struct ListAliasesHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ListAliasesRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: Option<KeyId>,
+
+    #[serde(rename = "Limit")]
+    #[serde(default = "default_limit")]
+    #[garde(range(min = 1, max = 100))]
+    limit: i32,
+
+    #[serde(rename = "Marker")]
+    #[serde(default = "default_marker")]
+    #[garde(dive)]
+    marker: PaginationToken,
+}
+
+#[derive(Serialize)]
+pub struct ListAliasesResponse {
+    #[serde(rename = "Aliases")]
+    aliases: Vec<AliasListEntry>,
+    #[serde(rename = "NextMarker")]
+    next_marker: Option<String>,
+    #[serde(rename = "Truncated")]
+    truncated: bool,
+}
+
+fn default_limit() -> i32 {
+    50
+}
+
+fn default_marker() -> PaginationToken {
+    PaginationToken {
+        page_size: 50,
+        page_index: 0,
+    }
+}
+
+impl Handler for ListAliasesHandler {
+    type Request = ListAliasesRequest;
+    type Response = ListAliasesResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let pagination_token = request.marker.page_size(request.limit);
+
+        let (limit, offset) = pagination_token.as_query_parts().ok_or_else(|| {
+            let error = ValidationException.with_message("The Marker value is invalid.");
+            AwsErrorResponse(error).into_response()
+        })?;
+
+        // Resolve the key to only list its aliases
+        let target_key_id = match request.key_id {
+            Some(KeyId(key_id)) => {
+                let key = get_kms_key(db, &context.caller.region, &key_id)
+                    .await
+                    //
+                    .map_err(|error| {
+                        tracing::error!(?error, "failed to get kms key");
+                        AwsErrorResponse(KMSInternalException).into_response()
+                    })?
+                    //
+                    .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+                Some(key.key_id)
+            }
+            None => None,
+        };
+
+        let (aliases, count) = join!(
+            get_kms_aliases_page(
+                db,
+                &context.caller.region,
+                target_key_id.as_deref(),
+                limit,
+                offset
+            ),
+            count_kms_aliases(db, &context.caller.region, target_key_id.as_deref()),
+        );
+
+        let aliases = aliases.map_err(|error| {
+            tracing::error!(?error, "failed to get kms aliases");
+            AwsErrorResponse(KMSInternalException).into_response()
+        })?;
+
+        let count = count.map_err(|error| {
+            tracing::error!(?error, "failed to get kms aliases count");
+            AwsErrorResponse(KMSInternalException).into_response()
+        })?;
+
+        let next_marker = pagination_token
+            .get_next_page(count)
+            .map(|value| value.to_string());
+
+        Ok(ListAliasesResponse {
+            aliases: aliases
+                .into_iter()
+                .map(AliasListEntry::from_stored)
+                .collect(),
+            truncated: next_marker.is_some(),
+            next_marker,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{count_kms_keys, get_kms_keys_page},
+    },
+    handlers::{
+        Handler,
+        error::{AwsError, AwsErrorResponse},
+        kms::error::{KMSInternalException, ValidationException},
+        models::PaginationToken,
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use tokio::join;
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_ListKeys.html
+pub struct ListKeysHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ListKeysRequest {
+    #[serde(rename = "Limit")]
+    #[serde(default = "default_limit")]
+    #[garde(range(min = 1, max = 1000))]
+    limit: i32,
+
+    #[serde(rename = "Marker")]
+    #[serde(default = "default_marker")]
+    #[garde(dive)]
+    marker: PaginationToken,
+}
+
+#[derive(Serialize)]
+pub struct ListKeysResponse {
+    #[serde(rename = "Keys")]
+    keys: Vec<KeyListEntry>,
+    #[serde(rename = "NextMarker")]
+    next_marker: Option<String>,
+    #[serde(rename = "Truncated")]
+    truncated: bool,
+}
+
+#[derive(Serialize)]
+pub struct KeyListEntry {
+    #[serde(rename = "KeyArn")]
+    key_arn: String,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+}
+
+fn default_limit() -> i32 {
+    100
+}
+
+fn default_marker() -> PaginationToken {
+    PaginationToken {
+        page_size: 100,
+        page_index: 0,
+    }
+}
+
+impl Handler for ListKeysHandler {
+    type Request = ListKeysRequest;
+    type Response = ListKeysResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let pagination_token = request.marker.page_size(request.limit);
+
+        let (limit, offset) = pagination_token.as_query_parts().ok_or_else(|| {
+            let error = ValidationException.with_message("The Marker value is invalid.");
+            AwsErrorResponse(error).into_response()
+        })?;
+
+        let (keys, count) = join!(
+            get_kms_keys_page(db, &context.caller.region, limit, offset),
+            count_kms_keys(db, &context.caller.region),
+        );
+
+        let keys = keys.map_err(|error| {
+            tracing::error!(?error, "failed to get kms keys");
+            AwsErrorResponse(KMSInternalException).into_response()
+        })?;
+
+        let count = count.map_err(|error| {
+            tracing::error!(?error, "failed to get kms keys count");
+            AwsErrorResponse(KMSInternalException).into_response()
+        })?;
+
+        let next_marker = pagination_token
+            .get_next_page(count)
+            .map(|value| value.to_string());
+
+        Ok(ListKeysResponse {
+            keys: keys
+                .into_iter()
+                .map(|key| KeyListEntry {
+                    key_arn: key.arn,
+                    key_id: key.key_id,
+                })
+                .collect(),
+            truncated: next_marker.is_some(),
+            next_marker,
+        })
+    }
+}
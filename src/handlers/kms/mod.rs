@@ -0,0 +1,19 @@
+//! KMS compatible endpoint, KMS uses the same JSON protocol as Secrets Manager
+//! with targets in the form `TrentService.Operation`
+
+pub mod cancel_key_deletion;
+pub mod create_alias;
+pub mod create_key;
+pub mod decrypt;
+pub mod delete_alias;
+pub mod describe_key;
+pub mod disable_key;
+pub mod enable_key;
+pub mod encrypt;
+pub mod error;
+pub mod generate_data_key;
+pub mod list_aliases;
+pub mod list_keys;
+pub mod models;
+pub mod schedule_key_deletion;
+pub mod update_alias;
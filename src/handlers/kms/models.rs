@@ -0,0 +1,217 @@
+use crate::{
+    database::kms::{KEY_STATE_ENABLED, StoredKmsAlias, StoredKmsKey},
+    handlers::models::Arn,
+    utils::{
+        blob::{deserialize_blob, serialize_blob},
+        date::Timestamp,
+    },
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// Usage of the symmetric keys created by the server
+pub const KEY_USAGE_ENCRYPT_DECRYPT: &str = "ENCRYPT_DECRYPT";
+
+/// Spec of the symmetric keys created by the server
+pub const KEY_SPEC_SYMMETRIC_DEFAULT: &str = "SYMMETRIC_DEFAULT";
+
+/// Encryption algorithm used by symmetric keys
+pub const ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT: &str = "SYMMETRIC_DEFAULT";
+
+/// Key ID, key ARN, alias name or alias ARN identifying a KMS key
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct KeyId(#[garde(length(min = 1, max = 2048))] pub String);
+
+impl Display for KeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct AliasName(
+    #[garde(length(min = 1, max = 256))]
+    #[garde(custom(is_valid_alias_name))]
+    pub String,
+);
+
+impl Display for AliasName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Checks if the provided value is a valid alias name, aliases must be prefixed
+/// with `alias/` and the `alias/aws/` prefix is reserved for AWS managed keys
+fn is_valid_alias_name(value: &str, _context: &()) -> garde::Result {
+    const ALLOWED_SPECIAL_CHARACTERS: &str = "/_-";
+
+    let name = match value.strip_prefix("alias/") {
+        Some(value) if !value.is_empty() => value,
+        _ => return Err(garde::Error::new("alias name must start with alias/")),
+    };
+
+    if name.starts_with("aws/") {
+        return Err(garde::Error::new(
+            "alias name must not start with alias/aws/",
+        ));
+    }
+
+    if !name
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || ALLOWED_SPECIAL_CHARACTERS.contains(char))
+    {
+        return Err(garde::Error::new(
+            "alias name contains disallowed characters",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Binary data that is base64 encoded in JSON requests and responses
+#[derive(Debug)]
+pub struct Blob(pub Vec<u8>);
+
+impl<'de> Deserialize<'de> for Blob {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_blob(deserializer).map(Blob)
+    }
+}
+
+impl Serialize for Blob {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serialize_blob(&self.0, serializer)
+    }
+}
+
+/// Checks if the provided blob is within the size limits of a plaintext
+pub fn is_valid_plaintext(value: &Blob, _context: &()) -> garde::Result {
+    if value.0.is_empty() || value.0.len() > 4096 {
+        return Err(garde::Error::new(
+            "plaintext must be between 1 and 4096 bytes",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks if the provided blob is within the size limits of a ciphertext
+pub fn is_valid_ciphertext(value: &Blob, _context: &()) -> garde::Result {
+    if value.0.is_empty() || value.0.len() > 6144 {
+        return Err(garde::Error::new(
+            "ciphertext must be between 1 and 6144 bytes",
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct KeyMetadata {
+    #[serde(rename = "AWSAccountId")]
+    pub aws_account_id: Option<String>,
+    #[serde(rename = "Arn")]
+    pub arn: String,
+    #[serde(rename = "CreationDate")]
+    pub creation_date: Timestamp,
+    #[serde(rename = "CustomerMasterKeySpec")]
+    pub customer_master_key_spec: String,
+    #[serde(rename = "DeletionDate")]
+    pub deletion_date: Option<Timestamp>,
+    #[serde(rename = "Description")]
+    pub description: String,
+    #[serde(rename = "Enabled")]
+    pub enabled: bool,
+    #[serde(rename = "EncryptionAlgorithms")]
+    pub encryption_algorithms: Vec<String>,
+    #[serde(rename = "KeyId")]
+    pub key_id: String,
+    #[serde(rename = "KeyManager")]
+    pub key_manager: String,
+    #[serde(rename = "KeySpec")]
+    pub key_spec: String,
+    #[serde(rename = "KeyState")]
+    pub key_state: String,
+    #[serde(rename = "KeyUsage")]
+    pub key_usage: String,
+    #[serde(rename = "MultiRegion")]
+    pub multi_region: bool,
+    #[serde(rename = "Origin")]
+    pub origin: String,
+}
+
+impl KeyMetadata {
+    pub fn from_stored(key: StoredKmsKey) -> Self {
+        Self {
+            aws_account_id: Arn::from_str(&key.arn)
+                .ok()
+                .filter(Arn::has_account_id)
+                .map(|arn| arn.account_id),
+            creation_date: Timestamp(key.created_at),
+            customer_master_key_spec: key.key_spec.clone(),
+            deletion_date: key.deletion_date.map(Timestamp),
+            description: key.description,
+            enabled: key.key_state == KEY_STATE_ENABLED,
+            encryption_algorithms: vec![ENCRYPTION_ALGORITHM_SYMMETRIC_DEFAULT.to_string()],
+            key_id: key.key_id,
+            key_manager: "CUSTOMER".to_string(),
+            key_spec: key.key_spec,
+            key_state: key.key_state,
+            key_usage: key.key_usage,
+            multi_region: false,
+            origin: "AWS_KMS".to_string(),
+            arn: key.arn,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AliasListEntry {
+    #[serde(rename = "AliasArn")]
+    pub alias_arn: String,
+    #[serde(rename = "AliasName")]
+    pub alias_name: String,
+    #[serde(rename = "CreationDate")]
+    pub creation_date: Timestamp,
+    #[serde(rename = "LastUpdatedDate")]
+    pub last_updated_date: Option<Timestamp>,
+    #[serde(rename = "TargetKeyId")]
+    pub target_key_id: String,
+}
+
+impl AliasListEntry {
+    pub fn from_stored(alias: StoredKmsAlias) -> Self {
+        Self {
+            alias_arn: alias.arn,
+            alias_name: alias.alias_name,
+            creation_date: Timestamp(alias.created_at),
+            last_updated_date: alias.updated_at.map(Timestamp),
+            target_key_id: alias.target_key_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_alias_name() {
+        assert!(is_valid_alias_name("alias/test", &()).is_ok());
+        assert!(is_valid_alias_name("alias/test/nested_name-1", &()).is_ok());
+        assert!(is_valid_alias_name("alias/", &()).is_err());
+        assert!(is_valid_alias_name("test", &()).is_err());
+        assert!(is_valid_alias_name("alias/aws/secretsmanager", &()).is_err());
+        assert!(is_valid_alias_name("alias/test name", &()).is_err());
+    }
+}
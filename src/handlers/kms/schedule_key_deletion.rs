@@ -0,0 +1,105 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{KEY_STATE_PENDING_DELETION, get_kms_key, update_kms_key_state},
+    },
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, KMSInvalidStateException, NotFoundException},
+            models::KeyId,
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
+};
+use axum::response::{IntoResponse, Response};
+use chrono::{Days, Utc};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_ScheduleKeyDeletion.html
+pub struct ScheduleKeyDeletionHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ScheduleKeyDeletionRequest {
+    #[serde(rename = "KeyId")]
+    #[garde(dive)]
+    key_id: KeyId,
+
+    #[serde(rename = "PendingWindowInDays")]
+    #[serde(default = "default_pending_window_in_days")]
+    #[garde(range(min = 7, max = 30))]
+    pending_window_in_days: i32,
+}
+
+#[derive(Serialize)]
+pub struct ScheduleKeyDeletionResponse {
+    #[serde(rename = "DeletionDate")]
+    deletion_date: Timestamp,
+    #[serde(rename = "KeyId")]
+    key_id: String,
+    #[serde(rename = "KeyState")]
+    key_state: String,
+    #[serde(rename = "PendingWindowInDays")]
+    pending_window_in_days: i32,
+}
+
+fn default_pending_window_in_days() -> i32 {
+    30
+}
+
+impl Handler for ScheduleKeyDeletionHandler {
+    type Request = ScheduleKeyDeletionRequest;
+    type Response = ScheduleKeyDeletionResponse;
+
+    #[tracing::instrument(skip_all, fields(key_id = %request.key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let KeyId(key_id) = request.key_id;
+        let pending_window_in_days = request.pending_window_in_days;
+
+        let key = get_kms_key(db, &context.caller.region, &key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        // Key is already scheduled for deletion
+        if key.key_state == KEY_STATE_PENDING_DELETION {
+            return Err(AwsErrorResponse(KMSInvalidStateException).into_response());
+        }
+
+        let deletion_date = Utc::now()
+            .checked_add_days(Days::new(pending_window_in_days as u64))
+            .ok_or_else(|| AwsErrorResponse(KMSInternalException).into_response())?;
+
+        if let Err(error) = update_kms_key_state(
+            db,
+            &key.key_id,
+            KEY_STATE_PENDING_DELETION,
+            Some(deletion_date),
+        )
+        .await
+        {
+            tracing::error!(?error, "failed to schedule kms key deletion");
+            return Err(AwsErrorResponse(KMSInternalException).into_response());
+        }
+
+        Ok(ScheduleKeyDeletionResponse {
+            deletion_date: Timestamp(deletion_date),
+            key_id: key.arn,
+            key_state: KEY_STATE_PENDING_DELETION.to_string(),
+            pending_window_in_days,
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    database::{
+        DbPool,
+        kms::{KEY_STATE_PENDING_DELETION, get_kms_alias, get_kms_key, update_kms_alias_target},
+    },
+    handlers::{
+        Handler,
+        error::AwsErrorResponse,
+        kms::{
+            error::{KMSInternalException, KMSInvalidStateException, NotFoundException},
+            models::{AliasName, KeyId},
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/kms/latest/APIReference/API_UpdateAlias.html
+pub struct UpdateAliasHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateAliasRequest {
+    #[serde(rename = "AliasName")]
+    #[garde(dive)]
+    alias_name: AliasName,
+
+    #[serde(rename = "TargetKeyId")]
+    #[garde(dive)]
+    target_key_id: KeyId,
+}
+
+#[derive(Serialize)]
+pub struct UpdateAliasResponse {}
+
+impl Handler for UpdateAliasHandler {
+    type Request = UpdateAliasRequest;
+    type Response = UpdateAliasResponse;
+
+    #[tracing::instrument(skip_all, fields(alias_name = %request.alias_name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AliasName(alias_name) = request.alias_name;
+        let KeyId(target_key_id) = request.target_key_id;
+
+        get_kms_alias(db, &context.caller.region, &alias_name)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms alias");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        let key = get_kms_key(db, &context.caller.region, &target_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get kms key");
+                AwsErrorResponse(KMSInternalException).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(NotFoundException).into_response())?;
+
+        // Aliases can't be moved to keys pending deletion
+        if key.key_state == KEY_STATE_PENDING_DELETION {
+            return Err(AwsErrorResponse(KMSInvalidStateException).into_response());
+        }
+
+        if let Err(error) =
+            update_kms_alias_target(db, &context.caller.region, &alias_name, &key.key_id).await
+        {
+            tracing::error!(?error, "failed to update kms alias");
+            return Err(AwsErrorResponse(KMSInternalException).into_response());
+        }
+
+        Ok(UpdateAliasResponse {})
+    }
+}
@@ -0,0 +1,250 @@
+//! HTTP endpoint compatible with the AWS Parameters and Secrets Lambda Extension,
+//! secrets are requested using `GET /secretsmanager/get?secretId=` and served
+//! through the GetSecretValue handler
+//!
+//! https://docs.aws.amazon.com/secretsmanager/latest/userguide/retrieving-secrets_lambda.html
+
+use crate::{
+    database::DbPool,
+    handlers::HandlerRouterService,
+    middleware::{
+        aws_sig_v4::{CallerIdentity, RequestContext},
+        request_id::RequestId,
+    },
+};
+use axum::{
+    Extension, Router,
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Header containing the token that authenticates requests to the extension
+const TOKEN_HEADER: &str = "x-aws-parameters-secrets-token";
+
+/// Access key ID reported for requests made through the extension
+const ACCESS_KEY_ID: &str = "lambda-extension";
+
+/// Options for the Lambda extension endpoint
+pub struct LambdaExtensionOptions {
+    /// Token requests must provide in the `X-Aws-Parameters-Secrets-Token` header
+    pub token: String,
+    /// Region secrets are read from
+    pub region: String,
+    /// Account requests are made as, requests are made as the root of the account
+    pub account_id: String,
+    /// Duration secret values are cached for, zero disables the cache
+    pub cache_ttl: Duration,
+    /// Maximum number of secret values to cache
+    pub cache_size: usize,
+}
+
+struct LambdaExtensionState {
+    handlers: HandlerRouterService,
+    token: String,
+    caller: CallerIdentity,
+    cache: SecretCache,
+}
+
+/// Create the router for the Lambda extension endpoint, requests are handled
+/// by the `handlers`
+pub fn lambda_extension_router(
+    handlers: HandlerRouterService,
+    options: LambdaExtensionOptions,
+) -> Router {
+    let caller = CallerIdentity::account_root(ACCESS_KEY_ID, options.account_id, options.region);
+
+    let state = LambdaExtensionState {
+        handlers,
+        token: options.token,
+        caller,
+        cache: SecretCache::new(options.cache_ttl, options.cache_size),
+    };
+
+    Router::new()
+        .route("/secretsmanager/get", get(get_secret))
+        .with_state(Arc::new(state))
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+struct GetSecretQuery {
+    #[serde(rename = "secretId")]
+    secret_id: String,
+    #[serde(rename = "versionId")]
+    version_id: Option<String>,
+    #[serde(rename = "versionStage")]
+    version_stage: Option<String>,
+}
+
+#[tracing::instrument(skip_all, fields(secret_id = %query.secret_id))]
+async fn get_secret(
+    State(state): State<Arc<LambdaExtensionState>>,
+    Extension(db): Extension<DbPool>,
+    headers: HeaderMap,
+    Query(query): Query<GetSecretQuery>,
+) -> Response {
+    let token = headers
+        .get(TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    if token != Some(state.token.as_str()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    if let Some(body) = state.cache.get(&query) {
+        return json_response(body);
+    }
+
+    let request = json!({
+        "SecretId": query.secret_id,
+        "VersionId": query.version_id,
+        "VersionStage": query.version_stage,
+    });
+    let request = match serde_json::to_vec(&request) {
+        Ok(value) => value,
+        Err(error) => {
+            tracing::error!(?error, "failed to serialize request");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let context = RequestContext::new(state.caller.clone(), RequestId::generate(), None);
+
+    let response = state
+        .handlers
+        .router
+        .handle_request(&db, &context, "secretsmanager.GetSecretValue", &request)
+        .await;
+
+    // Errors are passed through without being cached
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let body = match response.into_body().collect().await {
+        Ok(value) => value.to_bytes(),
+        Err(error) => {
+            tracing::error!(?error, "failed to collect response bytes");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    state.cache.insert(query, body.clone());
+    json_response(body)
+}
+
+fn json_response(body: Bytes) -> Response {
+    (
+        [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+        body,
+    )
+        .into_response()
+}
+
+/// In-process cache of GetSecretValue responses, entries expire after the
+/// TTL and the oldest entry is evicted when the cache is full
+struct SecretCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<GetSecretQuery, (Instant, Bytes)>>,
+}
+
+impl SecretCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.capacity > 0
+    }
+
+    fn get(&self, query: &GetSecretQuery) -> Option<Bytes> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let entries = self.entries.lock().expect("secret cache lock poisoned");
+        entries
+            .get(query)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, body)| body.clone())
+    }
+
+    fn insert(&self, query: GetSecretQuery, body: Bytes) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().expect("secret cache lock poisoned");
+        entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+
+        if entries.len() >= self.capacity && !entries.contains_key(&query) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (cached_at, _))| *cached_at)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(query, (Instant::now(), body));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(secret_id: &str) -> GetSecretQuery {
+        GetSecretQuery {
+            secret_id: secret_id.to_string(),
+            version_id: None,
+            version_stage: None,
+        }
+    }
+
+    #[test]
+    fn test_secret_cache_evicts_oldest() {
+        let cache = SecretCache::new(Duration::from_secs(60), 2);
+
+        // Entries are spaced out so the oldest entry is unambiguous
+        for secret_id in ["first", "second", "third"] {
+            cache.insert(query(secret_id), Bytes::from(secret_id));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(cache.get(&query("first")), None);
+        assert_eq!(
+            cache.get(&query("second")),
+            Some(Bytes::from_static(b"second"))
+        );
+        assert_eq!(
+            cache.get(&query("third")),
+            Some(Bytes::from_static(b"third"))
+        );
+    }
+
+    #[test]
+    fn test_secret_cache_disabled() {
+        let cache = SecretCache::new(Duration::ZERO, 2);
+
+        cache.insert(query("first"), Bytes::from_static(b"first"));
+        assert_eq!(cache.get(&query("first")), None);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{
+    database::{DbPool, access_keys::get_access_keys},
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError},
+        models::AccessKeyStatus,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    middleware::aws_sig_v4::default_principal_arn,
+    utils::date::Timestamp,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_ListAccessKeys.html
+pub struct ListAccessKeysHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ListAccessKeysRequest {}
+
+#[derive(Serialize)]
+pub struct ListAccessKeysResponse {
+    #[serde(rename = "AccessKeyMetadata")]
+    access_key_metadata: Vec<AccessKeyMetadata>,
+}
+
+#[derive(Serialize)]
+pub struct AccessKeyMetadata {
+    #[serde(rename = "AccessKeyId")]
+    access_key_id: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "PrincipalArn")]
+    principal_arn: String,
+    #[serde(rename = "Status")]
+    status: AccessKeyStatus,
+    #[serde(rename = "CreateDate")]
+    create_date: Timestamp,
+    #[serde(rename = "ExpirationDate")]
+    expiration_date: Option<Timestamp>,
+    #[serde(rename = "LastUsedDate")]
+    last_used_date: Option<Timestamp>,
+}
+
+impl Handler for ListAccessKeysHandler {
+    type Request = ListAccessKeysRequest;
+    type Response = ListAccessKeysResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        _request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let access_keys = get_access_keys(db).await.map_err(|error| {
+            tracing::error!(?error, "failed to get access keys");
+            AwsErrorResponse(InternalServiceError).into_response()
+        })?;
+
+        let access_key_metadata = access_keys
+            .into_iter()
+            .map(|access_key| AccessKeyMetadata {
+                principal_arn: access_key.principal_arn.unwrap_or_else(|| {
+                    default_principal_arn(&context.caller.account_id, &access_key.name)
+                }),
+                access_key_id: access_key.access_key_id,
+                name: access_key.name,
+                status: AccessKeyStatus::from_enabled(access_key.enabled),
+                create_date: Timestamp(access_key.created_at),
+                expiration_date: access_key.expires_at.map(Timestamp),
+                last_used_date: access_key.last_used_at.map(Timestamp),
+            })
+            .collect();
+
+        Ok(ListAccessKeysResponse {
+            access_key_metadata,
+        })
+    }
+}
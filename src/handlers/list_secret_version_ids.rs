@@ -6,12 +6,13 @@ use crate::{
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            AwsErrorResponse, InternalServiceError, InvalidNextTokenException,
             ResourceNotFoundException,
         },
         models::{PaginationToken, SecretId},
     },
-    utils::date::datetime_to_f64,
+    middleware::aws_sig_v4::RequestContext,
+    utils::date::Timestamp,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -58,11 +59,11 @@ pub struct ListSecretVersionIdsResponse {
 #[derive(Serialize)]
 pub struct SecretVersionsListEntry {
     #[serde(rename = "CreatedDate")]
-    created_date: f64,
+    created_date: Timestamp,
     #[serde(rename = "KmsKeyIds")]
     kms_key_ids: Option<Vec<String>>,
     #[serde(rename = "LastAccessedDate")]
-    last_accessed_date: Option<f64>,
+    last_accessed_date: Option<Timestamp>,
     #[serde(rename = "VersionId")]
     version_id: String,
     #[serde(rename = "VersionStages")]
@@ -84,8 +85,17 @@ impl Handler for ListSecretVersionIdsHandler {
     type Request = ListSecretVersionIdsRequest;
     type Response = ListSecretVersionIdsResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let ListSecretVersionIdsRequest {
             include_deprecated,
             max_results,
@@ -96,7 +106,7 @@ impl Handler for ListSecretVersionIdsHandler {
         let SecretId(secret_id) = secret_id;
         let pagination_token = next_token.page_size(max_results);
 
-        let secret = get_secret_latest_version(db, &secret_id)
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
             .await
             //
             .map_err(|error| {
@@ -108,7 +118,7 @@ impl Handler for ListSecretVersionIdsHandler {
 
         let (limit, offset) = pagination_token
             .as_query_parts()
-            .ok_or_else(|| AwsErrorResponse(InvalidRequestException).into_response())?;
+            .ok_or_else(|| AwsErrorResponse(InvalidNextTokenException).into_response())?;
 
         let (versions, count) = join!(
             get_secret_versions_page(db, &secret.arn, include_deprecated, limit, offset),
@@ -132,9 +142,9 @@ impl Handler for ListSecretVersionIdsHandler {
         let versions = versions
             .into_iter()
             .map(|version| SecretVersionsListEntry {
-                created_date: datetime_to_f64(version.created_at),
+                created_date: Timestamp(version.created_at),
                 kms_key_ids: None,
-                last_accessed_date: version.last_accessed_at.map(datetime_to_f64),
+                last_accessed_date: version.last_accessed_at.map(Timestamp),
                 version_id: version.version_id,
                 version_stages: version.version_stages,
             })
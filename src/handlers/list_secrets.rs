@@ -5,10 +5,11 @@ use crate::{
     },
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InternalServiceError, InvalidRequestException},
-        models::{Filter, PaginationToken, Tag},
+        error::{AwsErrorResponse, InternalServiceError, InvalidNextTokenException},
+        models::{Filter, PaginationToken, RotationRules, Tag},
     },
-    utils::{date::datetime_to_f64, string::join_iter_string},
+    middleware::aws_sig_v4::RequestContext,
+    utils::{date::Timestamp, string::join_iter_string},
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -60,23 +61,23 @@ pub struct SecretListEntry {
     #[serde(rename = "ARN")]
     arn: String,
     #[serde(rename = "CreatedDate")]
-    created_date: f64,
+    created_date: Timestamp,
     #[serde(rename = "DeletedDate")]
-    deleted_date: Option<f64>,
+    deleted_date: Option<Timestamp>,
     #[serde(rename = "Description")]
     description: Option<String>,
     #[serde(rename = "KmsKeyId")]
     kms_key_id: Option<String>,
     #[serde(rename = "LastAccessedDate")]
-    last_accessed_date: Option<f64>,
+    last_accessed_date: Option<Timestamp>,
     #[serde(rename = "LastChangedDate")]
-    last_changed_date: Option<f64>,
+    last_changed_date: Option<Timestamp>,
     #[serde(rename = "LastRotatedDate")]
-    last_rotated_date: Option<f64>,
+    last_rotated_date: Option<Timestamp>,
     #[serde(rename = "Name")]
     name: String,
     #[serde(rename = "NextRotationDate")]
-    next_rotation_date: Option<f64>,
+    next_rotation_date: Option<Timestamp>,
     #[serde(rename = "OwningService")]
     owning_service: Option<String>,
     #[serde(rename = "PrimaryRegion")]
@@ -86,7 +87,7 @@ pub struct SecretListEntry {
     #[serde(rename = "RotationLambdaARN")]
     rotation_lambda_arn: Option<String>,
     #[serde(rename = "RotationRules")]
-    rotation_rules: Option<serde_json::Value>,
+    rotation_rules: Option<RotationRules>,
     #[serde(rename = "SecretVersionsToStages")]
     secret_versions_to_stages: HashMap<String, Vec<String>>,
     #[serde(rename = "Tags")]
@@ -127,7 +128,12 @@ impl Handler for ListSecretsHandler {
     type Response = ListSecretsResponse;
 
     #[tracing::instrument(skip_all)]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let ListSecretsRequest {
             filters,
             include_planned_deletion,
@@ -141,11 +147,24 @@ impl Handler for ListSecretsHandler {
 
         let (limit, offset) = pagination_token
             .as_query_parts()
-            .ok_or_else(|| AwsErrorResponse(InvalidRequestException).into_response())?;
+            .ok_or_else(|| AwsErrorResponse(InvalidNextTokenException).into_response())?;
 
         let (secrets, count) = join!(
-            get_secrets_by_filter(db, &filters, include_planned_deletion, limit, offset, asc),
-            get_secrets_count_by_filter(db, &filters, include_planned_deletion),
+            get_secrets_by_filter(
+                db,
+                &context.caller.region,
+                &filters,
+                include_planned_deletion,
+                limit,
+                offset,
+                asc
+            ),
+            get_secrets_count_by_filter(
+                db,
+                &context.caller.region,
+                &filters,
+                include_planned_deletion
+            ),
         );
 
         let secrets = secrets.map_err(|error| {
@@ -198,19 +217,23 @@ impl Handler for ListSecretsHandler {
                 SecretListEntry {
                     arn: secret.arn,
                     description: secret.description,
-                    created_date: datetime_to_f64(secret.created_at),
-                    deleted_date: secret.deleted_at.map(datetime_to_f64),
-                    kms_key_id: None,
-                    last_accessed_date: most_recently_used.map(datetime_to_f64),
-                    last_changed_date: last_changed_date.map(datetime_to_f64),
-                    last_rotated_date: None,
+                    created_date: Timestamp(secret.created_at),
+                    deleted_date: secret.deleted_at.map(Timestamp),
+                    kms_key_id: secret.kms_key_id,
+                    last_accessed_date: most_recently_used.map(Timestamp),
+                    last_changed_date: last_changed_date.map(Timestamp),
+                    last_rotated_date: secret.last_rotated_at.map(Timestamp),
                     name: secret.name,
-                    next_rotation_date: None,
+                    next_rotation_date: secret.next_rotation_at.map(Timestamp),
                     owning_service: None,
-                    primary_region: None,
-                    rotation_enabled: false,
-                    rotation_lambda_arn: None,
-                    rotation_rules: None,
+                    primary_region: secret.primary_region,
+                    rotation_enabled: secret.rotation_enabled,
+                    rotation_lambda_arn: secret.rotation_lambda_arn,
+                    rotation_rules: RotationRules::from_stored(
+                        secret.rotation_automatically_after_days,
+                        secret.rotation_duration,
+                        secret.rotation_schedule_expression,
+                    ),
                     tags,
                     secret_versions_to_stages,
                 }
@@ -1,62 +1,151 @@
 use crate::{
     database::DbPool,
     handlers::{
-        batch_get_secret_value::BatchGetSecretValueHandler, create_secret::CreateSecretHandler,
-        delete_secret::DeleteSecretHandler, describe_secret::DescribeSecretHandler,
-        get_random_password::GetRandomPasswordHandler, get_secret_value::GetSecretValueHandler,
-        list_secret_version_ids::ListSecretVersionIdsHandler, list_secrets::ListSecretsHandler,
-        put_secret_value::PutSecretValueHandler, restore_secret::RestoreSecretHandler,
-        tag_resource::TagResourceHandler, untag_resource::UntagResourceHandler,
+        batch_get_secret_value::BatchGetSecretValueHandler,
+        cancel_rotate_secret::CancelRotateSecretHandler,
+        create_access_key::CreateAccessKeyHandler,
+        create_secret::CreateSecretHandler,
+        delete_access_key::DeleteAccessKeyHandler,
+        delete_access_key_policy::DeleteAccessKeyPolicyHandler,
+        delete_resource_policy::DeleteResourcePolicyHandler,
+        delete_secret::DeleteSecretHandler,
+        describe_secret::DescribeSecretHandler,
+        fault_injection::FaultInjector,
+        get_access_key_last_used::GetAccessKeyLastUsedHandler,
+        get_fault_injection_rules::GetFaultInjectionRulesHandler,
+        get_random_password::GetRandomPasswordHandler,
+        get_resource_policy::GetResourcePolicyHandler,
+        get_secret_value::GetSecretValueHandler,
+        kms::{
+            cancel_key_deletion::CancelKeyDeletionHandler, create_alias::CreateAliasHandler,
+            create_key::CreateKeyHandler, decrypt::DecryptHandler,
+            delete_alias::DeleteAliasHandler, describe_key::DescribeKeyHandler,
+            disable_key::DisableKeyHandler, enable_key::EnableKeyHandler, encrypt::EncryptHandler,
+            generate_data_key::GenerateDataKeyHandler, list_aliases::ListAliasesHandler,
+            list_keys::ListKeysHandler, schedule_key_deletion::ScheduleKeyDeletionHandler,
+            update_alias::UpdateAliasHandler,
+        },
+        list_access_keys::ListAccessKeysHandler,
+        list_secret_version_ids::ListSecretVersionIdsHandler,
+        list_secrets::ListSecretsHandler,
+        put_access_key_policy::PutAccessKeyPolicyHandler,
+        put_fault_injection_rules::PutFaultInjectionRulesHandler,
+        put_resource_policy::PutResourcePolicyHandler,
+        put_secret_value::PutSecretValueHandler,
+        quotas::{Quotas, RateLimiter},
+        remove_regions_from_replication::RemoveRegionsFromReplicationHandler,
+        replicate_secret_to_regions::ReplicateSecretToRegionsHandler,
+        restore_secret::RestoreSecretHandler,
+        rotate_secret::RotateSecretHandler,
+        ssm::{get_parameter::GetParameterHandler, get_parameters::GetParametersHandler},
+        stop_replication_to_replica::StopReplicationToReplicaHandler,
+        tag_resource::TagResourceHandler,
+        untag_resource::UntagResourceHandler,
+        update_access_key::UpdateAccessKeyHandler,
         update_secret::UpdateSecretHandler,
         update_secret_version_stage::UpdateSecretVersionStageHandler,
+        validate_resource_policy::ValidateResourcePolicyHandler,
     },
+    middleware::aws_sig_v4::RequestContext,
+    rotation::function::RotationFunctions,
 };
+use authorize::Authorizer;
 use axum::{
-    Json,
     body::Body,
-    http::Request,
+    http::{HeaderMap, Request, header::CONTENT_TYPE},
     response::{IntoResponse, Response},
 };
 use error::{
-    AwsErrorResponse, InternalServiceError, InvalidParameterException, InvalidRequestException,
-    NotImplemented,
+    AwsError, AwsErrorResponse, InternalServiceError, InvalidParameterException,
+    InvalidRequestException, MissingAuthenticationToken, NotImplemented, ThrottlingException,
+    validation_message,
 };
 use futures::future::BoxFuture;
 use garde::Validate;
 use http_body_util::BodyExt;
+use protocol::Protocol;
 use serde::{Serialize, de::DeserializeOwned};
 use std::{collections::HashMap, convert::Infallible, sync::Arc, task::Poll};
+use sts::handle_sts_request;
 use tower::Service;
 
+pub mod lambda_extension;
+pub mod vault;
+
 pub(crate) mod error;
+pub(crate) mod get_random_password;
 pub(crate) mod models;
 
+mod authorize;
 mod batch_get_secret_value;
+mod cancel_rotate_secret;
+mod create_access_key;
 mod create_secret;
+mod delete_access_key;
+mod delete_access_key_policy;
+mod delete_resource_policy;
 mod delete_secret;
 mod describe_secret;
-mod get_random_password;
+pub mod fault_injection;
+mod get_access_key_last_used;
+mod get_fault_injection_rules;
+mod get_resource_policy;
 mod get_secret_value;
+mod kms;
+mod list_access_keys;
 mod list_secret_version_ids;
 mod list_secrets;
+pub mod protocol;
+mod put_access_key_policy;
+mod put_fault_injection_rules;
+mod put_resource_policy;
 mod put_secret_value;
+pub mod quotas;
+mod remove_regions_from_replication;
+mod replicate_secret_to_regions;
 mod restore_secret;
+mod rotate_secret;
+mod ssm;
+mod stop_replication_to_replica;
+mod sts;
 mod tag_resource;
 mod untag_resource;
+mod update_access_key;
 mod update_secret;
 mod update_secret_version_stage;
+mod validate_resource_policy;
+
+pub fn create_handlers(
+    rotation_functions: Arc<RotationFunctions>,
+    fault_injector: Arc<FaultInjector>,
+    quotas: Quotas,
+    enforce_resource_policies: bool,
+) -> HandlerRouter {
+    let authorizer = Arc::new(Authorizer {
+        enforce_resource_policies,
+    });
 
-pub fn create_handlers() -> HandlerRouter {
     HandlerRouter::default()
-        .add_handler("secretsmanager.CreateSecret", CreateSecretHandler)
+        .authorizer(authorizer.clone())
+        .throttle_requests(quotas.is_strict())
+        .add_handler(
+            "secretsmanager.CreateSecret",
+            CreateSecretHandler { quotas },
+        )
         .add_handler("secretsmanager.DeleteSecret", DeleteSecretHandler)
         .add_handler("secretsmanager.DescribeSecret", DescribeSecretHandler)
         .add_handler("secretsmanager.GetSecretValue", GetSecretValueHandler)
         .add_handler("secretsmanager.ListSecrets", ListSecretsHandler)
-        .add_handler("secretsmanager.PutSecretValue", PutSecretValueHandler)
-        .add_handler("secretsmanager.UpdateSecret", UpdateSecretHandler)
+        .add_handler(
+            "secretsmanager.PutSecretValue",
+            PutSecretValueHandler { quotas },
+        )
+        .add_handler(
+            "secretsmanager.UpdateSecret",
+            UpdateSecretHandler { quotas },
+        )
         .add_handler("secretsmanager.RestoreSecret", RestoreSecretHandler)
-        .add_handler("secretsmanager.TagResource", TagResourceHandler)
+        .add_handler("secretsmanager.TagResource", TagResourceHandler { quotas })
         .add_handler("secretsmanager.UntagResource", UntagResourceHandler)
         .add_handler("secretsmanager.GetRandomPassword", GetRandomPasswordHandler)
         .add_handler(
@@ -65,32 +154,170 @@ pub fn create_handlers() -> HandlerRouter {
         )
         .add_handler(
             "secretsmanager.UpdateSecretVersionStage",
-            UpdateSecretVersionStageHandler,
+            UpdateSecretVersionStageHandler { quotas },
         )
         .add_handler(
             "secretsmanager.BatchGetSecretValue",
-            BatchGetSecretValueHandler,
+            BatchGetSecretValueHandler {
+                authorizer: authorizer.clone(),
+            },
+        )
+        .add_handler(
+            "secretsmanager.RotateSecret",
+            RotateSecretHandler {
+                rotation_functions,
+                quotas,
+            },
+        )
+        .add_handler(
+            "secretsmanager.CancelRotateSecret",
+            CancelRotateSecretHandler,
+        )
+        .add_handler("secretsmanager.PutResourcePolicy", PutResourcePolicyHandler)
+        .add_handler("secretsmanager.GetResourcePolicy", GetResourcePolicyHandler)
+        .add_handler(
+            "secretsmanager.DeleteResourcePolicy",
+            DeleteResourcePolicyHandler,
+        )
+        .add_handler(
+            "secretsmanager.ValidateResourcePolicy",
+            ValidateResourcePolicyHandler,
+        )
+        .add_handler(
+            "secretsmanager.ReplicateSecretToRegions",
+            ReplicateSecretToRegionsHandler,
+        )
+        .add_handler(
+            "secretsmanager.RemoveRegionsFromReplication",
+            RemoveRegionsFromReplicationHandler,
+        )
+        .add_handler(
+            "secretsmanager.StopReplicationToReplica",
+            StopReplicationToReplicaHandler,
+        )
+        // Access key management
+        .add_handler("loker.CreateAccessKey", CreateAccessKeyHandler)
+        .add_handler("loker.ListAccessKeys", ListAccessKeysHandler)
+        .add_handler("loker.UpdateAccessKey", UpdateAccessKeyHandler)
+        .add_handler("loker.DeleteAccessKey", DeleteAccessKeyHandler)
+        .add_handler("loker.GetAccessKeyLastUsed", GetAccessKeyLastUsedHandler)
+        .add_handler("loker.PutAccessKeyPolicy", PutAccessKeyPolicyHandler)
+        .add_handler("loker.DeleteAccessKeyPolicy", DeleteAccessKeyPolicyHandler)
+        // Fault injection management
+        .add_handler(
+            "loker.PutFaultInjectionRules",
+            PutFaultInjectionRulesHandler {
+                fault_injector: fault_injector.clone(),
+            },
+        )
+        .add_handler(
+            "loker.GetFaultInjectionRules",
+            GetFaultInjectionRulesHandler { fault_injector },
+        )
+        // KMS
+        .add_handler("TrentService.CreateKey", CreateKeyHandler)
+        .add_handler("TrentService.DescribeKey", DescribeKeyHandler)
+        .add_handler("TrentService.ListKeys", ListKeysHandler)
+        .add_handler("TrentService.EnableKey", EnableKeyHandler)
+        .add_handler("TrentService.DisableKey", DisableKeyHandler)
+        .add_handler(
+            "TrentService.ScheduleKeyDeletion",
+            ScheduleKeyDeletionHandler,
+        )
+        .add_handler("TrentService.CancelKeyDeletion", CancelKeyDeletionHandler)
+        .add_handler("TrentService.Encrypt", EncryptHandler)
+        .add_handler("TrentService.Decrypt", DecryptHandler)
+        .add_handler("TrentService.GenerateDataKey", GenerateDataKeyHandler)
+        .add_handler("TrentService.CreateAlias", CreateAliasHandler)
+        .add_handler("TrentService.UpdateAlias", UpdateAliasHandler)
+        .add_handler("TrentService.DeleteAlias", DeleteAliasHandler)
+        .add_handler("TrentService.ListAliases", ListAliasesHandler)
+        // SSM Parameter Store references to secrets
+        .add_handler(
+            "AmazonSSM.GetParameter",
+            GetParameterHandler {
+                authorizer: authorizer.clone(),
+            },
+        )
+        .add_handler(
+            "AmazonSSM.GetParameters",
+            GetParametersHandler { authorizer },
         )
 }
 
+/// Target prefixes of services whose policy actions use a different
+/// service prefix
+const TARGET_ACTION_PREFIXES: &[(&str, &str)] = &[("TrentService", "kms"), ("AmazonSSM", "ssm")];
+
 #[derive(Default)]
 pub struct HandlerRouter {
     handlers: HashMap<String, Box<dyn ErasedHandler>>,
+    /// Authorizer for the identity and resource policies of requests
+    authorizer: Arc<Authorizer>,
+    /// Rate limiter for the operations with a rate quota, requests are only
+    /// throttled when strict quotas are enabled
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl HandlerRouter {
     fn add_handler<H: Handler>(mut self, target: &str, handler: H) -> Self {
+        // Targets are in the form service.Operation while policy actions
+        // are in the form service:Operation
+        let action = match target.split_once('.') {
+            Some((prefix, operation)) => {
+                let service = TARGET_ACTION_PREFIXES
+                    .iter()
+                    .find(|(target_prefix, _)| *target_prefix == prefix)
+                    .map_or(prefix, |(_, service)| service);
+                format!("{service}:{operation}")
+            }
+            None => target.to_string(),
+        };
+
         self.handlers.insert(
             target.to_string(),
-            Box::new(HandlerBase { _handler: handler }),
+            Box::new(HandlerBase { handler, action }),
         );
         self
     }
 
+    /// Set the authorizer requests are authorized with before being handled
+    fn authorizer(mut self, authorizer: Arc<Authorizer>) -> Self {
+        self.authorizer = authorizer;
+        self
+    }
+
+    /// Set whether requests exceeding the rate quotas of their operation
+    /// should be throttled
+    fn throttle_requests(mut self, throttle_requests: bool) -> Self {
+        self.rate_limiter = throttle_requests.then(RateLimiter::default);
+        self
+    }
+
     fn get_handler(&self, target: &str) -> Option<&dyn ErasedHandler> {
         self.handlers.get(target).map(|value| value.as_ref())
     }
 
+    /// Handle a JSON `request` for the `target` within the request `context`,
+    /// used by the compatibility endpoints to perform operations through the
+    /// handlers
+    async fn handle_request(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        target: &str,
+        request: &[u8],
+    ) -> Response {
+        match self.get_handler(target) {
+            Some(handler) => {
+                handler
+                    .handle(db, &self.authorizer, context, Protocol::AwsJson, request)
+                    .await
+            }
+            None => AwsErrorResponse(NotImplemented).into_response(),
+        }
+    }
+
     pub fn into_service(self) -> HandlerRouterService {
         HandlerRouterService {
             router: Arc::new(self),
@@ -125,77 +352,165 @@ impl Service<Request<Body>> for HandlerRouterService {
                 .get::<DbPool>()
                 .expect("handler router service missing db pool");
 
-            let target = match parts
-                .headers
-                .get("x-amz-target")
-                .and_then(|v| v.to_str().ok())
-            {
+            let protocol = Protocol::from_headers(&parts.headers);
+            let target = protocol.target(&parts.uri, &parts.headers);
+
+            let context = match parts.extensions.get::<RequestContext>() {
                 Some(value) => value,
                 None => {
-                    return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                    let response = AwsErrorResponse(MissingAuthenticationToken).into_response();
+                    return Ok(protocol.error_response(response).await);
                 }
             };
 
-            let handler = handlers.get_handler(target);
-
             let body = match body.collect().await {
                 Ok(value) => value.to_bytes(),
                 Err(error) => {
                     tracing::error!(?error, "failed to collect bytes");
-                    return Ok(AwsErrorResponse(InternalServiceError).into_response());
+                    let response = AwsErrorResponse(InternalServiceError).into_response();
+                    return Ok(protocol.error_response(response).await);
+                }
+            };
+
+            let target = match target {
+                Some(value) => value,
+                // Requests without a target use the query protocol which is only
+                // used by the STS operations
+                None if protocol == Protocol::AwsJson && is_query_request(&parts.headers) => {
+                    return Ok(handle_sts_request(db, context, &body).await);
+                }
+                None => {
+                    let error =
+                        InvalidRequestException.with_message("Missing X-Amz-Target header.");
+                    let response = AwsErrorResponse(error).into_response();
+                    return Ok(protocol.error_response(response).await);
                 }
             };
 
+            if let Some(rate_limiter) = &handlers.rate_limiter
+                && !rate_limiter.try_acquire(&context.caller.account_id, &target)
+            {
+                let response = AwsErrorResponse(ThrottlingException).into_response();
+                return Ok(protocol.error_response(response).await);
+            }
+
+            let handler = handlers.get_handler(&target);
+
             Ok(match handler {
-                Some(value) => value.handle(db, &body).await,
-                None => AwsErrorResponse(NotImplemented).into_response(),
+                Some(value) => {
+                    value
+                        .handle(db, &handlers.authorizer, context, protocol, &body)
+                        .await
+                }
+                None => {
+                    let response = AwsErrorResponse(NotImplemented).into_response();
+                    protocol.error_response(response).await
+                }
             })
         })
     }
 }
 
+/// Check whether the request `headers` are for a form encoded query protocol request
+fn is_query_request(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"))
+}
+
 /// Handler for handling a specific request
 pub trait Handler: Send + Sync + 'static {
     type Request: DeserializeOwned + Validate<Context = ()> + Send + 'static;
     type Response: Serialize + Send + 'static;
 
+    /// Get the secret the `request` targets, used as the resource when
+    /// authorizing the request against the identity policy of the caller
+    /// and the resource policy of the secret
+    fn secret_id(_request: &Self::Request) -> Option<&str> {
+        None
+    }
+
     fn handle<'d>(
+        &'d self,
         db: &'d DbPool,
+        context: &'d RequestContext,
         request: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, Response>> + Send + 'd;
 }
 
 /// Associated type erased [Handler] that takes a generic request and provides
 /// a generic response
+///
+/// The caller within the request `context` is the identity the request is
+/// authorized for using the `authorizer`, the `request` is deserialized and
+/// the response serialized using the `protocol` of the request
 pub trait ErasedHandler: Send + Sync + 'static {
-    fn handle<'r>(&self, db: &'r DbPool, request: &'r [u8]) -> BoxFuture<'r, Response>;
+    fn handle<'r>(
+        &'r self,
+        db: &'r DbPool,
+        authorizer: &'r Authorizer,
+        context: &'r RequestContext,
+        protocol: Protocol,
+        request: &'r [u8],
+    ) -> BoxFuture<'r, Response>;
 }
 
 /// Handler that takes care of the process of deserializing the request
 /// type and serializing the response type to create a generic [ErasedHandler]
 pub struct HandlerBase<H: Handler> {
-    _handler: H,
+    handler: H,
+    /// Policy action the handler performs (i.e secretsmanager:GetSecretValue)
+    action: String,
 }
 
-impl<H: Handler> ErasedHandler for HandlerBase<H> {
-    fn handle<'r>(&self, db: &'r DbPool, request: &'r [u8]) -> BoxFuture<'r, Response> {
-        Box::pin(async move {
-            let request: H::Request = match serde_json::from_slice(request) {
-                Ok(value) => value,
-                Err(error) => {
-                    tracing::error!(?error, "failed to parse request");
-                    return AwsErrorResponse(InvalidRequestException).into_response();
-                }
-            };
-
-            if let Err(_error) = request.validate() {
-                // TODO: Share the error message with the user
-                return AwsErrorResponse(InvalidParameterException).into_response();
+impl<H: Handler> HandlerBase<H> {
+    async fn handle_request(
+        &self,
+        db: &DbPool,
+        authorizer: &Authorizer,
+        context: &RequestContext,
+        protocol: Protocol,
+        request: &[u8],
+    ) -> Result<H::Response, Response> {
+        let request: H::Request = match protocol.deserialize(request) {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to parse request");
+                let error = InvalidRequestException.with_message(error.to_string());
+                return Err(AwsErrorResponse(error).into_response());
             }
+        };
+
+        if let Err(report) = request.validate() {
+            let error = InvalidParameterException.with_message(validation_message(&report));
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
+        authorizer
+            .authorize(db, &context.caller, &self.action, H::secret_id(&request))
+            .await?;
+
+        self.handler.handle(db, context, request).await
+    }
+}
 
-            match H::handle(db, request).await {
-                Ok(response) => Json(response).into_response(),
-                Err(error) => error,
+impl<H: Handler> ErasedHandler for HandlerBase<H> {
+    fn handle<'r>(
+        &'r self,
+        db: &'r DbPool,
+        authorizer: &'r Authorizer,
+        context: &'r RequestContext,
+        protocol: Protocol,
+        request: &'r [u8],
+    ) -> BoxFuture<'r, Response> {
+        Box::pin(async move {
+            match self
+                .handle_request(db, authorizer, context, protocol, request)
+                .await
+            {
+                Ok(response) => protocol.response(&response),
+                Err(error) => protocol.error_response(error).await,
             }
         })
     }
@@ -1,10 +1,19 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
 use garde::Validate;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, ser};
 use std::{fmt::Display, str::FromStr};
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::utils::string::join_iter_string;
+use crate::{
+    database::replicas::StoredSecretReplica,
+    utils::{
+        arn::is_valid_account_id,
+        blob::{deserialize_blob, serialize_blob},
+        date::Timestamp,
+        string::join_iter_string,
+    },
+};
 
 #[derive(Debug, Deserialize, Validate)]
 #[garde(transparent)]
@@ -56,6 +65,260 @@ impl Display for SecretId {
     }
 }
 
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct AccessKeyId(#[garde(length(min = 1, max = 128))] pub String);
+
+impl Display for AccessKeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Partition used for the ARNs of resources created by the server
+pub const DEFAULT_PARTITION: &str = "aws";
+
+/// Amazon Resource Name identifying a resource
+/// (i.e arn:aws:secretsmanager:us-east-1:123456789012:secret:name-abcdef)
+///
+/// https://docs.aws.amazon.com/IAM/latest/UserGuide/reference-arns.html
+#[derive(Debug, Clone, PartialEq, Eq, Validate)]
+pub struct Arn {
+    /// Partition the resource is in (i.e aws)
+    #[garde(length(min = 1), custom(is_valid_arn_identifier))]
+    pub partition: String,
+    /// Service namespace of the resource (i.e secretsmanager)
+    #[garde(length(min = 1), custom(is_valid_arn_identifier))]
+    pub service: String,
+    /// Region of the resource, empty for global resources
+    #[garde(custom(is_valid_arn_region))]
+    pub region: String,
+    /// Account that owns the resource, empty for resources without an account
+    #[garde(custom(is_valid_arn_account_id))]
+    pub account_id: String,
+    /// Resource type and ID (i.e secret:name-abcdef)
+    #[garde(length(min = 1, max = 2048))]
+    pub resource: String,
+}
+
+impl Arn {
+    /// Create the ARN of a `resource` in the default partition
+    pub fn new(service: &str, region: &str, account_id: &str, resource: &str) -> Self {
+        Self {
+            partition: DEFAULT_PARTITION.to_string(),
+            service: service.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            resource: resource.to_string(),
+        }
+    }
+
+    /// Create the ARN of the root principal of the `account_id`
+    pub fn iam_root(account_id: &str) -> Self {
+        Self::new("iam", "", account_id, "root")
+    }
+
+    /// Type of the resource (i.e secret), [None] when the resource doesn't
+    /// specify a type
+    pub fn resource_type(&self) -> Option<&str> {
+        self.resource
+            .split_once([':', '/'])
+            .map(|(resource_type, _)| resource_type)
+    }
+
+    /// ID of the resource (i.e name-abcdef), the whole resource when the
+    /// resource doesn't specify a type
+    pub fn resource_id(&self) -> &str {
+        self.resource
+            .split_once([':', '/'])
+            .map_or(self.resource.as_str(), |(_, resource_id)| resource_id)
+    }
+
+    /// Checks if the ARN contains a valid account ID
+    pub fn has_account_id(&self) -> bool {
+        is_valid_account_id(&self.account_id)
+    }
+
+    /// Create a copy of the ARN within the `region`
+    pub fn with_region(&self, region: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for Arn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "arn:{}:{}:{}:{}:{}",
+            self.partition, self.service, self.region, self.account_id, self.resource
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid arn")]
+pub struct InvalidArn;
+
+impl FromStr for Arn {
+    type Err = InvalidArn;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.splitn(6, ':').collect();
+
+        match parts.as_slice() {
+            ["arn", partition, service, region, account_id, resource] => Ok(Arn {
+                partition: partition.to_string(),
+                service: service.to_string(),
+                region: region.to_string(),
+                account_id: account_id.to_string(),
+                resource: resource.to_string(),
+            }),
+            _ => Err(InvalidArn),
+        }
+    }
+}
+
+impl Serialize for Arn {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Arn {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Arn::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks if the provided value is a valid ARN partition or service (i.e aws-cn)
+fn is_valid_arn_identifier(value: &str, _context: &()) -> garde::Result {
+    if !value
+        .chars()
+        .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '-')
+    {
+        return Err(garde::Error::new("arn contains disallowed characters"));
+    }
+
+    Ok(())
+}
+
+/// Checks if the provided value is a valid ARN region, global resources
+/// don't specify a region
+fn is_valid_arn_region(value: &str, context: &()) -> garde::Result {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    is_valid_region(value, context)
+}
+
+/// Checks if the provided value is a valid ARN account ID, AWS managed
+/// resources use `aws` as their account
+fn is_valid_arn_account_id(value: &str, _context: &()) -> garde::Result {
+    if !(value.is_empty() || value == "aws" || is_valid_account_id(value)) {
+        return Err(garde::Error::new("invalid account id"));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct RoleArn(
+    #[garde(length(min = 20, max = 2048))]
+    #[garde(custom(is_valid_role_arn))]
+    pub String,
+);
+
+impl Display for RoleArn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Checks if the provided value is an IAM role ARN (i.e arn:aws:iam::123456789012:role/name)
+fn is_valid_role_arn(value: &str, _context: &()) -> garde::Result {
+    let is_valid = Arn::from_str(value).is_ok_and(|arn| {
+        arn.validate().is_ok()
+            && arn.service == "iam"
+            && arn.region.is_empty()
+            && arn.has_account_id()
+            && arn.resource_type() == Some("role")
+            && arn
+                .resource_id()
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| !name.is_empty())
+    });
+
+    if !is_valid {
+        return Err(garde::Error::new("role arn is not a valid IAM role ARN"));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct RoleSessionName(
+    #[garde(length(min = 2, max = 64))]
+    #[garde(custom(is_valid_role_session_name))]
+    pub String,
+);
+
+impl Display for RoleSessionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Checks if the provided value is a valid role session name
+fn is_valid_role_session_name(value: &str, _context: &()) -> garde::Result {
+    const ALLOWED_SPECIAL_CHARACTERS: &str = "_+=,.@-";
+
+    if !value
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || ALLOWED_SPECIAL_CHARACTERS.contains(char))
+    {
+        return Err(garde::Error::new(
+            "role session name contains disallowed characters",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Status of an access key, inactive access keys can't be used to sign requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AccessKeyStatus {
+    Active,
+    Inactive,
+}
+
+impl AccessKeyStatus {
+    pub fn from_enabled(enabled: bool) -> Self {
+        if enabled {
+            AccessKeyStatus::Active
+        } else {
+            AccessKeyStatus::Inactive
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        matches!(self, AccessKeyStatus::Active)
+    }
+}
+
 #[derive(Deserialize, Validate)]
 #[garde(transparent)]
 pub struct VersionId(#[garde(length(min = 32, max = 64))] pub String);
@@ -78,7 +341,7 @@ impl SecretString {
 
 /// TODO: Check if the length constraint here should be on the base64 value
 /// or the decoded blob itself
-#[derive(Deserialize, Validate)]
+#[derive(Validate)]
 #[garde(transparent)]
 pub struct SecretBinary(#[garde(length(min = 1, max = 65536))] pub String);
 
@@ -88,6 +351,36 @@ impl SecretBinary {
     }
 }
 
+impl<'de> Deserialize<'de> for SecretBinary {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            return String::deserialize(deserializer).map(SecretBinary);
+        }
+
+        // Binary formats provide the raw bytes, the value is stored base64 encoded
+        let value = deserialize_blob(deserializer)?;
+        Ok(SecretBinary(BASE64_STANDARD.encode(value)))
+    }
+}
+
+/// Serialize a base64 encoded secret binary value, binary formats are
+/// provided the decoded bytes
+pub fn serialize_secret_binary<S>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) if !serializer.is_human_readable() => {
+            let value = BASE64_STANDARD.decode(value).map_err(ser::Error::custom)?;
+            serialize_blob(&value, serializer)
+        }
+        value => value.serialize(serializer),
+    }
+}
+
 #[derive(Deserialize, Serialize, Validate)]
 pub struct Tag {
     #[serde(rename = "Key")]
@@ -99,6 +392,110 @@ pub struct Tag {
     pub value: String,
 }
 
+#[derive(Deserialize, Serialize, Validate)]
+pub struct RotationRules {
+    #[serde(rename = "AutomaticallyAfterDays")]
+    #[garde(inner(range(min = 1, max = 1000)))]
+    pub automatically_after_days: Option<i64>,
+
+    #[serde(rename = "Duration")]
+    #[garde(inner(length(min = 2, max = 3)))]
+    pub duration: Option<String>,
+
+    #[serde(rename = "ScheduleExpression")]
+    #[garde(inner(length(min = 1, max = 256)))]
+    pub schedule_expression: Option<String>,
+}
+
+impl RotationRules {
+    /// Create the rotation rules from the stored rule values, provides [None]
+    /// if no rules are set
+    pub fn from_stored(
+        automatically_after_days: Option<i64>,
+        duration: Option<String>,
+        schedule_expression: Option<String>,
+    ) -> Option<RotationRules> {
+        if automatically_after_days.is_none() && duration.is_none() && schedule_expression.is_none()
+        {
+            return None;
+        }
+
+        Some(RotationRules {
+            automatically_after_days,
+            duration,
+            schedule_expression,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct Region(#[garde(length(min = 1, max = 128), custom(is_valid_region))] pub String);
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Checks if the provided value is a valid region name (i.e us-east-1)
+fn is_valid_region(value: &str, _context: &()) -> garde::Result {
+    let valid = value.rsplit_once('-').is_some_and(|(prefix, number)| {
+        !number.is_empty()
+            && number.chars().all(|char| char.is_ascii_digit())
+            && prefix
+                .split('-')
+                .all(|part| !part.is_empty() && part.chars().all(|char| char.is_ascii_lowercase()))
+    });
+
+    if !valid {
+        return Err(garde::Error::new("invalid region"));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ReplicaRegionType {
+    #[serde(rename = "Region")]
+    #[garde(dive)]
+    pub region: Region,
+
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(max = 2048)))]
+    pub kms_key_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReplicationStatusType {
+    #[serde(rename = "Region")]
+    pub region: String,
+
+    #[serde(rename = "KmsKeyId")]
+    pub kms_key_id: Option<String>,
+
+    #[serde(rename = "Status")]
+    pub status: String,
+
+    #[serde(rename = "StatusMessage")]
+    pub status_message: Option<String>,
+
+    #[serde(rename = "LastAccessedDate")]
+    pub last_accessed_date: Option<Timestamp>,
+}
+
+impl ReplicationStatusType {
+    pub fn from_stored(replica: StoredSecretReplica) -> ReplicationStatusType {
+        ReplicationStatusType {
+            region: replica.region,
+            kms_key_id: replica.kms_key_id,
+            status: replica.status,
+            status_message: replica.status_message,
+            last_accessed_date: None,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Validate)]
 pub struct Filter {
     #[serde(rename = "Key")]
@@ -245,3 +642,64 @@ impl FromStr for PaginationToken {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arn() {
+        let arn: Arn = "arn:aws:secretsmanager:eu-west-1:123456789012:secret:test:a-abcdef"
+            .parse()
+            .unwrap();
+        assert_eq!(arn.partition, "aws");
+        assert_eq!(arn.service, "secretsmanager");
+        assert_eq!(arn.region, "eu-west-1");
+        assert_eq!(arn.account_id, "123456789012");
+        assert_eq!(arn.resource_type(), Some("secret"));
+        assert_eq!(arn.resource_id(), "test:a-abcdef");
+        assert!(arn.validate().is_ok());
+
+        let arn: Arn = "arn:aws:sts::123456789012:assumed-role/role/session"
+            .parse()
+            .unwrap();
+        assert_eq!(arn.region, "");
+        assert_eq!(arn.resource_type(), Some("assumed-role"));
+        assert_eq!(arn.resource_id(), "role/session");
+        assert!(arn.has_account_id());
+
+        let arn = Arn::iam_root("123456789012");
+        assert_eq!(arn.resource_type(), None);
+        assert_eq!(arn.resource_id(), "root");
+        assert_eq!(arn.to_string(), "arn:aws:iam::123456789012:root");
+
+        assert!(Arn::from_str("123456789012").is_err());
+        assert!(Arn::from_str("arn:aws:iam::123456789012").is_err());
+    }
+
+    #[test]
+    fn test_validate_arn() {
+        for value in [
+            "arn:aws:iam::1:root",
+            "arn:AWS:iam::123456789012:root",
+            "arn:aws:secretsmanager:invalid:123456789012:secret:test",
+            "arn:aws:secretsmanager:us-east-1:123456789012:",
+        ] {
+            let arn: Arn = value.parse().unwrap();
+            assert!(arn.validate().is_err(), "{value}");
+        }
+
+        assert!(!Arn::iam_root("1").has_account_id());
+    }
+
+    #[test]
+    fn test_arn_with_region() {
+        let arn: Arn = "arn:aws:secretsmanager:us-east-1:123456789012:secret:test:a-abcdef"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            arn.with_region("eu-west-1").to_string(),
+            "arn:aws:secretsmanager:eu-west-1:123456789012:secret:test:a-abcdef"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Protocols requests can be made with, the protocol of a request determines
+//! how the request body is deserialized and how responses are serialized
+//!
+//! https://smithy.io/2.0/aws/protocols/aws-json-1_1-protocol.html
+//! https://smithy.io/2.0/additional-specs/protocols/smithy-rpc-v2.html
+
+use crate::handlers::error::{AwsError, InternalServiceError};
+use axum::{
+    Json,
+    body::Body,
+    http::{
+        HeaderMap, HeaderValue, StatusCode, Uri,
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Response},
+};
+use http_body_util::BodyExt;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use thiserror::Error;
+
+/// Header identifying the Smithy protocol of requests and responses
+const SMITHY_PROTOCOL_HEADER: &str = "smithy-protocol";
+
+/// Value of the [SMITHY_PROTOCOL_HEADER] for the RPC v2 CBOR protocol
+const RPC_V2_CBOR: &str = "rpc-v2-cbor";
+
+/// Content type of RPC v2 CBOR requests and responses
+const CBOR_CONTENT_TYPE: &str = "application/cbor";
+
+/// CBOR encoding of an empty map, used as the input of RPC v2 CBOR requests
+/// that are sent without a body
+const CBOR_EMPTY_MAP: &[u8] = &[0xa0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// AWS JSON 1.1 protocol, the target is provided in the `X-Amz-Target`
+    /// header and requests are sent to the root path
+    AwsJson,
+    /// Smithy RPC v2 CBOR protocol, the target is provided in the path
+    /// (i.e /service/secretsmanager/operation/GetSecretValue)
+    RpcV2Cbor,
+}
+
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Cbor(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+impl Protocol {
+    /// Determine the protocol of a request from its `headers`
+    pub fn from_headers(headers: &HeaderMap) -> Protocol {
+        let is_rpc_v2_cbor = headers
+            .get(SMITHY_PROTOCOL_HEADER)
+            .is_some_and(|value| value == RPC_V2_CBOR);
+
+        if is_rpc_v2_cbor {
+            Protocol::RpcV2Cbor
+        } else {
+            Protocol::AwsJson
+        }
+    }
+
+    /// Get the target of a request (i.e secretsmanager.GetSecretValue) made
+    /// using this protocol
+    pub fn target(self, uri: &Uri, headers: &HeaderMap) -> Option<String> {
+        match self {
+            Protocol::AwsJson => headers
+                .get("x-amz-target")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            Protocol::RpcV2Cbor => {
+                let (service, operation) = uri
+                    .path()
+                    .strip_prefix("/service/")?
+                    .split_once("/operation/")?;
+
+                if service.is_empty() || operation.is_empty() || operation.contains('/') {
+                    return None;
+                }
+
+                Some(format!("{service}.{operation}"))
+            }
+        }
+    }
+
+    /// Deserialize a request `body` made using this protocol
+    pub fn deserialize<T: DeserializeOwned>(self, body: &[u8]) -> Result<T, ProtocolError> {
+        match self {
+            Protocol::AwsJson => Ok(serde_json::from_slice(body)?),
+            Protocol::RpcV2Cbor if body.is_empty() => Ok(ciborium::from_reader(CBOR_EMPTY_MAP)?),
+            Protocol::RpcV2Cbor => Ok(ciborium::from_reader(body)?),
+        }
+    }
+
+    /// Create a successful response containing the serialized `value`
+    pub fn response<T: Serialize>(self, value: &T) -> Response {
+        match self {
+            Protocol::AwsJson => Json(value).into_response(),
+            Protocol::RpcV2Cbor => {
+                let mut body = Vec::new();
+                if let Err(error) = ciborium::into_writer(value, &mut body) {
+                    tracing::error!(?error, "failed to serialize response");
+                    return cbor_internal_error();
+                }
+
+                cbor_response(StatusCode::OK, HeaderMap::new(), body)
+            }
+        }
+    }
+
+    /// Convert an error `response` created for the AWS JSON protocol into an
+    /// error response for this protocol
+    pub async fn error_response(self, response: Response) -> Response {
+        match self {
+            Protocol::AwsJson => response,
+            Protocol::RpcV2Cbor => {
+                let (parts, body) = response.into_parts();
+                let body = match body.collect().await {
+                    Ok(value) => value.to_bytes(),
+                    Err(error) => {
+                        tracing::error!(?error, "failed to collect error response bytes");
+                        return cbor_internal_error();
+                    }
+                };
+
+                // Bodies that aren't JSON are replaced with an empty map
+                let body: Value = serde_json::from_slice(&body).unwrap_or_else(|_| json!({}));
+                cbor_response(parts.status, parts.headers, encode_cbor(&body))
+            }
+        }
+    }
+}
+
+/// Create the RPC v2 CBOR response for an internal error
+fn cbor_internal_error() -> Response {
+    let body = json!({
+        "__type": InternalServiceError::TYPE,
+        "message": InternalServiceError::MESSAGE
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-amzn-errortype",
+        HeaderValue::from_static(InternalServiceError::TYPE),
+    );
+
+    cbor_response(
+        InternalServiceError::STATUS_CODE,
+        headers,
+        encode_cbor(&body),
+    )
+}
+
+fn encode_cbor(value: &Value) -> Vec<u8> {
+    let mut body = Vec::new();
+    // Serializing a JSON value into a vec can't fail
+    _ = ciborium::into_writer(value, &mut body);
+    body
+}
+
+fn cbor_response(status: StatusCode, mut headers: HeaderMap, body: Vec<u8>) -> Response {
+    headers.remove(CONTENT_LENGTH);
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(CBOR_CONTENT_TYPE));
+    headers.insert(
+        SMITHY_PROTOCOL_HEADER,
+        HeaderValue::from_static(RPC_V2_CBOR),
+    );
+
+    (status, headers, Body::from(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_v2_cbor_target() {
+        let headers = HeaderMap::new();
+
+        for (path, expected) in [
+            (
+                "/service/secretsmanager/operation/GetSecretValue",
+                Some("secretsmanager.GetSecretValue"),
+            ),
+            (
+                "/service/TrentService/operation/Encrypt",
+                Some("TrentService.Encrypt"),
+            ),
+            ("/service/secretsmanager/operation/", None),
+            ("/service/secretsmanager/operation/A/B", None),
+            ("/", None),
+        ] {
+            let uri: Uri = path.parse().unwrap();
+            assert_eq!(
+                Protocol::RpcV2Cbor.target(&uri, &headers).as_deref(),
+                expected
+            );
+        }
+    }
+}
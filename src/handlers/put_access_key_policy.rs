@@ -0,0 +1,72 @@
+use crate::{
+    database::{
+        DbPool,
+        access_keys::{get_access_key, update_access_key_policy},
+    },
+    handlers::{
+        Handler,
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            malformed_policy_response,
+        },
+        models::AccessKeyId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    policy::validate_identity_policy,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_PutUserPolicy.html
+pub struct PutAccessKeyPolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct PutAccessKeyPolicyRequest {
+    #[serde(rename = "AccessKeyId")]
+    #[garde(dive)]
+    access_key_id: AccessKeyId,
+
+    #[serde(rename = "PolicyDocument")]
+    #[garde(length(min = 1, max = 20480))]
+    policy_document: String,
+}
+
+#[derive(Serialize)]
+pub struct PutAccessKeyPolicyResponse {}
+
+impl Handler for PutAccessKeyPolicyHandler {
+    type Request = PutAccessKeyPolicyRequest;
+    type Response = PutAccessKeyPolicyResponse;
+
+    #[tracing::instrument(skip_all, fields(access_key_id = %request.access_key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AccessKeyId(access_key_id) = request.access_key_id;
+
+        get_access_key(db, &access_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get access key");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        validate_identity_policy(&request.policy_document).map_err(malformed_policy_response)?;
+
+        if let Err(error) =
+            update_access_key_policy(db, &access_key_id, Some(&request.policy_document)).await
+        {
+            tracing::error!(?error, "failed to put access key policy");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(PutAccessKeyPolicyResponse {})
+    }
+}
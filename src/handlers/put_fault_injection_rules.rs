@@ -0,0 +1,43 @@
+use crate::{
+    database::DbPool,
+    handlers::{
+        Handler,
+        fault_injection::{FaultInjector, FaultRule},
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::Response;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Replaces the fault injection rules, the call counts of the rules are reset
+pub struct PutFaultInjectionRulesHandler {
+    pub fault_injector: Arc<FaultInjector>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct PutFaultInjectionRulesRequest {
+    #[serde(rename = "Rules")]
+    #[garde(dive)]
+    rules: Vec<FaultRule>,
+}
+
+#[derive(Serialize)]
+pub struct PutFaultInjectionRulesResponse {}
+
+impl Handler for PutFaultInjectionRulesHandler {
+    type Request = PutFaultInjectionRulesRequest;
+    type Response = PutFaultInjectionRulesResponse;
+
+    #[tracing::instrument(skip_all, fields(rules = request.rules.len()))]
+    async fn handle(
+        &self,
+        _db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        self.fault_injector.set_rules(request.rules);
+        Ok(PutFaultInjectionRulesResponse {})
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    database::{DbPool, policies::put_secret_policy, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{
+            AwsErrorResponse, InternalServiceError, PublicPolicyException,
+            ResourceNotFoundException, malformed_policy_response, replica_secret_response,
+            scheduled_for_deletion_response,
+        },
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    policy::{is_public_policy, validate_policy},
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_PutResourcePolicy.html
+pub struct PutResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct PutResourcePolicyRequest {
+    #[serde(rename = "BlockPublicPolicy")]
+    #[serde(default)]
+    #[garde(skip)]
+    block_public_policy: bool,
+
+    #[serde(rename = "ResourcePolicy")]
+    #[garde(length(min = 1, max = 20480))]
+    resource_policy: String,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct PutResourcePolicyResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+impl Handler for PutResourcePolicyHandler {
+    type Request = PutResourcePolicyRequest;
+    type Response = PutResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let PutResourcePolicyRequest {
+            block_public_policy,
+            resource_policy,
+            secret_id,
+        } = request;
+
+        let SecretId(secret_id) = secret_id;
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // Secret is scheduled for deletion
+        if secret.scheduled_delete_at.is_some() {
+            return Err(scheduled_for_deletion_response());
+        }
+
+        let document = validate_policy(&resource_policy).map_err(malformed_policy_response)?;
+
+        if block_public_policy && is_public_policy(&document) {
+            return Err(AwsErrorResponse(PublicPolicyException).into_response());
+        }
+
+        if let Err(error) = put_secret_policy(db, &secret.arn, &resource_policy).await {
+            tracing::error!(?error, "failed to put secret policy");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(PutResourcePolicyResponse {
+            arn: secret.arn,
+            name: secret.name,
+        })
+    }
+}
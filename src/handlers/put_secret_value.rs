@@ -9,11 +9,16 @@ use crate::{
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
-            ResourceExistsException, ResourceNotFoundException,
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            ResourceExistsException, ResourceNotFoundException, decryption_failure_response,
+            encryption_failure_response, replica_secret_response,
         },
         models::{ClientRequestToken, SecretBinary, SecretId, SecretString},
+        quotas::Quotas,
     },
+    kms::{decrypt_stored_secret, encrypt_secret_value},
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -21,7 +26,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_PutSecretValue.html
-pub struct PutSecretValueHandler;
+pub struct PutSecretValueHandler {
+    /// Service quotas checked before creating the version
+    pub quotas: Quotas,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct PutSecretValueRequest {
@@ -62,8 +70,17 @@ impl Handler for PutSecretValueHandler {
     type Request = PutSecretValueRequest;
     type Response = PutSecretValueResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let SecretId(secret_id) = request.secret_id;
         let ClientRequestToken(version_id) = request.client_request_token.unwrap_or_default();
 
@@ -71,7 +88,9 @@ impl Handler for PutSecretValueHandler {
             Some(value) => {
                 // When specifying version stages must specify at least one
                 if value.is_empty() {
-                    return Err(AwsErrorResponse(InvalidRequestException).into_response());
+                    let error = InvalidRequestException
+                        .with_message("VersionStages must contain at least one staging label.");
+                    return Err(AwsErrorResponse(error).into_response());
                 }
 
                 value
@@ -84,15 +103,19 @@ impl Handler for PutSecretValueHandler {
 
         // Must only specify one of the two
         if secret_string.is_some() && secret_binary.is_some() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            let error = InvalidRequestException
+                .with_message("You can't specify both SecretString and SecretBinary.");
+            return Err(AwsErrorResponse(error).into_response());
         }
 
         // Must specify at least one
         if secret_string.is_none() && secret_binary.is_none() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            let error = InvalidRequestException
+                .with_message("You must provide either SecretString or SecretBinary.");
+            return Err(AwsErrorResponse(error).into_response());
         }
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -105,6 +128,24 @@ impl Handler for PutSecretValueHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        self.quotas.check_version_count(db, &secret.arn).await?;
+
+        // Encrypt the value using the KMS key of the secret
+        let value = encrypt_secret_value(
+            db,
+            &secret.region,
+            secret.kms_key_id.as_deref(),
+            secret_string.clone(),
+            secret_binary.clone(),
+        )
+        .await
+        .map_err(encryption_failure_response)?;
+
         let mut t = match db.begin().await {
             Ok(value) => value,
             Err(error) => {
@@ -119,8 +160,9 @@ impl Handler for PutSecretValueHandler {
             CreateSecretVersion {
                 secret_arn: secret.arn.clone(),
                 version_id: version_id.clone(),
-                secret_string: secret_string.clone(),
-                secret_binary: secret_binary.clone(),
+                secret_string: value.secret_string,
+                secret_binary: value.secret_binary,
+                encrypted_data_key: value.encrypted_data_key,
             },
         )
         .await
@@ -134,7 +176,14 @@ impl Handler for PutSecretValueHandler {
                 }
 
                 // Check if the secret has been created
-                let secret = match get_secret_by_version_id(db, &secret.arn, &version_id).await {
+                let secret = match get_secret_by_version_id(
+                    db,
+                    &context.caller.region,
+                    &secret.arn,
+                    &version_id,
+                )
+                .await
+                {
                     Ok(value) => value,
                     Err(error) => {
                         tracing::error!(?error, "failed to determine existing version");
@@ -142,7 +191,7 @@ impl Handler for PutSecretValueHandler {
                     }
                 };
 
-                let secret = match secret {
+                let mut secret = match secret {
                     Some(value) => value,
                     None => {
                         // Shouldn't be possible if we hit the unique violation
@@ -150,12 +199,19 @@ impl Handler for PutSecretValueHandler {
                     }
                 };
 
+                decrypt_stored_secret(db, &mut secret)
+                    .await
+                    .map_err(decryption_failure_response)?;
+
                 // If the stored version data doesn't match this is an error that
                 // the resource already exists
                 if secret.secret_string.ne(&secret_string)
                     || secret.secret_binary.ne(&secret_binary)
                 {
-                    return Err(AwsErrorResponse(ResourceExistsException).into_response());
+                    let error = ResourceExistsException.with_message(
+                        "A version with this ClientRequestToken already exists with a different value.",
+                    );
+                    return Err(AwsErrorResponse(error).into_response());
                 }
 
                 // Another request already created this version
@@ -219,6 +275,11 @@ impl Handler for PutSecretValueHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
         Ok(PutSecretValueResponse {
             arn: secret.arn,
             name: secret.name,
@@ -0,0 +1,286 @@
+//! Emulation of the Secrets Manager service quotas, the quotas are only
+//! enforced when strict quotas are enabled
+//!
+//! https://docs.aws.amazon.com/secretsmanager/latest/userguide/reference_limits.html
+
+use crate::{
+    database::{
+        DbExecutor,
+        secrets::{
+            count_account_secrets, count_retained_secret_versions, count_secret_version_stages,
+            get_secret_tag_keys,
+        },
+    },
+    handlers::error::{AwsError, AwsErrorResponse, InternalServiceError, LimitExceededException},
+};
+use axum::response::{IntoResponse, Response};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::Instant,
+};
+
+/// Maximum number of secrets in an account
+const MAX_SECRETS: i64 = 500_000;
+
+/// Maximum number of versions of a secret
+const MAX_SECRET_VERSIONS: i64 = 100;
+
+/// Maximum number of staging labels attached to a version of a secret
+const MAX_VERSION_STAGES: i64 = 20;
+
+/// Maximum number of tags attached to a secret
+const MAX_SECRET_TAGS: i64 = 50;
+
+/// Requests per second allowed for each group of operations sharing a
+/// rate quota, operations that aren't listed are not rate limited
+const RATE_LIMITS: &[(&[&str], f64)] = &[
+    (
+        &[
+            "secretsmanager.DescribeSecret",
+            "secretsmanager.GetSecretValue",
+        ],
+        10_000.0,
+    ),
+    (&["secretsmanager.BatchGetSecretValue"], 100.0),
+    (&["secretsmanager.ListSecrets"], 100.0),
+    (&["secretsmanager.ListSecretVersionIds"], 50.0),
+    (&["secretsmanager.GetRandomPassword"], 50.0),
+    (
+        &[
+            "secretsmanager.CreateSecret",
+            "secretsmanager.PutSecretValue",
+            "secretsmanager.UpdateSecret",
+            "secretsmanager.UpdateSecretVersionStage",
+            "secretsmanager.DeleteSecret",
+            "secretsmanager.RestoreSecret",
+            "secretsmanager.RotateSecret",
+            "secretsmanager.CancelRotateSecret",
+        ],
+        50.0,
+    ),
+    (
+        &[
+            "secretsmanager.GetResourcePolicy",
+            "secretsmanager.PutResourcePolicy",
+            "secretsmanager.DeleteResourcePolicy",
+            "secretsmanager.ValidateResourcePolicy",
+        ],
+        50.0,
+    ),
+    (
+        &["secretsmanager.TagResource", "secretsmanager.UntagResource"],
+        50.0,
+    ),
+    (
+        &[
+            "secretsmanager.ReplicateSecretToRegions",
+            "secretsmanager.RemoveRegionsFromReplication",
+            "secretsmanager.StopReplicationToReplica",
+        ],
+        50.0,
+    ),
+];
+
+/// Service quotas checked by the handlers that create resources
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quotas {
+    /// Whether the quotas are enforced
+    strict: bool,
+}
+
+impl Quotas {
+    pub fn new(strict: bool) -> Self {
+        Self { strict }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Check that another secret can be created in the account
+    pub async fn check_secret_count(
+        &self,
+        db: impl DbExecutor<'_>,
+        account_id: &str,
+    ) -> Result<(), Response> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        let count = count_account_secrets(db, account_id)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to count secrets");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?;
+
+        if count >= MAX_SECRETS {
+            return Err(limit_exceeded(format!(
+                "You have exceeded the maximum number of secrets ({MAX_SECRETS}) for this account."
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that another version of the secret can be created
+    pub async fn check_version_count(
+        &self,
+        db: impl DbExecutor<'_>,
+        secret_arn: &str,
+    ) -> Result<(), Response> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        let count = count_retained_secret_versions(db, secret_arn)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to count secret versions");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?;
+
+        if count >= MAX_SECRET_VERSIONS {
+            return Err(limit_exceeded(format!(
+                "You have exceeded the maximum number of versions ({MAX_SECRET_VERSIONS}) for this secret."
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that another staging label can be attached to the version of
+    /// the secret
+    pub async fn check_version_stage_count(
+        &self,
+        db: impl DbExecutor<'_>,
+        secret_arn: &str,
+        version_id: &str,
+    ) -> Result<(), Response> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        let count = count_secret_version_stages(db, secret_arn, version_id)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to count secret version stages");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?;
+
+        if count >= MAX_VERSION_STAGES {
+            return Err(limit_exceeded(format!(
+                "You have exceeded the maximum number of staging labels ({MAX_VERSION_STAGES}) for this version."
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the tags with the `tag_keys` can be attached to the secret,
+    /// tags replacing the value of an existing tag don't count against the quota
+    pub async fn check_tag_count<'k>(
+        &self,
+        db: impl DbExecutor<'_>,
+        secret_arn: &str,
+        tag_keys: impl IntoIterator<Item = &'k str>,
+    ) -> Result<(), Response> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        let existing_keys = get_secret_tag_keys(db, secret_arn).await.map_err(|error| {
+            tracing::error!(?error, "failed to get secret tag keys");
+            AwsErrorResponse(InternalServiceError).into_response()
+        })?;
+
+        let mut keys: HashSet<String> = existing_keys.into_iter().collect();
+        keys.extend(tag_keys.into_iter().map(str::to_string));
+
+        if keys.len() as i64 > MAX_SECRET_TAGS {
+            return Err(limit_exceeded(format!(
+                "You have exceeded the maximum number of tags ({MAX_SECRET_TAGS}) for this secret."
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Create the response for a request exceeding a quota
+fn limit_exceeded(message: String) -> Response {
+    AwsErrorResponse(LimitExceededException.with_message(message)).into_response()
+}
+
+/// Token bucket rate limiter for the operations of each account
+#[derive(Default)]
+pub struct RateLimiter {
+    /// Buckets for each account and index of the [RATE_LIMITS] group
+    buckets: Mutex<HashMap<(String, usize), TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Take a token for a request to the `target` operation made by the
+    /// `account_id`, provides false when the request should be throttled
+    pub fn try_acquire(&self, account_id: &str, target: &str) -> bool {
+        let Some((group, (_, rate))) = RATE_LIMITS
+            .iter()
+            .enumerate()
+            .find(|(_, (targets, _))| targets.contains(&target))
+        else {
+            return true;
+        };
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limit buckets poisoned");
+        let bucket = buckets
+            .entry((account_id.to_string(), group))
+            .or_insert(TokenBucket {
+                tokens: *rate,
+                refilled_at: now,
+            });
+
+        // Refill the bucket for the time elapsed, allowing up to a second of burst
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(*rate);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_buckets() {
+        let limiter = RateLimiter::default();
+
+        let allowed = (0..60)
+            .filter(|_| limiter.try_acquire("123456789012", "secretsmanager.CreateSecret"))
+            .count();
+        assert_eq!(allowed, 50);
+
+        // Operations in the same group share the bucket
+        assert!(!limiter.try_acquire("123456789012", "secretsmanager.PutSecretValue"));
+
+        // Buckets are separate for each group and account
+        assert!(limiter.try_acquire("123456789012", "secretsmanager.ListSecrets"));
+        assert!(limiter.try_acquire("210987654321", "secretsmanager.CreateSecret"));
+
+        // Operations without a rate quota are never limited
+        assert!((0..100).all(|_| limiter.try_acquire("123456789012", "loker.ListAccessKeys")));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    database::{DbPool, replicas::get_secret_replicas, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response,
+        },
+        models::{Region, ReplicationStatusType, SecretId},
+    },
+    middleware::aws_sig_v4::RequestContext,
+    replication::remove_regions_from_replication,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_RemoveRegionsFromReplication.html
+pub struct RemoveRegionsFromReplicationHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct RemoveRegionsFromReplicationRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+
+    #[serde(rename = "RemoveReplicaRegions")]
+    #[garde(length(min = 1), dive)]
+    remove_replica_regions: Vec<Region>,
+}
+
+#[derive(Serialize)]
+pub struct RemoveRegionsFromReplicationResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "ReplicationStatus")]
+    replication_status: Vec<ReplicationStatusType>,
+}
+
+impl Handler for RemoveRegionsFromReplicationHandler {
+    type Request = RemoveRegionsFromReplicationRequest;
+    type Response = RemoveRegionsFromReplicationResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+        let regions: Vec<String> = request
+            .remove_replica_regions
+            .into_iter()
+            .map(|Region(region)| region)
+            .collect();
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can only be removed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        if let Err(error) = remove_regions_from_replication(db, &secret, &regions).await {
+            tracing::error!(?error, "failed to remove secret replicas");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        let replicas = match get_secret_replicas(db, &secret.arn).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get secret replicas");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        Ok(RemoveRegionsFromReplicationResponse {
+            arn: secret.arn,
+            replication_status: replicas
+                .into_iter()
+                .map(ReplicationStatusType::from_stored)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    database::{DbPool, replicas::get_secret_replicas, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidParameterException,
+            ResourceNotFoundException, replica_secret_response, scheduled_for_deletion_response,
+        },
+        models::{ReplicaRegionType, ReplicationStatusType, SecretId},
+    },
+    middleware::aws_sig_v4::RequestContext,
+    replication::{ReplicaRegion, replicate_secret_to_regions},
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_ReplicateSecretToRegions.html
+pub struct ReplicateSecretToRegionsHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ReplicateSecretToRegionsRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+
+    #[serde(rename = "AddReplicaRegions")]
+    #[garde(length(min = 1), dive)]
+    add_replica_regions: Vec<ReplicaRegionType>,
+
+    #[serde(rename = "ForceOverwriteReplicaSecret")]
+    #[serde(default)]
+    #[garde(skip)]
+    force_overwrite_replica_secret: bool,
+}
+
+#[derive(Serialize)]
+pub struct ReplicateSecretToRegionsResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "ReplicationStatus")]
+    replication_status: Vec<ReplicationStatusType>,
+}
+
+impl Handler for ReplicateSecretToRegionsHandler {
+    type Request = ReplicateSecretToRegionsRequest;
+    type Response = ReplicateSecretToRegionsResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let replica_regions: Vec<ReplicaRegion> = request
+            .add_replica_regions
+            .into_iter()
+            .map(|replica_region| ReplicaRegion {
+                region: replica_region.region.0,
+                kms_key_id: replica_region.kms_key_id,
+            })
+            .collect();
+
+        // Secrets can't be replicated into their own region
+        if replica_regions
+            .iter()
+            .any(|replica_region| replica_region.region == context.caller.region)
+        {
+            let error = InvalidParameterException
+                .with_message("A secret can't be replicated to its own Region.");
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can't be replicated themselves
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // Secret is scheduled for deletion
+        if secret.scheduled_delete_at.is_some() {
+            return Err(scheduled_for_deletion_response());
+        }
+
+        if let Err(error) = replicate_secret_to_regions(
+            db,
+            &secret,
+            &replica_regions,
+            request.force_overwrite_replica_secret,
+        )
+        .await
+        {
+            tracing::error!(?error, "failed to replicate secret");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        let replicas = match get_secret_replicas(db, &secret.arn).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to get secret replicas");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        };
+
+        Ok(ReplicateSecretToRegionsResponse {
+            arn: secret.arn,
+            replication_status: replicas
+                .into_iter()
+                .map(ReplicationStatusType::from_stored)
+                .collect(),
+        })
+    }
+}
@@ -5,9 +5,13 @@ use crate::{
     },
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response,
+        },
         models::SecretId,
     },
+    middleware::aws_sig_v4::RequestContext,
 };
 use axum::response::IntoResponse;
 use garde::Validate;
@@ -35,14 +39,20 @@ impl Handler for RestoreSecretHandler {
     type Request = RestoreSecretRequest;
     type Response = RestoreSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
     async fn handle(
+        &self,
         db: &DbPool,
+        context: &RequestContext,
         request: Self::Request,
     ) -> Result<Self::Response, axum::response::Response> {
         let SecretId(secret_id) = request.secret_id;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -55,6 +65,11 @@ impl Handler for RestoreSecretHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
         if let Err(error) = cancel_delete_secret(db, &secret.arn).await {
             tracing::error!(?error, "failed to get secret");
             return Err(AwsErrorResponse(InternalServiceError).into_response());
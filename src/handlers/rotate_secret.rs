@@ -0,0 +1,297 @@
+use crate::{
+    database::{
+        DbPool,
+        secrets::{
+            StoredSecret, UpdateSecretRotation, get_secret_by_version_id,
+            get_secret_latest_version, update_secret_rotation,
+        },
+    },
+    handlers::{
+        Handler,
+        error::{
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidParameterException,
+            InvalidRequestException, ResourceNotFoundException, replica_secret_response,
+            scheduled_for_deletion_response,
+        },
+        models::{ClientRequestToken, RotationRules, SecretId},
+        quotas::Quotas,
+    },
+    kms::KmsError,
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
+    rotation::{
+        RotationError, check_rotation,
+        function::RotationFunctions,
+        rotate_secret,
+        schedule::{RotationSchedule, ScheduleError, parse_rotation_duration},
+    },
+};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_RotateSecret.html
+pub struct RotateSecretHandler {
+    /// Local functions to invoke in place of rotation Lambda functions
+    pub rotation_functions: Arc<RotationFunctions>,
+    /// Service quotas checked before creating the rotated version
+    pub quotas: Quotas,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RotateSecretRequest {
+    #[serde(rename = "ClientRequestToken")]
+    #[garde(dive)]
+    client_request_token: Option<ClientRequestToken>,
+
+    #[serde(rename = "RotateImmediately")]
+    #[serde(default = "default_rotate_immediately")]
+    #[garde(skip)]
+    rotate_immediately: bool,
+
+    #[serde(rename = "RotationLambdaARN")]
+    #[garde(inner(length(max = 2048)))]
+    rotation_lambda_arn: Option<String>,
+
+    #[serde(rename = "RotationRules")]
+    #[garde(dive)]
+    rotation_rules: Option<RotationRules>,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct RotateSecretResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "VersionId")]
+    version_id: Option<String>,
+}
+
+fn default_rotate_immediately() -> bool {
+    true
+}
+
+impl Handler for RotateSecretHandler {
+    type Request = RotateSecretRequest;
+    type Response = RotateSecretResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let RotateSecretRequest {
+            client_request_token,
+            rotate_immediately,
+            rotation_lambda_arn,
+            rotation_rules,
+            secret_id,
+        } = request;
+
+        let SecretId(secret_id) = secret_id;
+        let ClientRequestToken(version_id) = client_request_token.unwrap_or_default();
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // Secret is scheduled for deletion
+        if secret.scheduled_delete_at.is_some() {
+            return Err(scheduled_for_deletion_response());
+        }
+
+        // Rotation for this request has already been completed
+        match get_secret_by_version_id(db, &context.caller.region, &secret.arn, &version_id).await {
+            Ok(Some(version))
+                if version
+                    .version_stages
+                    .iter()
+                    .any(|stage| stage == "AWSCURRENT") =>
+            {
+                return Ok(RotateSecretResponse {
+                    arn: secret.arn,
+                    name: secret.name,
+                    version_id: Some(version_id),
+                });
+            }
+            Ok(_) => {}
+            Err(error) => {
+                tracing::error!(?error, "failed to determine existing version");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
+        }
+
+        if rotate_immediately {
+            self.quotas.check_version_count(db, &secret.arn).await?;
+        }
+
+        // Use the new rotation rules if provided otherwise keep the existing rules
+        let (automatically_after_days, duration, schedule_expression) = match rotation_rules {
+            Some(rules) => (
+                rules.automatically_after_days,
+                rules.duration,
+                rules.schedule_expression,
+            ),
+            None => (
+                secret.rotation_automatically_after_days,
+                secret.rotation_duration.clone(),
+                secret.rotation_schedule_expression.clone(),
+            ),
+        };
+
+        if let Some(duration) = duration.as_deref()
+            && parse_rotation_duration(duration).is_err()
+        {
+            let error = InvalidParameterException.with_message(
+                "RotationRules.Duration must be a number of hours between 1h and 24h.",
+            );
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
+        let schedule = match RotationSchedule::from_rules(
+            automatically_after_days,
+            schedule_expression.as_deref(),
+        ) {
+            Ok(value) => value,
+            Err(error) => {
+                let message = match error {
+                    ScheduleError::ConflictingRules => {
+                        "You can't specify both AutomaticallyAfterDays and ScheduleExpression."
+                    }
+                    _ => "RotationRules.ScheduleExpression is not a valid rate or cron expression.",
+                };
+                let error = InvalidParameterException.with_message(message);
+                return Err(AwsErrorResponse(error).into_response());
+            }
+        };
+
+        let rotation_lambda_arn = rotation_lambda_arn.or(secret.rotation_lambda_arn.clone());
+        let rotation_enabled = schedule.is_some();
+
+        // Reject rotations that can't be performed before storing the configuration
+        if rotate_immediately {
+            let secret = StoredSecret {
+                rotation_lambda_arn: rotation_lambda_arn.clone(),
+                ..secret.clone()
+            };
+
+            check_rotation(db, &self.rotation_functions, &secret, &version_id)
+                .await
+                .map_err(rotation_error_response)?;
+        }
+
+        let next_rotation_at = schedule.as_ref().and_then(|schedule| {
+            schedule.next_after(secret.last_rotated_at.unwrap_or_else(Utc::now))
+        });
+
+        if let Err(error) = update_secret_rotation(
+            db,
+            &secret.arn,
+            UpdateSecretRotation {
+                rotation_enabled,
+                rotation_lambda_arn,
+                rotation_automatically_after_days: automatically_after_days,
+                rotation_duration: duration,
+                rotation_schedule_expression: schedule_expression,
+                next_rotation_at,
+            },
+        )
+        .await
+        {
+            tracing::error!(?error, "failed to update secret rotation");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        if !rotate_immediately {
+            // Propagate the rotation configuration to any replicas of the secret
+            if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+                tracing::error!(?error, "failed to replicate secret");
+            }
+
+            return Ok(RotateSecretResponse {
+                arn: secret.arn,
+                name: secret.name,
+                version_id: None,
+            });
+        }
+
+        // Load the secret again to obtain the updated rotation configuration
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret.arn)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        let version_id = rotate_secret(db, &self.rotation_functions, &secret, version_id)
+            .await
+            .map_err(rotation_error_response)?;
+
+        Ok(RotateSecretResponse {
+            arn: secret.arn,
+            name: secret.name,
+            version_id: Some(version_id),
+        })
+    }
+}
+
+/// Create the response for a rotation that failed with `error`, the message
+/// describes the cause of the failure
+fn rotation_error_response(error: RotationError) -> Response {
+    let message = match error {
+        RotationError::Db(_)
+        | RotationError::Kms(KmsError::Db(_))
+        | RotationError::Replication(_) => {
+            tracing::error!(?error, "failed to rotate secret");
+            return AwsErrorResponse(InternalServiceError).into_response();
+        }
+        RotationError::MissingFunction => {
+            let error = InvalidRequestException
+                .with_message("No Lambda rotation function ARN is associated with this secret.");
+            return AwsErrorResponse(error).into_response();
+        }
+        RotationError::IncompleteRotation(version_id) => {
+            let error = InvalidRequestException.with_message(format!(
+                "A previous rotation into version {version_id} isn't complete, remove its AWSPENDING staging label to rotate again."
+            ));
+            return AwsErrorResponse(error).into_response();
+        }
+        RotationError::Function { step, source } => {
+            format!(
+                "Rotation failed during the {} step: {source}",
+                step.as_str()
+            )
+        }
+        error => format!("Rotation failed: {error}"),
+    };
+
+    tracing::debug!(%message, "failed to rotate secret");
+    AwsErrorResponse(InternalServiceError.with_message(message)).into_response()
+}
@@ -0,0 +1,32 @@
+use crate::handlers::error::AwsError;
+use axum::http::StatusCode;
+
+pub struct ParameterNotFound;
+
+impl AwsError for ParameterNotFound {
+    const TYPE: &str = "ParameterNotFound";
+    const MESSAGE: &str = "The parameter couldn't be found. Verify the name and try again.";
+}
+
+pub struct ParameterVersionNotFound;
+
+impl AwsError for ParameterVersionNotFound {
+    const TYPE: &str = "ParameterVersionNotFound";
+    const MESSAGE: &str = "The specified parameter version wasn't found. Verify the parameter name and version, and try again.";
+}
+
+pub struct ValidationException;
+
+impl AwsError for ValidationException {
+    const TYPE: &str = "ValidationException";
+    const MESSAGE: &str =
+        "WithDecryption flag must be True for retrieving a Secret Manager secret.";
+}
+
+pub struct InternalServerError;
+
+impl AwsError for InternalServerError {
+    const STATUS_CODE: StatusCode = StatusCode::INTERNAL_SERVER_ERROR;
+    const TYPE: &str = "InternalServerError";
+    const MESSAGE: &str = "An error occurred on the server side.";
+}
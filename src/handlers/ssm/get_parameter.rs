@@ -0,0 +1,78 @@
+use crate::{
+    database::DbPool,
+    handlers::{
+        Handler,
+        authorize::Authorizer,
+        error::AwsErrorResponse,
+        ssm::{
+            ParameterError,
+            error::{ParameterNotFound, ParameterVersionNotFound, ValidationException},
+            get_reference_parameter,
+            models::{Parameter, ParameterName, SecretReference},
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// https://docs.aws.amazon.com/systems-manager/latest/APIReference/API_GetParameter.html
+pub struct GetParameterHandler {
+    /// Authorizer for reading the referenced secret
+    pub authorizer: Arc<Authorizer>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct GetParameterRequest {
+    #[serde(rename = "Name")]
+    #[garde(dive)]
+    name: ParameterName,
+
+    #[serde(rename = "WithDecryption")]
+    #[serde(default)]
+    #[garde(skip)]
+    with_decryption: bool,
+}
+
+#[derive(Serialize)]
+pub struct GetParameterResponse {
+    #[serde(rename = "Parameter")]
+    parameter: Parameter,
+}
+
+impl Handler for GetParameterHandler {
+    type Request = GetParameterRequest;
+    type Response = GetParameterResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        SecretReference::parse(&request.name.0).map(|reference| reference.secret_id)
+    }
+
+    #[tracing::instrument(skip_all, fields(name = %request.name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        // Secret values are only provided decrypted
+        if !request.with_decryption {
+            return Err(AwsErrorResponse(ValidationException).into_response());
+        }
+
+        let parameter =
+            get_reference_parameter(db, &self.authorizer, &context.caller, &request.name.0)
+                .await
+                .map_err(|error| match error {
+                    ParameterError::NotFound => AwsErrorResponse(ParameterNotFound).into_response(),
+                    ParameterError::VersionNotFound => {
+                        AwsErrorResponse(ParameterVersionNotFound).into_response()
+                    }
+                    ParameterError::Response(response) => response,
+                })?;
+
+        Ok(GetParameterResponse { parameter })
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    database::DbPool,
+    handlers::{
+        Handler,
+        authorize::Authorizer,
+        error::AwsErrorResponse,
+        ssm::{
+            ParameterError,
+            error::ValidationException,
+            get_reference_parameter,
+            models::{Parameter, ParameterName},
+        },
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// https://docs.aws.amazon.com/systems-manager/latest/APIReference/API_GetParameters.html
+pub struct GetParametersHandler {
+    /// Authorizer for reading each of the referenced secrets
+    pub authorizer: Arc<Authorizer>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct GetParametersRequest {
+    #[serde(rename = "Names")]
+    #[garde(length(min = 1, max = 10), dive)]
+    names: Vec<ParameterName>,
+
+    #[serde(rename = "WithDecryption")]
+    #[serde(default)]
+    #[garde(skip)]
+    with_decryption: bool,
+}
+
+#[derive(Serialize)]
+pub struct GetParametersResponse {
+    #[serde(rename = "InvalidParameters")]
+    invalid_parameters: Vec<String>,
+    #[serde(rename = "Parameters")]
+    parameters: Vec<Parameter>,
+}
+
+impl Handler for GetParametersHandler {
+    type Request = GetParametersRequest;
+    type Response = GetParametersResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        // Secret values are only provided decrypted
+        if !request.with_decryption {
+            return Err(AwsErrorResponse(ValidationException).into_response());
+        }
+
+        let mut invalid_parameters = Vec::new();
+        let mut parameters = Vec::new();
+
+        for ParameterName(name) in request.names {
+            let parameter =
+                get_reference_parameter(db, &self.authorizer, &context.caller, &name).await;
+
+            match parameter {
+                Ok(parameter) => parameters.push(parameter),
+                Err(ParameterError::NotFound | ParameterError::VersionNotFound) => {
+                    invalid_parameters.push(name)
+                }
+                Err(ParameterError::Response(response)) => return Err(response),
+            }
+        }
+
+        Ok(GetParametersResponse {
+            invalid_parameters,
+            parameters,
+        })
+    }
+}
@@ -0,0 +1,139 @@
+//! SSM Parameter Store compatible endpoint, only parameters that reference
+//! secrets through the `/aws/reference/secretsmanager/` path are supported
+
+use crate::{
+    database::{
+        DbPool,
+        secrets::{
+            StoredSecret, get_secret_by_version_id, get_secret_by_version_stage,
+            get_secret_latest_version, update_secret_version_last_accessed,
+        },
+    },
+    handlers::{
+        authorize::{Authorizer, SecretResource},
+        error::{AccessDeniedException, AwsErrorResponse, decryption_failure_response},
+        ssm::{
+            error::InternalServerError,
+            models::{Parameter, SecretReference},
+        },
+    },
+    kms::decrypt_stored_secret,
+    middleware::aws_sig_v4::CallerIdentity,
+};
+use axum::response::{IntoResponse, Response};
+
+pub mod error;
+pub mod get_parameter;
+pub mod get_parameters;
+pub mod models;
+
+/// Policy actions the secret referenced by a parameter is authorized for, the
+/// parameter reads the secret value so the caller must be allowed both
+const GET_PARAMETER_ACTIONS: &[&str] = &["ssm:GetParameter", "secretsmanager:GetSecretValue"];
+
+/// Reasons a parameter could not be retrieved
+pub enum ParameterError {
+    /// The name does not reference a secret that exists
+    NotFound,
+    /// The secret exists but the selected version does not
+    VersionNotFound,
+    /// Failure unrelated to the parameter itself
+    Response(Response),
+}
+
+/// Get the parameter `name` that references a secret visible to the `caller`,
+/// the referenced secret must be allowed for the `ssm:GetParameter` and
+/// `secretsmanager:GetSecretValue` actions by the `authorizer`
+pub async fn get_reference_parameter(
+    db: &DbPool,
+    authorizer: &Authorizer,
+    caller: &CallerIdentity,
+    name: &str,
+) -> Result<Parameter, ParameterError> {
+    let reference = SecretReference::parse(name).ok_or(ParameterError::NotFound)?;
+    let secret = get_referenced_secret(db, &caller.region, &reference).await?;
+
+    let mut secret = match secret {
+        Some(value) => value,
+        None => return Err(ParameterError::NotFound),
+    };
+
+    // Secrets scheduled for deletion can't be read
+    if secret.scheduled_delete_at.is_some() {
+        return Err(ParameterError::NotFound);
+    }
+
+    for action in GET_PARAMETER_ACTIONS {
+        let allowed = authorizer
+            .is_secret_allowed(db, caller, action, SecretResource::from(&secret))
+            .await
+            .map_err(ParameterError::Response)?;
+
+        if !allowed {
+            return Err(ParameterError::Response(
+                AwsErrorResponse(AccessDeniedException).into_response(),
+            ));
+        }
+    }
+
+    decrypt_stored_secret(db, &mut secret)
+        .await
+        .map_err(|error| ParameterError::Response(decryption_failure_response(error)))?;
+
+    if let Err(error) =
+        update_secret_version_last_accessed(db, &secret.arn, &secret.version_id).await
+    {
+        tracing::error!(?error, "failed to update secret last accessed");
+        return Err(ParameterError::Response(
+            AwsErrorResponse(InternalServerError).into_response(),
+        ));
+    }
+
+    Ok(Parameter::from_secret(caller, &reference, secret))
+}
+
+/// Get the secret version referenced by the `reference`, the selector is
+/// matched against the version IDs first then the staging labels
+async fn get_referenced_secret(
+    db: &DbPool,
+    region: &str,
+    reference: &SecretReference<'_>,
+) -> Result<Option<StoredSecret>, ParameterError> {
+    let map_db_error = |error| {
+        tracing::error!(?error, "failed to get referenced secret");
+        ParameterError::Response(AwsErrorResponse(InternalServerError).into_response())
+    };
+
+    let selector = match reference.selector {
+        Some(value) => value,
+        None => {
+            return get_secret_latest_version(db, region, reference.secret_id)
+                .await
+                .map_err(map_db_error);
+        }
+    };
+
+    if let Some(secret) = get_secret_by_version_id(db, region, reference.secret_id, selector)
+        .await
+        .map_err(map_db_error)?
+    {
+        return Ok(Some(secret));
+    }
+
+    if let Some(secret) = get_secret_by_version_stage(db, region, reference.secret_id, selector)
+        .await
+        .map_err(map_db_error)?
+    {
+        return Ok(Some(secret));
+    }
+
+    // Distinguish between a missing secret and a missing version
+    let secret = get_secret_latest_version(db, region, reference.secret_id)
+        .await
+        .map_err(map_db_error)?;
+
+    match secret {
+        Some(_) => Err(ParameterError::VersionNotFound),
+        None => Ok(None),
+    }
+}
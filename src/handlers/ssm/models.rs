@@ -0,0 +1,192 @@
+use crate::{
+    database::secrets::StoredSecret, handlers::models::Arn, middleware::aws_sig_v4::CallerIdentity,
+    utils::date::Timestamp,
+};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// Path prefix of parameters that reference Secrets Manager secrets
+pub const SECRETS_MANAGER_REFERENCE_PREFIX: &str = "/aws/reference/secretsmanager/";
+
+#[derive(Debug, Deserialize, Validate)]
+#[garde(transparent)]
+pub struct ParameterName(#[garde(length(min = 1, max = 2048))] pub String);
+
+impl Display for ParameterName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Secret referenced by a parameter name in the form
+/// `/aws/reference/secretsmanager/<secret-id>[:<version-id or label>]`
+#[derive(Debug, PartialEq, Eq)]
+pub struct SecretReference<'a> {
+    /// Secret name or ARN
+    pub secret_id: &'a str,
+    /// Version ID or staging label selecting the secret version
+    pub selector: Option<&'a str>,
+}
+
+impl<'a> SecretReference<'a> {
+    pub fn parse(name: &'a str) -> Option<Self> {
+        let reference = name.strip_prefix(SECRETS_MANAGER_REFERENCE_PREFIX)?;
+
+        // Secret ARNs contain `:` themselves so only a trailing part beyond
+        // the secret resource ID is treated as the selector
+        let split = if reference.starts_with("arn:") {
+            Arn::from_str(reference)
+                .is_ok_and(|arn| arn.resource_id().contains(':'))
+                .then(|| reference.rsplit_once(':'))
+                .flatten()
+        } else {
+            reference.rsplit_once(':')
+        };
+
+        let (secret_id, selector) = match split {
+            Some((secret_id, selector)) => (secret_id, Some(selector)),
+            None => (reference, None),
+        };
+
+        if secret_id.is_empty() || selector.is_some_and(str::is_empty) {
+            return None;
+        }
+
+        Some(Self {
+            secret_id,
+            selector,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct Parameter {
+    #[serde(rename = "ARN")]
+    pub arn: String,
+    #[serde(rename = "DataType")]
+    pub data_type: &'static str,
+    #[serde(rename = "LastModifiedDate")]
+    pub last_modified_date: Timestamp,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Selector")]
+    pub selector: Option<String>,
+    #[serde(rename = "SourceResult")]
+    pub source_result: String,
+    #[serde(rename = "Type")]
+    pub r#type: &'static str,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+/// The GetSecretValue result the parameter was created from, provided as
+/// a JSON string in the `SourceResult` of the parameter
+#[derive(Serialize)]
+struct SourceResult<'a> {
+    #[serde(rename = "ARN")]
+    arn: &'a str,
+    #[serde(rename = "CreatedDate")]
+    created_date: Timestamp,
+    #[serde(rename = "Name")]
+    name: &'a str,
+    #[serde(rename = "SecretString")]
+    secret_string: Option<&'a str>,
+    #[serde(rename = "SecretBinary")]
+    secret_binary: Option<&'a str>,
+    #[serde(rename = "VersionId")]
+    version_id: &'a str,
+    #[serde(rename = "VersionStages")]
+    version_stages: &'a [String],
+}
+
+impl Parameter {
+    pub fn from_secret(
+        caller: &CallerIdentity,
+        reference: &SecretReference<'_>,
+        secret: StoredSecret,
+    ) -> Self {
+        let name = format!("{SECRETS_MANAGER_REFERENCE_PREFIX}{}", reference.secret_id);
+
+        let source_result = serde_json::to_string(&SourceResult {
+            arn: &secret.arn,
+            created_date: Timestamp(secret.version_created_at),
+            name: &secret.name,
+            secret_string: secret.secret_string.as_deref(),
+            secret_binary: secret.secret_binary.as_deref(),
+            version_id: &secret.version_id,
+            version_stages: &secret.version_stages,
+        })
+        .unwrap_or_default();
+
+        Self {
+            arn: Arn::new(
+                "ssm",
+                &caller.region,
+                &caller.account_id,
+                &format!("parameter{name}"),
+            )
+            .to_string(),
+            data_type: "text",
+            last_modified_date: Timestamp(secret.version_created_at),
+            name,
+            selector: reference.selector.map(|value| format!(":{value}")),
+            source_result,
+            r#type: "SecureString",
+            value: secret
+                .secret_string
+                .or(secret.secret_binary)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secret_reference() {
+        assert_eq!(
+            SecretReference::parse("/aws/reference/secretsmanager/test"),
+            Some(SecretReference {
+                secret_id: "test",
+                selector: None
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("/aws/reference/secretsmanager/app/test:AWSPREVIOUS"),
+            Some(SecretReference {
+                secret_id: "app/test",
+                selector: Some("AWSPREVIOUS")
+            })
+        );
+        assert_eq!(
+            SecretReference::parse(
+                "/aws/reference/secretsmanager/arn:aws:secretsmanager:us-east-1:123456789012:secret:test-AbCdEf"
+            ),
+            Some(SecretReference {
+                secret_id: "arn:aws:secretsmanager:us-east-1:123456789012:secret:test-AbCdEf",
+                selector: None
+            })
+        );
+        assert_eq!(
+            SecretReference::parse(
+                "/aws/reference/secretsmanager/arn:aws:secretsmanager:us-east-1:123456789012:secret:test-AbCdEf:AWSCURRENT"
+            ),
+            Some(SecretReference {
+                secret_id: "arn:aws:secretsmanager:us-east-1:123456789012:secret:test-AbCdEf",
+                selector: Some("AWSCURRENT")
+            })
+        );
+        assert_eq!(SecretReference::parse("/app/test"), None);
+        assert_eq!(
+            SecretReference::parse("/aws/reference/secretsmanager/"),
+            None
+        );
+        assert_eq!(
+            SecretReference::parse("/aws/reference/secretsmanager/test:"),
+            None
+        );
+    }
+}
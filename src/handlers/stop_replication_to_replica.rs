@@ -0,0 +1,74 @@
+use crate::{
+    database::{DbPool, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            ResourceNotFoundException,
+        },
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    replication::stop_replication_to_replica,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_StopReplicationToReplica.html
+pub struct StopReplicationToReplicaHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct StopReplicationToReplicaRequest {
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: SecretId,
+}
+
+#[derive(Serialize)]
+pub struct StopReplicationToReplicaResponse {
+    #[serde(rename = "ARN")]
+    arn: String,
+}
+
+impl Handler for StopReplicationToReplicaHandler {
+    type Request = StopReplicationToReplicaRequest;
+    type Response = StopReplicationToReplicaResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
+    #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let SecretId(secret_id) = request.secret_id;
+
+        let secret = get_secret_latest_version(db, &context.caller.region, &secret_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get secret");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        // Only replicas can be promoted
+        if secret.primary_arn.is_none() {
+            let error = InvalidRequestException.with_message("The secret is not a replica secret.");
+            return Err(AwsErrorResponse(error).into_response());
+        }
+
+        if let Err(error) = stop_replication_to_replica(db, &secret).await {
+            tracing::error!(?error, "failed to promote replica secret");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(StopReplicationToReplicaResponse { arn: secret.arn })
+    }
+}
@@ -0,0 +1,149 @@
+use super::{
+    StsHandler, StsResponse,
+    error::{AccessDenied, MalformedPolicyDocument, StsErrorResponse},
+    xml::XmlWriter,
+};
+use crate::{
+    database::{
+        DbPool,
+        session_credentials::{CreateSessionCredential, create_session_credential},
+    },
+    handlers::{
+        authorize::identity_policy_allows,
+        error::{AwsError, InternalServiceError, policy_validation_message},
+        models::{RoleArn, RoleSessionName},
+    },
+    middleware::aws_sig_v4::{RequestContext, assumed_role_arn},
+    policy::validate_identity_policy,
+    utils::access_key::{
+        generate_access_key_secret, generate_session_access_key_id, generate_session_token,
+    },
+};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use garde::Validate;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Default duration of the issued credentials when not specified
+const DEFAULT_DURATION_SECONDS: i64 = 60 * 60;
+
+// https://docs.aws.amazon.com/STS/latest/APIReference/API_AssumeRole.html
+pub struct AssumeRoleHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct AssumeRoleRequest {
+    #[serde(rename = "RoleArn")]
+    #[garde(dive)]
+    role_arn: RoleArn,
+
+    #[serde(rename = "RoleSessionName")]
+    #[garde(dive)]
+    role_session_name: RoleSessionName,
+
+    #[serde(rename = "DurationSeconds")]
+    #[garde(inner(range(min = 900, max = 43200)))]
+    duration_seconds: Option<i64>,
+
+    #[serde(rename = "Policy")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    policy: Option<String>,
+}
+
+pub struct AssumeRoleResponse {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    expiration: DateTime<Utc>,
+    assumed_role_id: String,
+    arn: String,
+}
+
+impl StsResponse for AssumeRoleResponse {
+    fn write_result(&self, xml: &mut XmlWriter) {
+        xml.start("Credentials", None);
+        xml.element("AccessKeyId", &self.access_key_id);
+        xml.element("SecretAccessKey", &self.secret_access_key);
+        xml.element("SessionToken", &self.session_token);
+        xml.element(
+            "Expiration",
+            &self.expiration.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+        xml.end("Credentials");
+        xml.start("AssumedRoleUser", None);
+        xml.element("AssumedRoleId", &self.assumed_role_id);
+        xml.element("Arn", &self.arn);
+        xml.end("AssumedRoleUser");
+    }
+}
+
+impl StsHandler for AssumeRoleHandler {
+    const ACTION: &str = "AssumeRole";
+
+    type Request = AssumeRoleRequest;
+    type Response = AssumeRoleResponse;
+
+    #[tracing::instrument(skip_all, fields(role_arn = %request.role_arn, session_name = %request.role_session_name))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let RoleArn(role_arn) = request.role_arn;
+        let RoleSessionName(session_name) = request.role_session_name;
+
+        if !identity_policy_allows(&context.caller, "sts:AssumeRole", &role_arn) {
+            return Err(StsErrorResponse(AccessDenied).into_response());
+        }
+
+        if let Some(policy) = &request.policy {
+            validate_identity_policy(policy).map_err(|errors| {
+                let message = policy_validation_message(&errors);
+                StsErrorResponse(MalformedPolicyDocument.with_message(message)).into_response()
+            })?;
+        }
+
+        // The session stays restricted by the identity policies of the caller
+        let source_policies: Vec<&Value> = context.caller.identity_policies().collect();
+        let source_policies = match source_policies.is_empty() {
+            true => None,
+            false => Some(serde_json::to_string(&source_policies).map_err(|error| {
+                tracing::error!(?error, "failed to serialize source policies");
+                StsErrorResponse(InternalServiceError).into_response()
+            })?),
+        };
+
+        let duration = request.duration_seconds.unwrap_or(DEFAULT_DURATION_SECONDS);
+        let expires_at = Utc::now() + Duration::seconds(duration);
+
+        let session = create_session_credential(
+            db,
+            CreateSessionCredential {
+                access_key_id: generate_session_access_key_id(),
+                access_key_secret: generate_access_key_secret(),
+                session_token: generate_session_token(),
+                role_arn,
+                session_name,
+                source_access_key_id: context.caller.access_key_id.clone(),
+                policy: request.policy,
+                source_policies,
+                expires_at,
+            },
+        )
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to create session credentials");
+            StsErrorResponse(InternalServiceError).into_response()
+        })?;
+
+        Ok(AssumeRoleResponse {
+            arn: assumed_role_arn(&session.role_arn, &session.session_name),
+            assumed_role_id: format!("{}:{}", session.access_key_id, session.session_name),
+            access_key_id: session.access_key_id,
+            secret_access_key: session.access_key_secret,
+            session_token: session.session_token,
+            expiration: session.expires_at,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::handlers::error::AwsError;
+use axum::{
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use super::xml::XmlWriter;
+
+/// Error response using the XML format of the AWS query protocol
+pub struct StsErrorResponse<A: AwsError>(pub A);
+
+impl<A: AwsError> IntoResponse for StsErrorResponse<A> {
+    fn into_response(self) -> axum::response::Response {
+        // Errors caused by the server are attributed to the receiver
+        let error_type = if A::STATUS_CODE.is_server_error() {
+            "Receiver"
+        } else {
+            "Sender"
+        };
+
+        let mut xml = XmlWriter::default();
+        xml.start("ErrorResponse", Some(super::STS_NAMESPACE));
+        xml.start("Error", None);
+        xml.element("Type", error_type);
+        xml.element("Code", A::TYPE);
+        xml.element("Message", self.0.message());
+        xml.end("Error");
+        xml.element("RequestId", &Uuid::new_v4().to_string());
+        xml.end("ErrorResponse");
+
+        let mut response = (A::STATUS_CODE, xml.finish()).into_response();
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/xml"));
+        response
+    }
+}
+
+pub struct InvalidAction;
+
+impl AwsError for InvalidAction {
+    const TYPE: &str = "InvalidAction";
+    const MESSAGE: &str =
+        "The action or operation requested is invalid. Verify that the action is typed correctly.";
+}
+
+pub struct ValidationError;
+
+impl AwsError for ValidationError {
+    const TYPE: &str = "ValidationError";
+    const MESSAGE: &str = "The input fails to satisfy the constraints specified by an AWS service.";
+}
+
+pub struct MalformedPolicyDocument;
+
+impl AwsError for MalformedPolicyDocument {
+    const TYPE: &str = "MalformedPolicyDocument";
+    const MESSAGE: &str = "The request was rejected because the policy document was malformed.";
+}
+
+pub struct AccessDenied;
+
+impl AwsError for AccessDenied {
+    const STATUS_CODE: StatusCode = StatusCode::FORBIDDEN;
+    const TYPE: &str = "AccessDenied";
+    const MESSAGE: &str = "User is not authorized to perform: sts:AssumeRole";
+}
@@ -0,0 +1,46 @@
+use super::{StsHandler, StsResponse, caller_user_id, xml::XmlWriter};
+use crate::{database::DbPool, middleware::aws_sig_v4::RequestContext};
+use axum::response::Response;
+use garde::Validate;
+use serde::Deserialize;
+
+// https://docs.aws.amazon.com/STS/latest/APIReference/API_GetCallerIdentity.html
+pub struct GetCallerIdentityHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct GetCallerIdentityRequest {}
+
+pub struct GetCallerIdentityResponse {
+    user_id: String,
+    account: String,
+    arn: String,
+}
+
+impl StsResponse for GetCallerIdentityResponse {
+    fn write_result(&self, xml: &mut XmlWriter) {
+        xml.element("UserId", &self.user_id);
+        xml.element("Account", &self.account);
+        xml.element("Arn", &self.arn);
+    }
+}
+
+impl StsHandler for GetCallerIdentityHandler {
+    const ACTION: &str = "GetCallerIdentity";
+
+    type Request = GetCallerIdentityRequest;
+    type Response = GetCallerIdentityResponse;
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        _db: &DbPool,
+        context: &RequestContext,
+        _request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        Ok(GetCallerIdentityResponse {
+            user_id: caller_user_id(&context.caller),
+            account: context.caller.account_id.clone(),
+            arn: context.caller.principal_arn.clone(),
+        })
+    }
+}
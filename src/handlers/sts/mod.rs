@@ -0,0 +1,125 @@
+//! STS compatible endpoint, STS uses the AWS query protocol where requests are
+//! form encoded with the operation in the `Action` parameter and responses are
+//! XML documents
+
+use crate::{
+    database::DbPool,
+    handlers::error::{AwsError, validation_message},
+    middleware::aws_sig_v4::{CallerIdentity, RequestContext},
+};
+use assume_role::AssumeRoleHandler;
+use axum::{
+    http::{HeaderValue, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use error::{InvalidAction, StsErrorResponse, ValidationError};
+use garde::Validate;
+use get_caller_identity::GetCallerIdentityHandler;
+use serde::{Deserialize, de::DeserializeOwned};
+use xml::XmlWriter;
+
+mod assume_role;
+mod error;
+mod get_caller_identity;
+mod xml;
+
+/// XML namespace of STS responses
+const STS_NAMESPACE: &str = "https://sts.amazonaws.com/doc/2011-06-15/";
+
+#[derive(Deserialize)]
+struct StsAction {
+    #[serde(rename = "Action")]
+    action: String,
+}
+
+/// Handle a query protocol STS `request` within the request `context`
+pub async fn handle_sts_request(db: &DbPool, context: &RequestContext, request: &[u8]) -> Response {
+    let StsAction { action } = match serde_urlencoded::from_bytes(request) {
+        Ok(value) => value,
+        Err(_) => return StsErrorResponse(InvalidAction).into_response(),
+    };
+
+    match action.as_str() {
+        "AssumeRole" => handle_action(&AssumeRoleHandler, db, context, request).await,
+        "GetCallerIdentity" => handle_action(&GetCallerIdentityHandler, db, context, request).await,
+        _ => StsErrorResponse(InvalidAction).into_response(),
+    }
+}
+
+/// Handler for a specific STS action
+pub trait StsHandler: Send + Sync + 'static {
+    /// Name of the action from the `Action` parameter
+    const ACTION: &str;
+
+    type Request: DeserializeOwned + Validate<Context = ()> + Send + 'static;
+    type Response: StsResponse + Send + 'static;
+
+    fn handle<'r>(
+        &'r self,
+        db: &'r DbPool,
+        context: &'r RequestContext,
+        request: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, Response>> + Send + 'r;
+}
+
+/// Result of an STS action that can be written as XML
+pub trait StsResponse {
+    /// Write the elements within the result element of the response
+    fn write_result(&self, xml: &mut XmlWriter);
+}
+
+async fn handle_action<H: StsHandler>(
+    handler: &H,
+    db: &DbPool,
+    context: &RequestContext,
+    request: &[u8],
+) -> Response {
+    let request: H::Request = match serde_urlencoded::from_bytes(request) {
+        Ok(value) => value,
+        Err(error) => {
+            tracing::error!(?error, "failed to parse sts request");
+            return StsErrorResponse(ValidationError).into_response();
+        }
+    };
+
+    if let Err(report) = request.validate() {
+        let error = ValidationError.with_message(validation_message(&report));
+        return StsErrorResponse(error).into_response();
+    }
+
+    let response = match handler.handle(db, context, request).await {
+        Ok(value) => value,
+        Err(error) => return error,
+    };
+
+    let response_name = format!("{}Response", H::ACTION);
+    let result_name = format!("{}Result", H::ACTION);
+
+    let mut xml = XmlWriter::default();
+    xml.start(&response_name, Some(STS_NAMESPACE));
+    xml.start(&result_name, None);
+    response.write_result(&mut xml);
+    xml.end(&result_name);
+    xml.start("ResponseMetadata", None);
+    xml.element("RequestId", &context.request_id);
+    xml.end("ResponseMetadata");
+    xml.end(&response_name);
+
+    let mut response = xml.finish().into_response();
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/xml"));
+    response
+}
+
+/// Get the unique ID for the caller, sessions of an assumed role are
+/// identified by the temporary access key and the session name
+fn caller_user_id(caller: &CallerIdentity) -> String {
+    match caller.principal_arn.split_once(":assumed-role/") {
+        Some((_, role_session)) => {
+            let session_name = role_session.rsplit('/').next().unwrap_or_default();
+            format!("{}:{session_name}", caller.access_key_id)
+        }
+        None => caller.access_key_id.clone(),
+    }
+}
@@ -0,0 +1,75 @@
+/// Minimal writer for the XML documents of query protocol responses
+#[derive(Default)]
+pub struct XmlWriter {
+    output: String,
+}
+
+impl XmlWriter {
+    /// Write the start tag of the element `name` with an optional `namespace`
+    pub fn start(&mut self, name: &str, namespace: Option<&str>) {
+        self.output.push('<');
+        self.output.push_str(name);
+
+        if let Some(namespace) = namespace {
+            self.output.push_str(" xmlns=\"");
+            self.output.push_str(&escape(namespace));
+            self.output.push('"');
+        }
+
+        self.output.push('>');
+    }
+
+    /// Write the end tag of the element `name`
+    pub fn end(&mut self, name: &str) {
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>');
+    }
+
+    /// Write an element `name` containing the text `value`
+    pub fn element(&mut self, name: &str, value: &str) {
+        self.start(name, None);
+        self.output.push_str(&escape(value));
+        self.end(name);
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+/// Escape the characters of `value` that can't appear within XML text
+fn escape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            char => output.push(char),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XmlWriter;
+
+    #[test]
+    fn test_write_escaped() {
+        let mut xml = XmlWriter::default();
+        xml.start("Response", Some("https://example.com/"));
+        xml.element("Value", "<a & \"b\">");
+        xml.end("Response");
+
+        assert_eq!(
+            xml.finish(),
+            r#"<Response xmlns="https://example.com/"><Value>&lt;a &amp; &quot;b&quot;&gt;</Value></Response>"#
+        );
+    }
+}
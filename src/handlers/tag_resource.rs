@@ -5,9 +5,15 @@ use crate::{
     },
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response,
+        },
         models::{SecretId, Tag},
+        quotas::Quotas,
     },
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
 };
 use axum::response::IntoResponse;
 use garde::Validate;
@@ -15,7 +21,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
 
 /// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_TagResource.html
-pub struct TagResourceHandler;
+pub struct TagResourceHandler {
+    /// Service quotas checked after attaching the tags
+    pub quotas: Quotas,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct TagResourceRequest {
@@ -35,15 +44,21 @@ impl Handler for TagResourceHandler {
     type Request = TagResourceRequest;
     type Response = TagResourceResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
     async fn handle(
+        &self,
         db: &DbPool,
+        context: &RequestContext,
         request: Self::Request,
     ) -> Result<Self::Response, axum::response::Response> {
         let SecretId(secret_id) = request.secret_id;
         let tags = request.tags;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -56,6 +71,11 @@ impl Handler for TagResourceHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
         let mut t = match db.begin().await {
             Ok(value) => value,
             Err(error) => {
@@ -64,6 +84,14 @@ impl Handler for TagResourceHandler {
             }
         };
 
+        self.quotas
+            .check_tag_count(
+                t.deref_mut(),
+                &secret.arn,
+                tags.iter().map(|tag| tag.key.as_str()),
+            )
+            .await?;
+
         // Attach all the secrets
         for tag in tags {
             if let Err(error) =
@@ -84,6 +112,11 @@ impl Handler for TagResourceHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
         Ok(TagResourceResponse {})
     }
 }
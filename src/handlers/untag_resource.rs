@@ -5,9 +5,14 @@ use crate::{
     },
     handlers::{
         Handler,
-        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        error::{
+            AwsErrorResponse, InternalServiceError, ResourceNotFoundException,
+            replica_secret_response,
+        },
         models::SecretId,
     },
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
 };
 use axum::response::IntoResponse;
 use garde::Validate;
@@ -35,15 +40,21 @@ impl Handler for UntagResourceHandler {
     type Request = UntagResourceRequest;
     type Response = UntagResourceResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
     async fn handle(
+        &self,
         db: &DbPool,
+        context: &RequestContext,
         request: Self::Request,
     ) -> Result<Self::Response, axum::response::Response> {
         let SecretId(secret_id) = request.secret_id;
         let tag_keys = request.tag_keys;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -56,6 +67,11 @@ impl Handler for UntagResourceHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
         let mut t = match db.begin().await {
             Ok(value) => value,
             Err(error) => {
@@ -82,6 +98,11 @@ impl Handler for UntagResourceHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
         Ok(UntagResourceResponse {})
     }
 }
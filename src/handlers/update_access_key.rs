@@ -0,0 +1,66 @@
+use crate::{
+    database::{
+        DbPool,
+        access_keys::{get_access_key, update_access_key_enabled},
+    },
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        models::{AccessKeyId, AccessKeyStatus},
+    },
+    middleware::aws_sig_v4::RequestContext,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// Modeled after https://docs.aws.amazon.com/IAM/latest/APIReference/API_UpdateAccessKey.html
+pub struct UpdateAccessKeyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateAccessKeyRequest {
+    #[serde(rename = "AccessKeyId")]
+    #[garde(dive)]
+    access_key_id: AccessKeyId,
+
+    #[serde(rename = "Status")]
+    #[garde(skip)]
+    status: AccessKeyStatus,
+}
+
+#[derive(Serialize)]
+pub struct UpdateAccessKeyResponse {}
+
+impl Handler for UpdateAccessKeyHandler {
+    type Request = UpdateAccessKeyRequest;
+    type Response = UpdateAccessKeyResponse;
+
+    #[tracing::instrument(skip_all, fields(access_key_id = %request.access_key_id))]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        _context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let AccessKeyId(access_key_id) = request.access_key_id;
+
+        get_access_key(db, &access_key_id)
+            .await
+            //
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get access key");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?
+            //
+            .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+
+        if let Err(error) =
+            update_access_key_enabled(db, &access_key_id, request.status.is_enabled()).await
+        {
+            tracing::error!(?error, "failed to update access key");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        Ok(UpdateAccessKeyResponse {})
+    }
+}
@@ -4,17 +4,21 @@ use crate::{
         secrets::{
             CreateSecretVersion, add_secret_version_stage, create_secret_version,
             get_secret_latest_version, remove_secret_version_stage,
-            remove_secret_version_stage_any, update_secret_description,
+            remove_secret_version_stage_any, update_secret_description, update_secret_kms_key_id,
         },
     },
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
-            ResourceNotFoundException,
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            ResourceNotFoundException, encryption_failure_response, replica_secret_response,
         },
         models::{ClientRequestToken, SecretBinary, SecretId, SecretString},
+        quotas::Quotas,
     },
+    kms::{encrypt_secret_value, find_enabled_key},
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -22,7 +26,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_UpdateSecret.html
-pub struct UpdateSecretHandler;
+pub struct UpdateSecretHandler {
+    /// Service quotas checked before creating a new version
+    pub quotas: Quotas,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct UpdateSecretRequest {
@@ -34,6 +41,10 @@ pub struct UpdateSecretRequest {
     #[garde(inner(length(max = 2048)))]
     description: Option<String>,
 
+    #[serde(rename = "KmsKeyId")]
+    #[garde(inner(length(min = 1, max = 2048)))]
+    kms_key_id: Option<String>,
+
     #[serde(rename = "SecretId")]
     #[garde(dive)]
     secret_id: SecretId,
@@ -61,11 +72,21 @@ impl Handler for UpdateSecretHandler {
     type Request = UpdateSecretRequest;
     type Response = UpdateSecretResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let UpdateSecretRequest {
             client_request_token,
             description,
+            kms_key_id,
             secret_id,
             secret_string,
             secret_binary,
@@ -77,10 +98,12 @@ impl Handler for UpdateSecretHandler {
 
         // Must only specify one of the two
         if secret_string.is_some() && secret_binary.is_some() {
-            return Err(AwsErrorResponse(InvalidRequestException).into_response());
+            let error = InvalidRequestException
+                .with_message("You can't specify both SecretString and SecretBinary.");
+            return Err(AwsErrorResponse(error).into_response());
         }
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -93,6 +116,34 @@ impl Handler for UpdateSecretHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
+        // New KMS key must be usable to encrypt future versions
+        if let Some(kms_key_id) = &kms_key_id {
+            find_enabled_key(db, &secret.region, kms_key_id)
+                .await
+                .map_err(encryption_failure_response)?;
+        }
+
+        // Encrypt the new value using the KMS key of the secret, changing the key
+        // only applies to new versions
+        let value = if secret_string.is_some() || secret_binary.is_some() {
+            self.quotas.check_version_count(db, &secret.arn).await?;
+
+            let kms_key_id = kms_key_id.as_deref().or(secret.kms_key_id.as_deref());
+            let value =
+                encrypt_secret_value(db, &secret.region, kms_key_id, secret_string, secret_binary)
+                    .await
+                    .map_err(encryption_failure_response)?;
+
+            Some(value)
+        } else {
+            None
+        };
+
         let mut t = match db.begin().await {
             Ok(value) => value,
             Err(error) => {
@@ -114,7 +165,20 @@ impl Handler for UpdateSecretHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
-        let version_id = if secret_string.is_some() || secret_binary.is_some() {
+        if let Some(kms_key_id) = kms_key_id
+            && let Err(error) =
+                update_secret_kms_key_id(t.deref_mut(), &secret.arn, Some(&kms_key_id)).await
+        {
+            // Rollback the transaction on failure
+            if let Err(error) = t.rollback().await {
+                tracing::error!(?error, "failed to rollback transaction");
+            }
+
+            tracing::error!(?error, "failed to update secret kms key");
+            return Err(AwsErrorResponse(InternalServiceError).into_response());
+        }
+
+        let version_id = if let Some(value) = value {
             let ClientRequestToken(version_id) = client_request_token.unwrap_or_default();
 
             // Create a new current secret version
@@ -123,8 +187,9 @@ impl Handler for UpdateSecretHandler {
                 CreateSecretVersion {
                     secret_arn: secret.arn.clone(),
                     version_id: version_id.clone(),
-                    secret_string,
-                    secret_binary,
+                    secret_string: value.secret_string,
+                    secret_binary: value.secret_binary,
+                    encrypted_data_key: value.encrypted_data_key,
                 },
             )
             .await
@@ -203,6 +268,11 @@ impl Handler for UpdateSecretHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
         Ok(UpdateSecretResponse {
             arn: secret.arn,
             name: secret.name,
@@ -9,11 +9,14 @@ use crate::{
     handlers::{
         Handler,
         error::{
-            AwsErrorResponse, InternalServiceError, InvalidRequestException,
-            ResourceNotFoundException,
+            AwsError, AwsErrorResponse, InternalServiceError, InvalidRequestException,
+            ResourceNotFoundException, replica_secret_response,
         },
         models::{SecretId, VersionId},
+        quotas::Quotas,
     },
+    middleware::aws_sig_v4::RequestContext,
+    replication::sync_secret_replicas,
 };
 use axum::response::{IntoResponse, Response};
 use garde::Validate;
@@ -21,7 +24,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
 
 // https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_UpdateSecretVersionStage.html
-pub struct UpdateSecretVersionStageHandler;
+pub struct UpdateSecretVersionStageHandler {
+    /// Service quotas checked after moving the version stage
+    pub quotas: Quotas,
+}
 
 #[derive(Deserialize, Validate)]
 pub struct UpdateSecretVersionStageRequest {
@@ -54,12 +60,21 @@ impl Handler for UpdateSecretVersionStageHandler {
     type Request = UpdateSecretVersionStageRequest;
     type Response = UpdateSecretVersionStageResponse;
 
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        Some(&request.secret_id.0)
+    }
+
     #[tracing::instrument(skip_all, fields(secret_id = %request.secret_id))]
-    async fn handle(db: &DbPool, request: Self::Request) -> Result<Self::Response, Response> {
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
         let SecretId(secret_id) = request.secret_id;
         let version_stage = request.version_stage;
 
-        let secret = match get_secret_latest_version(db, &secret_id).await {
+        let secret = match get_secret_latest_version(db, &context.caller.region, &secret_id).await {
             Ok(value) => value,
             Err(error) => {
                 tracing::error!(?error, "failed to get secret");
@@ -72,6 +87,11 @@ impl Handler for UpdateSecretVersionStageHandler {
             None => return Err(AwsErrorResponse(ResourceNotFoundException).into_response()),
         };
 
+        // Replicas can only be changed through their primary secret
+        if secret.primary_arn.is_some() {
+            return Err(replica_secret_response());
+        }
+
         let mut t = match db.begin().await {
             Ok(value) => value,
             Err(error) => {
@@ -93,7 +113,10 @@ impl Handler for UpdateSecretVersionStageHandler {
                 Ok(value) => {
                     // Secret version didn't have the stage attached
                     if value < 1 {
-                        return Err(AwsErrorResponse(InvalidRequestException).into_response());
+                        let error = InvalidRequestException.with_message(format!(
+                            "The staging label {version_stage} is not attached to version {source_version_id}."
+                        ));
+                        return Err(AwsErrorResponse(error).into_response());
                     }
                 }
                 Err(error) => {
@@ -127,25 +150,33 @@ impl Handler for UpdateSecretVersionStageHandler {
             }
         }
 
-        if let Some(VersionId(dest_version_id)) = request.move_to_version_id
-            && let Err(error) = add_secret_version_stage(
+        if let Some(VersionId(dest_version_id)) = request.move_to_version_id {
+            self.quotas
+                .check_version_stage_count(t.deref_mut(), &secret.arn, &dest_version_id)
+                .await?;
+
+            if let Err(error) = add_secret_version_stage(
                 t.deref_mut(),
                 &secret.arn,
                 &dest_version_id,
                 &version_stage,
             )
             .await
-        {
-            // Version stage is already attached to another version
-            if error
-                .as_database_error()
-                .is_some_and(|error| error.is_unique_violation())
             {
-                return Err(AwsErrorResponse(InvalidRequestException).into_response());
-            }
+                // Version stage is already attached to another version
+                if error
+                    .as_database_error()
+                    .is_some_and(|error| error.is_unique_violation())
+                {
+                    let error = InvalidRequestException.with_message(format!(
+                        "The staging label {version_stage} is currently attached to another version, specify RemoveFromVersionId to move it."
+                    ));
+                    return Err(AwsErrorResponse(error).into_response());
+                }
 
-            tracing::error!(?error, "failed to remove secret version stage");
-            return Err(AwsErrorResponse(InternalServiceError).into_response());
+                tracing::error!(?error, "failed to remove secret version stage");
+                return Err(AwsErrorResponse(InternalServiceError).into_response());
+            }
         }
 
         if let Err(error) = t.commit().await {
@@ -153,6 +184,11 @@ impl Handler for UpdateSecretVersionStageHandler {
             return Err(AwsErrorResponse(InternalServiceError).into_response());
         }
 
+        // Propagate the changes to any replicas of the secret
+        if let Err(error) = sync_secret_replicas(db, &secret.arn).await {
+            tracing::error!(?error, "failed to replicate secret");
+        }
+
         Ok(UpdateSecretVersionStageResponse {
             arn: secret.arn,
             name: secret.name,
@@ -0,0 +1,98 @@
+use crate::{
+    database::{DbPool, secrets::get_secret_latest_version},
+    handlers::{
+        Handler,
+        error::{AwsErrorResponse, InternalServiceError, ResourceNotFoundException},
+        models::SecretId,
+    },
+    middleware::aws_sig_v4::RequestContext,
+    policy::validate_policy,
+};
+use axum::response::{IntoResponse, Response};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+// https://docs.aws.amazon.com/secretsmanager/latest/apireference/API_ValidateResourcePolicy.html
+pub struct ValidateResourcePolicyHandler;
+
+#[derive(Deserialize, Validate)]
+pub struct ValidateResourcePolicyRequest {
+    #[serde(rename = "ResourcePolicy")]
+    #[garde(length(min = 1, max = 20480))]
+    resource_policy: String,
+
+    #[serde(rename = "SecretId")]
+    #[garde(dive)]
+    secret_id: Option<SecretId>,
+}
+
+#[derive(Serialize)]
+pub struct ValidateResourcePolicyResponse {
+    #[serde(rename = "PolicyValidationPassed")]
+    policy_validation_passed: bool,
+    #[serde(rename = "ValidationErrors")]
+    validation_errors: Vec<ValidationErrorsEntry>,
+}
+
+#[derive(Serialize)]
+pub struct ValidationErrorsEntry {
+    #[serde(rename = "CheckName")]
+    check_name: String,
+    #[serde(rename = "ErrorMessage")]
+    error_message: String,
+}
+
+impl Handler for ValidateResourcePolicyHandler {
+    type Request = ValidateResourcePolicyRequest;
+    type Response = ValidateResourcePolicyResponse;
+
+    fn secret_id(request: &Self::Request) -> Option<&str> {
+        request
+            .secret_id
+            .as_ref()
+            .map(|SecretId(secret_id)| secret_id.as_str())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn handle(
+        &self,
+        db: &DbPool,
+        context: &RequestContext,
+        request: Self::Request,
+    ) -> Result<Self::Response, Response> {
+        let ValidateResourcePolicyRequest {
+            resource_policy,
+            secret_id,
+        } = request;
+
+        // Policy is being validated against an existing secret
+        if let Some(SecretId(secret_id)) = secret_id {
+            get_secret_latest_version(db, &context.caller.region, &secret_id)
+                .await
+                //
+                .map_err(|error| {
+                    tracing::error!(?error, "failed to get secret");
+                    AwsErrorResponse(InternalServiceError).into_response()
+                })?
+                //
+                .ok_or_else(|| AwsErrorResponse(ResourceNotFoundException).into_response())?;
+        }
+
+        let validation_errors: Vec<ValidationErrorsEntry> = match validate_policy(&resource_policy)
+        {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .map(|error| ValidationErrorsEntry {
+                    check_name: error.check_name.to_string(),
+                    error_message: error.message,
+                })
+                .collect(),
+        };
+
+        Ok(ValidateResourcePolicyResponse {
+            policy_validation_passed: validation_errors.is_empty(),
+            validation_errors,
+        })
+    }
+}
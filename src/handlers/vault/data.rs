@@ -0,0 +1,181 @@
+use crate::{
+    database::{DbPool, secrets::update_secret_version_deleted},
+    handlers::vault::{
+        VaultState,
+        error::VaultError,
+        get_kv_secret, handle_request,
+        models::{ReadData, ReadQuery, VaultResponse, VersionMetadata, WriteRequest},
+        parse_request,
+    },
+};
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+
+/// Handle requests to `/data/{path}`
+pub async fn handle_data(
+    State(state): State<Arc<VaultState>>,
+    Extension(db): Extension<DbPool>,
+    method: Method,
+    Path(path): Path<String>,
+    Query(query): Query<ReadQuery>,
+    body: Bytes,
+) -> Result<Response, VaultError> {
+    match method {
+        Method::GET => read_secret(&state, &db, &path, query).await,
+        Method::POST | Method::PUT => write_secret(&state, &db, &path, &body).await,
+        Method::DELETE => delete_latest_version(&state, &db, &path).await,
+        _ => Err(VaultError::UnsupportedOperation),
+    }
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#read-secret-version
+#[tracing::instrument(skip_all, fields(path))]
+async fn read_secret(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+    query: ReadQuery,
+) -> Result<Response, VaultError> {
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::NotFound)?;
+
+    let number = match query.version {
+        Some(0) | None => secret.current_version,
+        Some(value) => value,
+    };
+
+    let version = secret.version(number).ok_or(VaultError::NotFound)?;
+    let metadata = VersionMetadata::from_version(version);
+
+    // Deleted versions report their metadata without the data
+    if version.deleted_at.is_some() {
+        let response = VaultResponse::new(ReadData {
+            data: None,
+            metadata,
+        });
+        return Ok((StatusCode::NOT_FOUND, Json(response)).into_response());
+    }
+
+    let response = handle_request(
+        state,
+        db,
+        "secretsmanager.GetSecretValue",
+        json!({ "SecretId": secret.arn, "VersionId": version.version_id }),
+    )
+    .await?;
+
+    let data = response["SecretString"]
+        .as_str()
+        .and_then(|value| serde_json::from_str::<Map<String, Value>>(value).ok())
+        .ok_or_else(|| {
+            VaultError::InvalidRequest("secret value is not a JSON object".to_string())
+        })?;
+
+    let response = VaultResponse::new(ReadData {
+        data: Some(data),
+        metadata,
+    });
+    Ok(Json(response).into_response())
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#create-update-secret
+#[tracing::instrument(skip_all, fields(path))]
+async fn write_secret(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+    body: &[u8],
+) -> Result<Response, VaultError> {
+    let request: WriteRequest = parse_request(body)?;
+
+    let secret = get_kv_secret(db, &state.caller, path).await?;
+
+    if let Some(cas) = request.options.cas {
+        let current_version = secret.as_ref().map_or(0, |secret| secret.current_version);
+        if cas != current_version {
+            return Err(VaultError::InvalidRequest(
+                "check-and-set parameter did not match the current version".to_string(),
+            ));
+        }
+    }
+
+    let secret_string = serde_json::to_string(&request.data).map_err(|error| {
+        tracing::error!(?error, "failed to serialize secret data");
+        VaultError::Internal
+    })?;
+
+    let response = match &secret {
+        Some(secret) => {
+            handle_request(
+                state,
+                db,
+                "secretsmanager.PutSecretValue",
+                json!({ "SecretId": secret.arn, "SecretString": secret_string }),
+            )
+            .await?
+        }
+        None => {
+            handle_request(
+                state,
+                db,
+                "secretsmanager.CreateSecret",
+                json!({ "Name": path, "SecretString": secret_string }),
+            )
+            .await?
+        }
+    };
+
+    let version_id = response["VersionId"].as_str().ok_or_else(|| {
+        tracing::error!("secret write response missing version id");
+        VaultError::Internal
+    })?;
+
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::Internal)?;
+
+    let version = secret
+        .versions
+        .iter()
+        .find(|version| version.version_id == version_id)
+        .ok_or(VaultError::Internal)?;
+
+    let response = VaultResponse::new(VersionMetadata::from_version(version));
+    Ok(Json(response).into_response())
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#delete-latest-version-of-secret
+#[tracing::instrument(skip_all, fields(path))]
+async fn delete_latest_version(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+) -> Result<Response, VaultError> {
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::NotFound)?;
+
+    let version = secret
+        .version(secret.current_version)
+        .ok_or(VaultError::NotFound)?;
+
+    if version.deleted_at.is_none()
+        && let Err(error) =
+            update_secret_version_deleted(db, &secret.arn, &version.version_id, Some(Utc::now()))
+                .await
+    {
+        tracing::error!(?error, "failed to delete secret version");
+        return Err(VaultError::Internal);
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
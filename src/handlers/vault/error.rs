@@ -0,0 +1,64 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+
+/// Errors returned in the Vault `{"errors": [...]}` format
+#[derive(Debug)]
+pub enum VaultError {
+    /// Nothing exists at the requested path
+    NotFound,
+    /// Missing or invalid token, or the request was not authorized
+    PermissionDenied,
+    /// The request was invalid
+    InvalidRequest(String),
+    /// The HTTP method is not supported for the path
+    UnsupportedOperation,
+    /// Unexpected server error
+    Internal,
+}
+
+impl VaultError {
+    /// Convert an AWS JSON error response with the `status` and `body` into
+    /// its Vault equivalent
+    pub fn from_aws_error(status: StatusCode, body: &Value) -> Self {
+        if status.is_server_error() {
+            return VaultError::Internal;
+        }
+
+        match body["__type"].as_str() {
+            Some("ResourceNotFoundException") => VaultError::NotFound,
+            Some("AccessDeniedException") => VaultError::PermissionDenied,
+            _ => VaultError::InvalidRequest(
+                body["message"]
+                    .as_str()
+                    .unwrap_or("invalid request")
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl IntoResponse for VaultError {
+    fn into_response(self) -> Response {
+        let (status, errors) = match self {
+            VaultError::NotFound => (StatusCode::NOT_FOUND, vec![]),
+            VaultError::PermissionDenied => {
+                (StatusCode::FORBIDDEN, vec!["permission denied".to_string()])
+            }
+            VaultError::InvalidRequest(message) => (StatusCode::BAD_REQUEST, vec![message]),
+            VaultError::UnsupportedOperation => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                vec!["unsupported operation".to_string()],
+            ),
+            VaultError::Internal => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                vec!["internal error".to_string()],
+            ),
+        };
+
+        (status, Json(json!({ "errors": errors }))).into_response()
+    }
+}
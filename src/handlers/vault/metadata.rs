@@ -0,0 +1,126 @@
+use crate::{
+    database::{DbPool, secrets::get_secret_names_by_prefix},
+    handlers::vault::{
+        VaultState,
+        error::VaultError,
+        get_kv_secret, handle_request,
+        models::{ListData, ListQuery, SecretMetadata, VaultResponse},
+    },
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Handle requests to the `/metadata` root, only listing is supported
+pub async fn handle_root(
+    State(state): State<Arc<VaultState>>,
+    Extension(db): Extension<DbPool>,
+    method: Method,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, VaultError> {
+    if !is_list_request(&method, &query) {
+        return Err(VaultError::UnsupportedOperation);
+    }
+
+    list_secrets(&state, &db, "").await
+}
+
+/// Handle requests to `/metadata/{path}`
+pub async fn handle_metadata(
+    State(state): State<Arc<VaultState>>,
+    Extension(db): Extension<DbPool>,
+    method: Method,
+    Path(path): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, VaultError> {
+    if is_list_request(&method, &query) {
+        return list_secrets(&state, &db, &path).await;
+    }
+
+    match method {
+        Method::GET => read_metadata(&state, &db, &path).await,
+        Method::DELETE => delete_metadata(&state, &db, &path).await,
+        _ => Err(VaultError::UnsupportedOperation),
+    }
+}
+
+/// Vault clients list using either the LIST method or GET with `?list=true`
+fn is_list_request(method: &Method, query: &ListQuery) -> bool {
+    method.as_str() == "LIST" || (method == Method::GET && query.list.as_deref() == Some("true"))
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#list-secrets
+#[tracing::instrument(skip_all, fields(path))]
+async fn list_secrets(state: &VaultState, db: &DbPool, path: &str) -> Result<Response, VaultError> {
+    let prefix = match path.trim_end_matches('/') {
+        "" => String::new(),
+        value => format!("{value}/"),
+    };
+
+    let names = get_secret_names_by_prefix(db, &state.caller.region, &prefix)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to get secret names");
+            VaultError::Internal
+        })?;
+
+    // Only the next path segment is listed, nested paths are listed as folders
+    let mut keys: Vec<String> = names
+        .iter()
+        .filter_map(|name| name.strip_prefix(&prefix))
+        .filter(|name| !name.is_empty())
+        .map(|name| match name.split_once('/') {
+            Some((folder, _)) => format!("{folder}/"),
+            None => name.to_string(),
+        })
+        .collect();
+    keys.dedup();
+
+    if keys.is_empty() {
+        return Err(VaultError::NotFound);
+    }
+
+    Ok(Json(VaultResponse::new(ListData { keys })).into_response())
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#read-secret-metadata
+#[tracing::instrument(skip_all, fields(path))]
+async fn read_metadata(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+) -> Result<Response, VaultError> {
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::NotFound)?;
+
+    let response = VaultResponse::new(SecretMetadata::from_secret(&secret));
+    Ok(Json(response).into_response())
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#delete-metadata-and-all-versions
+#[tracing::instrument(skip_all, fields(path))]
+async fn delete_metadata(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+) -> Result<Response, VaultError> {
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::NotFound)?;
+
+    handle_request(
+        state,
+        db,
+        "secretsmanager.DeleteSecret",
+        json!({ "SecretId": secret.arn, "ForceDeleteWithoutRecovery": true }),
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
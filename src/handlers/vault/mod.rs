@@ -0,0 +1,249 @@
+//! HashiCorp Vault KV v2 compatible endpoint, Vault paths map onto secret names
+//! and KV versions onto the versions of the secret numbered from oldest to newest
+//!
+//! https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2
+
+use crate::{
+    database::{
+        DbPool,
+        secrets::{get_secret_latest_version, get_secret_versions},
+    },
+    handlers::{HandlerRouterService, vault::error::VaultError},
+    middleware::{
+        aws_sig_v4::{CallerIdentity, RequestContext},
+        request_id::RequestId,
+    },
+};
+use axum::{
+    Router,
+    extract::{Request, State},
+    middleware::{Next, from_fn_with_state},
+    response::Response,
+    routing::{any, post},
+};
+use chrono::{DateTime, Utc};
+use http_body_util::BodyExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+
+mod data;
+mod error;
+mod metadata;
+mod models;
+mod versions;
+
+/// Header containing the token that authenticates requests
+const TOKEN_HEADER: &str = "x-vault-token";
+
+/// Access key ID reported for requests made through the Vault endpoint
+const ACCESS_KEY_ID: &str = "vault";
+
+/// Version stage of the version that is the current KV version
+const CURRENT_VERSION_STAGE: &str = "AWSCURRENT";
+
+/// Options for the Vault endpoint
+pub struct VaultOptions {
+    /// Token requests must provide in the `X-Vault-Token` header
+    pub token: String,
+    /// Region secrets are stored in
+    pub region: String,
+    /// Account requests are made as, requests are made as the root of the account
+    pub account_id: String,
+    /// Path the KV v2 secrets engine is mounted at
+    pub mount: String,
+}
+
+struct VaultState {
+    handlers: HandlerRouterService,
+    token: String,
+    caller: CallerIdentity,
+}
+
+/// Create the router for the Vault endpoint, requests are handled by the
+/// `handlers`
+pub fn vault_router(handlers: HandlerRouterService, options: VaultOptions) -> Router {
+    let state = Arc::new(VaultState {
+        handlers,
+        token: options.token,
+        caller: CallerIdentity::account_root(ACCESS_KEY_ID, options.account_id, options.region),
+    });
+
+    let mount = options.mount.trim_matches('/');
+
+    Router::new()
+        .route(
+            &format!("/v1/{mount}/data/{{*path}}"),
+            any(data::handle_data),
+        )
+        .route(&format!("/v1/{mount}/metadata"), any(metadata::handle_root))
+        .route(
+            &format!("/v1/{mount}/metadata/"),
+            any(metadata::handle_root),
+        )
+        .route(
+            &format!("/v1/{mount}/metadata/{{*path}}"),
+            any(metadata::handle_metadata),
+        )
+        .route(
+            &format!("/v1/{mount}/delete/{{*path}}"),
+            post(versions::delete_versions).put(versions::delete_versions),
+        )
+        .route(
+            &format!("/v1/{mount}/undelete/{{*path}}"),
+            post(versions::undelete_versions).put(versions::undelete_versions),
+        )
+        .route_layer(from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// Middleware rejecting requests without the expected `X-Vault-Token`
+async fn require_token(
+    State(state): State<Arc<VaultState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, VaultError> {
+    let token = request
+        .headers()
+        .get(TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    if token != Some(state.token.as_str()) {
+        return Err(VaultError::PermissionDenied);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Secret stored at a KV path
+struct KvSecret {
+    arn: String,
+    /// Versions ordered from oldest to newest
+    versions: Vec<KvVersion>,
+    /// Number of the version with the `AWSCURRENT` stage
+    current_version: u64,
+}
+
+struct KvVersion {
+    number: u64,
+    version_id: String,
+    created_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl KvSecret {
+    fn version(&self, number: u64) -> Option<&KvVersion> {
+        self.versions
+            .iter()
+            .find(|version| version.number == number)
+    }
+}
+
+/// Get the secret stored at the KV `path`, secrets scheduled for deletion
+/// are treated as missing
+async fn get_kv_secret(
+    db: &DbPool,
+    caller: &CallerIdentity,
+    path: &str,
+) -> Result<Option<KvSecret>, VaultError> {
+    let secret = get_secret_latest_version(db, &caller.region, path)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to get secret");
+            VaultError::Internal
+        })?;
+
+    let secret = match secret {
+        Some(value) if value.scheduled_delete_at.is_none() => value,
+        _ => return Ok(None),
+    };
+
+    let versions = get_secret_versions(db, &secret.arn)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to get secret versions");
+            VaultError::Internal
+        })?;
+
+    let mut current_version = 0;
+
+    // Versions are provided newest first
+    let versions = versions
+        .into_iter()
+        .rev()
+        .enumerate()
+        .map(|(index, version)| {
+            let number = index as u64 + 1;
+
+            if version
+                .version_stages
+                .iter()
+                .any(|stage| stage == CURRENT_VERSION_STAGE)
+            {
+                current_version = number;
+            }
+
+            KvVersion {
+                number,
+                version_id: version.version_id,
+                created_at: version.created_at,
+                deleted_at: version.deleted_at,
+            }
+        })
+        .collect();
+
+    Ok(Some(KvSecret {
+        arn: secret.arn,
+        versions,
+        current_version,
+    }))
+}
+
+/// Perform the `request` through the handler for the `target`, errors from
+/// the handler are converted into their Vault equivalent
+async fn handle_request(
+    state: &VaultState,
+    db: &DbPool,
+    target: &str,
+    request: Value,
+) -> Result<Value, VaultError> {
+    let request = serde_json::to_vec(&request).map_err(|error| {
+        tracing::error!(?error, "failed to serialize request");
+        VaultError::Internal
+    })?;
+
+    let context = RequestContext::new(state.caller.clone(), RequestId::generate(), None);
+
+    let response = state
+        .handlers
+        .router
+        .handle_request(db, &context, target, &request)
+        .await;
+
+    let status = response.status();
+
+    let body = response
+        .into_body()
+        .collect()
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, "failed to collect response bytes");
+            VaultError::Internal
+        })?
+        .to_bytes();
+
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    if status.is_success() {
+        return Ok(body);
+    }
+
+    Err(VaultError::from_aws_error(status, &body))
+}
+
+/// Parse the JSON request `body`, Vault clients don't always provide a
+/// content type so the body is parsed regardless of the headers
+fn parse_request<T: DeserializeOwned>(body: &[u8]) -> Result<T, VaultError> {
+    serde_json::from_slice(body)
+        .map_err(|_| VaultError::InvalidRequest("failed to parse JSON input".to_string()))
+}
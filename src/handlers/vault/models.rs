@@ -0,0 +1,160 @@
+use crate::handlers::vault::{KvSecret, KvVersion};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Envelope of successful Vault responses
+#[derive(Serialize)]
+pub struct VaultResponse<T> {
+    pub request_id: String,
+    pub lease_id: &'static str,
+    pub renewable: bool,
+    pub lease_duration: u64,
+    pub data: T,
+    pub wrap_info: Option<()>,
+    pub warnings: Option<()>,
+    pub auth: Option<()>,
+}
+
+impl<T> VaultResponse<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            lease_id: "",
+            renewable: false,
+            lease_duration: 0,
+            data,
+            wrap_info: None,
+            warnings: None,
+            auth: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct VersionMetadata {
+    pub created_time: String,
+    pub custom_metadata: Option<()>,
+    pub deletion_time: String,
+    pub destroyed: bool,
+    pub version: u64,
+}
+
+impl VersionMetadata {
+    pub fn from_version(version: &KvVersion) -> Self {
+        Self {
+            created_time: format_time(version.created_at),
+            custom_metadata: None,
+            deletion_time: version.deleted_at.map(format_time).unwrap_or_default(),
+            destroyed: false,
+            version: version.number,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ReadData {
+    pub data: Option<Map<String, Value>>,
+    pub metadata: VersionMetadata,
+}
+
+#[derive(Serialize)]
+pub struct SecretMetadata {
+    pub cas_required: bool,
+    pub created_time: String,
+    pub current_version: u64,
+    pub custom_metadata: Option<()>,
+    pub delete_version_after: &'static str,
+    pub max_versions: u64,
+    pub oldest_version: u64,
+    pub updated_time: String,
+    pub versions: BTreeMap<String, SecretMetadataVersion>,
+}
+
+#[derive(Serialize)]
+pub struct SecretMetadataVersion {
+    pub created_time: String,
+    pub deletion_time: String,
+    pub destroyed: bool,
+}
+
+impl SecretMetadata {
+    pub fn from_secret(secret: &KvSecret) -> Self {
+        let created_time = secret
+            .versions
+            .first()
+            .map(|version| format_time(version.created_at))
+            .unwrap_or_default();
+        let updated_time = secret
+            .versions
+            .last()
+            .map(|version| format_time(version.created_at))
+            .unwrap_or_default();
+
+        Self {
+            cas_required: false,
+            created_time,
+            current_version: secret.current_version,
+            custom_metadata: None,
+            delete_version_after: "0s",
+            max_versions: 0,
+            oldest_version: secret.versions.first().map_or(0, |version| version.number),
+            updated_time,
+            versions: secret
+                .versions
+                .iter()
+                .map(|version| {
+                    (
+                        version.number.to_string(),
+                        SecretMetadataVersion {
+                            created_time: format_time(version.created_at),
+                            deletion_time: version.deleted_at.map(format_time).unwrap_or_default(),
+                            destroyed: false,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ListData {
+    pub keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WriteRequest {
+    pub data: Map<String, Value>,
+    #[serde(default)]
+    pub options: WriteOptions,
+}
+
+#[derive(Default, Deserialize)]
+pub struct WriteOptions {
+    /// Version the current version must match for the write to be allowed,
+    /// zero only allows writing when the secret does not exist
+    pub cas: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct VersionsRequest {
+    pub versions: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReadQuery {
+    /// Version to read, zero or none reads the current version
+    pub version: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    pub list: Option<String>,
+}
+
+/// Format a timestamp the way Vault does
+pub fn format_time(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
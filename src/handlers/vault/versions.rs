@@ -0,0 +1,70 @@
+use crate::{
+    database::{DbPool, secrets::update_secret_version_deleted},
+    handlers::vault::{
+        VaultState, error::VaultError, get_kv_secret, models::VersionsRequest, parse_request,
+    },
+};
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use std::sync::Arc;
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#delete-secret-versions
+#[tracing::instrument(skip_all, fields(path))]
+pub async fn delete_versions(
+    State(state): State<Arc<VaultState>>,
+    Extension(db): Extension<DbPool>,
+    Path(path): Path<String>,
+    body: Bytes,
+) -> Result<StatusCode, VaultError> {
+    let request: VersionsRequest = parse_request(&body)?;
+    set_versions_deleted(&state, &db, &path, &request.versions, true).await
+}
+
+// https://developer.hashicorp.com/vault/api-docs/secret/kv/kv-v2#undelete-secret-versions
+#[tracing::instrument(skip_all, fields(path))]
+pub async fn undelete_versions(
+    State(state): State<Arc<VaultState>>,
+    Extension(db): Extension<DbPool>,
+    Path(path): Path<String>,
+    body: Bytes,
+) -> Result<StatusCode, VaultError> {
+    let request: VersionsRequest = parse_request(&body)?;
+    set_versions_deleted(&state, &db, &path, &request.versions, false).await
+}
+
+/// Soft delete or restore the `versions` of the secret at `path`, versions
+/// that don't exist are ignored
+async fn set_versions_deleted(
+    state: &VaultState,
+    db: &DbPool,
+    path: &str,
+    versions: &[u64],
+    deleted: bool,
+) -> Result<StatusCode, VaultError> {
+    let secret = get_kv_secret(db, &state.caller, path)
+        .await?
+        .ok_or(VaultError::NotFound)?;
+
+    let deleted_at = deleted.then(Utc::now);
+
+    for version in versions.iter().filter_map(|number| secret.version(*number)) {
+        // Keep the original deletion date of versions already deleted
+        if version.deleted_at.is_some() == deleted {
+            continue;
+        }
+
+        if let Err(error) =
+            update_secret_version_deleted(db, &secret.arn, &version.version_id, deleted_at).await
+        {
+            tracing::error!(?error, "failed to update secret version deletion");
+            return Err(VaultError::Internal);
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,301 @@
+//! Local stand-in for the symmetric encryption operations of KMS, keys are
+//! stored within the database and used to envelope encrypt secret values
+
+use crate::database::{
+    DbErr, DbExecutor,
+    kms::{KEY_STATE_DISABLED, KEY_STATE_ENABLED, StoredKmsKey, get_kms_key, get_kms_key_by_id},
+    secrets::StoredSecret,
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rand::Rng;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Length of the key material for symmetric keys and data keys
+pub const KEY_MATERIAL_LENGTH: usize = 32;
+
+/// Length of a key ID (UUID) at the start of a ciphertext blob
+const KEY_ID_LENGTH: usize = 36;
+
+/// Encryption context bound to a ciphertext, the same context must be provided
+/// to decrypt the ciphertext
+pub type EncryptionContext = BTreeMap<String, String>;
+
+#[derive(Debug, Error)]
+pub enum KmsError {
+    #[error(transparent)]
+    Db(#[from] DbErr),
+
+    #[error("key not found")]
+    KeyNotFound,
+
+    #[error("key is disabled")]
+    KeyDisabled,
+
+    #[error("key is pending deletion")]
+    KeyPendingDeletion,
+
+    #[error("invalid ciphertext")]
+    InvalidCiphertext,
+}
+
+/// Secret value ready to be stored, the value is encrypted when the
+/// `encrypted_data_key` is present
+pub struct SecretValue {
+    pub secret_string: Option<String>,
+    pub secret_binary: Option<String>,
+    pub encrypted_data_key: Option<String>,
+}
+
+/// Generate random key material of `length` bytes
+pub fn generate_key_material(length: usize) -> Vec<u8> {
+    let mut key_material = vec![0u8; length];
+    rand::rng().fill(key_material.as_mut_slice());
+    key_material
+}
+
+/// Ensure the `key` is in a state that allows it to be used for
+/// cryptographic operations
+pub fn ensure_key_enabled(key: &StoredKmsKey) -> Result<(), KmsError> {
+    match key.key_state.as_str() {
+        KEY_STATE_ENABLED => Ok(()),
+        KEY_STATE_DISABLED => Err(KmsError::KeyDisabled),
+        _ => Err(KmsError::KeyPendingDeletion),
+    }
+}
+
+/// Find the key `key_id` in `region` ensuring that it can be used for
+/// cryptographic operations, the `key_id` can be a key ID, key ARN, alias
+/// name or alias ARN
+pub async fn find_enabled_key(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    key_id: &str,
+) -> Result<StoredKmsKey, KmsError> {
+    let key = get_kms_key(db, region, key_id)
+        .await?
+        .ok_or(KmsError::KeyNotFound)?;
+
+    ensure_key_enabled(&key)?;
+    Ok(key)
+}
+
+/// Encrypt the `plaintext` using the `key`, the ciphertext blob starts with the
+/// key ID followed by the nonce and the AES-256-GCM sealed `plaintext`
+pub fn encrypt(
+    key: &StoredKmsKey,
+    plaintext: &[u8],
+    context: &EncryptionContext,
+) -> Result<Vec<u8>, KmsError> {
+    ensure_key_enabled(key)?;
+
+    let aad = encryption_context_aad(context);
+    let sealed = seal(&key.key_material, &aad, plaintext)?;
+
+    let mut ciphertext_blob = Vec::with_capacity(KEY_ID_LENGTH + sealed.len());
+    ciphertext_blob.extend_from_slice(key.key_id.as_bytes());
+    ciphertext_blob.extend_from_slice(&sealed);
+    Ok(ciphertext_blob)
+}
+
+/// Decrypt a `ciphertext_blob` created by [encrypt], the key is found using
+/// the key ID stored in the blob
+pub async fn decrypt(
+    db: impl DbExecutor<'_>,
+    ciphertext_blob: &[u8],
+    context: &EncryptionContext,
+) -> Result<(StoredKmsKey, Vec<u8>), KmsError> {
+    if ciphertext_blob.len() < KEY_ID_LENGTH {
+        return Err(KmsError::InvalidCiphertext);
+    }
+
+    let (key_id, sealed) = ciphertext_blob.split_at(KEY_ID_LENGTH);
+    let key_id = std::str::from_utf8(key_id).map_err(|_| KmsError::InvalidCiphertext)?;
+
+    let key = get_kms_key_by_id(db, key_id)
+        .await?
+        .ok_or(KmsError::InvalidCiphertext)?;
+
+    ensure_key_enabled(&key)?;
+
+    let aad = encryption_context_aad(context);
+    let plaintext = open(&key.key_material, &aad, sealed)?;
+    Ok((key, plaintext))
+}
+
+/// Encrypt a secret value using a new data key that is encrypted with the KMS
+/// key `kms_key_id` in `region`, the value is returned as is when the secret
+/// does not use a KMS key
+pub async fn encrypt_secret_value(
+    db: impl DbExecutor<'_>,
+    region: &str,
+    kms_key_id: Option<&str>,
+    secret_string: Option<String>,
+    secret_binary: Option<String>,
+) -> Result<SecretValue, KmsError> {
+    let kms_key_id = match kms_key_id {
+        Some(value) => value,
+        None => {
+            return Ok(SecretValue {
+                secret_string,
+                secret_binary,
+                encrypted_data_key: None,
+            });
+        }
+    };
+
+    let key = find_enabled_key(db, region, kms_key_id).await?;
+
+    let data_key = generate_key_material(KEY_MATERIAL_LENGTH);
+    let encrypted_data_key = encrypt(&key, &data_key, &EncryptionContext::new())?;
+
+    let encrypt_value = |value: String| -> Result<String, KmsError> {
+        let sealed = seal(&data_key, &[], value.as_bytes())?;
+        Ok(BASE64_STANDARD.encode(sealed))
+    };
+
+    Ok(SecretValue {
+        secret_string: secret_string.map(encrypt_value).transpose()?,
+        secret_binary: secret_binary.map(encrypt_value).transpose()?,
+        encrypted_data_key: Some(BASE64_STANDARD.encode(encrypted_data_key)),
+    })
+}
+
+/// Decrypt the `secret_string` and `secret_binary` of a secret version in place
+/// when the version was encrypted using the `encrypted_data_key`
+pub async fn decrypt_secret_value(
+    db: impl DbExecutor<'_>,
+    encrypted_data_key: Option<&str>,
+    secret_string: &mut Option<String>,
+    secret_binary: &mut Option<String>,
+) -> Result<(), KmsError> {
+    let encrypted_data_key = match encrypted_data_key {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    let encrypted_data_key = BASE64_STANDARD
+        .decode(encrypted_data_key)
+        .map_err(|_| KmsError::InvalidCiphertext)?;
+
+    let (_key, data_key) = decrypt(db, &encrypted_data_key, &EncryptionContext::new()).await?;
+
+    let decrypt_value = |value: &mut String| -> Result<(), KmsError> {
+        let sealed = BASE64_STANDARD
+            .decode(value.as_bytes())
+            .map_err(|_| KmsError::InvalidCiphertext)?;
+        let plaintext = open(&data_key, &[], &sealed)?;
+        *value = String::from_utf8(plaintext).map_err(|_| KmsError::InvalidCiphertext)?;
+        Ok(())
+    };
+
+    if let Some(value) = secret_string {
+        decrypt_value(value)?;
+    }
+
+    if let Some(value) = secret_binary {
+        decrypt_value(value)?;
+    }
+
+    Ok(())
+}
+
+/// Decrypt the value of the `secret` version in place, see [decrypt_secret_value]
+pub async fn decrypt_stored_secret(
+    db: impl DbExecutor<'_>,
+    secret: &mut StoredSecret,
+) -> Result<(), KmsError> {
+    decrypt_secret_value(
+        db,
+        secret.encrypted_data_key.as_deref(),
+        &mut secret.secret_string,
+        &mut secret.secret_binary,
+    )
+    .await
+}
+
+/// Additional authenticated data for the encryption `context`, the context is
+/// serialized in key order so the same context always produces the same data
+fn encryption_context_aad(context: &EncryptionContext) -> Vec<u8> {
+    if context.is_empty() {
+        return Vec::new();
+    }
+
+    serde_json::to_vec(context).unwrap_or_default()
+}
+
+/// Create a cipher for the 256-bit `key_material`
+fn create_cipher(key_material: &[u8]) -> Result<LessSafeKey, KmsError> {
+    UnboundKey::new(&AES_256_GCM, key_material)
+        .map(LessSafeKey::new)
+        .map_err(|_| KmsError::InvalidCiphertext)
+}
+
+/// Seal the `plaintext` with AES-256-GCM using a random nonce, the nonce is
+/// prepended to the sealed output
+fn seal(key_material: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, KmsError> {
+    let cipher = create_cipher(key_material)?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill(&mut nonce);
+
+    let mut in_out = plaintext.to_vec();
+    cipher
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| KmsError::InvalidCiphertext)?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + in_out.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&in_out);
+    Ok(sealed)
+}
+
+/// Open a value sealed by [seal]
+fn open(key_material: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, KmsError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(KmsError::InvalidCiphertext);
+    }
+
+    let cipher = create_cipher(key_material)?;
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| KmsError::InvalidCiphertext)?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = cipher
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| KmsError::InvalidCiphertext)?;
+
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let key_material = generate_key_material(KEY_MATERIAL_LENGTH);
+        let sealed = seal(&key_material, b"context", b"value").unwrap();
+
+        assert_eq!(open(&key_material, b"context", &sealed).unwrap(), b"value");
+        assert!(open(&key_material, b"other", &sealed).is_err());
+
+        let other_key_material = generate_key_material(KEY_MATERIAL_LENGTH);
+        assert!(open(&other_key_material, b"context", &sealed).is_err());
+    }
+
+    #[test]
+    fn test_encryption_context_aad() {
+        let mut context = EncryptionContext::new();
+        assert!(encryption_context_aad(&context).is_empty());
+
+        context.insert("b".to_string(), "2".to_string());
+        context.insert("a".to_string(), "1".to_string());
+        assert_eq!(encryption_context_aad(&context), br#"{"a":"1","b":"2"}"#);
+    }
+}
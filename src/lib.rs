@@ -1,4 +1,8 @@
 pub mod database;
 pub mod handlers;
+pub mod kms;
 pub mod middleware;
+pub mod policy;
+pub mod replication;
+pub mod rotation;
 mod utils;
@@ -2,26 +2,48 @@
 
 use crate::{
     background::perform_background_tasks,
-    config::Config,
-    middleware::aws_sig_v4::{AwsCredential, AwsSigV4AuthLayer},
+    cli::Args,
+    config::{Config, LambdaExtensionConfig, VaultConfig},
+    database::{DbPool, access_keys::get_access_keys},
+    handlers::{
+        HandlerRouterService,
+        fault_injection::{FaultInjectionLayer, FaultInjector},
+        lambda_extension::{LambdaExtensionOptions, lambda_extension_router},
+        quotas::Quotas,
+        vault::{VaultOptions, vault_router},
+    },
+    middleware::{
+        aws_sig_v4::{AwsCredential, AwsCredentials, AwsSigV4AuthLayer},
+        request_id::{RequestIdLayer, make_request_span},
+    },
+    rotation::function::RotationFunctions,
 };
 use axum::{Extension, Router, http::StatusCode, routing::post_service};
 use axum_server::tls_rustls::RustlsConfig;
-use std::{error::Error, net::SocketAddr};
+use clap::Parser;
+use std::{error::Error, net::SocketAddr, sync::Arc};
+use tower::Layer;
 use tower_http::trace::TraceLayer;
 
 pub mod database;
 pub mod middleware;
 
 mod background;
+mod cli;
 mod config;
 mod handlers;
+mod kms;
 mod logging;
+mod policy;
+mod replication;
+mod rotation;
 mod utils;
 
 fn main() -> Result<(), Box<dyn Error>> {
     _ = dotenvy::dotenv();
 
+    let args = Args::parse();
+
     logging::init_logging();
 
     tokio::runtime::Builder::new_current_thread()
@@ -29,6 +51,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         .build()
         .expect("Failed building the Runtime")
         .block_on(async move {
+            if let Some(command) = args.command {
+                if let Err(error) = cli::run(command).await {
+                    tracing::error!(?error, message = %error, "error running command");
+                    return Err(error);
+                }
+
+                return Ok(());
+            }
+
             if let Err(error) = server().await {
                 tracing::error!(?error, message = %error, "error running server");
                 return Err(error);
@@ -47,29 +78,109 @@ async fn server() -> Result<(), Box<dyn Error>> {
         }
     };
 
-    let credentials = AwsCredential::new(config.access_key_id, config.access_key_secret);
-
     // Setup database
     let db = database::create_database(config.encryption_key, config.database_path).await?;
 
+    // Load the access keys
+    let credentials = match load_credentials(
+        &db,
+        config.access_key,
+        config.access_key_principal_arn,
+        config.access_keys_path,
+    )
+    .await
+    {
+        Ok(value) => value,
+        Err(error) => {
+            tracing::error!(?error, "failed to load access keys");
+            return Err(error);
+        }
+    };
+
+    // Load the local rotation functions
+    let rotation_functions = match config.rotation_functions_path {
+        Some(path) => match RotationFunctions::load(path).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to load rotation functions");
+                return Err(error.into());
+            }
+        },
+        None => RotationFunctions::default(),
+    };
+    let rotation_functions =
+        Arc::new(rotation_functions.with_timeout(config.rotation_function_timeout));
+
+    // Load the fault injection rules
+    let fault_injector = match config.fault_injection_rules_path {
+        Some(path) => match FaultInjector::load(path).await {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(?error, "failed to load fault injection rules");
+                return Err(error.into());
+            }
+        },
+        None => FaultInjector::default(),
+    };
+    let fault_injector = Arc::new(fault_injector);
+
     // Setup the handlers
-    let handlers = handlers::create_handlers();
+    let handlers = handlers::create_handlers(
+        rotation_functions.clone(),
+        fault_injector.clone(),
+        Quotas::new(config.strict_quotas),
+        config.enforce_resource_policies,
+    );
     let handlers_service = handlers.into_service();
 
+    // Setup the optional compatibility endpoints, each served on its own listener
+    let mut endpoints = Vec::new();
+
+    if let Some(lambda_extension) = config.lambda_extension {
+        let server_address = lambda_extension.server_address;
+        let app = create_lambda_extension_app(
+            handlers_service.clone(),
+            db.clone(),
+            lambda_extension,
+            config.account_id.clone(),
+        );
+        endpoints.push(("lambda extension", app, server_address));
+    }
+
+    if let Some(vault) = config.vault {
+        let server_address = vault.server_address;
+        let app = create_vault_app(
+            handlers_service.clone(),
+            db.clone(),
+            vault,
+            config.account_id.clone(),
+        );
+        endpoints.push(("vault", app, server_address));
+    }
+
+    // Faults are only injected into requests to the AWS compatible endpoint
+    let handlers_service = FaultInjectionLayer::new(fault_injector).layer(handlers_service);
+
     // Setup router
     let app = Router::new()
-        .route_service("/", post_service(handlers_service))
-        .layer(AwsSigV4AuthLayer::new(credentials))
+        .route_service("/", post_service(handlers_service.clone()))
+        // Smithy RPC v2 CBOR requests provide the operation in the path
+        .route_service(
+            "/service/{service}/operation/{operation}",
+            post_service(handlers_service),
+        )
+        .layer(AwsSigV4AuthLayer::new(credentials).account_id(&config.account_id))
         .route("/health", axum::routing::get(health))
         .layer(Extension(db.clone()))
-        .layer(TraceLayer::new_for_http());
+        .layer(TraceLayer::new_for_http().make_span_with(make_request_span))
+        .layer(RequestIdLayer);
 
     // Development mode CORS access for local browser testing
     #[cfg(debug_assertions)]
     let app = app.layer(tower_http::cors::CorsLayer::very_permissive());
 
     // Spawn the background task runner
-    tokio::spawn(perform_background_tasks(db.clone()));
+    tokio::spawn(perform_background_tasks(db.clone(), rotation_functions));
 
     let handle = axum_server::Handle::default();
 
@@ -84,22 +195,105 @@ async fn server() -> Result<(), Box<dyn Error>> {
 
     tracing::debug!("starting server on {}", config.server_address);
 
-    if config.use_https {
-        serve_https(
-            app,
-            handle,
-            config.server_address,
-            config.certificate_path,
-            config.private_key_path,
-        )
-        .await?;
-    } else {
-        serve_http(app, handle, config.server_address).await?;
-    }
+    let server = async {
+        if config.use_https {
+            serve_https(
+                app,
+                handle.clone(),
+                config.server_address,
+                config.certificate_path,
+                config.private_key_path,
+            )
+            .await
+        } else {
+            serve_http(app, handle.clone(), config.server_address).await
+        }
+    };
+
+    let endpoint_servers =
+        futures::future::try_join_all(endpoints.into_iter().map(|(name, app, server_address)| {
+            tracing::debug!("starting {name} server on {server_address}");
+            serve_http(app, handle.clone(), server_address)
+        }));
+
+    tokio::try_join!(server, endpoint_servers)?;
 
     Ok(())
 }
 
+/// Create the app for the Lambda extension compatible endpoint, the endpoint
+/// is always served over HTTP like the real extension
+fn create_lambda_extension_app(
+    handlers_service: HandlerRouterService,
+    db: DbPool,
+    config: LambdaExtensionConfig,
+    account_id: String,
+) -> Router {
+    let options = LambdaExtensionOptions {
+        token: config.token,
+        region: config.region,
+        account_id,
+        cache_ttl: config.cache_ttl,
+        cache_size: config.cache_size,
+    };
+
+    lambda_extension_router(handlers_service, options)
+        .layer(Extension(db))
+        .layer(TraceLayer::new_for_http())
+}
+
+/// Create the app for the Vault KV v2 compatible endpoint, served over HTTP
+fn create_vault_app(
+    handlers_service: HandlerRouterService,
+    db: DbPool,
+    config: VaultConfig,
+    account_id: String,
+) -> Router {
+    let options = VaultOptions {
+        token: config.token,
+        region: config.region,
+        account_id,
+        mount: config.mount,
+    };
+
+    vault_router(handlers_service, options)
+        .layer(Extension(db))
+        .layer(TraceLayer::new_for_http())
+}
+
+/// Load the access keys from the environment and the access keys file, access
+/// keys stored in the database are looked up when requests are made
+async fn load_credentials(
+    db: &DbPool,
+    access_key: Option<(String, String)>,
+    access_key_principal_arn: String,
+    access_keys_path: Option<String>,
+) -> Result<AwsCredentials, Box<dyn Error>> {
+    let mut credentials = match access_keys_path {
+        Some(path) => AwsCredentials::load(path).await?,
+        None => AwsCredentials::default(),
+    };
+
+    if let Some((access_key_id, access_key_secret)) = access_key {
+        credentials.insert(AwsCredential {
+            name: "default".to_string(),
+            access_key_id,
+            access_key_secret,
+            principal_arn: Some(access_key_principal_arn),
+            expires_at: None,
+            enabled: true,
+            policy: None,
+            source_policies: Vec::new(),
+        });
+    }
+
+    if credentials.is_empty() && get_access_keys(db).await?.is_empty() {
+        return Err("no access keys configured, specify SM_ACCESS_KEY_ID and SM_ACCESS_KEY_SECRET, SM_ACCESS_KEYS_PATH, or create an access key".into());
+    }
+
+    Ok(credentials)
+}
+
 /// Health check route
 async fn health() -> StatusCode {
     StatusCode::OK
@@ -131,7 +325,7 @@ async fn serve_https(
 
     axum_server::bind_rustls(server_address, config)
         .handle(handle)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await?;
 
     Ok(())
@@ -145,7 +339,7 @@ async fn serve_http(
 ) -> Result<(), Box<dyn Error>> {
     axum_server::bind(server_address)
         .handle(handle)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await?;
 
     Ok(())
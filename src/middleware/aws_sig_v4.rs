@@ -1,52 +1,326 @@
 use crate::{
-    handlers::error::{
-        AwsErrorResponse, IncompleteSignature, InvalidClientTokenId, InvalidRequestException,
-        MissingAuthenticationToken, SignatureDoesNotMatch,
+    database::{
+        DbPool,
+        access_keys::{StoredAccessKey, get_access_key, update_access_key_last_used},
+        session_credentials::{StoredSessionCredential, get_session_credential},
     },
+    handlers::{
+        error::{
+            AwsError, AwsErrorResponse, ExpiredTokenException, IncompleteSignature,
+            InternalServiceError, InvalidClientTokenId, InvalidRequestException,
+            MissingAuthenticationToken, SignatureDoesNotMatch,
+        },
+        models::Arn,
+    },
+    middleware::request_id::RequestId,
+    policy::validate_identity_policy,
     utils::{
+        arn::DEFAULT_ACCOUNT_ID,
         aws_sig_v4::{aws_sig_v4, create_canonical_request, parse_auth_header},
         date::{parse_amz_date, parse_http_date},
     },
 };
 use axum::{
     body::Body,
+    extract::ConnectInfo,
     http::{Request, header::AUTHORIZATION},
     response::{IntoResponse, Response},
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
 use http_body_util::BodyExt;
-use std::{mem::swap, sync::Arc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, mem::swap, net::SocketAddr, path::Path, str::FromStr, sync::Arc};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
 use tower::{Layer, Service};
+use tracing::Span;
 
 /// Credential for the [AwsSigV4AuthLayer] to allow access to
+#[derive(Debug, Clone, Deserialize)]
 pub struct AwsCredential {
-    access_key_id: String,
-    access_key_secret: String,
+    /// Name identifying the credential
+    pub name: String,
+    /// Access key ID from the credential scope of requests
+    pub access_key_id: String,
+    /// Secret used to sign requests
+    pub access_key_secret: String,
+    /// ARN of the principal that requests signed with this credential
+    /// are made as, defaults to a user named after the credential. The
+    /// account of the principal is the account requests are made in
+    #[serde(default)]
+    pub principal_arn: Option<String>,
+    /// Date after which the credential can no longer be used
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the credential can be used
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Identity policy restricting what requests signed with the credential
+    /// can do, unrestricted when not specified
+    #[serde(default)]
+    pub policy: Option<Value>,
+    /// Identity policies of the caller that created the session the
+    /// credential belongs to, requests must be allowed by each of them
+    #[serde(skip)]
+    pub source_policies: Vec<Value>,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 impl AwsCredential {
-    pub fn new(access_key_id: String, access_key_secret: String) -> Self {
+    /// Get the ARN of the principal requests signed with this credential
+    /// are made as, credentials without a principal are users within the
+    /// `default_account_id`
+    pub fn principal_arn(&self, default_account_id: &str) -> String {
+        match &self.principal_arn {
+            Some(value) => value.clone(),
+            None => default_principal_arn(default_account_id, &self.name),
+        }
+    }
+}
+
+/// Get the ARN of the principal for an access key named `name` within the
+/// `account_id` that doesn't specify its own principal ARN
+pub fn default_principal_arn(account_id: &str, name: &str) -> String {
+    Arn::new("iam", "", account_id, &format!("user/{name}")).to_string()
+}
+
+/// Get the ARN of the principal for a session named `session_name` of the
+/// assumed role `role_arn` (i.e arn:aws:sts::123456789012:assumed-role/name/session)
+pub fn assumed_role_arn(role_arn: &str, session_name: &str) -> String {
+    let (account_id, role_name) = match Arn::from_str(role_arn) {
+        Ok(arn) => {
+            let role_name = arn.resource_id().rsplit('/').next().unwrap_or_default();
+            (arn.account_id.clone(), role_name.to_string())
+        }
+        Err(_) => Default::default(),
+    };
+
+    Arn::new(
+        "sts",
+        "",
+        &account_id,
+        &format!("assumed-role/{role_name}/{session_name}"),
+    )
+    .to_string()
+}
+
+impl TryFrom<StoredAccessKey> for AwsCredential {
+    type Error = serde_json::Error;
+
+    fn try_from(value: StoredAccessKey) -> Result<Self, Self::Error> {
+        let policy = match value.policy {
+            Some(policy) => Some(serde_json::from_str(&policy)?),
+            None => None,
+        };
+
+        Ok(Self {
+            name: value.name,
+            access_key_id: value.access_key_id,
+            access_key_secret: value.access_key_secret,
+            principal_arn: value.principal_arn,
+            expires_at: value.expires_at,
+            enabled: value.enabled,
+            policy,
+            source_policies: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<StoredSessionCredential> for AwsCredential {
+    type Error = serde_json::Error;
+
+    fn try_from(value: StoredSessionCredential) -> Result<Self, Self::Error> {
+        let policy = match value.policy {
+            Some(policy) => Some(serde_json::from_str(&policy)?),
+            None => None,
+        };
+
+        let source_policies = match value.source_policies {
+            Some(policies) => serde_json::from_str(&policies)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            principal_arn: Some(assumed_role_arn(&value.role_arn, &value.session_name)),
+            name: value.session_name,
+            access_key_id: value.access_key_id,
+            access_key_secret: value.access_key_secret,
+            expires_at: Some(value.expires_at),
+            enabled: true,
+            policy,
+            source_policies,
+        })
+    }
+}
+
+/// Collection of credentials for the [AwsSigV4AuthLayer] keyed by their
+/// access key ID
+#[derive(Debug, Default)]
+pub struct AwsCredentials {
+    credentials: HashMap<String, AwsCredential>,
+}
+
+#[derive(Debug, Error)]
+pub enum LoadCredentialsError {
+    #[error("failed to read access keys file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse access keys file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("duplicate access key id {0}")]
+    DuplicateAccessKeyId(String),
+
+    #[error("invalid policy for access key {0}")]
+    InvalidPolicy(String),
+
+    #[error("principal arn for access key {0} must contain a 12-digit account id")]
+    InvalidPrincipalArn(String),
+}
+
+impl AwsCredentials {
+    /// Load the credentials from the JSON file at `path`
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, LoadCredentialsError> {
+        let data = tokio::fs::read(path).await?;
+        let credentials: Vec<AwsCredential> = serde_json::from_slice(&data)?;
+
+        let mut loaded = AwsCredentials::default();
+        for credential in credentials {
+            if let Some(policy) = &credential.policy
+                && validate_identity_policy(&policy.to_string()).is_err()
+            {
+                return Err(LoadCredentialsError::InvalidPolicy(credential.name));
+            }
+
+            if let Some(principal_arn) = &credential.principal_arn
+                && !Arn::from_str(principal_arn).is_ok_and(|arn| arn.has_account_id())
+            {
+                return Err(LoadCredentialsError::InvalidPrincipalArn(credential.name));
+            }
+
+            if let Some(credential) = loaded.insert(credential) {
+                return Err(LoadCredentialsError::DuplicateAccessKeyId(
+                    credential.access_key_id,
+                ));
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Add a `credential`, provides the previous credential that used the
+    /// same access key ID
+    pub fn insert(&mut self, credential: AwsCredential) -> Option<AwsCredential> {
+        self.credentials
+            .insert(credential.access_key_id.clone(), credential)
+    }
+
+    /// Get the credential for the provided `access_key_id`
+    pub fn get(&self, access_key_id: &str) -> Option<&AwsCredential> {
+        self.credentials.get(access_key_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.credentials.is_empty()
+    }
+}
+
+/// Identity of the caller for a request that passed signature verification,
+/// available from the request extensions
+#[derive(Debug, Clone)]
+pub struct CallerIdentity {
+    /// Access key ID the request was signed with
+    pub access_key_id: String,
+    /// ARN of the principal the access key belongs to
+    pub principal_arn: String,
+    /// Account the principal belongs to
+    pub account_id: String,
+    /// Region from the credential scope the request was signed for
+    pub region: String,
+    /// Identity policy of the access key
+    pub policy: Option<Value>,
+    /// Identity policies of the caller that created the session the access
+    /// key belongs to, requests must be allowed by each of them
+    pub source_policies: Vec<Value>,
+}
+
+/// Context of a request that passed signature verification, filled in by the
+/// [AwsSigV4AuthMiddleware] and available from the request extensions
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    /// Identity of the caller, including the access key the request was signed
+    /// with and the region it was signed for
+    pub caller: CallerIdentity,
+    /// Unique ID of the request
+    pub request_id: String,
+    /// Address the request was made from, [None] when the connection
+    /// information isn't available
+    pub source_address: Option<SocketAddr>,
+}
+
+impl RequestContext {
+    /// Create the context for the request identified by `request_id` made by
+    /// the `caller` from the `source_address`
+    pub fn new(
+        caller: CallerIdentity,
+        RequestId(request_id): RequestId,
+        source_address: Option<SocketAddr>,
+    ) -> Self {
+        Self {
+            caller,
+            request_id,
+            source_address,
+        }
+    }
+}
+
+impl CallerIdentity {
+    /// Identity of the root of the `account_id` in `region`, used by the
+    /// compatibility endpoints that don't authenticate with access keys
+    pub fn account_root(access_key_id: &str, account_id: String, region: String) -> Self {
         Self {
-            access_key_id,
-            access_key_secret,
+            access_key_id: access_key_id.to_string(),
+            principal_arn: Arn::iam_root(&account_id).to_string(),
+            account_id,
+            region,
+            policy: None,
+            source_policies: Vec::new(),
         }
     }
+
+    /// Identity policies that must each allow the requests made by the caller
+    pub fn identity_policies(&self) -> impl Iterator<Item = &Value> {
+        self.policy.iter().chain(&self.source_policies)
+    }
 }
 
 /// Middleware provider layer
 #[derive(Clone)]
 pub struct AwsSigV4AuthLayer {
-    credentials: Arc<AwsCredential>,
+    credentials: Arc<AwsCredentials>,
+    /// Account for credentials that don't specify a principal
+    account_id: Arc<str>,
 }
 
 impl AwsSigV4AuthLayer {
     /// Create a new AWS SigV4 layer using the provided credentials
-    pub fn new(credentials: AwsCredential) -> Self {
+    pub fn new(credentials: AwsCredentials) -> Self {
         Self {
             credentials: Arc::new(credentials),
+            account_id: Arc::from(DEFAULT_ACCOUNT_ID),
         }
     }
+
+    /// Set the account that credentials without a principal belong to
+    pub fn account_id(mut self, account_id: &str) -> Self {
+        self.account_id = Arc::from(account_id);
+        self
+    }
 }
 
 impl<S> Layer<S> for AwsSigV4AuthLayer {
@@ -56,6 +330,7 @@ impl<S> Layer<S> for AwsSigV4AuthLayer {
         AwsSigV4AuthMiddleware {
             inner,
             credentials: self.credentials.clone(),
+            account_id: self.account_id.clone(),
         }
     }
 }
@@ -64,7 +339,8 @@ impl<S> Layer<S> for AwsSigV4AuthLayer {
 #[derive(Clone)]
 pub struct AwsSigV4AuthMiddleware<S> {
     inner: S,
-    credentials: Arc<AwsCredential>,
+    credentials: Arc<AwsCredentials>,
+    account_id: Arc<str>,
 }
 
 impl<S> Service<Request<Body>> for AwsSigV4AuthMiddleware<S>
@@ -85,20 +361,25 @@ where
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
         let mut inner = self.inner.clone();
-        let credential = self.credentials.clone();
+        let credentials = self.credentials.clone();
+        let default_account_id = self.account_id.clone();
 
         // Swap to ensure we get the service that was ready and not the cloned one
         swap(&mut inner, &mut self.inner);
 
         Box::pin(async move {
-            let (parts, body) = req.into_parts();
+            let (mut parts, body) = req.into_parts();
 
             let authorization = match parts.headers.get(AUTHORIZATION) {
                 Some(value) => match value.to_str() {
                     Ok(value) => value,
                     // Invalid auth header
                     Err(_) => {
-                        return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                        return Ok(AwsErrorResponse(
+                            InvalidRequestException
+                                .with_message("The Authorization header is not valid."),
+                        )
+                        .into_response());
                     }
                 },
                 None => {
@@ -114,7 +395,11 @@ where
                         Ok(value) => value,
                         Err(_) => {
                             // Date header is invalid
-                            return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                            return Ok(AwsErrorResponse(
+                                InvalidRequestException
+                                    .with_message("The X-Amz-Date header is not valid."),
+                            )
+                            .into_response());
                         }
                     };
 
@@ -122,7 +407,11 @@ where
                         Ok(value) => value,
                         Err(_) => {
                             // Date header is invalid
-                            return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                            return Ok(AwsErrorResponse(
+                                InvalidRequestException
+                                    .with_message("The X-Amz-Date header is not valid."),
+                            )
+                            .into_response());
                         }
                     };
 
@@ -138,7 +427,11 @@ where
                         Ok(value) => value,
                         Err(_) => {
                             // Date header is invalid
-                            return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                            return Ok(AwsErrorResponse(
+                                InvalidRequestException
+                                    .with_message("The Date header is not valid."),
+                            )
+                            .into_response());
                         }
                     };
 
@@ -146,7 +439,11 @@ where
                         Ok(value) => value,
                         Err(_) => {
                             // Date header is invalid
-                            return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                            return Ok(AwsErrorResponse(
+                                InvalidRequestException
+                                    .with_message("The Date header is not valid."),
+                            )
+                            .into_response());
                         }
                     };
 
@@ -159,7 +456,11 @@ where
                 Some(value) => value,
                 None => {
                     // No date present on the request
-                    return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                    return Ok(AwsErrorResponse(
+                        InvalidRequestException
+                            .with_message("Request is missing the X-Amz-Date or Date header."),
+                    )
+                    .into_response());
                 }
             };
 
@@ -168,7 +469,7 @@ where
             if time_diff_now > 60 * 5 {
                 // Request date is not within the expected 5 minute tolerance window
                 // of the server time
-                return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                return Ok(AwsErrorResponse(InvalidRequestException.with_message("Signature expired, the request date is more than 5 minutes from the server time.")).into_response());
             }
 
             let auth = match parse_auth_header(authorization) {
@@ -215,16 +516,63 @@ where
                 return Ok(AwsErrorResponse(IncompleteSignature).into_response());
             }
 
-            if access_key_id != credential.access_key_id {
-                // Invalid access key
+            // Access keys stored in the database are looked up on each request so
+            // that changes apply without restarting the server
+            let db = parts.extensions.get::<DbPool>().cloned();
+
+            // Temporary credentials issued by STS must provide their session token
+            let security_token = match parts.headers.get("x-amz-security-token") {
+                Some(value) => match value.to_str() {
+                    Ok(value) => Some(value),
+                    Err(_) => {
+                        return Ok(AwsErrorResponse(
+                            InvalidRequestException
+                                .with_message("The X-Amz-Security-Token header is not valid."),
+                        )
+                        .into_response());
+                    }
+                },
+                None => None,
+            };
+
+            let (credential, is_stored) = match security_token {
+                Some(security_token) => {
+                    match find_session_credential(db.as_ref(), access_key_id, security_token).await
+                    {
+                        Ok(value) => (value, false),
+                        Err(error) => return Ok(error),
+                    }
+                }
+                None => match credentials.get(access_key_id) {
+                    Some(value) => (value.clone(), false),
+                    None => match find_stored_credential(db.as_ref(), access_key_id).await {
+                        Ok(value) => (value, true),
+                        Err(error) => return Ok(error),
+                    },
+                },
+            };
+
+            if !credential.enabled {
+                // Access key has been disabled
                 return Ok(AwsErrorResponse(InvalidClientTokenId).into_response());
             }
 
+            if credential
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now)
+            {
+                // Access key has expired
+                return Ok(AwsErrorResponse(ExpiredTokenException).into_response());
+            }
+
             let body = match body.collect().await {
                 Ok(value) => value.to_bytes(),
                 Err(_) => {
                     // Failed to ready body
-                    return Ok(AwsErrorResponse(InvalidRequestException).into_response());
+                    return Ok(AwsErrorResponse(
+                        InvalidRequestException.with_message("Failed to read the request body."),
+                    )
+                    .into_response());
                 }
             };
 
@@ -242,6 +590,47 @@ where
                 return Ok(AwsErrorResponse(SignatureDoesNotMatch).into_response());
             }
 
+            if is_stored
+                && let Some(db) = &db
+                && let Err(error) =
+                    update_access_key_last_used(db, &credential.access_key_id, now).await
+            {
+                tracing::error!(?error, "failed to update access key last used date");
+            }
+
+            let principal_arn = credential.principal_arn(&default_account_id);
+            let account_id = Arn::from_str(&principal_arn)
+                .ok()
+                .filter(Arn::has_account_id)
+                .map_or_else(|| default_account_id.to_string(), |arn| arn.account_id);
+
+            let caller = CallerIdentity {
+                access_key_id: credential.access_key_id,
+                principal_arn,
+                account_id,
+                region: region.to_string(),
+                policy: credential.policy,
+                source_policies: credential.source_policies,
+            };
+
+            Span::current().record("access_key_id", caller.access_key_id.as_str());
+
+            // Requests served without the request ID layer are given their own ID
+            let request_id = parts
+                .extensions
+                .get::<RequestId>()
+                .cloned()
+                .unwrap_or_else(RequestId::generate);
+
+            let source_address = parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(address)| *address);
+
+            parts
+                .extensions
+                .insert(RequestContext::new(caller, request_id, source_address));
+
             // Re-create the body since we consumed the previous one
             let body = Body::from(body);
 
@@ -251,3 +640,60 @@ where
         })
     }
 }
+
+/// Find the access key stored in the database for the `access_key_id`
+async fn find_stored_credential(
+    db: Option<&DbPool>,
+    access_key_id: &str,
+) -> Result<AwsCredential, Response> {
+    let stored = match db {
+        Some(db) => get_access_key(db, access_key_id).await.map_err(|error| {
+            tracing::error!(?error, "failed to get access key");
+            AwsErrorResponse(InternalServiceError).into_response()
+        })?,
+        None => None,
+    };
+
+    // Invalid access key
+    let stored = stored.ok_or_else(|| AwsErrorResponse(InvalidClientTokenId).into_response())?;
+
+    AwsCredential::try_from(stored).map_err(|error| {
+        tracing::error!(?error, "failed to parse access key policy");
+        AwsErrorResponse(InternalServiceError).into_response()
+    })
+}
+
+/// Find the temporary session credentials for the `access_key_id` ensuring
+/// the `security_token` matches the session
+async fn find_session_credential(
+    db: Option<&DbPool>,
+    access_key_id: &str,
+    security_token: &str,
+) -> Result<AwsCredential, Response> {
+    let stored = match db {
+        Some(db) => get_session_credential(db, access_key_id)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "failed to get session credentials");
+                AwsErrorResponse(InternalServiceError).into_response()
+            })?,
+        None => None,
+    };
+
+    // Unknown temporary credentials or a session token for another session, the
+    // token is compared in constant time to avoid leaking it through timing
+    let stored = stored
+        .filter(|stored| {
+            stored
+                .session_token
+                .as_bytes()
+                .ct_eq(security_token.as_bytes())
+                .into()
+        })
+        .ok_or_else(|| AwsErrorResponse(InvalidClientTokenId).into_response())?;
+
+    AwsCredential::try_from(stored).map_err(|error| {
+        tracing::error!(?error, "failed to parse session policy");
+        AwsErrorResponse(InternalServiceError).into_response()
+    })
+}
@@ -0,0 +1,109 @@
+//! Request IDs identifying each request, the ID is provided to clients in the
+//! `x-amzn-RequestId` response header and attached to the tracing span of
+//! the request so client side failures can be matched to the server logs
+
+use crate::handlers::protocol::Protocol;
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    response::Response,
+};
+use futures::future::BoxFuture;
+use std::mem::swap;
+use tower::{Layer, Service};
+use tracing::{Span, field::Empty};
+use uuid::Uuid;
+
+/// Header the request ID is provided to clients in
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-amzn-requestid");
+
+/// Unique ID of a request, available from the request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Generate a new unique request ID
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+/// Layer that assigns each request a [RequestId], must be applied outside of
+/// the `TraceLayer` for the ID to be included in the request span
+#[derive(Clone, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdMiddleware { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestIdMiddleware<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for RequestIdMiddleware<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+
+        // Swap to ensure we get the service that was ready and not the cloned one
+        swap(&mut inner, &mut self.inner);
+
+        let request_id = RequestId::generate();
+        let header_value = HeaderValue::from_str(&request_id.0).ok();
+        req.extensions_mut().insert(request_id);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+
+            if let Some(header_value) = header_value {
+                response
+                    .headers_mut()
+                    .insert(REQUEST_ID_HEADER, header_value);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+/// Create the tracing span for a `request`, the span includes the request ID,
+/// the operation being performed and the access key ID, which is recorded
+/// once the request signature has been verified
+pub fn make_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(value)| value.as_str());
+
+    let protocol = Protocol::from_headers(request.headers());
+    let operation = protocol.target(request.uri(), request.headers());
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id,
+        operation,
+        access_key_id = Empty,
+    )
+}
@@ -0,0 +1,519 @@
+use super::{statements, string_values};
+use crate::handlers::models::Arn;
+use serde_json::{Map, Value};
+use std::{collections::HashMap, str::FromStr};
+
+/// Condition key for the name of the secret
+pub const SECRET_NAME_KEY: &str = "secretsmanager:Name";
+
+/// Condition key prefix for the tags of the secret
+pub const RESOURCE_TAG_KEY_PREFIX: &str = "secretsmanager:ResourceTag/";
+
+/// Condition key for the ARN of the calling principal
+pub const PRINCIPAL_ARN_KEY: &str = "aws:PrincipalArn";
+
+/// Outcome of evaluating a policy document against a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// A statement explicitly allows the request
+    Allow,
+    /// A statement explicitly denies the request
+    Deny,
+    /// No statement applies to the request
+    NotApplicable,
+}
+
+/// Values of the condition keys available to a request, condition keys
+/// are matched case insensitively
+#[derive(Debug, Default)]
+pub struct ConditionContext {
+    values: HashMap<String, String>,
+}
+
+impl ConditionContext {
+    /// Set the `value` of the condition `key`
+    pub fn insert(&mut self, key: &str, value: impl Into<String>) {
+        self.values.insert(key.to_ascii_lowercase(), value.into());
+    }
+
+    /// Get the value of the condition `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .get(&key.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Request to evaluate a policy document against
+pub struct PolicyRequest<'a> {
+    /// ARN of the principal making the request
+    pub principal_arn: &'a str,
+    /// Action being performed (i.e secretsmanager:GetSecretValue)
+    pub action: &'a str,
+    /// ARN of the resource the action is performed on
+    pub resource: &'a str,
+    /// Condition keys available to the request
+    pub context: &'a ConditionContext,
+}
+
+/// Evaluate a validated policy `document` against the `request`, an explicit
+/// deny from any statement takes precedence over an allow
+pub fn evaluate_policy(document: &Value, request: &PolicyRequest<'_>) -> PolicyDecision {
+    let mut decision = PolicyDecision::NotApplicable;
+
+    for statement in statements(document) {
+        if !statement_applies(statement, request) {
+            continue;
+        }
+
+        match statement.get("Effect").and_then(Value::as_str) {
+            Some("Deny") => return PolicyDecision::Deny,
+            Some("Allow") => decision = PolicyDecision::Allow,
+            _ => {}
+        }
+    }
+
+    decision
+}
+
+/// Check if a policy `statement` applies to the `request`
+fn statement_applies(statement: &Map<String, Value>, request: &PolicyRequest<'_>) -> bool {
+    let principal = match (statement.get("Principal"), statement.get("NotPrincipal")) {
+        (Some(principal), _) => principal_matches(principal, request.principal_arn),
+        (None, Some(principal)) => !principal_matches(principal, request.principal_arn),
+        (None, None) => true,
+    };
+
+    let action = match (statement.get("Action"), statement.get("NotAction")) {
+        (Some(action), _) => action_matches(action, request.action),
+        (None, Some(action)) => !action_matches(action, request.action),
+        (None, None) => false,
+    };
+
+    let resource = match (statement.get("Resource"), statement.get("NotResource")) {
+        (Some(resource), _) => resource_matches(resource, request.resource),
+        (None, Some(resource)) => !resource_matches(resource, request.resource),
+        // Resource policies apply to the resource they are attached to
+        (None, None) => true,
+    };
+
+    let condition = match statement.get("Condition") {
+        Some(Value::Object(condition)) => condition_matches(condition, request.context),
+        _ => true,
+    };
+
+    principal && action && resource && condition
+}
+
+/// Check if the principal element `principal` matches the `principal_arn`
+fn principal_matches(principal: &Value, principal_arn: &str) -> bool {
+    let principals = match principal {
+        Value::String(value) => return value == "*",
+        Value::Object(principal) => match principal.get("AWS") {
+            Some(value) => value,
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    let account_id = Arn::from_str(principal_arn).ok().map(|arn| arn.account_id);
+
+    string_values(principals).any(|value| {
+        if value == "*" || value == principal_arn {
+            return true;
+        }
+
+        // Account principals (i.e 123456789012 or the ARN of the account
+        // root) match every principal within the account
+        let account = match Arn::from_str(value) {
+            Ok(arn) if arn.service == "iam" && arn.resource == "root" => arn.account_id,
+            Ok(_) => return false,
+            Err(_) => value.to_string(),
+        };
+
+        account_id.as_deref() == Some(account.as_str())
+    })
+}
+
+/// Check if the action element `action` matches the `requested` action
+fn action_matches(action: &Value, requested: &str) -> bool {
+    string_values(action).any(|pattern| wildcard_match(pattern, requested, true))
+}
+
+/// Check if the resource element `resource` matches the `requested` resource
+fn resource_matches(resource: &Value, requested: &str) -> bool {
+    string_values(resource).any(|pattern| wildcard_match(pattern, requested, false))
+}
+
+/// Check if every operator within the `condition` element is satisfied
+/// by the `context`
+fn condition_matches(condition: &Map<String, Value>, context: &ConditionContext) -> bool {
+    condition.iter().all(|(operator, keys)| {
+        let keys = match keys.as_object() {
+            Some(value) => value,
+            None => return false,
+        };
+
+        keys.iter().all(|(key, expected)| {
+            let expected: Vec<String> = condition_values(expected).collect();
+            operator_matches(operator, context.get(key), &expected)
+        })
+    })
+}
+
+/// Check if a single condition `operator` is satisfied for the `actual` value
+/// of a condition key
+fn operator_matches(operator: &str, actual: Option<&str>, expected: &[String]) -> bool {
+    if operator == "Null" {
+        // Null checks for the presence of the key rather than its value
+        return expected.iter().any(|expected| {
+            expected
+                .parse::<bool>()
+                .is_ok_and(|is_null| is_null == actual.is_none())
+        });
+    }
+
+    let (operator, if_exists) = match operator.strip_suffix("IfExists") {
+        Some(operator) => (operator, true),
+        None => (operator, false),
+    };
+
+    let (matches, negated): (fn(&str, &str) -> bool, bool) = match operator {
+        "StringEquals" | "ArnEquals" => (|expected, actual| expected == actual, false),
+        "StringNotEquals" | "ArnNotEquals" => (|expected, actual| expected == actual, true),
+        "StringEqualsIgnoreCase" => (
+            |expected, actual| expected.eq_ignore_ascii_case(actual),
+            false,
+        ),
+        "StringNotEqualsIgnoreCase" => (
+            |expected, actual| expected.eq_ignore_ascii_case(actual),
+            true,
+        ),
+        "StringLike" | "ArnLike" => (
+            |expected, actual| wildcard_match(expected, actual, false),
+            false,
+        ),
+        "StringNotLike" | "ArnNotLike" => (
+            |expected, actual| wildcard_match(expected, actual, false),
+            true,
+        ),
+        "Bool" => (
+            |expected, actual| expected.eq_ignore_ascii_case(actual),
+            false,
+        ),
+        // Unsupported operators never match
+        _ => return false,
+    };
+
+    let actual = match actual {
+        Some(value) => value,
+        // Missing keys satisfy negated operators and IfExists operators
+        None => return negated || if_exists,
+    };
+
+    let any_match = expected.iter().any(|expected| matches(expected, actual));
+    any_match != negated
+}
+
+/// Iterate the values of a condition key, condition values may be strings,
+/// booleans or numbers either on their own or in a list
+fn condition_values(value: &Value) -> impl Iterator<Item = String> + '_ {
+    let values: &[Value] = match value {
+        Value::Array(values) => values,
+        value => std::slice::from_ref(value),
+    };
+
+    values.iter().filter_map(|value| match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    })
+}
+
+/// Match `value` against a `pattern` where `*` matches any sequence of
+/// characters and `?` matches any single character
+pub(crate) fn wildcard_match(pattern: &str, value: &str, ignore_case: bool) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let char_eq = |a: char, b: char| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+
+    let (mut pattern_index, mut value_index) = (0, 0);
+    // Position of the last `*` in the pattern and the value index it matched from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while value_index < value.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, value_index));
+                pattern_index += 1;
+            }
+            Some(&char) if char == '?' || char_eq(char, value[value_index]) => {
+                pattern_index += 1;
+                value_index += 1;
+            }
+            _ => match backtrack {
+                // Extend the sequence matched by the last `*` by one character
+                Some((star_index, star_value_index)) => {
+                    pattern_index = star_index + 1;
+                    value_index = star_value_index + 1;
+                    backtrack = Some((star_index, value_index));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|char| *char == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PRINCIPAL_ARN: &str = "arn:aws:iam::1:user/test";
+    const SECRET_ARN: &str = "arn:aws:secretsmanager:us-east-1:1:secret:test-AbCdEf";
+
+    fn evaluate(document: Value, action: &str, context: &ConditionContext) -> PolicyDecision {
+        evaluate_policy(
+            &document,
+            &PolicyRequest {
+                principal_arn: PRINCIPAL_ARN,
+                action,
+                resource: SECRET_ARN,
+                context,
+            },
+        )
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "", false));
+        assert!(wildcard_match(
+            "secretsmanager:*",
+            "secretsmanager:GetSecretValue",
+            false
+        ));
+        assert!(wildcard_match(
+            "secretsmanager:Get*",
+            "secretsmanager:GetSecretValue",
+            false
+        ));
+        assert!(wildcard_match(
+            "secretsmanager:get*",
+            "secretsmanager:GetSecretValue",
+            true
+        ));
+        assert!(wildcard_match("test-??????", "test-AbCdEf", false));
+        assert!(wildcard_match("*:secret:test*", SECRET_ARN, false));
+        assert!(!wildcard_match(
+            "secretsmanager:get*",
+            "secretsmanager:GetSecretValue",
+            false
+        ));
+        assert!(!wildcard_match(
+            "secretsmanager:Put*",
+            "secretsmanager:GetSecretValue",
+            false
+        ));
+        assert!(!wildcard_match("test-?????", "test-AbCdEf", false));
+    }
+
+    #[test]
+    fn test_allow_and_deny() {
+        let context = ConditionContext::default();
+        let document = json!({
+            "Version": "2012-10-17",
+            "Statement": [
+                {
+                    "Effect": "Allow",
+                    "Principal": { "AWS": PRINCIPAL_ARN },
+                    "Action": "secretsmanager:*",
+                    "Resource": "*"
+                },
+                {
+                    "Effect": "Deny",
+                    "Principal": "*",
+                    "Action": "secretsmanager:DeleteSecret",
+                    "Resource": "*"
+                }
+            ]
+        });
+
+        assert_eq!(
+            evaluate(document.clone(), "secretsmanager:GetSecretValue", &context),
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            evaluate(document, "secretsmanager:DeleteSecret", &context),
+            PolicyDecision::Deny
+        );
+    }
+
+    #[test]
+    fn test_not_applicable() {
+        let context = ConditionContext::default();
+        let document = json!({
+            "Version": "2012-10-17",
+            "Statement": {
+                "Effect": "Allow",
+                "Principal": { "AWS": "arn:aws:iam::1:user/other" },
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "*"
+            }
+        });
+
+        assert_eq!(
+            evaluate(document, "secretsmanager:GetSecretValue", &context),
+            PolicyDecision::NotApplicable
+        );
+    }
+
+    #[test]
+    fn test_account_principal() {
+        let context = ConditionContext::default();
+
+        for principal in ["1", "arn:aws:iam::1:root"] {
+            let document = json!({
+                "Statement": {
+                    "Effect": "Allow",
+                    "Principal": { "AWS": principal },
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*"
+                }
+            });
+
+            assert_eq!(
+                evaluate(document, "secretsmanager:GetSecretValue", &context),
+                PolicyDecision::Allow
+            );
+        }
+    }
+
+    #[test]
+    fn test_not_action() {
+        let context = ConditionContext::default();
+        let document = json!({
+            "Statement": {
+                "Effect": "Deny",
+                "Principal": "*",
+                "NotAction": ["secretsmanager:Describe*", "secretsmanager:GetResourcePolicy"],
+                "Resource": "*"
+            }
+        });
+
+        assert_eq!(
+            evaluate(document.clone(), "secretsmanager:DescribeSecret", &context),
+            PolicyDecision::NotApplicable
+        );
+        assert_eq!(
+            evaluate(document, "secretsmanager:GetSecretValue", &context),
+            PolicyDecision::Deny
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        let mut context = ConditionContext::default();
+        context.insert(SECRET_NAME_KEY, "prod/database");
+        context.insert(&format!("{RESOURCE_TAG_KEY_PREFIX}Environment"), "prod");
+        context.insert(PRINCIPAL_ARN_KEY, PRINCIPAL_ARN);
+
+        let allow = |condition: Value| {
+            json!({
+                "Statement": {
+                    "Effect": "Allow",
+                    "Principal": "*",
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*",
+                    "Condition": condition
+                }
+            })
+        };
+
+        let cases = [
+            (
+                json!({ "StringEquals": { "secretsmanager:ResourceTag/Environment": "prod" } }),
+                true,
+            ),
+            (
+                json!({ "StringEquals": { "secretsmanager:resourcetag/environment": "prod" } }),
+                true,
+            ),
+            (
+                json!({ "StringEquals": { "secretsmanager:ResourceTag/Environment": "dev" } }),
+                false,
+            ),
+            (
+                json!({ "StringNotEquals": { "secretsmanager:ResourceTag/Environment": "dev" } }),
+                true,
+            ),
+            (
+                json!({ "StringLike": { "secretsmanager:Name": "prod/*" } }),
+                true,
+            ),
+            (
+                json!({ "StringNotLike": { "secretsmanager:Name": ["prod/*", "dev/*"] } }),
+                false,
+            ),
+            (
+                json!({ "ArnEquals": { "aws:PrincipalArn": PRINCIPAL_ARN } }),
+                true,
+            ),
+            (
+                json!({ "ArnLike": { "aws:PrincipalArn": "arn:aws:iam::1:role/*" } }),
+                false,
+            ),
+            (
+                json!({ "StringEquals": { "secretsmanager:ResourceTag/Team": "ops" } }),
+                false,
+            ),
+            (
+                json!({ "StringEqualsIfExists": { "secretsmanager:ResourceTag/Team": "ops" } }),
+                true,
+            ),
+            (
+                json!({ "StringNotEquals": { "secretsmanager:ResourceTag/Team": "ops" } }),
+                true,
+            ),
+            (
+                json!({ "Null": { "secretsmanager:ResourceTag/Team": "true" } }),
+                true,
+            ),
+            (
+                json!({ "Null": { "secretsmanager:ResourceTag/Environment": "true" } }),
+                false,
+            ),
+            (
+                json!({
+                    "StringEquals": { "secretsmanager:ResourceTag/Environment": "prod" },
+                    "StringLike": { "secretsmanager:Name": "dev/*" }
+                }),
+                false,
+            ),
+        ];
+
+        for (condition, allowed) in cases {
+            let decision = evaluate(
+                allow(condition.clone()),
+                "secretsmanager:GetSecretValue",
+                &context,
+            );
+            let expected = if allowed {
+                PolicyDecision::Allow
+            } else {
+                PolicyDecision::NotApplicable
+            };
+
+            assert_eq!(decision, expected, "condition {condition}");
+        }
+    }
+}
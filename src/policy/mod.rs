@@ -0,0 +1,501 @@
+use serde_json::{Map, Value};
+
+pub mod evaluate;
+
+/// Name of the check for policies that are not valid JSON
+const JSON_SYNTAX_CHECK: &str = "JSON_SYNTAX_CHECKING";
+
+/// Name of the check for policies with an invalid structure
+const SYNTAX_CHECK: &str = "SYNTAX_CHECKING";
+
+/// Supported policy language versions
+const POLICY_VERSIONS: &[&str] = &["2012-10-17", "2008-10-17"];
+
+/// Elements allowed at the top level of a policy document
+const POLICY_ELEMENTS: &[&str] = &["Version", "Id", "Statement"];
+
+/// Elements allowed within a policy statement
+const STATEMENT_ELEMENTS: &[&str] = &[
+    "Sid",
+    "Effect",
+    "Principal",
+    "NotPrincipal",
+    "Action",
+    "NotAction",
+    "Resource",
+    "NotResource",
+    "Condition",
+];
+
+/// Principal types allowed within a principal element
+const PRINCIPAL_TYPES: &[&str] = &["AWS", "Service", "Federated", "CanonicalUser"];
+
+/// Error found while validating a policy document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyValidationError {
+    /// Name of the check that failed
+    pub check_name: &'static str,
+    /// Message describing the error
+    pub message: String,
+}
+
+impl PolicyValidationError {
+    fn syntax(message: impl Into<String>) -> Self {
+        Self {
+            check_name: SYNTAX_CHECK,
+            message: message.into(),
+        }
+    }
+}
+
+/// Kind of policy document being validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PolicyKind {
+    /// Policy attached to a secret, statements must specify a principal
+    Resource,
+    /// Policy attached to an access key, statements apply to the access key
+    /// and can't specify a principal
+    Identity,
+}
+
+/// Validate the structure of the resource `policy` document, provides the
+/// parsed document when the policy is valid
+pub fn validate_policy(policy: &str) -> Result<Value, Vec<PolicyValidationError>> {
+    validate(policy, PolicyKind::Resource)
+}
+
+/// Validate the structure of the identity `policy` document of an access key,
+/// provides the parsed document when the policy is valid
+pub fn validate_identity_policy(policy: &str) -> Result<Value, Vec<PolicyValidationError>> {
+    validate(policy, PolicyKind::Identity)
+}
+
+fn validate(policy: &str, kind: PolicyKind) -> Result<Value, Vec<PolicyValidationError>> {
+    let document: Value = match serde_json::from_str(policy) {
+        Ok(value) => value,
+        Err(error) => {
+            return Err(vec![PolicyValidationError {
+                check_name: JSON_SYNTAX_CHECK,
+                message: format!("Policy is not valid JSON: {error}"),
+            }]);
+        }
+    };
+
+    let mut errors = Vec::new();
+    validate_document(&document, kind, &mut errors);
+
+    if errors.is_empty() {
+        Ok(document)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Check if the policy `document` allows broad public access to the resource,
+/// either through a wildcard principal or by allowing every principal except
+/// a specific few
+pub fn is_public_policy(document: &Value) -> bool {
+    statements(document)
+        .filter(|statement| statement.get("Effect").and_then(Value::as_str) == Some("Allow"))
+        .any(|statement| {
+            if statement.get("NotPrincipal").is_some() {
+                return true;
+            }
+
+            match statement.get("Principal") {
+                Some(Value::String(principal)) => principal == "*",
+                Some(Value::Object(principal)) => principal
+                    .get("AWS")
+                    .is_some_and(|value| string_values(value).any(|value| value == "*")),
+                _ => false,
+            }
+        })
+}
+
+/// Iterate the statements within a policy `document`
+fn statements(document: &Value) -> impl Iterator<Item = &Map<String, Value>> {
+    let statements: &[Value] = match document.get("Statement") {
+        Some(Value::Array(statements)) => statements,
+        Some(statement) => std::slice::from_ref(statement),
+        None => &[],
+    };
+
+    statements.iter().filter_map(Value::as_object)
+}
+
+/// Iterate the string values of a policy element that can be either a single
+/// string or a list of strings
+fn string_values(value: &Value) -> impl Iterator<Item = &str> {
+    let values: &[Value] = match value {
+        Value::Array(values) => values,
+        value => std::slice::from_ref(value),
+    };
+
+    values.iter().filter_map(Value::as_str)
+}
+
+fn validate_document(document: &Value, kind: PolicyKind, errors: &mut Vec<PolicyValidationError>) {
+    let document = match document.as_object() {
+        Some(value) => value,
+        None => {
+            errors.push(PolicyValidationError::syntax(
+                "Policy document must be a JSON object",
+            ));
+            return;
+        }
+    };
+
+    for key in document.keys() {
+        if !POLICY_ELEMENTS.contains(&key.as_str()) {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Unsupported policy element: {key}"
+            )));
+        }
+    }
+
+    if let Some(version) = document.get("Version")
+        && !version
+            .as_str()
+            .is_some_and(|version| POLICY_VERSIONS.contains(&version))
+    {
+        errors.push(PolicyValidationError::syntax(
+            "Version must be either 2012-10-17 or 2008-10-17",
+        ));
+    }
+
+    if let Some(id) = document.get("Id")
+        && !id.is_string()
+    {
+        errors.push(PolicyValidationError::syntax("Id must be a string"));
+    }
+
+    let statements: &[Value] = match document.get("Statement") {
+        Some(Value::Array(statements)) => statements,
+        Some(statement @ Value::Object(_)) => std::slice::from_ref(statement),
+        Some(_) => {
+            errors.push(PolicyValidationError::syntax(
+                "Statement must be an object or a list of objects",
+            ));
+            return;
+        }
+        None => {
+            errors.push(PolicyValidationError::syntax(
+                "Missing required policy element: Statement",
+            ));
+            return;
+        }
+    };
+
+    if statements.is_empty() {
+        errors.push(PolicyValidationError::syntax(
+            "Statement must contain at least one statement",
+        ));
+    }
+
+    for (index, statement) in statements.iter().enumerate() {
+        validate_statement(index, statement, kind, errors);
+    }
+}
+
+fn validate_statement(
+    index: usize,
+    statement: &Value,
+    kind: PolicyKind,
+    errors: &mut Vec<PolicyValidationError>,
+) {
+    let statement = match statement.as_object() {
+        Some(value) => value,
+        None => {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} must be an object"
+            )));
+            return;
+        }
+    };
+
+    for key in statement.keys() {
+        if !STATEMENT_ELEMENTS.contains(&key.as_str()) {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} has an unsupported element: {key}"
+            )));
+        }
+    }
+
+    if let Some(sid) = statement.get("Sid")
+        && !sid.is_string()
+    {
+        errors.push(PolicyValidationError::syntax(format!(
+            "Statement {index} Sid must be a string"
+        )));
+    }
+
+    match statement.get("Effect").and_then(Value::as_str) {
+        Some("Allow" | "Deny") => {}
+        _ => errors.push(PolicyValidationError::syntax(format!(
+            "Statement {index} Effect must be either Allow or Deny"
+        ))),
+    }
+
+    match kind {
+        PolicyKind::Resource => {
+            if let Some(principal) =
+                exclusive_element(index, statement, "Principal", "NotPrincipal", errors)
+            {
+                validate_principal(index, principal, errors);
+            }
+        }
+        PolicyKind::Identity => {
+            if statement.contains_key("Principal") || statement.contains_key("NotPrincipal") {
+                errors.push(PolicyValidationError::syntax(format!(
+                    "Statement {index} cannot specify a Principal in an identity policy"
+                )));
+            }
+        }
+    }
+
+    for (element, not_element) in [("Action", "NotAction"), ("Resource", "NotResource")] {
+        if let Some(value) = exclusive_element(index, statement, element, not_element, errors)
+            && !is_string_list(value)
+        {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} {element} must be a string or a list of strings"
+            )));
+        }
+    }
+
+    if let Some(condition) = statement.get("Condition") {
+        validate_condition(index, condition, errors);
+    }
+}
+
+/// Get the value of a statement element that must be specified using exactly
+/// one of `element` or `not_element`
+fn exclusive_element<'a>(
+    index: usize,
+    statement: &'a Map<String, Value>,
+    element: &str,
+    not_element: &str,
+    errors: &mut Vec<PolicyValidationError>,
+) -> Option<&'a Value> {
+    match (statement.get(element), statement.get(not_element)) {
+        (Some(value), None) | (None, Some(value)) => Some(value),
+        (Some(_), Some(_)) => {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} cannot specify both {element} and {not_element}"
+            )));
+            None
+        }
+        (None, None) => {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} must specify either {element} or {not_element}"
+            )));
+            None
+        }
+    }
+}
+
+fn validate_principal(index: usize, principal: &Value, errors: &mut Vec<PolicyValidationError>) {
+    match principal {
+        Value::String(value) if value == "*" => {}
+        Value::Object(principal) if !principal.is_empty() => {
+            for (key, value) in principal {
+                if !PRINCIPAL_TYPES.contains(&key.as_str()) {
+                    errors.push(PolicyValidationError::syntax(format!(
+                        "Statement {index} has an unsupported principal type: {key}"
+                    )));
+                }
+
+                if !is_string_list(value) {
+                    errors.push(PolicyValidationError::syntax(format!(
+                        "Statement {index} principal {key} must be a string or a list of strings"
+                    )));
+                }
+            }
+        }
+        _ => errors.push(PolicyValidationError::syntax(format!(
+            "Statement {index} Principal must be \"*\" or an object of principal types"
+        ))),
+    }
+}
+
+fn validate_condition(index: usize, condition: &Value, errors: &mut Vec<PolicyValidationError>) {
+    let condition = match condition.as_object() {
+        Some(value) => value,
+        None => {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} Condition must be an object"
+            )));
+            return;
+        }
+    };
+
+    for (operator, keys) in condition {
+        let valid = keys.as_object().is_some_and(|keys| {
+            keys.values().all(|value| match value {
+                Value::Array(values) => !values.is_empty() && values.iter().all(is_condition_value),
+                value => is_condition_value(value),
+            })
+        });
+
+        if !valid {
+            errors.push(PolicyValidationError::syntax(format!(
+                "Statement {index} Condition operator {operator} must map condition keys to values"
+            )));
+        }
+    }
+}
+
+/// Check if the value is a string or a non-empty list of strings
+fn is_string_list(value: &Value) -> bool {
+    match value {
+        Value::String(_) => true,
+        Value::Array(values) => !values.is_empty() && values.iter().all(Value::is_string),
+        _ => false,
+    }
+}
+
+/// Check if the value is allowed as a condition value
+fn is_condition_value(value: &Value) -> bool {
+    matches!(value, Value::String(_) | Value::Bool(_) | Value::Number(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_POLICY: &str = r#"{
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "*"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_valid_policy() {
+        let document = validate_policy(VALID_POLICY).unwrap();
+        assert!(!is_public_policy(&document));
+    }
+
+    #[test]
+    fn test_identity_policy() {
+        let policy = r#"{
+            "Version": "2012-10-17",
+            "Statement": {
+                "Effect": "Allow",
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+            }
+        }"#;
+
+        assert!(validate_identity_policy(policy).is_ok());
+        assert!(validate_policy(policy).is_err());
+
+        let errors = validate_identity_policy(VALID_POLICY).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![PolicyValidationError::syntax(
+                "Statement 0 cannot specify a Principal in an identity policy"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let errors = validate_policy("{").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].check_name, JSON_SYNTAX_CHECK);
+    }
+
+    #[test]
+    fn test_missing_statement() {
+        let errors = validate_policy(r#"{"Version": "2012-10-17"}"#).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![PolicyValidationError::syntax(
+                "Missing required policy element: Statement"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_invalid_statement() {
+        let errors = validate_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Maybe",
+                    "Action": "secretsmanager:GetSecretValue",
+                    "NotAction": "secretsmanager:PutSecretValue",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                PolicyValidationError::syntax("Statement 0 Effect must be either Allow or Deny"),
+                PolicyValidationError::syntax(
+                    "Statement 0 must specify either Principal or NotPrincipal"
+                ),
+                PolicyValidationError::syntax(
+                    "Statement 0 cannot specify both Action and NotAction"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_public_policy() {
+        let document = validate_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Principal": "*",
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(is_public_policy(&document));
+
+        let document = validate_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Principal": { "AWS": ["arn:aws:iam::123456789012:root", "*"] },
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(is_public_policy(&document));
+    }
+
+    #[test]
+    fn test_public_deny_policy() {
+        let document = validate_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Deny",
+                    "Principal": "*",
+                    "Action": "secretsmanager:DeleteSecret",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(!is_public_policy(&document));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    database::{
+        DbErr, DbPool, DbResult, DbTransaction,
+        replicas::{
+            PutSecretReplica, REPLICA_STATUS_FAILED, REPLICA_STATUS_IN_SYNC, delete_secret_replica,
+            put_secret_replica,
+        },
+        secrets::{
+            CreateSecret, StoredSecret, copy_secret_metadata, copy_secret_tags,
+            copy_secret_version_stages, copy_secret_versions, create_secret, delete_secret,
+            delete_secret_tags, delete_secret_versions, get_secret_latest_version,
+            get_secret_replica_secrets, get_secret_versions, promote_replica_secret,
+            update_secret_kms_key_id, update_secret_version_value,
+        },
+    },
+    handlers::models::Arn,
+    kms::{KmsError, decrypt_secret_value, encrypt_secret_value, find_enabled_key},
+};
+use std::{ops::DerefMut, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ReplicationError {
+    #[error(transparent)]
+    Db(#[from] DbErr),
+
+    #[error(transparent)]
+    Kms(#[from] KmsError),
+}
+
+/// Region that a secret should be replicated to
+pub struct ReplicaRegion {
+    /// Region to create the replica in
+    pub region: String,
+    /// KMS key to encrypt the replica with
+    pub kms_key_id: Option<String>,
+}
+
+/// Replicate the primary `secret` to each of the `regions`, the replicas share
+/// the name of the primary and an ARN that only differs by region
+///
+/// Regions that already contain another secret with the same name are marked
+/// as failed unless `force_overwrite` is set, in which case the existing secret
+/// is replaced by the replica
+pub async fn replicate_secret_to_regions(
+    db: &DbPool,
+    secret: &StoredSecret,
+    regions: &[ReplicaRegion],
+    force_overwrite: bool,
+) -> Result<(), ReplicationError> {
+    for ReplicaRegion { region, kms_key_id } in regions {
+        let replica_arn = match Arn::from_str(&secret.arn) {
+            Ok(value) => value.with_region(region).to_string(),
+            Err(_) => continue,
+        };
+
+        // Replica key must be usable within the replica region
+        if let Some(kms_key_id) = kms_key_id {
+            match find_enabled_key(db, region, kms_key_id).await {
+                Ok(_) => {}
+                Err(KmsError::Db(error)) => return Err(error.into()),
+                Err(error) => {
+                    tracing::debug!(%region, ?error, "replica kms key is not usable");
+
+                    put_secret_replica(
+                        db,
+                        PutSecretReplica {
+                            secret_arn: secret.arn.clone(),
+                            region: region.clone(),
+                            kms_key_id: Some(kms_key_id.clone()),
+                            status: REPLICA_STATUS_FAILED.to_string(),
+                            status_message: Some(
+                                "Replication failed: KMS key is not accessible in Region."
+                                    .to_string(),
+                            ),
+                        },
+                    )
+                    .await?;
+                    continue;
+                }
+            }
+        }
+
+        let existing = get_secret_latest_version(db, region, &secret.name).await?;
+        let mut t = db.begin().await?;
+
+        match existing {
+            // Region already has the replica, only the replication details change
+            Some(existing) if existing.primary_arn.as_ref() == Some(&secret.arn) => {
+                update_secret_kms_key_id(t.deref_mut(), &replica_arn, kms_key_id.as_deref())
+                    .await?;
+            }
+
+            // Region already has a secret with the same name
+            Some(existing) if !force_overwrite => {
+                tracing::debug!(%region, existing_arn = %existing.arn, "replica secret name already exists");
+
+                put_secret_replica(
+                    t.deref_mut(),
+                    PutSecretReplica {
+                        secret_arn: secret.arn.clone(),
+                        region: region.clone(),
+                        kms_key_id: kms_key_id.clone(),
+                        status: REPLICA_STATUS_FAILED.to_string(),
+                        status_message: Some(
+                            "Replication failed: Secret name already exists in Region.".to_string(),
+                        ),
+                    },
+                )
+                .await?;
+                t.commit().await?;
+                continue;
+            }
+
+            existing => {
+                // Replace the existing secret with the replica
+                if let Some(existing) = existing {
+                    delete_secret(t.deref_mut(), &existing.arn).await?;
+                }
+
+                create_secret(
+                    t.deref_mut(),
+                    CreateSecret {
+                        arn: replica_arn.clone(),
+                        region: region.clone(),
+                        name: secret.name.clone(),
+                        description: secret.description.clone(),
+                        primary_arn: Some(secret.arn.clone()),
+                        kms_key_id: kms_key_id.clone(),
+                    },
+                )
+                .await?;
+            }
+        }
+
+        copy_secret_contents(
+            &mut t,
+            &secret.arn,
+            &replica_arn,
+            region,
+            kms_key_id.as_deref(),
+        )
+        .await?;
+
+        put_secret_replica(
+            t.deref_mut(),
+            PutSecretReplica {
+                secret_arn: secret.arn.clone(),
+                region: region.clone(),
+                kms_key_id: kms_key_id.clone(),
+                status: REPLICA_STATUS_IN_SYNC.to_string(),
+                status_message: None,
+            },
+        )
+        .await?;
+
+        t.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Propagate the current state of the primary secret `secret_arn` to all of
+/// its replicas, replicas are replaced with a copy of the primary versions,
+/// version stages, tags, and description
+pub async fn sync_secret_replicas(db: &DbPool, secret_arn: &str) -> Result<(), ReplicationError> {
+    let replicas = get_secret_replica_secrets(db, secret_arn).await?;
+
+    for replica in replicas {
+        let mut t = db.begin().await?;
+        copy_secret_contents(
+            &mut t,
+            secret_arn,
+            &replica.arn,
+            &replica.region,
+            replica.kms_key_id.as_deref(),
+        )
+        .await?;
+        t.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Remove the replicas of the primary `secret` from each of the `regions`,
+/// the replica secrets are deleted immediately
+pub async fn remove_regions_from_replication(
+    db: &DbPool,
+    secret: &StoredSecret,
+    regions: &[String],
+) -> DbResult<()> {
+    let replicas = get_secret_replica_secrets(db, &secret.arn).await?;
+    let mut t = db.begin().await?;
+
+    for region in regions {
+        let replica = replicas.iter().find(|replica| &replica.region == region);
+
+        if let Some(replica) = replica {
+            delete_secret(t.deref_mut(), &replica.arn).await?;
+        }
+
+        delete_secret_replica(t.deref_mut(), &secret.arn, region).await?;
+    }
+
+    t.commit().await?;
+
+    Ok(())
+}
+
+/// Promote the `replica` to a standalone secret, the replica keeps its current
+/// value but no longer receives changes from its primary
+pub async fn stop_replication_to_replica(db: &DbPool, replica: &StoredSecret) -> DbResult<()> {
+    let mut t = db.begin().await?;
+
+    promote_replica_secret(t.deref_mut(), &replica.arn).await?;
+
+    if let Some(primary_arn) = &replica.primary_arn {
+        delete_secret_replica(t.deref_mut(), primary_arn, &replica.region).await?;
+    }
+
+    t.commit().await?;
+
+    Ok(())
+}
+
+/// Replace the description, rotation configuration, versions, version stages
+/// and tags of the secret `target_arn` with those of the secret `source_arn`
+///
+/// The copied versions are re-encrypted with the KMS key `kms_key_id` of the
+/// target secret in its `region`
+async fn copy_secret_contents(
+    t: &mut DbTransaction<'_>,
+    source_arn: &str,
+    target_arn: &str,
+    region: &str,
+    kms_key_id: Option<&str>,
+) -> Result<(), ReplicationError> {
+    copy_secret_metadata(t.deref_mut(), source_arn, target_arn).await?;
+
+    delete_secret_versions(t.deref_mut(), target_arn).await?;
+    copy_secret_versions(t.deref_mut(), source_arn, target_arn).await?;
+    copy_secret_version_stages(t.deref_mut(), source_arn, target_arn).await?;
+
+    for version in get_secret_versions(t.deref_mut(), target_arn).await? {
+        // Unencrypted values stay as is when the target doesn't use a key
+        if version.encrypted_data_key.is_none() && kms_key_id.is_none() {
+            continue;
+        }
+
+        let mut secret_string = version.secret_string;
+        let mut secret_binary = version.secret_binary;
+
+        decrypt_secret_value(
+            t.deref_mut(),
+            version.encrypted_data_key.as_deref(),
+            &mut secret_string,
+            &mut secret_binary,
+        )
+        .await?;
+
+        let value = encrypt_secret_value(
+            t.deref_mut(),
+            region,
+            kms_key_id,
+            secret_string,
+            secret_binary,
+        )
+        .await?;
+
+        update_secret_version_value(
+            t.deref_mut(),
+            target_arn,
+            &version.version_id,
+            value.secret_string,
+            value.secret_binary,
+            value.encrypted_data_key,
+        )
+        .await?;
+    }
+
+    delete_secret_tags(t.deref_mut(), target_arn).await?;
+    copy_secret_tags(t.deref_mut(), source_arn, target_arn).await?;
+
+    Ok(())
+}
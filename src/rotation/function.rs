@@ -0,0 +1,202 @@
+use crate::rotation::RotationStep;
+use axum::http::{Request, StatusCode, Uri, header::CONTENT_TYPE, uri::InvalidUri};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path, process::Stdio, time::Duration};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// Default duration a rotation function can take to complete a step
+pub const DEFAULT_ROTATION_FUNCTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Collection of local rotation functions mapped from the rotation
+/// function ARN that they stand in for
+#[derive(Debug, Deserialize)]
+#[serde(from = "HashMap<String, RotationFunction>")]
+pub struct RotationFunctions {
+    functions: HashMap<String, RotationFunction>,
+    /// Duration each invocation of a function can take before it is aborted
+    timeout: Duration,
+}
+
+/// Local stand-in for a rotation Lambda function
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationFunction {
+    /// Invoke the function by sending a POST request with the JSON payload
+    Http {
+        /// URL to send the request to
+        url: String,
+    },
+    /// Invoke the function by running a command providing the JSON payload
+    /// through stdin
+    Command {
+        /// Program to execute
+        program: String,
+        /// Arguments to provide to the program
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// Payload provided to rotation functions, matches the event that AWS
+/// provides to rotation Lambda functions
+#[derive(Debug, Serialize)]
+pub struct RotationFunctionPayload<'a> {
+    #[serde(rename = "SecretId")]
+    pub secret_id: &'a str,
+    #[serde(rename = "ClientRequestToken")]
+    pub client_request_token: &'a str,
+    #[serde(rename = "Step")]
+    pub step: RotationStep,
+}
+
+#[derive(Debug, Error)]
+pub enum LoadRotationFunctionsError {
+    #[error("failed to read rotation functions file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse rotation functions file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum RotationFunctionError {
+    #[error("failed to serialize rotation function payload: {0}")]
+    Payload(#[from] serde_json::Error),
+
+    #[error("invalid rotation function url: {0}")]
+    InvalidUrl(#[from] InvalidUri),
+
+    #[error("failed to build rotation function request: {0}")]
+    Request(#[from] axum::http::Error),
+
+    #[error("failed to send rotation function request: {0}")]
+    Http(#[from] hyper_util::client::legacy::Error),
+
+    #[error("rotation function responded with status {0}")]
+    Status(StatusCode),
+
+    #[error("failed to run rotation function command: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("rotation function command exited with {0}")]
+    Exit(std::process::ExitStatus),
+
+    #[error("rotation function timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl Default for RotationFunctions {
+    fn default() -> Self {
+        Self::from(HashMap::new())
+    }
+}
+
+impl From<HashMap<String, RotationFunction>> for RotationFunctions {
+    fn from(functions: HashMap<String, RotationFunction>) -> Self {
+        Self {
+            functions,
+            timeout: DEFAULT_ROTATION_FUNCTION_TIMEOUT,
+        }
+    }
+}
+
+impl RotationFunctions {
+    /// Load the rotation functions from the JSON file at `path`
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, LoadRotationFunctionsError> {
+        let data = tokio::fs::read(path).await?;
+        let functions = serde_json::from_slice(&data)?;
+        Ok(functions)
+    }
+
+    /// Set the duration each invocation of a function can take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Duration each invocation of a function can take before it is aborted
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Get the rotation function mapped to the provided `arn`
+    pub fn get(&self, arn: &str) -> Option<&RotationFunction> {
+        self.functions.get(arn)
+    }
+}
+
+impl RotationFunction {
+    /// Invoke the rotation function with the provided `payload`, failing
+    /// when the invocation doesn't complete within the `timeout`
+    pub async fn invoke(
+        &self,
+        payload: &RotationFunctionPayload<'_>,
+        timeout: Duration,
+    ) -> Result<(), RotationFunctionError> {
+        let payload = serde_json::to_vec(payload)?;
+
+        // Timing out drops the invocation future along with the request or
+        // child process, commands are killed on drop
+        let invocation = async {
+            match self {
+                RotationFunction::Http { url } => invoke_http(url, payload).await,
+                RotationFunction::Command { program, args } => {
+                    invoke_command(program, args, payload).await
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, invocation)
+            .await
+            .map_err(|_| RotationFunctionError::Timeout(timeout))?
+    }
+}
+
+/// Invoke a HTTP rotation function, any non-success status is treated as
+/// a failure of the step
+async fn invoke_http(url: &str, payload: Vec<u8>) -> Result<(), RotationFunctionError> {
+    let uri: Uri = url.parse()?;
+    let request = Request::post(uri)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(payload)))?;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let response = client.request(request).await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(RotationFunctionError::Status(status));
+    }
+
+    Ok(())
+}
+
+/// Invoke a command rotation function, any non-zero exit status is treated
+/// as a failure of the step
+async fn invoke_command(
+    program: &str,
+    args: &[String],
+    payload: Vec<u8>,
+) -> Result<(), RotationFunctionError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&payload).await?;
+        // Dropping stdin closes the pipe allowing the command to finish reading
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(RotationFunctionError::Exit(status));
+    }
+
+    Ok(())
+}
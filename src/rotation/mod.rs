@@ -0,0 +1,399 @@
+use crate::{
+    database::{
+        DbErr, DbPool,
+        secrets::{
+            CreateSecretVersion, StoredSecret, add_secret_version_stage, create_secret_version,
+            get_secret_by_version_id, get_secret_by_version_stage, get_secret_latest_version,
+            get_secrets_due_rotation, remove_secret_version_stage, remove_secret_version_stage_any,
+            update_secret_next_rotation, update_secret_rotated,
+        },
+    },
+    kms::{KmsError, decrypt_stored_secret, encrypt_secret_value},
+    replication::{ReplicationError, sync_secret_replicas},
+    rotation::{
+        function::{
+            RotationFunction, RotationFunctionError, RotationFunctionPayload, RotationFunctions,
+        },
+        schedule::{RotationSchedule, parse_rotation_duration},
+        strategy::{RotationStrategy, StrategyError},
+    },
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::ops::DerefMut;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub mod function;
+pub mod schedule;
+pub mod strategy;
+
+/// Steps performed in order to rotate a secret, matches the steps that
+/// AWS invokes on a rotation function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RotationStep {
+    /// Create a new version of the secret in the AWSPENDING stage
+    #[serde(rename = "createSecret")]
+    Create,
+    /// Set the pending secret in the database or service
+    #[serde(rename = "setSecret")]
+    Set,
+    /// Test the pending version of the secret
+    #[serde(rename = "testSecret")]
+    Test,
+    /// Move the AWSCURRENT stage to the pending version
+    #[serde(rename = "finishSecret")]
+    Finish,
+}
+
+impl RotationStep {
+    /// All of the rotation steps in the order they are performed
+    pub const ALL: [RotationStep; 4] = [
+        RotationStep::Create,
+        RotationStep::Set,
+        RotationStep::Test,
+        RotationStep::Finish,
+    ];
+
+    /// Name of the step as used by AWS rotation functions
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotationStep::Create => "createSecret",
+            RotationStep::Set => "setSecret",
+            RotationStep::Test => "testSecret",
+            RotationStep::Finish => "finishSecret",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RotationError {
+    #[error(transparent)]
+    Db(#[from] DbErr),
+
+    #[error("secret is missing its pending version")]
+    MissingPendingVersion,
+
+    #[error(transparent)]
+    Strategy(#[from] StrategyError),
+
+    #[error(transparent)]
+    Kms(#[from] KmsError),
+
+    #[error(transparent)]
+    Replication(#[from] ReplicationError),
+
+    #[error("rotation function {0} is not mapped to a local rotation function")]
+    UnmappedFunction(String),
+
+    #[error("secret has no rotation function or rotation strategy")]
+    MissingFunction,
+
+    #[error("previous rotation into version {0} is not complete")]
+    IncompleteRotation(String),
+
+    #[error("rotation function failed during {}: {source}", step.as_str())]
+    Function {
+        step: RotationStep,
+        source: RotationFunctionError,
+    },
+}
+
+/// Method used to create the new version of a secret during a rotation
+enum Rotator<'a> {
+    /// Steps are delegated to a local rotation function
+    Function(&'a RotationFunction),
+    /// New version is created by a built-in rotation strategy
+    Strategy(RotationStrategy),
+}
+
+/// Check that the `secret` can be rotated into the version `version_id`
+/// without performing the rotation
+///
+/// The secret must not have an incomplete rotation for another version and
+/// must have either a mapped rotation function or a built-in strategy
+pub async fn check_rotation(
+    db: &DbPool,
+    functions: &RotationFunctions,
+    secret: &StoredSecret,
+    version_id: &str,
+) -> Result<(), RotationError> {
+    ensure_no_pending_rotation(db, secret, version_id).await?;
+    find_rotator(functions, secret)?;
+    Ok(())
+}
+
+/// Rotate the provided `secret` creating a new version with the ID `version_id`,
+/// provides the ID of the version that was promoted to AWSCURRENT
+///
+/// When the rotation function ARN of the secret is mapped to a local rotation
+/// function the steps are delegated to that function. A failed step stops the
+/// rotation leaving the AWSPENDING version in place, the secret can't be
+/// rotated again until the AWSPENDING stage is removed from that version
+///
+/// Otherwise the new version is created using the built-in [RotationStrategy]
+/// of the secret, secrets without either can't be rotated
+pub async fn rotate_secret(
+    db: &DbPool,
+    functions: &RotationFunctions,
+    secret: &StoredSecret,
+    version_id: String,
+) -> Result<String, RotationError> {
+    ensure_no_pending_rotation(db, secret, &version_id).await?;
+    let rotator = find_rotator(functions, secret)?;
+
+    for step in RotationStep::ALL {
+        tracing::debug!(step = step.as_str(), %version_id, "performing rotation step");
+
+        match &rotator {
+            Rotator::Function(function) => {
+                let payload = RotationFunctionPayload {
+                    secret_id: &secret.arn,
+                    client_request_token: &version_id,
+                    step,
+                };
+
+                function
+                    .invoke(&payload, functions.timeout())
+                    .await
+                    .map_err(|source| RotationError::Function { step, source })?;
+            }
+            Rotator::Strategy(strategy) => {
+                perform_rotation_step(db, secret, strategy, &version_id, step).await?
+            }
+        }
+    }
+
+    let rotated_at = Utc::now();
+    let schedule = RotationSchedule::from_rules(
+        secret.rotation_automatically_after_days,
+        secret.rotation_schedule_expression.as_deref(),
+    )
+    .ok()
+    .flatten();
+
+    let next_rotation_at = if secret.rotation_enabled {
+        schedule.and_then(|schedule| schedule.next_after(rotated_at))
+    } else {
+        None
+    };
+
+    update_secret_rotated(db, &secret.arn, rotated_at, next_rotation_at).await?;
+
+    // Replicas receive the rotated value from their primary
+    sync_secret_replicas(db, &secret.arn).await?;
+
+    Ok(version_id)
+}
+
+/// Rotate all the secrets that have a scheduled rotation due at `now`
+///
+/// Secrets are only rotated within their rotation window, when the window
+/// has already closed the rotation is skipped and the next rotation is
+/// scheduled from `now`
+pub async fn rotate_scheduled_secrets(
+    db: &DbPool,
+    functions: &RotationFunctions,
+    now: DateTime<Utc>,
+) -> Result<(), DbErr> {
+    let secret_arns = get_secrets_due_rotation(db, now).await?;
+
+    for (secret_arn, secret_region) in secret_arns {
+        let secret = match get_secret_latest_version(db, &secret_region, &secret_arn).await? {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let (Some(next_rotation_at), Ok(Some(schedule))) = (
+            secret.next_rotation_at,
+            RotationSchedule::from_rules(
+                secret.rotation_automatically_after_days,
+                secret.rotation_schedule_expression.as_deref(),
+            ),
+        ) else {
+            tracing::warn!(%secret_arn, "secret has an invalid rotation schedule");
+            continue;
+        };
+
+        let duration = secret
+            .rotation_duration
+            .as_deref()
+            .and_then(|duration| parse_rotation_duration(duration).ok());
+
+        // Rotation window has already closed
+        if now >= schedule.window_end(next_rotation_at, duration) {
+            tracing::debug!(%secret_arn, "skipping missed rotation window");
+            update_secret_next_rotation(db, &secret_arn, schedule.next_after(now)).await?;
+            continue;
+        }
+
+        tracing::debug!(%secret_arn, "performing scheduled rotation");
+
+        let version_id = Uuid::new_v4().to_string();
+        if let Err(error) = rotate_secret(db, functions, &secret, version_id).await {
+            tracing::error!(?error, %secret_arn, "failed to perform scheduled rotation");
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure the `secret` doesn't have an AWSPENDING version left behind by an
+/// incomplete rotation, other than the version `version_id` being rotated into
+async fn ensure_no_pending_rotation(
+    db: &DbPool,
+    secret: &StoredSecret,
+    version_id: &str,
+) -> Result<(), RotationError> {
+    let pending =
+        get_secret_by_version_stage(db, &secret.region, &secret.arn, "AWSPENDING").await?;
+
+    match pending {
+        Some(pending)
+            if pending.version_id != secret.version_id && pending.version_id != version_id =>
+        {
+            Err(RotationError::IncompleteRotation(pending.version_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Find how the new version of the `secret` is created, a mapped rotation
+/// function takes precedence over the built-in strategy of the secret
+fn find_rotator<'a>(
+    functions: &'a RotationFunctions,
+    secret: &StoredSecret,
+) -> Result<Rotator<'a>, RotationError> {
+    let arn = secret.rotation_lambda_arn.as_deref();
+
+    if let Some(function) = arn.and_then(|arn| functions.get(arn)) {
+        return Ok(Rotator::Function(function));
+    }
+
+    match RotationStrategy::from_secret(secret)? {
+        Some(strategy) => Ok(Rotator::Strategy(strategy)),
+        None => match arn {
+            Some(arn) => Err(RotationError::UnmappedFunction(arn.to_string())),
+            None => Err(RotationError::MissingFunction),
+        },
+    }
+}
+
+/// Perform an individual `step` of the rotation for the version `version_id`
+async fn perform_rotation_step(
+    db: &DbPool,
+    secret: &StoredSecret,
+    strategy: &RotationStrategy,
+    version_id: &str,
+    step: RotationStep,
+) -> Result<(), RotationError> {
+    match step {
+        RotationStep::Create => create_pending_version(db, secret, strategy, version_id).await,
+        // Nothing external needs to be updated for local rotations
+        RotationStep::Set | RotationStep::Test => Ok(()),
+        RotationStep::Finish => finish_pending_version(db, secret, version_id).await,
+    }
+}
+
+/// Creates the AWSPENDING version of the secret if it does not already exist,
+/// the new version takes the value created by the `strategy`
+async fn create_pending_version(
+    db: &DbPool,
+    secret: &StoredSecret,
+    strategy: &RotationStrategy,
+    version_id: &str,
+) -> Result<(), RotationError> {
+    // Version has already been created
+    if get_secret_by_version_id(db, &secret.region, &secret.arn, version_id)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let mut current = secret.clone();
+    decrypt_stored_secret(db, &mut current).await?;
+
+    let secret_string = strategy.rotate(&current)?;
+
+    // Encrypt the value using the KMS key of the secret
+    let value = encrypt_secret_value(
+        db,
+        &secret.region,
+        secret.kms_key_id.as_deref(),
+        Some(secret_string),
+        None,
+    )
+    .await?;
+
+    let mut t = db.begin().await?;
+
+    create_secret_version(
+        t.deref_mut(),
+        CreateSecretVersion {
+            secret_arn: secret.arn.clone(),
+            version_id: version_id.to_string(),
+            secret_string: value.secret_string,
+            secret_binary: value.secret_binary,
+            encrypted_data_key: value.encrypted_data_key,
+        },
+    )
+    .await?;
+
+    remove_secret_version_stage_any(t.deref_mut(), &secret.arn, "AWSPENDING").await?;
+    add_secret_version_stage(t.deref_mut(), &secret.arn, version_id, "AWSPENDING").await?;
+
+    t.commit().await?;
+
+    Ok(())
+}
+
+/// Promotes the pending version `version_id` to AWSCURRENT moving the AWSPREVIOUS
+/// stage onto the old current version
+async fn finish_pending_version(
+    db: &DbPool,
+    secret: &StoredSecret,
+    version_id: &str,
+) -> Result<(), RotationError> {
+    let pending = get_secret_by_version_id(db, &secret.region, &secret.arn, version_id)
+        .await?
+        .ok_or(RotationError::MissingPendingVersion)?;
+
+    let current =
+        get_secret_by_version_stage(db, &secret.region, &secret.arn, "AWSCURRENT").await?;
+
+    let mut t = db.begin().await?;
+
+    // Pending version is not already the current version
+    if !pending
+        .version_stages
+        .iter()
+        .any(|stage| stage == "AWSCURRENT")
+    {
+        if let Some(current) = current {
+            remove_secret_version_stage_any(t.deref_mut(), &secret.arn, "AWSPREVIOUS").await?;
+            add_secret_version_stage(
+                t.deref_mut(),
+                &secret.arn,
+                &current.version_id,
+                "AWSPREVIOUS",
+            )
+            .await?;
+            remove_secret_version_stage(
+                t.deref_mut(),
+                &secret.arn,
+                &current.version_id,
+                "AWSCURRENT",
+            )
+            .await?;
+        }
+
+        add_secret_version_stage(t.deref_mut(), &secret.arn, version_id, "AWSCURRENT").await?;
+    }
+
+    remove_secret_version_stage(t.deref_mut(), &secret.arn, version_id, "AWSPENDING").await?;
+
+    t.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Schedule determining when a secret should next be rotated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationSchedule {
+    /// Rotate at a fixed interval after the previous rotation
+    Rate(Duration),
+
+    /// Rotate based on a cron expression (minutes hours day-of-month month day-of-week year)
+    Cron(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("cannot specify both AutomaticallyAfterDays and ScheduleExpression")]
+    ConflictingRules,
+
+    #[error("invalid schedule expression")]
+    InvalidExpression,
+
+    #[error("invalid rotation window duration")]
+    InvalidDuration,
+}
+
+/// Minimum number of hours allowed between rotations for a rate expression
+const MIN_RATE_HOURS: i64 = 4;
+
+/// Maximum number of days allowed between rotations
+const MAX_RATE_DAYS: i64 = 1000;
+
+impl RotationSchedule {
+    /// Create a schedule from the rotation rules of a secret, provides [None]
+    /// when the rules don't specify a schedule
+    pub fn from_rules(
+        automatically_after_days: Option<i64>,
+        schedule_expression: Option<&str>,
+    ) -> Result<Option<RotationSchedule>, ScheduleError> {
+        match (automatically_after_days, schedule_expression) {
+            (Some(_), Some(_)) => Err(ScheduleError::ConflictingRules),
+            (Some(days), None) => {
+                if !(1..=MAX_RATE_DAYS).contains(&days) {
+                    return Err(ScheduleError::InvalidExpression);
+                }
+
+                Ok(Some(RotationSchedule::Rate(Duration::days(days))))
+            }
+            (None, Some(expression)) => parse_schedule_expression(expression).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Get the next date a rotation should occur given the date of the
+    /// previous rotation
+    pub fn next_after(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            RotationSchedule::Rate(interval) => from.checked_add_signed(*interval),
+            RotationSchedule::Cron(expression) => {
+                parse_cron_expression(expression).ok()?.after(&from).next()
+            }
+        }
+    }
+
+    /// Get the end of the rotation window that starts at `start`, rotations
+    /// that don't begin before the end of the window are skipped
+    ///
+    /// When no `duration` is specified the window for schedules in hours closes
+    /// after one hour and the window for schedules in days closes at the end
+    /// of the UTC day
+    pub fn window_end(&self, start: DateTime<Utc>, duration: Option<Duration>) -> DateTime<Utc> {
+        if let Some(duration) = duration {
+            return start + duration;
+        }
+
+        match self {
+            RotationSchedule::Rate(interval) if *interval < Duration::days(1) => {
+                start + Duration::hours(1)
+            }
+            _ => start
+                .date_naive()
+                .succ_opt()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+                .unwrap_or(start + Duration::days(1)),
+        }
+    }
+}
+
+/// Parse a rotation ScheduleExpression in either the `rate(value unit)` or
+/// `cron(expression)` format
+pub fn parse_schedule_expression(value: &str) -> Result<RotationSchedule, ScheduleError> {
+    let value = value.trim();
+
+    if let Some(rate) = value
+        .strip_prefix("rate(")
+        .and_then(|value| value.strip_suffix(')'))
+    {
+        return parse_rate_expression(rate);
+    }
+
+    if let Some(cron) = value
+        .strip_prefix("cron(")
+        .and_then(|value| value.strip_suffix(')'))
+    {
+        parse_cron_expression(cron)?;
+        return Ok(RotationSchedule::Cron(cron.to_string()));
+    }
+
+    Err(ScheduleError::InvalidExpression)
+}
+
+/// Parse the expression portion of a cron expression, AWS cron expressions
+/// have 6 fields and don't include the seconds field
+fn parse_cron_expression(value: &str) -> Result<Schedule, ScheduleError> {
+    if value.split_whitespace().count() != 6 {
+        return Err(ScheduleError::InvalidExpression);
+    }
+
+    Schedule::from_str(&format!("0 {value}")).map_err(|_| ScheduleError::InvalidExpression)
+}
+
+/// Parse the `value unit` portion of a rate expression
+fn parse_rate_expression(value: &str) -> Result<RotationSchedule, ScheduleError> {
+    let (amount, unit) = value
+        .trim()
+        .split_once(' ')
+        .ok_or(ScheduleError::InvalidExpression)?;
+
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| ScheduleError::InvalidExpression)?;
+
+    let interval = match unit.trim() {
+        "hour" | "hours" => {
+            if !(MIN_RATE_HOURS..=MAX_RATE_DAYS * 24).contains(&amount) {
+                return Err(ScheduleError::InvalidExpression);
+            }
+
+            Duration::hours(amount)
+        }
+        "day" | "days" => {
+            if !(1..=MAX_RATE_DAYS).contains(&amount) {
+                return Err(ScheduleError::InvalidExpression);
+            }
+
+            Duration::days(amount)
+        }
+        _ => return Err(ScheduleError::InvalidExpression),
+    };
+
+    Ok(RotationSchedule::Rate(interval))
+}
+
+/// Parse the rotation window Duration in the `{hours}h` format
+pub fn parse_rotation_duration(value: &str) -> Result<Duration, ScheduleError> {
+    let hours: i64 = value
+        .strip_suffix('h')
+        .ok_or(ScheduleError::InvalidDuration)?
+        .parse()
+        .map_err(|_| ScheduleError::InvalidDuration)?;
+
+    if !(1..=24).contains(&hours) {
+        return Err(ScheduleError::InvalidDuration);
+    }
+
+    Ok(Duration::hours(hours))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rate_days() {
+        assert_eq!(
+            parse_schedule_expression("rate(10 days)").unwrap(),
+            RotationSchedule::Rate(Duration::days(10))
+        );
+        assert_eq!(
+            parse_schedule_expression("rate(1 day)").unwrap(),
+            RotationSchedule::Rate(Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn test_rate_hours() {
+        assert_eq!(
+            parse_schedule_expression("rate(4 hours)").unwrap(),
+            RotationSchedule::Rate(Duration::hours(4))
+        );
+    }
+
+    #[test]
+    fn test_rate_out_of_range() {
+        assert!(parse_schedule_expression("rate(1 hour)").is_err());
+        assert!(parse_schedule_expression("rate(0 days)").is_err());
+        assert!(parse_schedule_expression("rate(1001 days)").is_err());
+        assert!(parse_schedule_expression("rate(10 weeks)").is_err());
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+        assert!(parse_schedule_expression("").is_err());
+        assert!(parse_schedule_expression("rate(10days)").is_err());
+        assert!(parse_schedule_expression("every 10 days").is_err());
+        assert!(parse_schedule_expression("cron(0 16 1,15 * ?)").is_err());
+    }
+
+    #[test]
+    fn test_conflicting_rules() {
+        assert!(matches!(
+            RotationSchedule::from_rules(Some(10), Some("rate(10 days)")),
+            Err(ScheduleError::ConflictingRules)
+        ));
+    }
+
+    #[test]
+    fn test_next_after_rate() {
+        let from = Utc.with_ymd_and_hms(2025, 10, 31, 12, 0, 0).unwrap();
+        let schedule = RotationSchedule::from_rules(Some(30), None)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            schedule.next_after(from),
+            Some(Utc.with_ymd_and_hms(2025, 11, 30, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_after_cron() {
+        let from = Utc.with_ymd_and_hms(2025, 10, 31, 12, 0, 0).unwrap();
+        let schedule = parse_schedule_expression("cron(0 16 1,15 * ? *)").unwrap();
+
+        assert_eq!(
+            schedule.next_after(from),
+            Some(Utc.with_ymd_and_hms(2025, 11, 1, 16, 0, 0).unwrap())
+        );
+
+        let schedule = parse_schedule_expression("cron(30 8 ? * MON *)").unwrap();
+
+        assert_eq!(
+            schedule.next_after(from),
+            Some(Utc.with_ymd_and_hms(2025, 11, 3, 8, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invalid_cron_expression() {
+        assert!(parse_schedule_expression("cron(0 25 * * ? *)").is_err());
+        assert!(parse_schedule_expression("cron(every day)").is_err());
+    }
+
+    #[test]
+    fn test_window_end() {
+        let start = Utc.with_ymd_and_hms(2025, 10, 31, 12, 0, 0).unwrap();
+
+        let schedule = parse_schedule_expression("rate(10 days)").unwrap();
+        assert_eq!(
+            schedule.window_end(start, Some(Duration::hours(3))),
+            Utc.with_ymd_and_hms(2025, 10, 31, 15, 0, 0).unwrap()
+        );
+        assert_eq!(
+            schedule.window_end(start, None),
+            Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap()
+        );
+
+        let schedule = parse_schedule_expression("rate(4 hours)").unwrap();
+        assert_eq!(
+            schedule.window_end(start, None),
+            Utc.with_ymd_and_hms(2025, 10, 31, 13, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rotation_duration() {
+        assert_eq!(parse_rotation_duration("3h").unwrap(), Duration::hours(3));
+        assert_eq!(parse_rotation_duration("24h").unwrap(), Duration::hours(24));
+        assert!(parse_rotation_duration("0h").is_err());
+        assert!(parse_rotation_duration("25h").is_err());
+        assert!(parse_rotation_duration("3").is_err());
+    }
+}
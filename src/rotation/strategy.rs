@@ -0,0 +1,215 @@
+use crate::{
+    database::secrets::StoredSecret,
+    handlers::get_random_password::{PasswordOptions, RandomPasswordError, get_random_password},
+};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Reserved tag for selecting the built-in rotation strategy of a secret
+pub const ROTATION_STRATEGY_TAG: &str = "loker:rotation:strategy";
+
+/// Reserved tag for the JSON key to regenerate when using the JSON key strategy
+pub const ROTATION_JSON_KEY_TAG: &str = "loker:rotation:json-key";
+
+/// Reserved tag for the length of generated passwords
+pub const ROTATION_PASSWORD_LENGTH_TAG: &str = "loker:rotation:password-length";
+
+/// Reserved tag for characters to exclude from generated passwords
+pub const ROTATION_EXCLUDE_CHARACTERS_TAG: &str = "loker:rotation:exclude-characters";
+
+/// Prefix for rotation function names that select a built-in strategy instead
+/// of a rotation function (i.e `function:loker-rotation-password`)
+const ROTATION_FUNCTION_PREFIX: &str = "loker-rotation-";
+
+/// JSON key that is regenerated when no key is specified
+const DEFAULT_JSON_KEY: &str = "password";
+
+/// Built-in strategy for rotating a secret without a rotation function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationStrategy {
+    /// Replace the whole secret string with a new password
+    Password,
+    /// Replace the value of a single key in a JSON secret string with a
+    /// new password
+    JsonKey(String),
+}
+
+#[derive(Debug, Error)]
+pub enum StrategyError {
+    #[error("unknown rotation strategy")]
+    UnknownStrategy,
+
+    #[error("invalid rotation password length")]
+    InvalidPasswordLength,
+
+    #[error("secret does not have a secret string to rotate")]
+    MissingSecretString,
+
+    #[error("secret string is not a JSON object")]
+    InvalidJson,
+
+    #[error(transparent)]
+    Password(#[from] RandomPasswordError),
+}
+
+impl RotationStrategy {
+    /// Determine the built-in strategy for the `secret` from its reserved tag
+    /// or otherwise from the name of its rotation function
+    pub fn from_secret(secret: &StoredSecret) -> Result<Option<RotationStrategy>, StrategyError> {
+        let name = match get_tag(secret, ROTATION_STRATEGY_TAG) {
+            Some(value) => value,
+            None => match secret
+                .rotation_lambda_arn
+                .as_deref()
+                .and_then(get_function_name)
+                .and_then(|name| name.strip_prefix(ROTATION_FUNCTION_PREFIX))
+            {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+
+        let strategy = match name {
+            "password" => RotationStrategy::Password,
+            "json-key" => {
+                let key = get_tag(secret, ROTATION_JSON_KEY_TAG).unwrap_or(DEFAULT_JSON_KEY);
+                RotationStrategy::JsonKey(key.to_string())
+            }
+            _ => return Err(StrategyError::UnknownStrategy),
+        };
+
+        Ok(Some(strategy))
+    }
+
+    /// Create the new secret string for the `secret` using this strategy
+    pub fn rotate(&self, secret: &StoredSecret) -> Result<String, StrategyError> {
+        let options = password_options(secret)?;
+
+        match self {
+            RotationStrategy::Password => Ok(get_random_password(options)?),
+            RotationStrategy::JsonKey(key) => {
+                let secret_string = secret
+                    .secret_string
+                    .as_deref()
+                    .ok_or(StrategyError::MissingSecretString)?;
+                let password = get_random_password(options)?;
+                replace_json_key(secret_string, key, password)
+            }
+        }
+    }
+}
+
+/// Get the value of the tag with the provided `key` from the `secret`
+fn get_tag<'a>(secret: &'a StoredSecret, key: &str) -> Option<&'a str> {
+    secret
+        .version_tags
+        .iter()
+        .find(|tag| tag.key == key)
+        .map(|tag| tag.value.as_str())
+}
+
+/// Get the function name portion of a Lambda function ARN excluding any
+/// version or alias qualifier
+fn get_function_name(arn: &str) -> Option<&str> {
+    let mut parts = arn.split(':');
+    match parts.nth(5) {
+        Some("function") => parts.next(),
+        _ => None,
+    }
+}
+
+/// Create the password generation options from the reserved tags on the `secret`
+fn password_options(secret: &StoredSecret) -> Result<PasswordOptions, StrategyError> {
+    let mut options = PasswordOptions::default();
+
+    if let Some(length) = get_tag(secret, ROTATION_PASSWORD_LENGTH_TAG) {
+        options.password_length = length
+            .parse::<usize>()
+            .ok()
+            .filter(|length| (1..=4096).contains(length))
+            .ok_or(StrategyError::InvalidPasswordLength)?;
+    }
+
+    if let Some(exclude_characters) = get_tag(secret, ROTATION_EXCLUDE_CHARACTERS_TAG) {
+        options.exclude_characters = exclude_characters.to_string();
+    }
+
+    Ok(options)
+}
+
+/// Replace the value of `key` in the JSON object `secret_string` with `value`
+fn replace_json_key(
+    secret_string: &str,
+    key: &str,
+    value: String,
+) -> Result<String, StrategyError> {
+    let mut object = match serde_json::from_str::<Value>(secret_string) {
+        Ok(Value::Object(object)) => object,
+        _ => return Err(StrategyError::InvalidJson),
+    };
+
+    object.insert(key.to_string(), Value::String(value));
+
+    serde_json::to_string(&object).map_err(|_| StrategyError::InvalidJson)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_function_name() {
+        assert_eq!(
+            get_function_name(
+                "arn:aws:lambda:us-east-1:123456789012:function:loker-rotation-password"
+            ),
+            Some("loker-rotation-password")
+        );
+        assert_eq!(
+            get_function_name("arn:aws:lambda:us-east-1:123456789012:function:rotate:1"),
+            Some("rotate")
+        );
+        assert_eq!(
+            get_function_name("arn:aws:lambda:us-east-1:123456789012:layer:rotate"),
+            None
+        );
+        assert_eq!(get_function_name("rotate"), None);
+    }
+
+    #[test]
+    fn test_replace_json_key() {
+        let value = replace_json_key(
+            r#"{"username":"admin","password":"old"}"#,
+            "password",
+            "new".to_string(),
+        )
+        .unwrap();
+
+        let value: Value = serde_json::from_str(&value).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "username": "admin", "password": "new" })
+        );
+    }
+
+    #[test]
+    fn test_replace_json_key_missing_key() {
+        let value =
+            replace_json_key(r#"{"username":"admin"}"#, "password", "new".to_string()).unwrap();
+
+        let value: Value = serde_json::from_str(&value).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "username": "admin", "password": "new" })
+        );
+    }
+
+    #[test]
+    fn test_replace_json_key_not_object() {
+        let error = replace_json_key(r#"["admin"]"#, "password", "new".to_string()).unwrap_err();
+        assert!(matches!(error, StrategyError::InvalidJson));
+
+        let error = replace_json_key("admin", "password", "new".to_string()).unwrap_err();
+        assert!(matches!(error, StrategyError::InvalidJson));
+    }
+}
@@ -0,0 +1,86 @@
+use rand::seq::IndexedRandom;
+
+/// Prefix for generated access key IDs, matches the prefix AWS uses for
+/// long-term access keys
+const ACCESS_KEY_ID_PREFIX: &str = "AKIA";
+
+/// Prefix for generated temporary access key IDs, matches the prefix AWS
+/// uses for access keys issued by STS
+const SESSION_ACCESS_KEY_ID_PREFIX: &str = "ASIA";
+
+/// Characters used in generated access key IDs
+const ACCESS_KEY_ID_CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Characters used in generated access key secrets
+const ACCESS_KEY_SECRET_CHARACTERS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Generate a new random access key ID
+pub fn generate_access_key_id() -> String {
+    let mut value = String::from(ACCESS_KEY_ID_PREFIX);
+    value.push_str(&random_string(ACCESS_KEY_ID_CHARACTERS, 16));
+    value
+}
+
+/// Generate a new random temporary access key ID
+pub fn generate_session_access_key_id() -> String {
+    let mut value = String::from(SESSION_ACCESS_KEY_ID_PREFIX);
+    value.push_str(&random_string(ACCESS_KEY_ID_CHARACTERS, 16));
+    value
+}
+
+/// Generate a new random access key secret
+pub fn generate_access_key_secret() -> String {
+    random_string(ACCESS_KEY_SECRET_CHARACTERS, 40)
+}
+
+/// Generate a new random session token for temporary credentials
+pub fn generate_session_token() -> String {
+    random_string(ACCESS_KEY_SECRET_CHARACTERS, 256)
+}
+
+fn random_string(characters: &[u8], length: usize) -> String {
+    let mut rng = rand::rng();
+    (0..length)
+        .filter_map(|_| characters.choose(&mut rng))
+        .map(|value| *value as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_access_key_id() {
+        let value = generate_access_key_id();
+        assert_eq!(value.len(), 20);
+        assert!(value.starts_with("AKIA"));
+        assert!(
+            value
+                .bytes()
+                .all(|value| ACCESS_KEY_ID_CHARACTERS.contains(&value))
+        );
+    }
+
+    #[test]
+    fn test_generate_session_access_key_id() {
+        let value = generate_session_access_key_id();
+        assert_eq!(value.len(), 20);
+        assert!(value.starts_with("ASIA"));
+    }
+
+    #[test]
+    fn test_generate_session_token() {
+        let value = generate_session_token();
+        assert_eq!(value.len(), 256);
+        assert_ne!(value, generate_session_token());
+    }
+
+    #[test]
+    fn test_generate_access_key_secret() {
+        let value = generate_access_key_secret();
+        assert_eq!(value.len(), 40);
+        assert_ne!(value, generate_access_key_secret());
+    }
+}
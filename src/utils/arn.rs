@@ -0,0 +1,21 @@
+/// Account ID used when no account is configured
+pub const DEFAULT_ACCOUNT_ID: &str = "000000000000";
+
+/// Checks if the provided value is a valid 12-digit AWS account ID
+pub fn is_valid_account_id(value: &str) -> bool {
+    value.len() == 12 && value.bytes().all(|value| value.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_account_id() {
+        assert!(is_valid_account_id("123456789012"));
+        assert!(is_valid_account_id(DEFAULT_ACCOUNT_ID));
+        assert!(!is_valid_account_id("1"));
+        assert!(!is_valid_account_id("12345678901a"));
+        assert!(!is_valid_account_id("1234567890123"));
+    }
+}
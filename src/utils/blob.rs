@@ -0,0 +1,58 @@
+//! Serialization of binary data, text formats (JSON) base64 encode the data
+//! while binary formats (CBOR) use the raw bytes
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use serde::{
+    Deserialize, Deserializer, Serializer,
+    de::{Error, Visitor},
+};
+use std::fmt;
+
+/// Serialize the binary `value` as base64 for text formats and as raw bytes
+/// for binary formats
+pub fn serialize_blob<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        return serializer.serialize_str(&BASE64_STANDARD.encode(value));
+    }
+
+    serializer.serialize_bytes(value)
+}
+
+/// Deserialize binary data that is base64 encoded for text formats and raw
+/// bytes for binary formats
+pub fn deserialize_blob<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let value = String::deserialize(deserializer)?;
+        return BASE64_STANDARD.decode(value).map_err(Error::custom);
+    }
+
+    deserializer.deserialize_byte_buf(BlobVisitor)
+}
+
+struct BlobVisitor;
+
+impl<'de> Visitor<'de> for BlobVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("binary data")
+    }
+
+    fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        BASE64_STANDARD.decode(value).map_err(Error::custom)
+    }
+}
@@ -1,6 +1,11 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
+use ciborium::tag::Required;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 
+/// CBOR tag for a date and time represented as seconds since the epoch
+const CBOR_EPOCH_DATE_TIME_TAG: u64 = 1;
+
 /// Turn the provided DateTime into a f64 representing the seconds with fractional
 /// seconds for the sub-second milliseconds
 pub fn datetime_to_f64(dt: DateTime<Utc>) -> f64 {
@@ -9,6 +14,37 @@ pub fn datetime_to_f64(dt: DateTime<Utc>) -> f64 {
     seconds + millis
 }
 
+/// Date and time in a response, serialized as seconds since the epoch with
+/// fractional milliseconds. Binary formats (CBOR) tag the value as an epoch
+/// based date and time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let value = datetime_to_f64(self.0);
+
+        if serializer.is_human_readable() {
+            return serializer.serialize_f64(value);
+        }
+
+        Required::<f64, CBOR_EPOCH_DATE_TIME_TAG>(value).serialize(serializer)
+    }
+}
+
+/// Turn the provided seconds with fractional milliseconds into a DateTime,
+/// provides [None] when the value is out of range
+pub fn f64_to_datetime(value: f64) -> Option<DateTime<Utc>> {
+    if !value.is_finite() {
+        return None;
+    }
+
+    DateTime::from_timestamp_millis((value * 1000.0).round() as i64)
+}
+
 #[derive(Debug, Error)]
 pub enum AmzDateError {
     #[error(transparent)]
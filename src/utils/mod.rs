@@ -1,4 +1,7 @@
+pub mod access_key;
+pub mod arn;
 pub mod aws_sig_v4;
+pub mod blob;
 pub mod date;
 pub mod filter;
 pub mod string;
@@ -1,17 +1,50 @@
-use axum::{Extension, Router, routing::post_service};
+use aws_sigv4::{
+    http_request::{SignableBody, SignableRequest, SigningSettings, sign},
+    sign::v4,
+};
+use axum::{
+    Extension, Router,
+    http::{
+        HeaderMap, Request, StatusCode,
+        header::{ACCEPT, CONTENT_TYPE},
+    },
+    routing::post_service,
+};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
 use loker::{
     database::{DbPool, initialize_database},
-    handlers::{self},
-    middleware::aws_sig_v4::{AwsCredential, AwsSigV4AuthLayer},
+    handlers::{
+        self,
+        fault_injection::{FaultInjectionLayer, FaultInjector},
+        lambda_extension::{LambdaExtensionOptions, lambda_extension_router},
+        quotas::Quotas,
+        vault::{VaultOptions, vault_router},
+    },
+    middleware::{
+        aws_sig_v4::{AwsCredential, AwsCredentials, AwsSigV4AuthLayer},
+        request_id::RequestIdLayer,
+    },
+    rotation::function::RotationFunctions,
 };
+use serde_json::Value;
 use sqlx::sqlite::SqlitePoolOptions;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tower::Layer;
 
 use aws_config::{BehaviorVersion, Region, SdkConfig};
-use aws_sdk_secretsmanager::config::{Credentials, SharedCredentialsProvider};
+use aws_sdk_secretsmanager::{
+    config::{Credentials, SharedCredentialsProvider},
+    error::{ProvideErrorMetadata, SdkError},
+};
 use tokio::task::AbortHandle;
 
 const TEST_ACCESS_KEY_ID: &str = "test";
 const TEST_ACCESS_KEY_SECRET: &str = "test";
+pub const TEST_PRINCIPAL_ARN: &str = "arn:aws:iam::123456789012:user/test";
 
 /// Create an AWS sdk config for use in tests
 #[allow(dead_code)]
@@ -35,13 +68,173 @@ pub fn test_sdk_config(endpoint_url: &str) -> SdkConfig {
 #[allow(dead_code)]
 pub struct TestServer {
     sdk_config: SdkConfig,
+    /// URL of the AWS compatible endpoint
+    pub endpoint_url: String,
     pub db: DbPool,
     handle: AbortHandle,
+    /// URL of the Lambda extension endpoint when enabled
+    pub lambda_extension_url: Option<String>,
+    /// URL of the Vault endpoint when enabled
+    pub vault_url: Option<String>,
+    /// Handles for the additional endpoint servers
+    endpoint_handles: Vec<AbortHandle>,
+}
+
+impl TestServer {
+    /// Get the SDK config for the server that signs requests using the test
+    /// access key
+    #[allow(dead_code)]
+    pub fn sdk_config(&self) -> &SdkConfig {
+        &self.sdk_config
+    }
+
+    /// Get the SDK config for the server that signs requests using the
+    /// provided `credentials`
+    #[allow(dead_code)]
+    pub fn sdk_config_with_credentials(&self, credentials: Credentials) -> SdkConfig {
+        self.sdk_config
+            .to_builder()
+            .credentials_provider(SharedCredentialsProvider::new(credentials))
+            .build()
+    }
+
+    /// Create a client for the server that signs requests using the
+    /// provided access key
+    #[allow(dead_code)]
+    pub fn client_with_credentials(
+        &self,
+        access_key_id: &str,
+        access_key_secret: &str,
+    ) -> aws_sdk_secretsmanager::Client {
+        let credentials = Credentials::new(access_key_id, access_key_secret, None, None, "test");
+        aws_sdk_secretsmanager::Client::new(&self.sdk_config_with_credentials(credentials))
+    }
+
+    /// Send a JSON request for the `target` operation signed using the test
+    /// access key, used for operations that aren't part of the SDK
+    #[allow(dead_code)]
+    pub async fn json_request(&self, target: &str, body: Value) -> (StatusCode, Value) {
+        self.json_request_with_credentials(TEST_ACCESS_KEY_ID, TEST_ACCESS_KEY_SECRET, target, body)
+            .await
+    }
+
+    /// Send a JSON request for the `target` operation signed using the
+    /// provided access key
+    #[allow(dead_code)]
+    pub async fn json_request_with_credentials(
+        &self,
+        access_key_id: &str,
+        access_key_secret: &str,
+        target: &str,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let body = serde_json::to_vec(&body).unwrap();
+
+        let request = Request::post(&self.endpoint_url)
+            .header(CONTENT_TYPE, "application/x-amz-json-1.1")
+            .header("x-amz-target", target)
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+
+        let (status, _headers, body) = self
+            .signed_request(access_key_id, access_key_secret, request)
+            .await;
+        let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+        (status, body)
+    }
+
+    /// Send a Smithy RPC v2 CBOR request for the `operation` of the `service`
+    /// signed using the test access key, a null `body` sends the request
+    /// without a body
+    #[allow(dead_code)]
+    pub async fn cbor_request(
+        &self,
+        service: &str,
+        operation: &str,
+        body: ciborium::Value,
+    ) -> (StatusCode, HeaderMap, ciborium::Value) {
+        let mut request_body = Vec::new();
+        if !body.is_null() {
+            ciborium::into_writer(&body, &mut request_body).unwrap();
+        }
+
+        let url = format!(
+            "{}service/{service}/operation/{operation}",
+            self.endpoint_url
+        );
+
+        let request = Request::post(url)
+            .header(CONTENT_TYPE, "application/cbor")
+            .header(ACCEPT, "application/cbor")
+            .header("smithy-protocol", "rpc-v2-cbor")
+            .body(Full::new(Bytes::from(request_body)))
+            .unwrap();
+
+        let (status, headers, body) = self
+            .signed_request(TEST_ACCESS_KEY_ID, TEST_ACCESS_KEY_SECRET, request)
+            .await;
+        let body = ciborium::from_reader(body.as_ref()).unwrap_or(ciborium::Value::Null);
+
+        (status, headers, body)
+    }
+
+    /// Sign the `request` using the provided access key and send it to the
+    /// server
+    async fn signed_request(
+        &self,
+        access_key_id: &str,
+        access_key_secret: &str,
+        mut request: Request<Full<Bytes>>,
+    ) -> (StatusCode, HeaderMap, Bytes) {
+        let body = request.body().clone().collect().await.unwrap().to_bytes();
+        let uri = request.uri().to_string();
+
+        let identity =
+            Credentials::new(access_key_id, access_key_secret, None, None, "test").into();
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region("us-east-1")
+            .name("secretsmanager")
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .unwrap()
+            .into();
+
+        let signable_request = SignableRequest::new(
+            "POST",
+            &uri,
+            request
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.to_str().unwrap())),
+            SignableBody::Bytes(&body),
+        )
+        .unwrap();
+
+        let (instructions, _signature) = sign(signable_request, &signing_params)
+            .unwrap()
+            .into_parts();
+        instructions.apply_to_request_http1x(&mut request);
+
+        let client = Client::builder(TokioExecutor::new()).build_http();
+        let response = client.request(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        (status, headers, body)
+    }
 }
 
 impl Drop for TestServer {
     fn drop(&mut self) {
         self.handle.abort();
+
+        for handle in &self.endpoint_handles {
+            handle.abort();
+        }
     }
 }
 
@@ -67,35 +260,177 @@ async fn memory_database() -> DbPool {
 
 #[allow(dead_code)]
 pub async fn test_server() -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_rotation_functions(RotationFunctions::default()).await
+}
+
+/// Create a test server that invokes the provided local `rotation_functions`
+#[allow(dead_code)]
+pub async fn test_server_with_rotation_functions(
+    rotation_functions: RotationFunctions,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_options(TestServerOptions {
+        rotation_functions,
+        ..Default::default()
+    })
+    .await
+}
+
+/// Create a test server that enforces the resource policies of secrets
+/// against the [TEST_PRINCIPAL_ARN]
+#[allow(dead_code)]
+pub async fn test_server_with_resource_policies() -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_options(TestServerOptions {
+        enforce_resource_policies: true,
+        ..Default::default()
+    })
+    .await
+}
+
+/// Options for creating a test server
+#[derive(Default)]
+pub struct TestServerOptions {
+    /// Local rotation functions the server can invoke
+    pub rotation_functions: RotationFunctions,
+    /// Whether to enforce the resource policies of secrets
+    pub enforce_resource_policies: bool,
+    /// Whether to enforce the service quotas and rate limits
+    pub strict_quotas: bool,
+    /// Additional credentials the server accepts
+    pub credentials: Vec<AwsCredential>,
+    /// Options for serving the Lambda extension endpoint
+    pub lambda_extension: Option<LambdaExtensionOptions>,
+    /// Options for serving the Vault endpoint
+    pub vault: Option<VaultOptions>,
+}
+
+/// Create a test server using the provided `options`
+#[allow(dead_code)]
+pub async fn test_server_with_options(
+    options: TestServerOptions,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    let TestServerOptions {
+        rotation_functions,
+        enforce_resource_policies,
+        strict_quotas,
+        credentials: extra_credentials,
+        lambda_extension,
+        vault,
+    } = options;
+
+    let mut credentials = AwsCredentials::default();
+    credentials.insert(AwsCredential {
+        name: "test".to_string(),
+        access_key_id: TEST_ACCESS_KEY_ID.to_string(),
+        access_key_secret: TEST_ACCESS_KEY_SECRET.to_string(),
+        principal_arn: Some(TEST_PRINCIPAL_ARN.to_string()),
+        expires_at: None,
+        enabled: true,
+        policy: None,
+        source_policies: Vec::new(),
+    });
+
+    for credential in extra_credentials {
+        credentials.insert(credential);
+    }
+
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let db = memory_database().await;
     let server_address = listener.local_addr().unwrap();
     let harness_db = db.clone();
 
+    let fault_injector = Arc::new(FaultInjector::default());
+    let handlers = handlers::create_handlers(
+        Arc::new(rotation_functions),
+        fault_injector.clone(),
+        Quotas::new(strict_quotas),
+        enforce_resource_policies,
+    );
+    let handlers_service = handlers.into_service();
+
+    let mut endpoint_handles = Vec::new();
+
+    let lambda_extension_url = match lambda_extension {
+        Some(options) => {
+            let app = lambda_extension_router(handlers_service.clone(), options)
+                .layer(Extension(db.clone()));
+            let (url, handle) = spawn_endpoint(app).await;
+            endpoint_handles.push(handle);
+            Some(url)
+        }
+        None => None,
+    };
+
+    let vault_url = match vault {
+        Some(options) => {
+            let app = vault_router(handlers_service.clone(), options).layer(Extension(db.clone()));
+            let (url, handle) = spawn_endpoint(app).await;
+            endpoint_handles.push(handle);
+            Some(url)
+        }
+        None => None,
+    };
+
+    let handlers_service = FaultInjectionLayer::new(fault_injector).layer(handlers_service);
+
     let abort_handle = tokio::spawn(async move {
-        let handlers = handlers::create_handlers();
-        let handlers_service = handlers.into_service();
         let app = Router::new()
-            .route_service("/", post_service(handlers_service))
-            .layer(AwsSigV4AuthLayer::new(AwsCredential::new(
-                TEST_ACCESS_KEY_ID.to_string(),
-                TEST_ACCESS_KEY_SECRET.to_string(),
-            )))
-            .layer(Extension(db.clone()));
+            .route_service("/", post_service(handlers_service.clone()))
+            // Smithy RPC v2 CBOR requests provide the operation in the path
+            .route_service(
+                "/service/{service}/operation/{operation}",
+                post_service(handlers_service),
+            )
+            .layer(AwsSigV4AuthLayer::new(credentials))
+            .layer(Extension(db.clone()))
+            .layer(RequestIdLayer);
 
-        axum::serve(listener, app).await.unwrap();
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
     })
     .abort_handle();
 
-    let sdk_config = test_sdk_config(&format!("http://{server_address}/"));
+    let endpoint_url = format!("http://{server_address}/");
+    let sdk_config = test_sdk_config(&endpoint_url);
     let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
 
     (
         client,
         TestServer {
             sdk_config,
+            endpoint_url,
             handle: abort_handle,
             db: harness_db,
+            lambda_extension_url,
+            vault_url,
+            endpoint_handles,
         },
     )
 }
+
+/// Serve the `app` of an additional endpoint on a random port, returning the
+/// URL of the endpoint
+async fn spawn_endpoint(app: Router) -> (String, AbortHandle) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    })
+    .abort_handle();
+
+    (url, handle)
+}
+
+/// Get the error code from the `result` of a request
+#[allow(dead_code)]
+pub fn error_code<T, E: ProvideErrorMetadata, R>(result: Result<T, SdkError<E, R>>) -> String {
+    match result {
+        Ok(_) => panic!("expected error"),
+        Err(SdkError::ServiceError(error)) => error.into_err().code().unwrap().to_string(),
+        Err(_) => panic!("expected service error"),
+    }
+}
@@ -0,0 +1,243 @@
+use aws_sdk_secretsmanager::error::{ProvideErrorMetadata, SdkError};
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use loker::database::access_keys::{
+    CreateAccessKey, create_access_key, get_access_key, update_access_key_enabled,
+};
+use serde_json::{Value, json};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests creating an access key through the admin API and using it to
+/// sign requests without restarting the server
+#[tokio::test]
+async fn test_create_access_key() {
+    let (_client, server) = test_server().await;
+
+    let expiration_date = (Utc::now() + Duration::days(1)).timestamp() as f64;
+    let (status, body) = server
+        .json_request(
+            "loker.CreateAccessKey",
+            json!({ "Name": "ci", "ExpirationDate": expiration_date }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK);
+
+    let access_key = &body["AccessKey"];
+    let access_key_id = access_key["AccessKeyId"].as_str().unwrap();
+    let access_key_secret = access_key["SecretAccessKey"].as_str().unwrap();
+
+    assert!(access_key_id.starts_with("AKIA"));
+    assert_eq!(access_key_secret.len(), 40);
+    assert_eq!(access_key["Name"], "ci");
+    assert_eq!(
+        access_key["PrincipalArn"],
+        "arn:aws:iam::123456789012:user/ci"
+    );
+    assert_eq!(access_key["Status"], "Active");
+    assert_eq!(access_key["ExpirationDate"], expiration_date);
+
+    let client = server.client_with_credentials(access_key_id, access_key_secret);
+    client.list_secrets().send().await.unwrap();
+}
+
+/// Tests that access keys that are already expired can't be created
+#[tokio::test]
+async fn test_create_access_key_expired() {
+    let (_client, server) = test_server().await;
+
+    let expiration_date = (Utc::now() - Duration::days(1)).timestamp() as f64;
+    let (status, body) = server
+        .json_request(
+            "loker.CreateAccessKey",
+            json!({ "Name": "ci", "ExpirationDate": expiration_date }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "InvalidParameterException");
+    assert_eq!(body["message"], "ExpirationDate must be in the future.");
+}
+
+/// Tests listing the access keys stored in the database
+#[tokio::test]
+async fn test_list_access_keys() {
+    let (_client, server) = test_server().await;
+
+    for name in ["service", "ci"] {
+        let (status, _body) = server
+            .json_request(
+                "loker.CreateAccessKey",
+                json!({ "Name": name, "PrincipalArn": format!("arn:aws:iam::123456789012:role/{name}") }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = server.json_request("loker.ListAccessKeys", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let access_keys = body["AccessKeyMetadata"].as_array().unwrap();
+    assert_eq!(access_keys.len(), 2);
+
+    assert_eq!(access_keys[0]["Name"], "service");
+    assert_eq!(
+        access_keys[0]["PrincipalArn"],
+        "arn:aws:iam::123456789012:role/service"
+    );
+    assert_eq!(access_keys[1]["Name"], "ci");
+    assert_eq!(
+        access_keys[1]["PrincipalArn"],
+        "arn:aws:iam::123456789012:role/ci"
+    );
+
+    for access_key in access_keys {
+        assert_eq!(access_key["Status"], "Active");
+        assert_eq!(access_key["LastUsedDate"], Value::Null);
+        // Secrets are only provided when the access key is created
+        assert!(access_key.get("SecretAccessKey").is_none());
+    }
+}
+
+/// Tests disabling and re-enabling an access key through the admin API
+#[tokio::test]
+async fn test_update_access_key() {
+    let (_client, server) = test_server().await;
+
+    let (_status, body) = server
+        .json_request("loker.CreateAccessKey", json!({ "Name": "ci" }))
+        .await;
+    let access_key_id = body["AccessKey"]["AccessKeyId"].as_str().unwrap();
+    let access_key_secret = body["AccessKey"]["SecretAccessKey"].as_str().unwrap();
+    let client = server.client_with_credentials(access_key_id, access_key_secret);
+
+    let (status, _body) = server
+        .json_request(
+            "loker.UpdateAccessKey",
+            json!({ "AccessKeyId": access_key_id, "Status": "Inactive" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let error = client.list_secrets().send().await.unwrap_err();
+    let error = match error {
+        SdkError::ServiceError(error) => error.into_err(),
+        _ => panic!("expected service error"),
+    };
+    assert_eq!(error.code(), Some("InvalidClientTokenId"));
+
+    let (status, _body) = server
+        .json_request(
+            "loker.UpdateAccessKey",
+            json!({ "AccessKeyId": access_key_id, "Status": "Active" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    client.list_secrets().send().await.unwrap();
+}
+
+/// Tests deleting an access key through the admin API
+#[tokio::test]
+async fn test_delete_access_key() {
+    let (_client, server) = test_server().await;
+
+    let (_status, body) = server
+        .json_request("loker.CreateAccessKey", json!({ "Name": "ci" }))
+        .await;
+    let access_key_id = body["AccessKey"]["AccessKeyId"].as_str().unwrap();
+    let access_key_secret = body["AccessKey"]["SecretAccessKey"].as_str().unwrap();
+    let client = server.client_with_credentials(access_key_id, access_key_secret);
+
+    let (status, _body) = server
+        .json_request(
+            "loker.DeleteAccessKey",
+            json!({ "AccessKeyId": access_key_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(client.list_secrets().send().await.is_err());
+
+    let (status, body) = server
+        .json_request(
+            "loker.DeleteAccessKey",
+            json!({ "AccessKeyId": access_key_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ResourceNotFoundException");
+}
+
+/// Tests that the last used date of an access key is tracked
+#[tokio::test]
+async fn test_get_access_key_last_used() {
+    let (_client, server) = test_server().await;
+
+    let (_status, body) = server
+        .json_request("loker.CreateAccessKey", json!({ "Name": "ci" }))
+        .await;
+    let access_key_id = body["AccessKey"]["AccessKeyId"].as_str().unwrap();
+    let access_key_secret = body["AccessKey"]["SecretAccessKey"].as_str().unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "loker.GetAccessKeyLastUsed",
+            json!({ "AccessKeyId": access_key_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Name"], "ci");
+    assert_eq!(body["LastUsedDate"], Value::Null);
+
+    let client = server.client_with_credentials(access_key_id, access_key_secret);
+    client.list_secrets().send().await.unwrap();
+
+    let (_status, body) = server
+        .json_request(
+            "loker.GetAccessKeyLastUsed",
+            json!({ "AccessKeyId": access_key_id }),
+        )
+        .await;
+    let last_used_date = body["LastUsedDate"].as_f64().unwrap();
+    assert!((Utc::now().timestamp() as f64 - last_used_date).abs() < 60.0);
+}
+
+/// Tests that access keys changed directly in the database, as the CLI
+/// does, apply without restarting the server
+#[tokio::test]
+async fn test_database_access_key_reload() {
+    let (_client, server) = test_server().await;
+
+    create_access_key(
+        &server.db,
+        CreateAccessKey {
+            access_key_id: "AKIADATABASE".to_string(),
+            access_key_secret: "database-secret".to_string(),
+            name: "database".to_string(),
+            principal_arn: None,
+            expires_at: None,
+            policy: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let client = server.client_with_credentials("AKIADATABASE", "database-secret");
+    client.list_secrets().send().await.unwrap();
+
+    let access_key = get_access_key(&server.db, "AKIADATABASE")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(access_key.last_used_at.is_some());
+
+    update_access_key_enabled(&server.db, "AKIADATABASE", false)
+        .await
+        .unwrap();
+
+    assert!(client.list_secrets().send().await.is_err());
+}
@@ -0,0 +1,246 @@
+use aws_sdk_secretsmanager::error::{ProvideErrorMetadata, SdkError};
+use chrono::{Duration, Utc};
+use loker::middleware::aws_sig_v4::{AwsCredential, AwsCredentials};
+use serde_json::json;
+
+use crate::common::{TestServerOptions, test_server_with_options};
+
+mod common;
+
+/// Create an enabled credential named `name` that uses the name as both
+/// the access key ID and secret
+fn credential(name: &str) -> AwsCredential {
+    AwsCredential {
+        name: name.to_string(),
+        access_key_id: name.to_string(),
+        access_key_secret: name.to_string(),
+        principal_arn: None,
+        expires_at: None,
+        enabled: true,
+        policy: None,
+        source_policies: Vec::new(),
+    }
+}
+
+/// Assert that the `result` of a request failed with the error `code`
+fn assert_error_code<T, E: ProvideErrorMetadata, R>(result: Result<T, SdkError<E, R>>, code: &str) {
+    let error = match result {
+        Ok(_) => panic!("expected {code} error"),
+        Err(SdkError::ServiceError(error)) => error.into_err(),
+        Err(_) => panic!("expected service error"),
+    };
+
+    assert_eq!(error.code(), Some(code));
+}
+
+/// Tests that requests signed with any of the access keys are accepted
+#[tokio::test]
+async fn test_multiple_access_keys() {
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential("service"), credential("ci")],
+        ..Default::default()
+    })
+    .await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    for name in ["service", "ci"] {
+        let client = server.client_with_credentials(name, name);
+        let response = client
+            .get_secret_value()
+            .secret_id("test")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.secret_string(), Some("test"));
+    }
+}
+
+/// Tests that requests using an unknown access key are rejected
+#[tokio::test]
+async fn test_unknown_access_key() {
+    let (_client, server) = test_server_with_options(TestServerOptions::default()).await;
+
+    let client = server.client_with_credentials("unknown", "unknown");
+    assert_error_code(client.list_secrets().send().await, "InvalidClientTokenId");
+}
+
+/// Tests that requests signed with the wrong secret for an access key
+/// are rejected
+#[tokio::test]
+async fn test_access_key_wrong_secret() {
+    let (_client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential("service")],
+        ..Default::default()
+    })
+    .await;
+
+    let client = server.client_with_credentials("service", "wrong");
+    assert_error_code(client.list_secrets().send().await, "SignatureDoesNotMatch");
+}
+
+/// Tests that disabled access keys are rejected
+#[tokio::test]
+async fn test_disabled_access_key() {
+    let (_client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![AwsCredential {
+            enabled: false,
+            ..credential("disabled")
+        }],
+        ..Default::default()
+    })
+    .await;
+
+    let client = server.client_with_credentials("disabled", "disabled");
+    assert_error_code(client.list_secrets().send().await, "InvalidClientTokenId");
+}
+
+/// Tests that access keys are rejected once they expire
+#[tokio::test]
+async fn test_expired_access_key() {
+    let (_client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![
+            AwsCredential {
+                expires_at: Some(Utc::now() - Duration::minutes(1)),
+                ..credential("expired")
+            },
+            AwsCredential {
+                expires_at: Some(Utc::now() + Duration::days(1)),
+                ..credential("active")
+            },
+        ],
+        ..Default::default()
+    })
+    .await;
+
+    let client = server.client_with_credentials("expired", "expired");
+    assert_error_code(client.list_secrets().send().await, "ExpiredTokenException");
+
+    let client = server.client_with_credentials("active", "active");
+    client.list_secrets().send().await.unwrap();
+}
+
+/// Tests that each access key makes requests as its own principal
+#[tokio::test]
+async fn test_access_key_principal() {
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential("service"), credential("ci")],
+        enforce_resource_policies: true,
+        ..Default::default()
+    })
+    .await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::000000000000:user/service" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    });
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(policy.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let service_client = server.client_with_credentials("service", "service");
+    service_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let ci_client = server.client_with_credentials("ci", "ci");
+    assert_error_code(
+        ci_client.get_secret_value().secret_id("test").send().await,
+        "AccessDeniedException",
+    );
+}
+
+/// Tests loading access keys from a JSON file
+#[tokio::test]
+async fn test_load_access_keys_file() {
+    let path =
+        std::env::temp_dir().join(format!("loker-access-keys-{}.json", uuid::Uuid::new_v4()));
+    let keys = json!([
+        {
+            "name": "service",
+            "access_key_id": "AKIASERVICE",
+            "access_key_secret": "service-secret"
+        },
+        {
+            "name": "ci",
+            "access_key_id": "AKIACI",
+            "access_key_secret": "ci-secret",
+            "principal_arn": "arn:aws:iam::123456789012:role/ci",
+            "expires_at": "2030-01-01T00:00:00Z",
+            "enabled": false
+        }
+    ]);
+    tokio::fs::write(&path, keys.to_string()).await.unwrap();
+
+    let credentials = AwsCredentials::load(&path).await;
+    _ = tokio::fs::remove_file(&path).await;
+    let credentials = credentials.unwrap();
+
+    let service = credentials.get("AKIASERVICE").unwrap();
+    assert_eq!(service.name, "service");
+    assert_eq!(service.access_key_secret, "service-secret");
+    assert_eq!(
+        service.principal_arn("000000000000"),
+        "arn:aws:iam::000000000000:user/service"
+    );
+    assert_eq!(service.expires_at, None);
+    assert!(service.enabled);
+
+    let ci = credentials.get("AKIACI").unwrap();
+    assert_eq!(
+        ci.principal_arn("000000000000"),
+        "arn:aws:iam::123456789012:role/ci"
+    );
+    assert_eq!(
+        ci.expires_at.unwrap().to_rfc3339(),
+        "2030-01-01T00:00:00+00:00"
+    );
+    assert!(!ci.enabled);
+
+    assert!(credentials.get("unknown").is_none());
+}
+
+/// Tests that access keys files with duplicate access key IDs are rejected
+#[tokio::test]
+async fn test_load_access_keys_file_duplicate() {
+    let path =
+        std::env::temp_dir().join(format!("loker-access-keys-{}.json", uuid::Uuid::new_v4()));
+    let keys = json!([
+        { "name": "a", "access_key_id": "AKIA", "access_key_secret": "a" },
+        { "name": "b", "access_key_id": "AKIA", "access_key_secret": "b" }
+    ]);
+    tokio::fs::write(&path, keys.to_string()).await.unwrap();
+
+    let credentials = AwsCredentials::load(&path).await;
+    _ = tokio::fs::remove_file(&path).await;
+
+    assert!(credentials.is_err());
+}
@@ -930,7 +930,7 @@ async fn test_batch_get_secret_value_find_missing_secrets() {
         .batch_get_secret_value()
         .secret_id_list("test-1")
         .secret_id_list("test-2")
-        .secret_id_list("arn:aws:secretsmanager:us-west-2:1:secret:test-test")
+        .secret_id_list("arn:aws:secretsmanager:us-west-2:123456789012:secret:test-test")
         .secret_id_list("test-4")
         .send()
         .await
@@ -964,7 +964,7 @@ async fn test_batch_get_secret_value_find_missing_secrets() {
     let error_3 = errors.next().unwrap();
     assert_eq!(
         error_3.secret_id(),
-        Some("arn:aws:secretsmanager:us-west-2:1:secret:test-test")
+        Some("arn:aws:secretsmanager:us-west-2:123456789012:secret:test-test")
     );
     assert_eq!(error_3.error_code(), Some("ResourceNotFoundException"));
 }
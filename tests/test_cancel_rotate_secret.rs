@@ -0,0 +1,129 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError,
+    operation::cancel_rotate_secret::CancelRotateSecretError,
+    types::{RotationRulesType, error::ResourceNotFoundException},
+};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Rotation function ARN selecting the built-in password rotation strategy
+const PASSWORD_STRATEGY_ARN: &str =
+    "arn:aws:lambda:us-east-1:123456789012:function:loker-rotation-password";
+
+/// Tests that cancelling rotation disables rotation but keeps the rotation rules
+#[tokio::test]
+async fn test_cancel_rotate_secret_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let cancel_response = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(cancel_response.arn(), create_response.arn());
+    assert_eq!(cancel_response.name(), create_response.name());
+
+    // No rotation was in progress
+    assert_eq!(cancel_response.version_id(), None);
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(false));
+    assert_eq!(describe_response.next_rotation_date(), None);
+    assert!(describe_response.last_rotated_date().is_some());
+    assert_eq!(
+        describe_response.rotation_rules(),
+        Some(
+            &RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build()
+        )
+    );
+}
+
+/// Tests that cancelling provides the version ID of an incomplete rotation
+#[tokio::test]
+async fn test_cancel_rotate_secret_pending_version() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let pending = client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-pending")
+        .version_stages("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    let cancel_response = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(cancel_response.version_id(), pending.version_id());
+}
+
+/// Tests that trying to cancel rotation of an unknown secret will fail
+#[tokio::test]
+async fn test_cancel_rotate_secret_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let cancel_err = client
+        .cancel_rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let cancel_err = match cancel_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match cancel_err.into_err() {
+        CancelRotateSecretError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected CancelRotateSecretError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}
@@ -1,3 +1,4 @@
+use aws_config::Region;
 use aws_sdk_secretsmanager::{
     error::SdkError,
     operation::create_secret::CreateSecretError,
@@ -7,9 +8,12 @@ use aws_sdk_secretsmanager::{
         error::{InvalidParameterException, InvalidRequestException, ResourceExistsException},
     },
 };
+use axum::http::StatusCode;
+use loker::middleware::aws_sig_v4::AwsCredential;
+use serde_json::json;
 use uuid::Uuid;
 
-use crate::common::test_server;
+use crate::common::{TestServerOptions, test_server, test_server_with_options};
 
 mod common;
 
@@ -364,6 +368,50 @@ async fn test_create_secret_value_name_length_validation_errors() {
     };
 }
 
+/// Tests that validation errors describe the invalid field
+#[tokio::test]
+async fn test_create_secret_validation_error_message() {
+    let (client, _server) = test_server().await;
+
+    let create_error = client
+        .create_secret()
+        .name("t".repeat(2049))
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let create_error = match create_error {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let exception: InvalidParameterException = match create_error.into_err() {
+        CreateSecretError::InvalidParameterException(error) => error,
+        error => panic!("expected CreateSecretError::InvalidParameterException got {error:?}"),
+    };
+
+    assert_eq!(
+        exception.message(),
+        Some(
+            "1 validation error detected: Value at 'name' failed to satisfy constraint: \
+             length is greater than 512"
+        )
+    );
+}
+
+/// Tests that operations the server doesn't implement are reported as such
+#[tokio::test]
+async fn test_unknown_operation_not_implemented() {
+    let (_client, server) = test_server().await;
+
+    let (status, body) = server
+        .json_request("secretsmanager.UnknownOperation", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "NotImplemented");
+}
+
 /// Tests name characters validation errors
 #[tokio::test]
 async fn test_create_secret_value_name_characters_validation_errors() {
@@ -430,3 +478,78 @@ async fn test_create_secret_tags() {
         ]
     );
 }
+
+/// Tests that the secret ARN uses the region the request was signed for and
+/// the account of the calling access key
+#[tokio::test]
+async fn test_create_secret_arn_region_account() {
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![AwsCredential {
+            name: "service".to_string(),
+            access_key_id: "service".to_string(),
+            access_key_secret: "service".to_string(),
+            principal_arn: None,
+            expires_at: None,
+            enabled: true,
+            policy: None,
+            source_policies: Vec::new(),
+        }],
+        ..Default::default()
+    })
+    .await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Account should be taken from the principal of the test access key
+    assert!(
+        create_response
+            .arn()
+            .unwrap()
+            .starts_with("arn:aws:secretsmanager:us-east-1:123456789012:secret:test-")
+    );
+
+    let config = server
+        .sdk_config()
+        .to_builder()
+        .region(Region::from_static("eu-west-1"))
+        .build();
+    let eu_client = aws_sdk_secretsmanager::Client::new(&config);
+    let create_response = eu_client
+        .create_secret()
+        .name("test-eu")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Region should be taken from the signing scope
+    assert!(
+        create_response
+            .arn()
+            .unwrap()
+            .starts_with("arn:aws:secretsmanager:eu-west-1:123456789012:secret:test-eu-")
+    );
+
+    let service_client = server.client_with_credentials("service", "service");
+    let create_response = service_client
+        .create_secret()
+        .name("test-service")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Access keys without a principal use the default account
+    assert!(
+        create_response
+            .arn()
+            .unwrap()
+            .starts_with("arn:aws:secretsmanager:us-east-1:000000000000:secret:test-service-")
+    );
+}
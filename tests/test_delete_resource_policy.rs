@@ -0,0 +1,107 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError, operation::delete_resource_policy::DeleteResourcePolicyError,
+    types::error::ResourceNotFoundException,
+};
+
+use crate::common::test_server;
+
+mod common;
+
+const TEST_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    ]
+}"#;
+
+/// Tests that a resource policy can be removed from a secret
+#[tokio::test]
+async fn test_delete_resource_policy_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    let delete_response = client
+        .delete_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(delete_response.arn(), create_response.arn());
+    assert_eq!(delete_response.name(), create_response.name());
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.resource_policy(), None);
+}
+
+/// Tests that deleting the policy of a secret without a policy succeeds
+#[tokio::test]
+async fn test_delete_resource_policy_no_policy() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .delete_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that deleting the policy of an unknown secret will fail
+#[tokio::test]
+async fn test_delete_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let delete_err = client
+        .delete_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let delete_err = match delete_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match delete_err.into_err() {
+        DeleteResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected DeleteResourcePolicyError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}
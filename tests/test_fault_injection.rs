@@ -0,0 +1,205 @@
+use aws_sdk_secretsmanager::error::SdkError;
+use axum::http::StatusCode;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests that the fault injection rules can be replaced and read back
+#[tokio::test]
+async fn test_put_get_fault_injection_rules() {
+    let (_client, server) = test_server().await;
+
+    let rules = json!([
+        {
+            "Operation": "secretsmanager.GetSecretValue",
+            "SecretId": "prod/*",
+            "Fault": "ThrottlingException",
+            "Probability": 0.5
+        },
+        {
+            "Fault": "DropConnection",
+            "NthCall": 3
+        }
+    ]);
+
+    let (status, _body) = server
+        .json_request("loker.PutFaultInjectionRules", json!({ "Rules": rules }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request("loker.GetFaultInjectionRules", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Rules"], rules);
+
+    // Rules are validated
+    let (status, body) = server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{ "Probability": 2.0 }] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "InvalidParameterException");
+}
+
+/// Tests that faults are only injected into requests matching the operation
+/// and secret patterns of a rule
+#[tokio::test]
+async fn test_fault_injection_matching() {
+    let (client, server) = test_server().await;
+
+    for name in ["prod/db", "dev/db"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let (status, _body) = server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{
+                "Operation": "secretsmanager.DescribeSecret",
+                "SecretId": "prod/*",
+                "Fault": "ServiceUnavailable"
+            }] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.DescribeSecret",
+            json!({ "SecretId": "prod/db" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["__type"], "ServiceUnavailable");
+
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.DescribeSecret",
+            json!({ "SecretId": "dev/db" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.GetSecretValue",
+            json!({ "SecretId": "prod/db" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests that a rule with an Nth call only fails the Nth matching request
+#[tokio::test]
+async fn test_fault_injection_nth_call() {
+    let (_client, server) = test_server().await;
+
+    let (status, _body) = server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{
+                "Operation": "secretsmanager.ListSecrets",
+                "Fault": "ThrottlingException",
+                "NthCall": 2
+            }] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ThrottlingException");
+
+    let (status, _body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests that injected internal errors use the status AWS responds with
+#[tokio::test]
+async fn test_fault_injection_internal_error() {
+    let (_client, server) = test_server().await;
+
+    server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{ "Fault": "InternalServiceError" }] }),
+        )
+        .await;
+
+    let (status, body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["__type"], "InternalServiceError");
+
+    // Faults are never injected into the fault injection operations
+    let (status, _body) = server
+        .json_request("loker.PutFaultInjectionRules", json!({ "Rules": [] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests that latency rules delay requests without failing them
+#[tokio::test]
+async fn test_fault_injection_latency() {
+    let (_client, server) = test_server().await;
+
+    server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{ "LatencyMs": 200 }] }),
+        )
+        .await;
+
+    let start = Instant::now();
+    let (status, _body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+/// Tests that dropped connections surface as a non-service error
+#[tokio::test]
+async fn test_fault_injection_drop_connection() {
+    let (client, server) = test_server().await;
+
+    server
+        .json_request(
+            "loker.PutFaultInjectionRules",
+            json!({ "Rules": [{ "Fault": "DropConnection" }] }),
+        )
+        .await;
+
+    let error = client.list_secrets().send().await.unwrap_err();
+    assert!(
+        !matches!(error, SdkError::ServiceError(_)),
+        "expected connection error got {error:?}"
+    );
+}
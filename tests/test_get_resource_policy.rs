@@ -0,0 +1,93 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError, operation::get_resource_policy::GetResourcePolicyError,
+    types::error::ResourceNotFoundException,
+};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests that the policy is provided exactly as it was stored
+#[tokio::test]
+async fn test_get_resource_policy_verbatim() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    // Unusual formatting that should be preserved
+    let policy = r#"{ "Statement": { "Effect": "Allow",   "Principal": {"AWS": ["arn:aws:iam::123456789012:root"]},
+        "Action": ["secretsmanager:GetSecretValue"], "Resource": "*" }, "Version": "2012-10-17" }"#;
+
+    client
+        .put_resource_policy()
+        .secret_id("test")
+        .resource_policy(policy)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.arn(), create_response.arn());
+    assert_eq!(get_response.name(), create_response.name());
+    assert_eq!(get_response.resource_policy(), Some(policy));
+}
+
+/// Tests that a secret without a policy has no policy
+#[tokio::test]
+async fn test_get_resource_policy_none() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(get_response.resource_policy(), None);
+}
+
+/// Tests that getting the policy of an unknown secret will fail
+#[tokio::test]
+async fn test_get_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let get_err = client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let get_err = match get_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match get_err.into_err() {
+        GetResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected GetResourcePolicyError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}
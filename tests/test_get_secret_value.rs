@@ -173,7 +173,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:us-east-1:1:secret:*")
+        .secret_id("arn:aws:secretsmanager:us-east-1:123456789012:secret:*")
         .send()
         .await
         .unwrap();
@@ -198,7 +198,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:*:1:secret:*")
+        .secret_id("arn:aws:secretsmanager:*:123456789012:secret:*")
         .send()
         .await
         .unwrap();
@@ -223,7 +223,7 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
 
     let get_response = client
         .get_secret_value()
-        .secret_id("arn:aws:secretsmanager:us-east-1:1:secret:test-??????")
+        .secret_id("arn:aws:secretsmanager:us-east-1:123456789012:secret:test-??????")
         .send()
         .await
         .unwrap();
@@ -247,6 +247,128 @@ async fn test_get_secret_value_by_partial_arn_string_success() {
     );
 }
 
+/// Tests that a secret can be retrieved by its ARN without the random suffix
+#[tokio::test]
+async fn test_get_secret_value_by_partial_arn_without_suffix_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("app/test_secret")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let arn = create_response.arn().unwrap();
+    let partial_arn = &arn[..arn.len() - 7];
+    assert_eq!(
+        partial_arn,
+        "arn:aws:secretsmanager:us-east-1:123456789012:secret:app/test_secret"
+    );
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id(partial_arn)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(get_response.arn(), Some(arn));
+    assert_eq!(get_response.secret_string(), Some("test"));
+
+    // Other operations resolve partial ARNs the same way
+    client
+        .put_secret_value()
+        .secret_id(partial_arn)
+        .secret_string("updated")
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id(partial_arn)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(describe_response.arn(), Some(arn));
+
+    // Partial ARNs must match up to the random suffix
+    let result = client
+        .get_secret_value()
+        .secret_id(&partial_arn[..partial_arn.len() - 1])
+        .send()
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        SdkError::ServiceError(error)
+            if matches!(error.err(), GetSecretValueError::ResourceNotFoundException(_))
+    ));
+}
+
+/// Tests that exact ARNs are preferred over partial ARNs when the name of
+/// another secret ends with a hyphen and 6 characters
+#[tokio::test]
+async fn test_get_secret_value_exact_arn_preferred_over_partial_arn() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("app")
+        .secret_string("first")
+        .send()
+        .await
+        .unwrap();
+    let first_arn = create_response.arn().unwrap();
+
+    // Name the second secret so its partial ARN is the full ARN of the first
+    let suffix = &first_arn[first_arn.len() - 6..];
+    let create_response = client
+        .create_secret()
+        .name(format!("app-{suffix}"))
+        .secret_string("second")
+        .send()
+        .await
+        .unwrap();
+    let second_arn = create_response.arn().unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id(first_arn)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(get_response.arn(), Some(first_arn));
+    assert_eq!(get_response.secret_string(), Some("first"));
+
+    // Names ending with a suffix are matched by name
+    let get_response = client
+        .get_secret_value()
+        .secret_id(format!("app-{suffix}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(get_response.arn(), Some(second_arn));
+
+    // Once the first secret is removed its ARN is a partial ARN of the second
+    client
+        .delete_secret()
+        .secret_id(first_arn)
+        .force_delete_without_recovery(true)
+        .send()
+        .await
+        .unwrap();
+
+    let get_response = client
+        .get_secret_value()
+        .secret_id(first_arn)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(get_response.arn(), Some(second_arn));
+    assert_eq!(get_response.secret_string(), Some("second"));
+}
+
 /// Tests that a string secret can be retrieved by ARN using a specific version successfully
 #[tokio::test]
 async fn test_get_secret_value_by_arn_with_version_string_success() {
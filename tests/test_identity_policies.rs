@@ -0,0 +1,496 @@
+use aws_sdk_secretsmanager::error::{ProvideErrorMetadata, SdkError};
+use axum::http::StatusCode;
+use loker::middleware::aws_sig_v4::AwsCredential;
+use serde_json::{Value, json};
+
+use crate::common::{TestServerOptions, test_server, test_server_with_options};
+
+mod common;
+
+/// Policy that only allows reading secrets under the ci/ prefix
+fn ci_read_only_policy() -> Value {
+    json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Action": ["secretsmanager:GetSecretValue", "secretsmanager:DescribeSecret"],
+            "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+        }
+    })
+}
+
+/// Create a credential named `name` using the name as both the access key
+/// ID and secret with the identity `policy` attached
+fn credential_with_policy(name: &str, policy: Value) -> AwsCredential {
+    AwsCredential {
+        name: name.to_string(),
+        access_key_id: name.to_string(),
+        access_key_secret: name.to_string(),
+        principal_arn: None,
+        expires_at: None,
+        enabled: true,
+        policy: Some(policy),
+        source_policies: Vec::new(),
+    }
+}
+
+/// Assert that the `result` of a request was denied by a policy
+fn assert_access_denied<T, E: ProvideErrorMetadata, R>(result: Result<T, SdkError<E, R>>) {
+    let error = match result {
+        Ok(_) => panic!("expected access denied error"),
+        Err(SdkError::ServiceError(error)) => error.into_err(),
+        Err(_) => panic!("expected service error"),
+    };
+
+    assert_eq!(error.code(), Some("AccessDeniedException"));
+}
+
+/// Tests that a key with a read-only policy can only read the secrets
+/// matching the policy resource
+#[tokio::test]
+async fn test_identity_policy_read_only() {
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("ci", ci_read_only_policy())],
+        ..Default::default()
+    })
+    .await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let ci_client = server.client_with_credentials("ci", "ci");
+
+    let response = ci_client
+        .get_secret_value()
+        .secret_id("ci/test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test"));
+
+    ci_client
+        .describe_secret()
+        .secret_id("ci/test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_access_denied(
+        ci_client
+            .get_secret_value()
+            .secret_id("prod/test")
+            .send()
+            .await,
+    );
+    assert_access_denied(
+        ci_client
+            .put_secret_value()
+            .secret_id("ci/test")
+            .secret_string("updated")
+            .send()
+            .await,
+    );
+    assert_access_denied(
+        ci_client
+            .create_secret()
+            .name("ci/new")
+            .secret_string("test")
+            .send()
+            .await,
+    );
+    assert_access_denied(ci_client.list_secrets().send().await);
+}
+
+/// Tests that explicit denies in the identity policy take precedence
+#[tokio::test]
+async fn test_identity_policy_explicit_deny() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Action": "secretsmanager:*",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Deny",
+                "Action": "secretsmanager:DeleteSecret",
+                "Resource": "*"
+            }
+        ]
+    });
+
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("service", policy)],
+        ..Default::default()
+    })
+    .await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let service_client = server.client_with_credentials("service", "service");
+    service_client.list_secrets().send().await.unwrap();
+
+    assert_access_denied(
+        service_client
+            .delete_secret()
+            .secret_id("test")
+            .send()
+            .await,
+    );
+}
+
+/// Tests that secrets read by BatchGetSecretValue are authorized separately,
+/// allowing the batch operation doesn't allow reading every secret
+#[tokio::test]
+async fn test_identity_policy_batch_get_secret_value() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Action": "secretsmanager:BatchGetSecretValue",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Allow",
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+            }
+        ]
+    });
+
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("ci", policy)],
+        ..Default::default()
+    })
+    .await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let ci_client = server.client_with_credentials("ci", "ci");
+
+    let response = ci_client
+        .batch_get_secret_value()
+        .secret_id_list("ci/test")
+        .secret_id_list("prod/test")
+        .send()
+        .await
+        .unwrap();
+
+    let secret_values = response.secret_values();
+    assert_eq!(secret_values.len(), 1);
+    assert_eq!(secret_values[0].name(), Some("ci/test"));
+
+    let errors = response.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].secret_id(), Some("prod/test"));
+    assert_eq!(errors[0].error_code(), Some("AccessDeniedException"));
+}
+
+/// Tests that secrets read by GetParameters are authorized separately as
+/// though they were read by GetParameter
+#[tokio::test]
+async fn test_identity_policy_get_parameters() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Action": "ssm:GetParameters",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Allow",
+                "Action": ["ssm:GetParameter", "secretsmanager:GetSecretValue"],
+                "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+            }
+        ]
+    });
+
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("ci", policy)],
+        ..Default::default()
+    })
+    .await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request_with_credentials(
+            "ci",
+            "ci",
+            "AmazonSSM.GetParameters",
+            json!({
+                "Names": [
+                    "/aws/reference/secretsmanager/ci/test",
+                    "/aws/reference/secretsmanager/missing"
+                ],
+                "WithDecryption": true
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Parameters"][0]["Value"], "test");
+    assert_eq!(
+        body["InvalidParameters"][0],
+        "/aws/reference/secretsmanager/missing"
+    );
+
+    let (status, body) = server
+        .json_request_with_credentials(
+            "ci",
+            "ci",
+            "AmazonSSM.GetParameters",
+            json!({
+                "Names": [
+                    "/aws/reference/secretsmanager/ci/test",
+                    "/aws/reference/secretsmanager/prod/test"
+                ],
+                "WithDecryption": true
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "AccessDeniedException");
+}
+
+/// Tests that parameters can't be read when reading the referenced secret
+/// value is denied
+#[tokio::test]
+async fn test_identity_policy_parameter_secret_value_denied() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Action": ["ssm:*", "secretsmanager:GetSecretValue"],
+                "Resource": "*"
+            },
+            {
+                "Effect": "Deny",
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "arn:aws:secretsmanager:*:*:secret:prod/*"
+            }
+        ]
+    });
+
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("ci", policy)],
+        ..Default::default()
+    })
+    .await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request_with_credentials(
+            "ci",
+            "ci",
+            "AmazonSSM.GetParameter",
+            json!({ "Name": "/aws/reference/secretsmanager/ci/test", "WithDecryption": true }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Parameter"]["Value"], "test");
+
+    let (status, body) = server
+        .json_request_with_credentials(
+            "ci",
+            "ci",
+            "AmazonSSM.GetParameter",
+            json!({ "Name": "/aws/reference/secretsmanager/prod/test", "WithDecryption": true }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "AccessDeniedException");
+
+    let (status, body) = server
+        .json_request_with_credentials(
+            "ci",
+            "ci",
+            "AmazonSSM.GetParameters",
+            json!({
+                "Names": ["/aws/reference/secretsmanager/prod/test"],
+                "WithDecryption": true
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "AccessDeniedException");
+}
+
+/// Tests that identity policies restrict the access key management operations
+#[tokio::test]
+async fn test_identity_policy_admin_operations() {
+    let (_client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![credential_with_policy("ci", ci_read_only_policy())],
+        ..Default::default()
+    })
+    .await;
+
+    let (status, body) = server
+        .json_request_with_credentials("ci", "ci", "loker.CreateAccessKey", json!({ "Name": "ci" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "AccessDeniedException");
+
+    let (status, _body) = server
+        .json_request("loker.CreateAccessKey", json!({ "Name": "admin" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests attaching and removing the policy of an access key stored in the
+/// database through the admin API
+#[tokio::test]
+async fn test_put_delete_access_key_policy() {
+    let (client, server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("prod/test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "loker.CreateAccessKey",
+            json!({ "Name": "ci", "Policy": ci_read_only_policy().to_string() }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let access_key_id = body["AccessKey"]["AccessKeyId"].as_str().unwrap();
+    let access_key_secret = body["AccessKey"]["SecretAccessKey"].as_str().unwrap();
+    let ci_client = server.client_with_credentials(access_key_id, access_key_secret);
+
+    assert_access_denied(
+        ci_client
+            .get_secret_value()
+            .secret_id("prod/test")
+            .send()
+            .await,
+    );
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    });
+    let (status, _body) = server
+        .json_request(
+            "loker.PutAccessKeyPolicy",
+            json!({ "AccessKeyId": access_key_id, "PolicyDocument": policy.to_string() }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    ci_client
+        .get_secret_value()
+        .secret_id("prod/test")
+        .send()
+        .await
+        .unwrap();
+    assert_access_denied(ci_client.list_secrets().send().await);
+
+    let (status, _body) = server
+        .json_request(
+            "loker.DeleteAccessKeyPolicy",
+            json!({ "AccessKeyId": access_key_id }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    ci_client.list_secrets().send().await.unwrap();
+}
+
+/// Tests that invalid identity policies are rejected
+#[tokio::test]
+async fn test_put_access_key_policy_malformed() {
+    let (_client, server) = test_server().await;
+
+    let (status, body) = server
+        .json_request("loker.CreateAccessKey", json!({ "Name": "ci" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let access_key_id = body["AccessKey"]["AccessKeyId"].as_str().unwrap();
+
+    // Identity policies can't specify a principal
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": "secretsmanager:*",
+            "Resource": "*"
+        }
+    });
+
+    for target in ["loker.PutAccessKeyPolicy", "loker.CreateAccessKey"] {
+        let (status, body) = server
+            .json_request(
+                target,
+                json!({
+                    "AccessKeyId": access_key_id,
+                    "Name": "ci",
+                    "PolicyDocument": policy.to_string(),
+                    "Policy": policy.to_string()
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["__type"], "MalformedPolicyDocumentException");
+    }
+
+    let (status, body) = server
+        .json_request(
+            "loker.PutAccessKeyPolicy",
+            json!({ "AccessKeyId": "AKIAUNKNOWN", "PolicyDocument": ci_read_only_policy().to_string() }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ResourceNotFoundException");
+}
@@ -0,0 +1,546 @@
+use aws_config::Region;
+use aws_sdk_secretsmanager::types::{ReplicaRegionType, StatusType};
+use axum::http::StatusCode;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use loker::{
+    database::kms::{CreateKmsKey, create_kms_key},
+    kms::{KEY_MATERIAL_LENGTH, generate_key_material},
+};
+use serde_json::{Value, json};
+
+use crate::common::{TestServer, error_code, test_server};
+
+mod common;
+
+/// Create a KMS key returning its metadata
+async fn create_key(server: &TestServer) -> Value {
+    let (status, body) = server
+        .json_request("TrentService.CreateKey", json!({ "Description": "test" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    body["KeyMetadata"].clone()
+}
+
+/// Tests creating a key and describing it by its ID and ARN
+#[tokio::test]
+async fn test_create_describe_key() {
+    let (_client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+    let key_id = key["KeyId"].as_str().unwrap();
+    let key_arn = key["Arn"].as_str().unwrap();
+
+    assert_eq!(
+        key_arn,
+        format!("arn:aws:kms:us-east-1:123456789012:key/{key_id}")
+    );
+    assert_eq!(key["KeyState"], "Enabled");
+    assert_eq!(key["Enabled"], true);
+    assert_eq!(key["KeyUsage"], "ENCRYPT_DECRYPT");
+    assert_eq!(key["KeySpec"], "SYMMETRIC_DEFAULT");
+
+    for key_id in [key_id, key_arn] {
+        let (status, body) = server
+            .json_request("TrentService.DescribeKey", json!({ "KeyId": key_id }))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["KeyMetadata"]["Arn"], key_arn);
+    }
+
+    let (status, body) = server
+        .json_request("TrentService.ListKeys", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Keys"][0]["KeyArn"], key_arn);
+    assert_eq!(body["Truncated"], false);
+}
+
+/// Tests describing a key that does not exist
+#[tokio::test]
+async fn test_describe_unknown_key() {
+    let (_client, server) = test_server().await;
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.DescribeKey",
+            json!({ "KeyId": "00000000-0000-0000-0000-000000000000" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "NotFoundException");
+}
+
+/// Tests that values encrypted with a key can be decrypted with the same
+/// encryption context
+#[tokio::test]
+async fn test_encrypt_decrypt() {
+    let (_client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+    let key_id = key["KeyId"].as_str().unwrap();
+    let plaintext = BASE64_STANDARD.encode("test-value");
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.Encrypt",
+            json!({
+                "KeyId": key_id,
+                "Plaintext": plaintext,
+                "EncryptionContext": { "purpose": "test" }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let ciphertext_blob = body["CiphertextBlob"].as_str().unwrap().to_string();
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.Decrypt",
+            json!({
+                "CiphertextBlob": ciphertext_blob,
+                "EncryptionContext": { "purpose": "test" }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Plaintext"], plaintext);
+    assert_eq!(body["KeyId"], key["Arn"]);
+
+    // A different encryption context must not decrypt the value
+    let (status, body) = server
+        .json_request(
+            "TrentService.Decrypt",
+            json!({
+                "CiphertextBlob": ciphertext_blob,
+                "EncryptionContext": { "purpose": "other" }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "InvalidCiphertextException");
+}
+
+/// Tests generating a data key and decrypting its encrypted copy
+#[tokio::test]
+async fn test_generate_data_key() {
+    let (_client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.GenerateDataKey",
+            json!({ "KeyId": key["KeyId"], "KeySpec": "AES_256" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let plaintext = body["Plaintext"].as_str().unwrap();
+    assert_eq!(BASE64_STANDARD.decode(plaintext).unwrap().len(), 32);
+
+    let (status, decrypted) = server
+        .json_request(
+            "TrentService.Decrypt",
+            json!({ "CiphertextBlob": body["CiphertextBlob"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(decrypted["Plaintext"], plaintext);
+
+    // Exactly one of KeySpec and NumberOfBytes must be provided
+    let (status, body) = server
+        .json_request(
+            "TrentService.GenerateDataKey",
+            json!({ "KeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ValidationException");
+}
+
+/// Tests creating, listing, updating and deleting aliases and using an alias
+/// in place of the key ID
+#[tokio::test]
+async fn test_aliases() {
+    let (_client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+    let other_key = create_key(&server).await;
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.CreateAlias",
+            json!({ "AliasName": "alias/test", "TargetKeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.CreateAlias",
+            json!({ "AliasName": "alias/test", "TargetKeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "AlreadyExistsException");
+
+    let (status, body) = server
+        .json_request("TrentService.DescribeKey", json!({ "KeyId": "alias/test" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["KeyMetadata"]["KeyId"], key["KeyId"]);
+
+    let (status, body) = server
+        .json_request("TrentService.ListAliases", json!({ "KeyId": key["KeyId"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["Aliases"].as_array().unwrap().len(), 1);
+    assert_eq!(body["Aliases"][0]["AliasName"], "alias/test");
+    assert_eq!(
+        body["Aliases"][0]["AliasArn"],
+        "arn:aws:kms:us-east-1:123456789012:alias/test"
+    );
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.UpdateAlias",
+            json!({ "AliasName": "alias/test", "TargetKeyId": other_key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request("TrentService.DescribeKey", json!({ "KeyId": "alias/test" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["KeyMetadata"]["KeyId"], other_key["KeyId"]);
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.DeleteAlias",
+            json!({ "AliasName": "alias/test" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request("TrentService.DescribeKey", json!({ "KeyId": "alias/test" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "NotFoundException");
+}
+
+/// Tests that the alias/aws/ prefix is reserved
+#[tokio::test]
+async fn test_create_reserved_alias() {
+    let (_client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.CreateAlias",
+            json!({ "AliasName": "alias/aws/secretsmanager", "TargetKeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// Tests that secrets created with a KMS key are encrypted with the key and
+/// can be read back
+#[tokio::test]
+async fn test_secret_with_kms_key() {
+    let (client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+    let key_arn = key["Arn"].as_str().unwrap();
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.CreateAlias",
+            json!({ "AliasName": "alias/secrets", "TargetKeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    client
+        .create_secret()
+        .name("test")
+        .kms_key_id("alias/secrets")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value"));
+
+    let response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.kms_key_id(), Some("alias/secrets"));
+
+    // The value must not be stored in plaintext
+    let (secret_string, encrypted_data_key): (Option<String>, Option<String>) = sqlx::query_as(
+        r#"SELECT "secret_string", "encrypted_data_key" FROM "secrets_versions" LIMIT 1"#,
+    )
+    .fetch_one(&server.db)
+    .await
+    .unwrap();
+    assert_ne!(secret_string.as_deref(), Some("test-value"));
+    assert!(encrypted_data_key.is_some());
+
+    // New versions are encrypted with the same key
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-value-2")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value-2"));
+
+    let (status, body) = server
+        .json_request("TrentService.DescribeKey", json!({ "KeyId": key_arn }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["KeyMetadata"]["KeyState"], "Enabled");
+}
+
+/// Tests creating a secret with a key that does not exist
+#[tokio::test]
+async fn test_secret_with_unknown_kms_key() {
+    let (client, _server) = test_server().await;
+
+    let result = client
+        .create_secret()
+        .name("test")
+        .kms_key_id("alias/missing")
+        .secret_string("test-value")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "EncryptionFailure");
+}
+
+/// Tests that secrets can't be read while their key is disabled
+#[tokio::test]
+async fn test_secret_with_disabled_kms_key() {
+    let (client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .kms_key_id(key["KeyId"].as_str().unwrap())
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, _body) = server
+        .json_request("TrentService.DisableKey", json!({ "KeyId": key["KeyId"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let result = client.get_secret_value().secret_id("test").send().await;
+    assert_eq!(error_code(result), "DecryptionFailure");
+
+    let (status, _body) = server
+        .json_request("TrentService.EnableKey", json!({ "KeyId": key["KeyId"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value"));
+}
+
+/// Tests that secrets can't be read or written while their key is pending
+/// deletion and can be once the deletion is cancelled
+#[tokio::test]
+async fn test_secret_with_kms_key_pending_deletion() {
+    let (client, server) = test_server().await;
+
+    let key = create_key(&server).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .kms_key_id(key["KeyId"].as_str().unwrap())
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "TrentService.ScheduleKeyDeletion",
+            json!({ "KeyId": key["KeyId"], "PendingWindowInDays": 7 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["KeyState"], "PendingDeletion");
+
+    let result = client.get_secret_value().secret_id("test").send().await;
+    assert_eq!(error_code(result), "DecryptionFailure");
+
+    let result = client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-value-2")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "EncryptionFailure");
+
+    let (status, _body) = server
+        .json_request(
+            "TrentService.CancelKeyDeletion",
+            json!({ "KeyId": key["KeyId"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Cancelled keys are left disabled until they are enabled again
+    let (status, _body) = server
+        .json_request("TrentService.EnableKey", json!({ "KeyId": key["KeyId"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value"));
+}
+
+/// Tests that replicas are encrypted with the KMS key of their own region and
+/// remain usable once they are promoted to standalone secrets
+#[tokio::test]
+async fn test_replica_with_kms_key() {
+    let (client, server) = test_server().await;
+    let eu_config = server
+        .sdk_config()
+        .to_builder()
+        .region(Region::from_static("eu-west-1"))
+        .build();
+    let eu_client = aws_sdk_secretsmanager::Client::new(&eu_config);
+
+    let key = create_key(&server).await;
+    let key_id = key["KeyId"].as_str().unwrap();
+
+    let eu_key = create_kms_key(
+        &server.db,
+        CreateKmsKey {
+            key_id: "11111111-1111-1111-1111-111111111111".to_string(),
+            arn: "arn:aws:kms:eu-west-1:123456789012:key/11111111-1111-1111-1111-111111111111"
+                .to_string(),
+            region: "eu-west-1".to_string(),
+            description: "test".to_string(),
+            key_usage: "ENCRYPT_DECRYPT".to_string(),
+            key_spec: "SYMMETRIC_DEFAULT".to_string(),
+            key_material: generate_key_material(KEY_MATERIAL_LENGTH),
+        },
+    )
+    .await
+    .unwrap();
+
+    let response = client
+        .create_secret()
+        .name("test")
+        .kms_key_id(key_id)
+        .secret_string("test-value")
+        .add_replica_regions(
+            ReplicaRegionType::builder()
+                .region("eu-west-1")
+                .kms_key_id(&eu_key.key_id)
+                .build(),
+        )
+        .add_replica_regions(
+            ReplicaRegionType::builder()
+                .region("eu-central-1")
+                .kms_key_id("00000000-0000-0000-0000-000000000000")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    // Replicas can't use a key that doesn't exist in their region
+    let replication_status = response.replication_status();
+    assert_eq!(replication_status[0].status(), Some(&StatusType::InSync));
+    assert_eq!(replication_status[1].status(), Some(&StatusType::Failed));
+
+    let response = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.kms_key_id(), Some(eu_key.key_id.as_str()));
+    let replica_arn = response.arn().unwrap().to_string();
+
+    // The replica data key is wrapped by the key of the replica region
+    let (encrypted_data_key,): (String,) = sqlx::query_as(
+        r#"SELECT "encrypted_data_key" FROM "secrets_versions" WHERE "secret_arn" = ?"#,
+    )
+    .bind(&replica_arn)
+    .fetch_one(&server.db)
+    .await
+    .unwrap();
+    let encrypted_data_key = BASE64_STANDARD.decode(encrypted_data_key).unwrap();
+    assert!(encrypted_data_key.starts_with(eu_key.key_id.as_bytes()));
+
+    let response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value"));
+
+    // Promoted replicas keep encrypting new versions with their own key
+    eu_client
+        .stop_replication_to_replica()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-value-2")
+        .send()
+        .await
+        .unwrap();
+
+    let response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test-value-2"));
+}
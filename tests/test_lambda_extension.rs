@@ -0,0 +1,214 @@
+use axum::http::{Request, StatusCode};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use loker::handlers::lambda_extension::LambdaExtensionOptions;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::common::{TestServer, TestServerOptions, test_server_with_options};
+
+mod common;
+
+const TEST_TOKEN: &str = "test-session-token";
+
+/// Create a test server with the Lambda extension endpoint caching secrets
+/// for `cache_ttl`
+async fn lambda_extension_server(
+    cache_ttl: Duration,
+) -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_options(TestServerOptions {
+        lambda_extension: Some(LambdaExtensionOptions {
+            token: TEST_TOKEN.to_string(),
+            region: "us-east-1".to_string(),
+            account_id: "123456789012".to_string(),
+            cache_ttl,
+            cache_size: 1000,
+        }),
+        ..Default::default()
+    })
+    .await
+}
+
+/// Send a GET request for the `path_and_query` to the Lambda extension
+/// endpoint using the provided `token`
+async fn extension_request(
+    server: &TestServer,
+    path_and_query: &str,
+    token: Option<&str>,
+) -> (StatusCode, Value) {
+    let url = format!(
+        "{}{path_and_query}",
+        server.lambda_extension_url.as_ref().unwrap()
+    );
+
+    let mut request = Request::get(url);
+    if let Some(token) = token {
+        request = request.header("X-Aws-Parameters-Secrets-Token", token);
+    }
+    let request = request.body(Full::new(Bytes::new())).unwrap();
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let response = client.request(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    (status, body)
+}
+
+/// Tests getting a secret value through the Lambda extension endpoint
+#[tokio::test]
+async fn test_get_secret() {
+    let (client, server) = lambda_extension_server(Duration::ZERO).await;
+
+    let create_response = client
+        .create_secret()
+        .name("app/test")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=app%2Ftest",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ARN"], create_response.arn().unwrap());
+    assert_eq!(body["Name"], "app/test");
+    assert_eq!(body["SecretString"], "test-value");
+    assert_eq!(body["VersionId"], create_response.version_id().unwrap());
+    assert_eq!(body["VersionStages"][0], "AWSCURRENT");
+}
+
+/// Tests selecting secret versions by version ID and stage
+#[tokio::test]
+async fn test_get_secret_version() {
+    let (client, server) = lambda_extension_server(Duration::ZERO).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("first-value")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("second-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = extension_request(
+        &server,
+        &format!(
+            "/secretsmanager/get?secretId=test&versionId={}",
+            create_response.version_id().unwrap()
+        ),
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["SecretString"], "first-value");
+
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=test&versionStage=AWSPREVIOUS",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["SecretString"], "first-value");
+}
+
+/// Tests that requests without the expected token are rejected
+#[tokio::test]
+async fn test_get_secret_invalid_token() {
+    let (client, server) = lambda_extension_server(Duration::ZERO).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    for token in [None, Some("other-token")] {
+        let (status, _body) =
+            extension_request(&server, "/secretsmanager/get?secretId=test", token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
+
+/// Tests that errors from the GetSecretValue handler are passed through
+#[tokio::test]
+async fn test_get_secret_not_found() {
+    let (_client, server) = lambda_extension_server(Duration::ZERO).await;
+
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=missing",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ResourceNotFoundException");
+}
+
+/// Tests that secret values are cached until the TTL has elapsed
+#[tokio::test]
+async fn test_get_secret_cached() {
+    let (client, server) = lambda_extension_server(Duration::from_secs(60)).await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("first-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=test",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["SecretString"], "first-value");
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("second-value")
+        .send()
+        .await
+        .unwrap();
+
+    // The cached value is served until it expires
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=test",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["SecretString"], "first-value");
+
+    // Requests for a different version aren't served from the cache
+    let (status, body) = extension_request(
+        &server,
+        "/secretsmanager/get?secretId=test&versionStage=AWSCURRENT",
+        Some(TEST_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["SecretString"], "second-value");
+}
@@ -1,5 +1,10 @@
 use crate::common::test_server;
 use aws_sdk_secretsmanager::types::Tag;
+use chrono::{Duration, Utc};
+use loker::database::secrets::{
+    CreateSecretVersion, count_secret_versions, create_secret_version,
+    delete_excess_secret_versions,
+};
 
 mod common;
 
@@ -398,3 +403,89 @@ async fn test_list_secret_version_ids_test_custom_pagination() {
         }
     }
 }
+
+/// Tests that versions over the 100 most recent are removed once they are a
+/// day old, keeping recent versions and versions with a staging label
+#[tokio::test]
+async fn test_delete_excess_secret_versions() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let arn = create_response.arn().unwrap();
+    let current_version_id = create_response.version_id().unwrap();
+
+    // The labelled version is the oldest so it is beyond the 100 most recent
+    sqlx::query(r#"UPDATE "secrets_versions" SET "created_at" = ? WHERE "secret_arn" = ?"#)
+        .bind(Utc::now() - Duration::days(10))
+        .bind(arn)
+        .execute(&server.db)
+        .await
+        .unwrap();
+
+    // Versions old enough to be removed
+    for index in 0..105 {
+        create_secret_version(
+            &server.db,
+            CreateSecretVersion {
+                secret_arn: arn.to_string(),
+                version_id: format!("old-{index}"),
+                secret_string: Some("test".to_string()),
+                secret_binary: None,
+                encrypted_data_key: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE "secrets_versions"
+        SET "created_at" = ?
+        WHERE "secret_arn" = ? AND "version_id" LIKE 'old-%'
+        "#,
+    )
+    .bind(Utc::now() - Duration::days(2))
+    .bind(arn)
+    .execute(&server.db)
+    .await
+    .unwrap();
+
+    // Recent versions that are never removed
+    for index in 0..5 {
+        create_secret_version(
+            &server.db,
+            CreateSecretVersion {
+                secret_arn: arn.to_string(),
+                version_id: format!("new-{index}"),
+                secret_string: Some("test".to_string()),
+                secret_binary: None,
+                encrypted_data_key: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    delete_excess_secret_versions(&server.db).await.unwrap();
+
+    // The 100 most recent versions are kept along with the labelled version
+    assert_eq!(
+        count_secret_versions(&server.db, arn, true).await.unwrap(),
+        101
+    );
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.version_id(), Some(current_version_id));
+}
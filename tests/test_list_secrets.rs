@@ -2,7 +2,10 @@ use crate::common::test_server;
 use aws_sdk_secretsmanager::{
     error::SdkError,
     operation::{create_secret::CreateSecretOutput, list_secrets::ListSecretsError},
-    types::{Filter, Tag, error::InvalidRequestException},
+    types::{
+        Filter, Tag,
+        error::{InvalidNextTokenException, InvalidRequestException},
+    },
 };
 
 mod common;
@@ -1604,3 +1607,26 @@ async fn test_list_secrets_invalid_pagination_error() {
         error => panic!("expected BatchGetSecretValueError::InvalidRequestException got {error:?}"),
     };
 }
+
+/// Tests that a pagination token beyond the supported pages is reported as an
+/// invalid next token
+#[tokio::test]
+async fn test_list_secrets_out_of_range_pagination_error() {
+    let (client, _server) = test_server().await;
+    let list_error = client
+        .list_secrets()
+        .next_token(format!("100:{}", i64::MAX))
+        .send()
+        .await
+        .unwrap_err();
+
+    let list_error = match list_error {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidNextTokenException = match list_error.into_err() {
+        ListSecretsError::InvalidNextTokenException(error) => error,
+        error => panic!("expected ListSecretsError::InvalidNextTokenException got {error:?}"),
+    };
+}
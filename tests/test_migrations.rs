@@ -0,0 +1,113 @@
+use loker::database::{
+    DbPool,
+    migrations::{MIGRATIONS, apply_migration, setup_migrations},
+};
+use sqlx::sqlite::SqlitePoolOptions;
+
+/// Create an in-memory database with all migrations applied up to but not
+/// including the migration named `name`
+async fn database_before_migration(name: &str) -> DbPool {
+    // Single connection so every query uses the same in-memory database
+    let db = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    let mut t = db.begin().await.unwrap();
+    setup_migrations(&mut t).await.unwrap();
+
+    for (migration_name, migration) in MIGRATIONS {
+        if migration_name.eq(&name) {
+            break;
+        }
+
+        apply_migration(&mut t, migration_name, migration)
+            .await
+            .unwrap();
+    }
+
+    t.commit().await.unwrap();
+    db
+}
+
+/// Apply the migration named `name` to the `db`
+async fn apply_named_migration(db: &DbPool, name: &str) {
+    let (migration_name, migration) = MIGRATIONS
+        .iter()
+        .find(|(migration_name, _)| migration_name.eq(&name))
+        .unwrap();
+
+    let mut t = db.begin().await.unwrap();
+    apply_migration(&mut t, migration_name, migration)
+        .await
+        .unwrap();
+    t.commit().await.unwrap();
+}
+
+/// Tests that existing secrets are moved into the region from their ARN
+/// without losing any of their related data
+#[tokio::test]
+async fn test_secrets_region_migration() {
+    let db = database_before_migration("m8_create_secrets_region_column").await;
+    let arn = "arn:aws:secretsmanager:us-east-1:1:secret:test-abcdef";
+
+    for query in [
+        r#"INSERT INTO "secrets" ("arn", "name", "created_at") VALUES (?1, 'test', '2024-01-01T00:00:00Z')"#,
+        r#"INSERT INTO "secrets_versions" ("secret_arn", "version_id", "secret_string", "created_at") VALUES (?1, 'v1', 'value', '2024-01-01T00:00:00Z')"#,
+        r#"INSERT INTO "secret_version_stages" ("secret_arn", "version_id", "value", "created_at") VALUES (?1, 'v1', 'AWSCURRENT', '2024-01-01T00:00:00Z')"#,
+        r#"INSERT INTO "secrets_tags" ("secret_arn", "key", "value", "created_at") VALUES (?1, 'key', 'value', '2024-01-01T00:00:00Z')"#,
+        r#"INSERT INTO "secrets_policies" ("secret_arn", "policy", "created_at") VALUES (?1, '{}', '2024-01-01T00:00:00Z')"#,
+    ] {
+        sqlx::query(query).bind(arn).execute(&db).await.unwrap();
+    }
+
+    apply_named_migration(&db, "m8_create_secrets_region_column").await;
+
+    let (region,): (String,) = sqlx::query_as(r#"SELECT "region" FROM "secrets" WHERE "arn" = ?"#)
+        .bind(arn)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(region, "us-east-1");
+
+    for table in [
+        "secrets_versions",
+        "secret_version_stages",
+        "secrets_tags",
+        "secrets_policies",
+    ] {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            r#"SELECT COUNT(*) FROM "{table}" WHERE "secret_arn" = ?"#
+        ))
+        .bind(arn)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "{table} should keep its rows");
+    }
+
+    // Same name is allowed within another region
+    sqlx::query(
+        r#"INSERT INTO "secrets" ("arn", "region", "name", "created_at") VALUES (?, 'eu-west-1', 'test', '2024-01-01T00:00:00Z')"#,
+    )
+    .bind("arn:aws:secretsmanager:eu-west-1:1:secret:test-abcdef")
+    .execute(&db)
+    .await
+    .unwrap();
+
+    // Foreign keys still cascade to the dependent tables
+    sqlx::query(r#"DELETE FROM "secrets" WHERE "arn" = ?"#)
+        .bind(arn)
+        .execute(&db)
+        .await
+        .unwrap();
+
+    let (count,): (i64,) =
+        sqlx::query_as(r#"SELECT COUNT(*) FROM "secrets_versions" WHERE "secret_arn" = ?"#)
+            .bind(arn)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+    assert_eq!(count, 0);
+}
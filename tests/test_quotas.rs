@@ -0,0 +1,375 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use loker::database::secrets::{
+    CreateSecretVersion, add_secret_version_stage, create_secret_version, put_secret_tag,
+};
+use serde_json::{Value, json};
+
+use crate::common::{TestServer, TestServerOptions, test_server, test_server_with_options};
+
+mod common;
+
+async fn strict_test_server() -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_options(TestServerOptions {
+        strict_quotas: true,
+        ..Default::default()
+    })
+    .await
+}
+
+/// Create the JSON tags with keys prefixed by `prefix`
+fn tags(prefix: &str, count: usize) -> Value {
+    (0..count)
+        .map(|index| json!({ "Key": format!("{prefix}{index}"), "Value": "test" }))
+        .collect()
+}
+
+/// Tests that secrets can't have more than 50 tags
+#[tokio::test]
+async fn test_tag_quota() {
+    let (_client, server) = strict_test_server().await;
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.CreateSecret",
+            json!({ "Name": "too-many", "SecretString": "test", "Tags": tags("key", 51) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+
+    // The secret is not created when the quota is exceeded
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.DescribeSecret",
+            json!({ "SecretId": "too-many" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.CreateSecret",
+            json!({ "Name": "test", "SecretString": "test", "Tags": tags("key", 50) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Replacing the value of existing tags doesn't count against the quota
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.TagResource",
+            json!({ "SecretId": "test", "Tags": [{ "Key": "key0", "Value": "other" }] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.TagResource",
+            json!({ "SecretId": "test", "Tags": tags("other", 1) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+}
+
+/// Tests that the tag quota is checked against the existing tags of the
+/// secret before any of the new tags are attached
+#[tokio::test]
+async fn test_tag_quota_existing_tags() {
+    let (client, server) = strict_test_server().await;
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let arn = secret.arn().unwrap();
+
+    for index in 0..49 {
+        put_secret_tag(&server.db, arn, &format!("key{index}"), "test")
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.TagResource",
+            json!({ "SecretId": "test", "Tags": tags("other", 2) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+
+    // None of the tags are attached when the quota is exceeded
+    let secret = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(secret.tags().len(), 49);
+
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.TagResource",
+            json!({ "SecretId": "test", "Tags": tags("other", 1) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests that quotas are not enforced without strict quotas
+#[tokio::test]
+async fn test_quotas_not_strict() {
+    let (_client, server) = test_server().await;
+
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.CreateSecret",
+            json!({ "Name": "test", "SecretString": "test", "Tags": tags("key", 51) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Tests that secrets can't have more than 100 versions
+#[tokio::test]
+async fn test_version_quota() {
+    let (client, server) = strict_test_server().await;
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let arn = secret.arn().unwrap();
+
+    for index in 0..99 {
+        create_secret_version(
+            &server.db,
+            CreateSecretVersion {
+                secret_arn: arn.to_string(),
+                version_id: format!("version-{index}"),
+                secret_string: Some("test".to_string()),
+                secret_binary: None,
+                encrypted_data_key: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.PutSecretValue",
+            json!({ "SecretId": "test", "SecretString": "new" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.UpdateSecret",
+            json!({ "SecretId": "test", "SecretString": "new" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+}
+
+/// Tests that old versions without staging labels, which are eligible to be
+/// purged, don't count towards the version quota
+#[tokio::test]
+async fn test_version_quota_purgeable_versions() {
+    let (client, server) = strict_test_server().await;
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let arn = secret.arn().unwrap();
+
+    for index in 0..105 {
+        create_secret_version(
+            &server.db,
+            CreateSecretVersion {
+                secret_arn: arn.to_string(),
+                version_id: format!("version-{index}"),
+                secret_string: Some("test".to_string()),
+                secret_binary: None,
+                encrypted_data_key: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    sqlx::query(r#"UPDATE "secrets_versions" SET "created_at" = ? WHERE "secret_arn" = ?"#)
+        .bind(Utc::now() - Duration::days(2))
+        .bind(arn)
+        .execute(&server.db)
+        .await
+        .unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.PutSecretValue",
+            json!({ "SecretId": "test", "SecretString": "new" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+/// Tests that versions can't have more than 20 staging labels
+#[tokio::test]
+async fn test_version_stage_quota() {
+    let (client, server) = strict_test_server().await;
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let version_id = secret.version_id().unwrap();
+
+    // The version already has the AWSCURRENT label
+    for index in 0..19 {
+        let (status, _body) = server
+            .json_request(
+                "secretsmanager.UpdateSecretVersionStage",
+                json!({
+                    "SecretId": "test",
+                    "VersionStage": format!("stage-{index}"),
+                    "MoveToVersionId": version_id
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.UpdateSecretVersionStage",
+            json!({
+                "SecretId": "test",
+                "VersionStage": "stage-19",
+                "MoveToVersionId": version_id
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+
+    let secret = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    let version_stages = &secret.version_ids_to_stages().unwrap()[version_id];
+    assert_eq!(version_stages.len(), 20);
+}
+
+/// Tests that the staging label quota is checked against the existing labels
+/// of the version before the label is attached
+#[tokio::test]
+async fn test_version_stage_quota_existing_stages() {
+    let (client, server) = strict_test_server().await;
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let arn = secret.arn().unwrap();
+    let version_id = secret.version_id().unwrap();
+
+    // The version already has the AWSCURRENT label
+    for index in 0..19 {
+        add_secret_version_stage(&server.db, arn, version_id, &format!("stage-{index}"))
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request(
+            "secretsmanager.UpdateSecretVersionStage",
+            json!({
+                "SecretId": "test",
+                "VersionStage": "stage-19",
+                "MoveToVersionId": version_id
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "LimitExceededException");
+
+    // Moving a label that is already attached to the version doesn't add a label
+    let (status, _body) = server
+        .json_request(
+            "secretsmanager.UpdateSecretVersionStage",
+            json!({
+                "SecretId": "test",
+                "VersionStage": "stage-0",
+                "RemoveFromVersionId": version_id,
+                "MoveToVersionId": version_id
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let secret = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    let version_stages = &secret.version_ids_to_stages().unwrap()[version_id];
+    assert_eq!(version_stages.len(), 20);
+    assert!(!version_stages.iter().any(|stage| stage == "stage-19"));
+}
+
+/// Tests that requests exceeding the rate quota of their operation are throttled
+#[tokio::test]
+async fn test_rate_quota() {
+    let (_client, server) = strict_test_server().await;
+
+    let responses = futures::future::join_all(
+        (0..80).map(|_| server.json_request("secretsmanager.GetRandomPassword", json!({}))),
+    )
+    .await;
+
+    let throttled = responses
+        .iter()
+        .filter(|(status, body)| {
+            *status == StatusCode::BAD_REQUEST && body["__type"] == "ThrottlingException"
+        })
+        .count();
+    assert!(throttled > 0);
+    assert!(
+        responses
+            .iter()
+            .all(|(status, body)| *status == StatusCode::OK
+                || body["__type"] == "ThrottlingException")
+    );
+
+    // Operations with a separate rate quota are not affected
+    let (status, _body) = server
+        .json_request("secretsmanager.ListSecrets", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,115 @@
+use aws_config::Region;
+
+use crate::common::{TestServer, error_code, test_server};
+
+mod common;
+
+/// Create a client for the server that signs requests for the `region`
+fn regional_client(server: &TestServer, region: &'static str) -> aws_sdk_secretsmanager::Client {
+    let config = server
+        .sdk_config()
+        .to_builder()
+        .region(Region::from_static(region))
+        .build();
+    aws_sdk_secretsmanager::Client::new(&config)
+}
+
+/// Tests that the same secret name can exist in multiple regions
+#[tokio::test]
+async fn test_same_name_in_multiple_regions() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    let us_secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("us")
+        .send()
+        .await
+        .unwrap();
+
+    let eu_secret = eu_client
+        .create_secret()
+        .name("test")
+        .secret_string("eu")
+        .send()
+        .await
+        .unwrap();
+
+    assert_ne!(us_secret.arn(), eu_secret.arn());
+    assert!(eu_secret.arn().unwrap().contains(":eu-west-1:"));
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.arn(), us_secret.arn());
+    assert_eq!(response.secret_string(), Some("us"));
+
+    let response = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.arn(), eu_secret.arn());
+    assert_eq!(response.secret_string(), Some("eu"));
+
+    // Names are still unique within a region
+    assert_eq!(
+        error_code(
+            eu_client
+                .create_secret()
+                .name("test")
+                .secret_string("other")
+                .send()
+                .await
+        ),
+        "ResourceExistsException"
+    );
+}
+
+/// Tests that secrets are not visible from other regions
+#[tokio::test]
+async fn test_secrets_scoped_to_region() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    let us_secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("us")
+        .send()
+        .await
+        .unwrap();
+
+    let response = eu_client.list_secrets().send().await.unwrap();
+    assert!(response.secret_list().is_empty());
+
+    let response = client.list_secrets().send().await.unwrap();
+    assert_eq!(response.secret_list().len(), 1);
+
+    assert_eq!(
+        error_code(eu_client.describe_secret().secret_id("test").send().await),
+        "ResourceNotFoundException"
+    );
+
+    // ARNs from another region are not resolved either
+    assert_eq!(
+        error_code(
+            eu_client
+                .get_secret_value()
+                .secret_id(us_secret.arn().unwrap())
+                .send()
+                .await
+        ),
+        "ResourceNotFoundException"
+    );
+
+    assert_eq!(
+        error_code(eu_client.delete_secret().secret_id("test").send().await),
+        "ResourceNotFoundException"
+    );
+}
@@ -0,0 +1,395 @@
+use aws_config::Region;
+use aws_sdk_secretsmanager::types::{
+    Filter, FilterNameStringType, ReplicaRegionType, StatusType, Tag,
+};
+
+use crate::common::{TestServer, error_code, test_server};
+
+mod common;
+
+/// Create a client for the server that signs requests for the `region`
+fn regional_client(server: &TestServer, region: &'static str) -> aws_sdk_secretsmanager::Client {
+    let config = server
+        .sdk_config()
+        .to_builder()
+        .region(Region::from_static(region))
+        .build();
+    aws_sdk_secretsmanager::Client::new(&config)
+}
+
+fn replica_region(region: &str) -> ReplicaRegionType {
+    ReplicaRegionType::builder().region(region).build()
+}
+
+/// Tests that a secret can be replicated when it is created
+#[tokio::test]
+async fn test_create_secret_with_replica_regions() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    let response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    let replication_status = response.replication_status();
+    assert_eq!(replication_status.len(), 1);
+    assert_eq!(replication_status[0].region(), Some("eu-west-1"));
+    assert_eq!(replication_status[0].status(), Some(&StatusType::InSync));
+
+    let replica = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(replica.secret_string(), Some("test"));
+    assert_eq!(
+        replica.arn(),
+        Some(
+            response
+                .arn()
+                .unwrap()
+                .replace(":us-east-1:", ":eu-west-1:")
+                .as_str()
+        )
+    );
+}
+
+/// Tests that replicas are described with their primary region and are read-only
+#[tokio::test]
+async fn test_replicate_secret_to_regions() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    let secret = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.arn(), secret.arn());
+    assert_eq!(response.replication_status().len(), 1);
+    assert_eq!(
+        response.replication_status()[0].status(),
+        Some(&StatusType::InSync)
+    );
+
+    let primary = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(primary.primary_region(), Some("us-east-1"));
+    assert_eq!(primary.replication_status().len(), 1);
+
+    let replica = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replica.primary_region(), Some("us-east-1"));
+
+    let result = eu_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("changed")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "InvalidRequestException");
+
+    let result = eu_client.delete_secret().secret_id("test").send().await;
+    assert_eq!(error_code(result), "InvalidRequestException");
+
+    // Secrets can't be replicated into their own region
+    let result = client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(replica_region("us-east-1"))
+        .send()
+        .await;
+    assert_eq!(error_code(result), "InvalidParameterException");
+}
+
+/// Tests that changes to the primary secret are copied to its replicas
+#[tokio::test]
+async fn test_replica_receives_changes() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("changed")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .update_secret()
+        .secret_id("test")
+        .description("description")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .tag_resource()
+        .secret_id("test")
+        .tags(Tag::builder().key("key").value("value").build())
+        .send()
+        .await
+        .unwrap();
+
+    let replica = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replica.secret_string(), Some("changed"));
+
+    let replica = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replica.description(), Some("description"));
+    assert_eq!(replica.tags().len(), 1);
+    assert_eq!(replica.tags()[0].key(), Some("key"));
+    assert_eq!(replica.version_ids_to_stages().unwrap().len(), 2);
+}
+
+/// Tests that existing secrets in the replica region are only replaced when
+/// overwriting is forced
+#[tokio::test]
+async fn test_replicate_secret_name_conflict() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("primary")
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .create_secret()
+        .name("test")
+        .secret_string("existing")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    let status = &response.replication_status()[0];
+    assert_eq!(status.status(), Some(&StatusType::Failed));
+    assert!(status.status_message().is_some());
+
+    let existing = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(existing.secret_string(), Some("existing"));
+
+    let response = client
+        .replicate_secret_to_regions()
+        .secret_id("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .force_overwrite_replica_secret(true)
+        .send()
+        .await
+        .unwrap();
+
+    let status = &response.replication_status()[0];
+    assert_eq!(status.status(), Some(&StatusType::InSync));
+
+    let replica = eu_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replica.secret_string(), Some("primary"));
+}
+
+/// Tests that removing a region deletes its replica
+#[tokio::test]
+async fn test_remove_regions_from_replication() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    // Primary secrets can't be deleted while they have replicas
+    let result = client.delete_secret().secret_id("test").send().await;
+    assert_eq!(error_code(result), "InvalidRequestException");
+
+    let response = client
+        .remove_regions_from_replication()
+        .secret_id("test")
+        .remove_replica_regions("eu-west-1")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.replication_status().is_empty());
+
+    let result = eu_client.get_secret_value().secret_id("test").send().await;
+    assert_eq!(error_code(result), "ResourceNotFoundException");
+
+    client
+        .delete_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Tests that stopping replication promotes the replica to a standalone secret
+#[tokio::test]
+async fn test_stop_replication_to_replica() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    // Only replicas can be promoted
+    let result = client
+        .stop_replication_to_replica()
+        .secret_id("test")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "InvalidRequestException");
+
+    eu_client
+        .stop_replication_to_replica()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let replica = eu_client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replica.primary_region(), None);
+
+    eu_client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("changed")
+        .send()
+        .await
+        .unwrap();
+
+    let primary = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(primary.secret_string(), Some("test"));
+
+    let primary = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert!(primary.replication_status().is_empty());
+}
+
+/// Tests that secrets can be filtered by their primary region
+#[tokio::test]
+async fn test_list_secrets_primary_region_filter() {
+    let (client, server) = test_server().await;
+    let eu_client = regional_client(&server, "eu-west-1");
+
+    client
+        .create_secret()
+        .name("replicated")
+        .secret_string("test")
+        .add_replica_regions(replica_region("eu-west-1"))
+        .send()
+        .await
+        .unwrap();
+
+    eu_client
+        .create_secret()
+        .name("standalone")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = eu_client
+        .list_secrets()
+        .filters(
+            Filter::builder()
+                .key(FilterNameStringType::PrimaryRegion)
+                .values("us-east-1")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let secrets = response.secret_list();
+    assert_eq!(secrets.len(), 1);
+    assert_eq!(secrets[0].name(), Some("replicated"));
+    assert_eq!(secrets[0].primary_region(), Some("us-east-1"));
+}
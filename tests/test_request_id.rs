@@ -0,0 +1,74 @@
+use aws_sdk_secretsmanager::{error::SdkError, operation::RequestId};
+use axum::http::{Request, StatusCode};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use uuid::Uuid;
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests that successful responses provide a request ID
+#[tokio::test]
+async fn test_request_id_success() {
+    let (client, _server) = test_server().await;
+
+    let first = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+    let second = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let first = first.request_id().unwrap();
+    let second = second.request_id().unwrap();
+    assert!(Uuid::parse_str(first).is_ok());
+    assert_ne!(first, second);
+}
+
+/// Tests that error responses provide a request ID
+#[tokio::test]
+async fn test_request_id_error() {
+    let (client, _server) = test_server().await;
+
+    let error = client
+        .get_secret_value()
+        .secret_id("missing")
+        .send()
+        .await
+        .unwrap_err();
+
+    let error = match error {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    assert!(error.err().request_id().is_some());
+}
+
+/// Tests that requests rejected before reaching the handlers provide a
+/// request ID
+#[tokio::test]
+async fn test_request_id_unauthenticated() {
+    let (_client, server) = test_server().await;
+
+    let request = Request::post(server.endpoint_url.as_str())
+        .header("x-amz-target", "secretsmanager.ListSecrets")
+        .body(Full::new(Bytes::from_static(b"{}")))
+        .unwrap();
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let response = client.request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let request_id = response.headers()["x-amzn-requestid"].to_str().unwrap();
+    assert!(Uuid::parse_str(request_id).is_ok());
+}
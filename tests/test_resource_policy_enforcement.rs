@@ -0,0 +1,340 @@
+use aws_sdk_secretsmanager::{
+    Client,
+    error::{ProvideErrorMetadata, SdkError},
+    types::{Filter, FilterNameStringType, Tag},
+};
+use loker::database::secrets::remove_secret_version_stage_any;
+use serde_json::json;
+
+use crate::common::{TEST_PRINCIPAL_ARN, test_server, test_server_with_resource_policies};
+
+mod common;
+
+/// Create a secret named `name` with the resource `policy` attached
+async fn create_secret_with_policy(
+    client: &Client,
+    name: &str,
+    tags: &[(&str, &str)],
+    policy: &str,
+) {
+    let mut request = client.create_secret().name(name).secret_string("test");
+
+    for (key, value) in tags {
+        request = request.tags(Tag::builder().key(*key).value(*value).build());
+    }
+
+    request.send().await.unwrap();
+
+    client
+        .put_resource_policy()
+        .secret_id(name)
+        .resource_policy(policy)
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Assert that the `result` of a request was denied by a resource policy
+fn assert_access_denied<T, E: ProvideErrorMetadata, R>(result: Result<T, SdkError<E, R>>) {
+    let error = match result {
+        Ok(_) => panic!("expected access denied error"),
+        Err(SdkError::ServiceError(error)) => error.into_err(),
+        Err(_) => panic!("expected service error"),
+    };
+
+    assert_eq!(error.code(), Some("AccessDeniedException"));
+}
+
+/// Tests that secrets without a resource policy are not restricted
+#[tokio::test]
+async fn test_enforce_without_policy() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.secret_string(), Some("test"));
+}
+
+/// Tests that only the actions allowed by the policy can be performed
+#[tokio::test]
+async fn test_enforce_allowed_actions() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": { "AWS": TEST_PRINCIPAL_ARN },
+            "Action": ["secretsmanager:GetSecretValue", "secretsmanager:*ResourcePolicy"],
+            "Resource": "*"
+        }
+    });
+    create_secret_with_policy(&client, "test", &[], &policy.to_string()).await;
+
+    client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .get_resource_policy()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_access_denied(client.describe_secret().secret_id("test").send().await);
+    assert_access_denied(
+        client
+            .put_secret_value()
+            .secret_id("test")
+            .secret_string("updated")
+            .send()
+            .await,
+    );
+}
+
+/// Tests that policies allowing other principals deny the caller
+#[tokio::test]
+async fn test_enforce_other_principal() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::123456789012:user/other" },
+            "Action": "secretsmanager:*",
+            "Resource": "*"
+        }
+    });
+    create_secret_with_policy(&client, "test", &[], &policy.to_string()).await;
+
+    assert_access_denied(client.get_secret_value().secret_id("test").send().await);
+}
+
+/// Tests that an explicit deny takes precedence over an allow
+#[tokio::test]
+async fn test_enforce_explicit_deny() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+                "Action": "secretsmanager:*",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Deny",
+                "Principal": { "AWS": "*" },
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "*",
+                "Condition": {
+                    "ArnEquals": { "aws:PrincipalArn": TEST_PRINCIPAL_ARN }
+                }
+            }
+        ]
+    });
+    create_secret_with_policy(&client, "test", &[], &policy.to_string()).await;
+
+    client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_access_denied(client.get_secret_value().secret_id("test").send().await);
+}
+
+/// Tests that the resource policy is enforced for secrets without an
+/// AWSCURRENT version
+#[tokio::test]
+async fn test_enforce_without_current_version() {
+    let (client, server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+                "Action": "secretsmanager:*",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Deny",
+                "Principal": { "AWS": "*" },
+                "Action": "secretsmanager:PutSecretValue",
+                "Resource": "*"
+            }
+        ]
+    });
+    create_secret_with_policy(&client, "test", &[], &policy.to_string()).await;
+
+    let secret = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    remove_secret_version_stage_any(&server.db, secret.arn().unwrap(), "AWSCURRENT")
+        .await
+        .unwrap();
+
+    assert_access_denied(
+        client
+            .put_secret_value()
+            .secret_id("test")
+            .secret_string("new")
+            .send()
+            .await,
+    );
+}
+
+/// Tests that the resource tag and name condition keys are evaluated
+#[tokio::test]
+async fn test_enforce_conditions() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*",
+            "Condition": {
+                "StringEquals": { "secretsmanager:ResourceTag/Environment": "dev" },
+                "StringLike": { "secretsmanager:Name": "dev/*" }
+            }
+        }
+    });
+    let policy = policy.to_string();
+
+    create_secret_with_policy(&client, "dev/test", &[("Environment", "dev")], &policy).await;
+    create_secret_with_policy(&client, "dev/prod", &[("Environment", "prod")], &policy).await;
+    create_secret_with_policy(&client, "prod/test", &[("Environment", "dev")], &policy).await;
+
+    client
+        .get_secret_value()
+        .secret_id("dev/test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_access_denied(client.get_secret_value().secret_id("dev/prod").send().await);
+    assert_access_denied(
+        client
+            .get_secret_value()
+            .secret_id("prod/test")
+            .send()
+            .await,
+    );
+}
+
+/// Tests that each secret read by a batch is authorized against its own
+/// resource policy, denied secrets are reported as errors
+#[tokio::test]
+async fn test_enforce_batch_get_secret_value() {
+    let (client, _server) = test_server_with_resource_policies().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Deny",
+            "Principal": "*",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    });
+    create_secret_with_policy(&client, "batch/denied", &[], &policy.to_string()).await;
+
+    client
+        .create_secret()
+        .name("batch/allowed")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let response = client
+        .batch_get_secret_value()
+        .secret_id_list("batch/allowed")
+        .secret_id_list("batch/denied")
+        .send()
+        .await
+        .unwrap();
+
+    let secret_values = response.secret_values();
+    assert_eq!(secret_values.len(), 1);
+    assert_eq!(secret_values[0].name(), Some("batch/allowed"));
+
+    let errors = response.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].secret_id(), Some("batch/denied"));
+    assert_eq!(errors[0].error_code(), Some("AccessDeniedException"));
+
+    let response = client
+        .batch_get_secret_value()
+        .filters(
+            Filter::builder()
+                .key(FilterNameStringType::Name)
+                .values("batch/")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let secret_values = response.secret_values();
+    assert_eq!(secret_values.len(), 1);
+    assert_eq!(secret_values[0].name(), Some("batch/allowed"));
+
+    let errors = response.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].error_code(), Some("AccessDeniedException"));
+}
+
+/// Tests that resource policies are not enforced unless enabled
+#[tokio::test]
+async fn test_enforce_disabled() {
+    let (client, _server) = test_server().await;
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Deny",
+            "Principal": "*",
+            "Action": "secretsmanager:*",
+            "Resource": "*"
+        }
+    });
+    create_secret_with_policy(&client, "test", &[], &policy.to_string()).await;
+
+    client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+}
@@ -0,0 +1,1383 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use aws_sdk_secretsmanager::{
+    error::SdkError,
+    operation::rotate_secret::RotateSecretError,
+    types::{
+        RotationRulesType, Tag,
+        error::{
+            InternalServiceError, InvalidParameterException, InvalidRequestException,
+            ResourceNotFoundException,
+        },
+    },
+};
+use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+use chrono::{DateTime, Duration};
+use loker::rotation::{function::RotationFunctions, rotate_scheduled_secrets};
+use serde_json::{Value, json};
+
+use crate::common::{test_server, test_server_with_rotation_functions};
+
+mod common;
+
+const TEST_FUNCTION_ARN: &str = "arn:aws:lambda:us-east-1:123456789012:function:rotate";
+
+/// Rotation function ARN selecting the built-in password rotation strategy
+const PASSWORD_STRATEGY_ARN: &str =
+    "arn:aws:lambda:us-east-1:123456789012:function:loker-rotation-password";
+
+/// State for a local HTTP rotation function used by the tests
+#[derive(Default)]
+struct TestRotationFunction {
+    /// Client for calling back into the server
+    client: OnceLock<aws_sdk_secretsmanager::Client>,
+    /// Steps that the function has been invoked with
+    steps: Mutex<Vec<String>>,
+    /// Step that the function should fail on
+    fail_step: Option<&'static str>,
+}
+
+/// Rotation function that performs the same steps as a typical rotation
+/// Lambda, creating a pending version and promoting it once finished
+async fn rotation_function(
+    State(state): State<Arc<TestRotationFunction>>,
+    Json(payload): Json<Value>,
+) -> StatusCode {
+    let client = state.client.get().unwrap();
+    let secret_id = payload["SecretId"].as_str().unwrap();
+    let token = payload["ClientRequestToken"].as_str().unwrap();
+    let step = payload["Step"].as_str().unwrap();
+
+    state.steps.lock().unwrap().push(step.to_string());
+
+    if state.fail_step == Some(step) {
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    match step {
+        "createSecret" => {
+            client
+                .put_secret_value()
+                .secret_id(secret_id)
+                .client_request_token(token)
+                .secret_string("rotated")
+                .version_stages("AWSPENDING")
+                .send()
+                .await
+                .unwrap();
+        }
+        "finishSecret" => {
+            let current = client
+                .get_secret_value()
+                .secret_id(secret_id)
+                .send()
+                .await
+                .unwrap();
+
+            client
+                .update_secret_version_stage()
+                .secret_id(secret_id)
+                .version_stage("AWSCURRENT")
+                .move_to_version_id(token)
+                .remove_from_version_id(current.version_id().unwrap())
+                .send()
+                .await
+                .unwrap();
+        }
+        _ => {}
+    }
+
+    StatusCode::OK
+}
+
+/// Start a local HTTP rotation function and a test server that maps
+/// [TEST_FUNCTION_ARN] to it
+async fn test_server_with_http_function(
+    fail_step: Option<&'static str>,
+) -> (
+    aws_sdk_secretsmanager::Client,
+    common::TestServer,
+    Arc<TestRotationFunction>,
+) {
+    let state = Arc::new(TestRotationFunction {
+        fail_step,
+        ..Default::default()
+    });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let function_address = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/", post(rotation_function))
+        .with_state(state.clone());
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let rotation_functions: RotationFunctions = serde_json::from_value(json!({
+        TEST_FUNCTION_ARN: { "http": { "url": format!("http://{function_address}/") } }
+    }))
+    .unwrap();
+
+    let (client, server) = test_server_with_rotation_functions(rotation_functions).await;
+    _ = state.client.set(client.clone());
+
+    (client, server, state)
+}
+
+/// Tests that rotating a secret promotes a new version to AWSCURRENT and moves
+/// AWSPREVIOUS onto the old version
+#[tokio::test]
+async fn test_rotate_secret_success() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(rotate_response.arn(), create_response.arn());
+    assert_eq!(rotate_response.name(), create_response.name());
+
+    let new_version_id = rotate_response.version_id().unwrap();
+    assert_ne!(Some(new_version_id), create_response.version_id());
+
+    // New version should be current
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), Some(new_version_id));
+    assert_eq!(current.version_stages(), &["AWSCURRENT".to_string()]);
+
+    // Old version should be previous
+    let previous = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_id(create_response.version_id().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(previous.version_stages(), &["AWSPREVIOUS".to_string()]);
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(true));
+    assert_eq!(
+        describe_response.rotation_rules(),
+        Some(
+            &RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build()
+        )
+    );
+
+    let last_rotated = describe_response.last_rotated_date().unwrap().secs();
+    let next_rotation = describe_response.next_rotation_date().unwrap().secs();
+
+    // Next rotation should be 30 days after the last rotation
+    assert_eq!(next_rotation - last_rotated, 30 * 24 * 60 * 60);
+}
+
+/// Tests that the rotation function ARN is stored on the secret
+#[tokio::test]
+async fn test_rotate_secret_lambda_arn() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn("arn:aws:lambda:us-east-1:123456789012:function:rotate")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .rotate_immediately(false)
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        describe_response.rotation_lambda_arn(),
+        Some("arn:aws:lambda:us-east-1:123456789012:function:rotate")
+    );
+}
+
+/// Tests that rotating with a rotation function ARN that isn't mapped to a
+/// local function or built-in strategy fails without creating a version
+#[tokio::test]
+async fn test_rotate_secret_unmapped_function_error() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InternalServiceError = match rotate_err.into_err() {
+        RotateSecretError::InternalServiceError(error) => error,
+        error => panic!("expected RotateSecretError::InternalServiceError got {error:?}"),
+    };
+
+    let list_response = client
+        .list_secret_version_ids()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let versions = list_response.versions();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].version_id(), create_response.version_id());
+    assert_eq!(versions[0].version_stages(), ["AWSCURRENT"]);
+}
+
+/// Tests that rotating with a rate schedule expression stores the rules and
+/// computes the next rotation date
+#[tokio::test]
+async fn test_rotate_secret_schedule_expression() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("rate(10 days)")
+                .duration("3h")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(true));
+    assert_eq!(
+        describe_response.rotation_rules(),
+        Some(
+            &RotationRulesType::builder()
+                .schedule_expression("rate(10 days)")
+                .duration("3h")
+                .build()
+        )
+    );
+
+    let last_rotated = describe_response.last_rotated_date().unwrap().secs();
+    let next_rotation = describe_response.next_rotation_date().unwrap().secs();
+    assert_eq!(next_rotation - last_rotated, 10 * 24 * 60 * 60);
+}
+
+/// Tests that rotation can be configured without immediately rotating
+#[tokio::test]
+async fn test_rotate_secret_not_immediately() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(7)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(rotate_response.version_id(), None);
+
+    // Current version should not have changed
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(true));
+    assert_eq!(describe_response.last_rotated_date(), None);
+    assert!(describe_response.next_rotation_date().is_some());
+}
+
+/// Tests that a secret with an incomplete rotation, left behind as an
+/// AWSPENDING version, can't be rotated again
+#[tokio::test]
+async fn test_rotate_secret_pending_version_error() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("test-pending")
+        .version_stages("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+
+    // Current version should not have changed
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+    assert_eq!(current.secret_string(), Some("test"));
+}
+
+/// Tests that secrets without a rotation function or built-in strategy can't
+/// be rotated and their rotation configuration is not stored
+#[tokio::test]
+async fn test_rotate_secret_missing_function_error() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidRequestException = match rotate_err.into_err() {
+        RotateSecretError::InvalidRequestException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidRequestException got {error:?}"),
+    };
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.rotation_enabled(), Some(false));
+}
+
+/// Tests that repeating a rotation with the same token does not rotate twice
+#[tokio::test]
+async fn test_rotate_secret_idempotent() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let token = "b6e9c3e4-7a9b-4a3c-9a5e-2e0b1f0e6a11";
+
+    for _ in 0..2 {
+        let rotate_response = client
+            .rotate_secret()
+            .secret_id("test")
+            .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+            .client_request_token(token)
+            .rotation_rules(
+                RotationRulesType::builder()
+                    .automatically_after_days(30)
+                    .build(),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(rotate_response.version_id(), Some(token));
+    }
+
+    let versions = client
+        .list_secret_version_ids()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(versions.versions().len(), 2);
+}
+
+/// Tests that specifying both AutomaticallyAfterDays and ScheduleExpression fails
+#[tokio::test]
+async fn test_rotate_secret_conflicting_rules_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .schedule_expression("rate(10 days)")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidParameterException = match rotate_err.into_err() {
+        RotateSecretError::InvalidParameterException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidParameterException got {error:?}"),
+    };
+}
+
+/// Tests that an invalid schedule expression fails
+#[tokio::test]
+async fn test_rotate_secret_invalid_schedule_expression_error() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("every 10 days")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InvalidParameterException = match rotate_err.into_err() {
+        RotateSecretError::InvalidParameterException(error) => error,
+        error => panic!("expected RotateSecretError::InvalidParameterException got {error:?}"),
+    };
+}
+
+/// Tests that trying to rotate an unknown secret will fail
+#[tokio::test]
+async fn test_rotate_secret_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match rotate_err.into_err() {
+        RotateSecretError::ResourceNotFoundException(error) => error,
+        error => panic!("expected RotateSecretError::ResourceNotFoundException got {error:?}"),
+    };
+}
+
+/// Tests that a rotation function mapped to a local HTTP endpoint is invoked
+/// for each of the rotation steps
+#[tokio::test]
+async fn test_rotate_secret_http_function() {
+    let (client, _server, function) = test_server_with_http_function(None).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        function.steps.lock().unwrap().as_slice(),
+        &["createSecret", "setSecret", "testSecret", "finishSecret"]
+    );
+
+    // Version created by the function should be current
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), rotate_response.version_id());
+    assert_eq!(current.secret_string(), Some("rotated"));
+
+    let previous = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_id(create_response.version_id().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(previous.version_stages(), &["AWSPREVIOUS".to_string()]);
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(describe_response.last_rotated_date().is_some());
+}
+
+/// Tests that a failed step of a rotation function stops the rotation and
+/// leaves the AWSPENDING version in place
+#[tokio::test]
+async fn test_rotate_secret_http_function_failure() {
+    let (client, _server, function) = test_server_with_http_function(Some("testSecret")).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let exception: InternalServiceError = match rotate_err.into_err() {
+        RotateSecretError::InternalServiceError(error) => error,
+        error => panic!("expected RotateSecretError::InternalServiceError got {error:?}"),
+    };
+
+    // Error describes the step that failed
+    assert!(exception.message().unwrap().contains("testSecret"));
+
+    // Steps after the failed step should not be invoked
+    assert_eq!(
+        function.steps.lock().unwrap().as_slice(),
+        &["createSecret", "setSecret", "testSecret"]
+    );
+
+    // Current version should not have changed
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+
+    // Pending version should remain
+    let pending = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_stage("AWSPENDING")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(pending.secret_string(), Some("rotated"));
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.last_rotated_date(), None);
+}
+
+/// Tests that a rotation function mapped to a local command is provided the
+/// payload for each of the rotation steps through stdin
+#[tokio::test]
+async fn test_rotate_secret_command_function() {
+    let output_path = std::env::temp_dir().join(format!("loker-{}.jsonl", uuid::Uuid::new_v4()));
+
+    let rotation_functions: RotationFunctions = serde_json::from_value(json!({
+        TEST_FUNCTION_ARN: {
+            "command": {
+                "program": "sh",
+                "args": ["-c", "cat >> \"$0\"; echo >> \"$0\"", output_path]
+            }
+        }
+    }))
+    .unwrap();
+
+    let (client, _server) = test_server_with_rotation_functions(rotation_functions).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let output = std::fs::read_to_string(&output_path).unwrap();
+    _ = std::fs::remove_file(&output_path);
+
+    let payloads: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let steps: Vec<&str> = payloads
+        .iter()
+        .map(|payload| payload["Step"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(
+        steps,
+        ["createSecret", "setSecret", "testSecret", "finishSecret"]
+    );
+
+    for payload in &payloads {
+        assert_eq!(payload["SecretId"].as_str(), create_response.arn());
+        assert_eq!(
+            payload["ClientRequestToken"].as_str(),
+            rotate_response.version_id()
+        );
+    }
+}
+
+/// Tests that a failing rotation command stops the rotation
+#[tokio::test]
+async fn test_rotate_secret_command_function_failure() {
+    let rotation_functions: RotationFunctions = serde_json::from_value(json!({
+        TEST_FUNCTION_ARN: {
+            "command": { "program": "sh", "args": ["-c", "cat > /dev/null; exit 1"] }
+        }
+    }))
+    .unwrap();
+
+    let (client, _server) = test_server_with_rotation_functions(rotation_functions).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InternalServiceError = match rotate_err.into_err() {
+        RotateSecretError::InternalServiceError(error) => error,
+        error => panic!("expected RotateSecretError::InternalServiceError got {error:?}"),
+    };
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+}
+
+/// Tests that a rotation command taking longer than the timeout fails the
+/// rotation and is killed
+#[tokio::test]
+async fn test_rotate_secret_command_function_timeout() {
+    let output_path = std::env::temp_dir().join(format!("loker-{}", uuid::Uuid::new_v4()));
+
+    let rotation_functions: RotationFunctions = serde_json::from_value(json!({
+        TEST_FUNCTION_ARN: {
+            "command": {
+                "program": "sh",
+                "args": ["-c", "cat > /dev/null; sleep 1; touch \"$0\"", output_path]
+            }
+        }
+    }))
+    .unwrap();
+    let rotation_functions = rotation_functions.with_timeout(std::time::Duration::from_millis(200));
+
+    let (client, _server) = test_server_with_rotation_functions(rotation_functions).await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(TEST_FUNCTION_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InternalServiceError = match rotate_err.into_err() {
+        RotateSecretError::InternalServiceError(error) => error,
+        error => panic!("expected RotateSecretError::InternalServiceError got {error:?}"),
+    };
+
+    // The command is killed before it can finish
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(!output_path.exists());
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+}
+
+/// Tests that the password strategy selected through the reserved tag replaces
+/// the whole secret string with a new password
+#[tokio::test]
+async fn test_rotate_secret_password_strategy() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .tags(
+            Tag::builder()
+                .key("loker:rotation:strategy")
+                .value("password")
+                .build(),
+        )
+        .tags(
+            Tag::builder()
+                .key("loker:rotation:password-length")
+                .value("16")
+                .build(),
+        )
+        .tags(
+            Tag::builder()
+                .key("loker:rotation:exclude-characters")
+                .value("abc")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_response = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), rotate_response.version_id());
+    assert_eq!(current.version_stages(), &["AWSCURRENT".to_string()]);
+
+    let password = current.secret_string().unwrap();
+    assert_eq!(password.len(), 16);
+    assert!(password.chars().all(|c| !"abc".contains(c)));
+
+    let previous = client
+        .get_secret_value()
+        .secret_id("test")
+        .version_id(create_response.version_id().unwrap())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(previous.secret_string(), Some("test"));
+    assert_eq!(previous.version_stages(), &["AWSPREVIOUS".to_string()]);
+}
+
+/// Tests that the JSON key strategy selected through the rotation function
+/// name only replaces the password key of the secret string
+#[tokio::test]
+async fn test_rotate_secret_json_key_strategy() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string(r#"{"username":"admin","password":"test"}"#)
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(
+            "arn:aws:lambda:us-east-1:123456789012:function:loker-rotation-json-key",
+        )
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let value: Value = serde_json::from_str(current.secret_string().unwrap()).unwrap();
+    assert_eq!(value["username"].as_str(), Some("admin"));
+
+    let password = value["password"].as_str().unwrap();
+    assert_ne!(password, "test");
+    assert_eq!(password.len(), 32);
+}
+
+/// Tests that the JSON key strategy replaces the key specified by the reserved tag
+#[tokio::test]
+async fn test_rotate_secret_json_key_strategy_custom_key() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string(r#"{"username":"admin","token":"test"}"#)
+        .tags(
+            Tag::builder()
+                .key("loker:rotation:strategy")
+                .value("json-key")
+                .build(),
+        )
+        .tags(
+            Tag::builder()
+                .key("loker:rotation:json-key")
+                .value("token")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let value: Value = serde_json::from_str(current.secret_string().unwrap()).unwrap();
+    assert_eq!(value["username"].as_str(), Some("admin"));
+    assert_ne!(value["token"].as_str(), Some("test"));
+    assert_eq!(value.get("password"), None);
+}
+
+/// Tests that the JSON key strategy fails when the secret string is not JSON
+#[tokio::test]
+async fn test_rotate_secret_json_key_strategy_invalid_json_error() {
+    let (client, _server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let rotate_err = client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(
+            "arn:aws:lambda:us-east-1:123456789012:function:loker-rotation-json-key",
+        )
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(30)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap_err();
+
+    let rotate_err = match rotate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: InternalServiceError = match rotate_err.into_err() {
+        RotateSecretError::InternalServiceError(error) => error,
+        error => panic!("expected RotateSecretError::InternalServiceError got {error:?}"),
+    };
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+    assert_eq!(current.secret_string(), Some("test"));
+}
+
+/// Tests that a scheduled rotation is performed once it is due
+#[tokio::test]
+async fn test_rotate_secret_scheduled() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .automatically_after_days(1)
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let next_rotation =
+        DateTime::from_timestamp(describe_response.next_rotation_date().unwrap().secs(), 0)
+            .unwrap();
+
+    // Rotation should not happen before its due
+    rotate_scheduled_secrets(
+        &server.db,
+        &RotationFunctions::default(),
+        next_rotation - Duration::minutes(1),
+    )
+    .await
+    .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+
+    rotate_scheduled_secrets(
+        &server.db,
+        &RotationFunctions::default(),
+        next_rotation + Duration::minutes(1),
+    )
+    .await
+    .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_ne!(current.version_id(), create_response.version_id());
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let last_rotated = describe_response.last_rotated_date().unwrap().secs();
+    let next_rotation = describe_response.next_rotation_date().unwrap().secs();
+    assert_eq!(next_rotation - last_rotated, 24 * 60 * 60);
+}
+
+/// Tests that a scheduled rotation is skipped when its rotation window has
+/// already closed
+#[tokio::test]
+async fn test_rotate_secret_scheduled_missed_window() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotate_immediately(false)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("rate(1 day)")
+                .duration("2h")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let next_rotation =
+        DateTime::from_timestamp(describe_response.next_rotation_date().unwrap().secs(), 0)
+            .unwrap();
+
+    let now = next_rotation + Duration::hours(3);
+    rotate_scheduled_secrets(&server.db, &RotationFunctions::default(), now)
+        .await
+        .unwrap();
+
+    let current = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(current.version_id(), create_response.version_id());
+
+    // Next rotation should be scheduled after the missed window
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(describe_response.last_rotated_date(), None);
+    assert_eq!(
+        describe_response.next_rotation_date().unwrap().secs(),
+        (now + Duration::days(1)).timestamp()
+    );
+}
+
+/// Tests that a cron schedule expression provides the next rotation date
+#[tokio::test]
+async fn test_rotate_secret_cron_schedule_expression() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .rotate_secret()
+        .secret_id("test")
+        .rotation_lambda_arn(PASSWORD_STRATEGY_ARN)
+        .rotation_rules(
+            RotationRulesType::builder()
+                .schedule_expression("cron(0 16 1,15 * ? *)")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let describe_response = client
+        .describe_secret()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+
+    let next_rotation =
+        DateTime::from_timestamp(describe_response.next_rotation_date().unwrap().secs(), 0)
+            .unwrap();
+
+    assert_eq!(next_rotation.format("%H:%M").to_string(), "16:00");
+    assert!(matches!(
+        next_rotation.format("%d").to_string().as_str(),
+        "01" | "15"
+    ));
+}
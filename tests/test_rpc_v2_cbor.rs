@@ -0,0 +1,205 @@
+use aws_sdk_secretsmanager::primitives::Blob;
+use axum::http::{StatusCode, header::CONTENT_TYPE};
+use ciborium::{Value, cbor};
+
+use crate::common::test_server;
+
+mod common;
+
+/// Get the value of the `key` field in a CBOR map `value`
+fn field<'a>(value: &'a Value, key: &str) -> &'a Value {
+    value
+        .as_map()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|(name, _)| name.as_text() == Some(key))
+                .map(|(_, value)| value)
+        })
+        .unwrap_or_else(|| panic!("missing field {key}"))
+}
+
+/// Tests creating and getting a secret using the RPC v2 CBOR protocol
+#[tokio::test]
+async fn test_create_get_secret() {
+    let (client, server) = test_server().await;
+
+    let (status, headers, body) = server
+        .cbor_request(
+            "secretsmanager",
+            "CreateSecret",
+            cbor!({ "Name" => "test", "SecretString" => "test-value" }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["smithy-protocol"], "rpc-v2-cbor");
+    assert_eq!(headers[CONTENT_TYPE], "application/cbor");
+
+    let arn = field(&body, "ARN").as_text().unwrap().to_string();
+    let version_id = field(&body, "VersionId").as_text().unwrap().to_string();
+
+    let (status, _headers, body) = server
+        .cbor_request(
+            "secretsmanager",
+            "GetSecretValue",
+            cbor!({ "SecretId" => "test" }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(field(&body, "ARN").as_text(), Some(arn.as_str()));
+    assert_eq!(field(&body, "SecretString").as_text(), Some("test-value"));
+    assert_eq!(
+        field(&body, "VersionId").as_text(),
+        Some(version_id.as_str())
+    );
+
+    // Timestamps are tagged as epoch based date times
+    match field(&body, "CreatedDate") {
+        Value::Tag(1, value) => assert!(value.is_float()),
+        value => panic!("expected timestamp, got {value:?}"),
+    }
+
+    // The secret is shared with the AWS JSON protocol
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.arn(), Some(arn.as_str()));
+    assert_eq!(response.secret_string(), Some("test-value"));
+}
+
+/// Tests that binary secrets are sent and received as CBOR byte strings
+#[tokio::test]
+async fn test_secret_binary() {
+    let (client, server) = test_server().await;
+
+    let secret_binary = vec![0u8, 1, 2, 3, 255];
+
+    let (status, _headers, _body) = server
+        .cbor_request(
+            "secretsmanager",
+            "CreateSecret",
+            cbor!({ "Name" => "test", "SecretBinary" => Value::Bytes(secret_binary.clone()) })
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _headers, body) = server
+        .cbor_request(
+            "secretsmanager",
+            "GetSecretValue",
+            cbor!({ "SecretId" => "test" }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        field(&body, "SecretBinary").as_bytes(),
+        Some(&secret_binary)
+    );
+
+    let response = client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_binary(), Some(&Blob::new(secret_binary)));
+}
+
+/// Tests that operations without required input can be sent without a body
+#[tokio::test]
+async fn test_empty_request() {
+    let (client, server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, _headers, body) = server
+        .cbor_request("secretsmanager", "ListSecrets", Value::Null)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let secrets = field(&body, "SecretList").as_array().unwrap();
+    assert_eq!(secrets.len(), 1);
+    assert_eq!(field(&secrets[0], "Name").as_text(), Some("test"));
+}
+
+/// Tests that errors are serialized as CBOR
+#[tokio::test]
+async fn test_error_response() {
+    let (_client, server) = test_server().await;
+
+    let (status, headers, body) = server
+        .cbor_request(
+            "secretsmanager",
+            "GetSecretValue",
+            cbor!({ "SecretId" => "missing" }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(headers["smithy-protocol"], "rpc-v2-cbor");
+    assert_eq!(headers[CONTENT_TYPE], "application/cbor");
+    assert_eq!(
+        field(&body, "__type").as_text(),
+        Some("ResourceNotFoundException")
+    );
+
+    // Requests that can't be deserialized
+    let (status, _headers, body) = server
+        .cbor_request(
+            "secretsmanager",
+            "GetSecretValue",
+            cbor!({ "SecretId" => 1 }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        field(&body, "__type").as_text(),
+        Some("InvalidRequestException")
+    );
+}
+
+/// Tests that the KMS operations are available using the RPC v2 CBOR protocol
+#[tokio::test]
+async fn test_kms_encrypt_decrypt() {
+    let (_client, server) = test_server().await;
+
+    let (status, _headers, body) = server
+        .cbor_request("TrentService", "CreateKey", Value::Map(Vec::new()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let key_metadata = field(&body, "KeyMetadata");
+    let key_id = field(key_metadata, "KeyId").as_text().unwrap().to_string();
+
+    let plaintext = b"test-plaintext".to_vec();
+
+    let (status, _headers, body) = server
+        .cbor_request(
+            "TrentService",
+            "Encrypt",
+            cbor!({ "KeyId" => key_id, "Plaintext" => Value::Bytes(plaintext.clone()) }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let ciphertext = field(&body, "CiphertextBlob").as_bytes().unwrap().clone();
+
+    let (status, _headers, body) = server
+        .cbor_request(
+            "TrentService",
+            "Decrypt",
+            cbor!({ "CiphertextBlob" => Value::Bytes(ciphertext) }).unwrap(),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(field(&body, "Plaintext").as_bytes(), Some(&plaintext));
+}
@@ -0,0 +1,177 @@
+use axum::http::StatusCode;
+use serde_json::json;
+
+use crate::common::test_server;
+
+mod common;
+
+/// Tests getting the current value of a secret through its parameter reference
+#[tokio::test]
+async fn test_get_parameter() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("app/test")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "AmazonSSM.GetParameter",
+            json!({ "Name": "/aws/reference/secretsmanager/app/test", "WithDecryption": true }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let parameter = &body["Parameter"];
+    assert_eq!(parameter["Name"], "/aws/reference/secretsmanager/app/test");
+    assert_eq!(
+        parameter["ARN"],
+        "arn:aws:ssm:us-east-1:123456789012:parameter/aws/reference/secretsmanager/app/test"
+    );
+    assert_eq!(parameter["Type"], "SecureString");
+    assert_eq!(parameter["Value"], "test-value");
+
+    let source_result: serde_json::Value =
+        serde_json::from_str(parameter["SourceResult"].as_str().unwrap()).unwrap();
+    assert_eq!(source_result["ARN"], create_response.arn().unwrap());
+    assert_eq!(
+        source_result["VersionId"],
+        create_response.version_id().unwrap()
+    );
+}
+
+/// Tests selecting secret versions by their version ID and staging label
+#[tokio::test]
+async fn test_get_parameter_selector() {
+    let (client, server) = test_server().await;
+
+    let create_response = client
+        .create_secret()
+        .name("test")
+        .secret_string("first-value")
+        .send()
+        .await
+        .unwrap();
+
+    client
+        .put_secret_value()
+        .secret_id("test")
+        .secret_string("second-value")
+        .send()
+        .await
+        .unwrap();
+
+    let first_version_id = create_response.version_id().unwrap();
+
+    for (selector, value) in [
+        (first_version_id, "first-value"),
+        ("AWSPREVIOUS", "first-value"),
+        ("AWSCURRENT", "second-value"),
+    ] {
+        let (status, body) = server
+            .json_request(
+                "AmazonSSM.GetParameter",
+                json!({
+                    "Name": format!("/aws/reference/secretsmanager/test:{selector}"),
+                    "WithDecryption": true
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["Parameter"]["Value"], value);
+        assert_eq!(body["Parameter"]["Selector"], format!(":{selector}"));
+    }
+
+    let (status, body) = server
+        .json_request(
+            "AmazonSSM.GetParameter",
+            json!({ "Name": "/aws/reference/secretsmanager/test:MISSING", "WithDecryption": true }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ParameterVersionNotFound");
+}
+
+/// Tests that secret references must be requested with decryption
+#[tokio::test]
+async fn test_get_parameter_without_decryption() {
+    let (client, server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test-value")
+        .send()
+        .await
+        .unwrap();
+
+    let (status, body) = server
+        .json_request(
+            "AmazonSSM.GetParameter",
+            json!({ "Name": "/aws/reference/secretsmanager/test" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["__type"], "ValidationException");
+}
+
+/// Tests getting parameters that don't reference an existing secret
+#[tokio::test]
+async fn test_get_parameter_not_found() {
+    let (_client, server) = test_server().await;
+
+    for name in ["/aws/reference/secretsmanager/missing", "/app/test"] {
+        let (status, body) = server
+            .json_request(
+                "AmazonSSM.GetParameter",
+                json!({ "Name": name, "WithDecryption": true }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["__type"], "ParameterNotFound");
+    }
+}
+
+/// Tests getting multiple parameters with missing names reported as invalid
+#[tokio::test]
+async fn test_get_parameters() {
+    let (client, server) = test_server().await;
+
+    for (name, value) in [("first", "first-value"), ("second", "second-value")] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string(value)
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = server
+        .json_request(
+            "AmazonSSM.GetParameters",
+            json!({
+                "Names": [
+                    "/aws/reference/secretsmanager/first",
+                    "/aws/reference/secretsmanager/second:AWSCURRENT",
+                    "/aws/reference/secretsmanager/missing"
+                ],
+                "WithDecryption": true
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let parameters = body["Parameters"].as_array().unwrap();
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(parameters[0]["Value"], "first-value");
+    assert_eq!(parameters[1]["Value"], "second-value");
+    assert_eq!(
+        body["InvalidParameters"],
+        json!(["/aws/reference/secretsmanager/missing"])
+    );
+}
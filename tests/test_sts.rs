@@ -0,0 +1,436 @@
+use aws_sdk_secretsmanager::config::Credentials;
+use chrono::{Duration, Utc};
+use loker::{
+    database::session_credentials::{CreateSessionCredential, create_session_credential},
+    middleware::aws_sig_v4::AwsCredential,
+};
+use serde_json::json;
+
+use crate::common::{
+    TEST_PRINCIPAL_ARN, TestServer, TestServerOptions, error_code, test_server,
+    test_server_with_options,
+};
+
+mod common;
+
+const TEST_ROLE_ARN: &str = "arn:aws:iam::123456789012:role/service/deploy";
+
+/// Create an STS client that signs requests using the `credentials`
+fn sts_client(server: &TestServer, credentials: Credentials) -> aws_sdk_sts::Client {
+    aws_sdk_sts::Client::new(&server.sdk_config_with_credentials(credentials))
+}
+
+/// Assume the [TEST_ROLE_ARN] role using the test access key, provides the
+/// issued temporary credentials
+async fn assume_role(server: &TestServer, policy: Option<String>) -> Credentials {
+    let client = aws_sdk_sts::Client::new(server.sdk_config());
+    let response = client
+        .assume_role()
+        .role_arn(TEST_ROLE_ARN)
+        .role_session_name("session")
+        .set_policy(policy)
+        .send()
+        .await
+        .unwrap();
+
+    let credentials = response.credentials().unwrap();
+    Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        None,
+        "test",
+    )
+}
+
+/// Tests getting the identity of a long-term access key
+#[tokio::test]
+async fn test_get_caller_identity() {
+    let (_client, server) = test_server().await;
+
+    let client = aws_sdk_sts::Client::new(server.sdk_config());
+    let response = client.get_caller_identity().send().await.unwrap();
+
+    assert_eq!(response.arn(), Some(TEST_PRINCIPAL_ARN));
+    assert_eq!(response.account(), Some("123456789012"));
+    assert_eq!(response.user_id(), Some("test"));
+}
+
+/// Tests assuming a role and using the temporary credentials
+#[tokio::test]
+async fn test_assume_role() {
+    let (client, server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let sts = aws_sdk_sts::Client::new(server.sdk_config());
+    let response = sts
+        .assume_role()
+        .role_arn(TEST_ROLE_ARN)
+        .role_session_name("session")
+        .duration_seconds(900)
+        .send()
+        .await
+        .unwrap();
+
+    let assumed_role_user = response.assumed_role_user().unwrap();
+    assert_eq!(
+        assumed_role_user.arn(),
+        "arn:aws:sts::123456789012:assumed-role/deploy/session"
+    );
+
+    let credentials = response.credentials().unwrap();
+    assert!(credentials.access_key_id().starts_with("ASIA"));
+    assert!(credentials.expiration().secs() <= (Utc::now() + Duration::seconds(900)).timestamp());
+
+    let credentials = Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        None,
+        "test",
+    );
+
+    let session_client = aws_sdk_secretsmanager::Client::new(
+        &server.sdk_config_with_credentials(credentials.clone()),
+    );
+    let response = session_client
+        .get_secret_value()
+        .secret_id("test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.secret_string(), Some("test"));
+
+    let response = sts_client(&server, credentials)
+        .get_caller_identity()
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.arn(),
+        Some("arn:aws:sts::123456789012:assumed-role/deploy/session")
+    );
+    assert_eq!(response.account(), Some("123456789012"));
+    assert_eq!(
+        response.user_id(),
+        Some(assumed_role_user.assumed_role_id())
+    );
+}
+
+/// Tests that the session policy restricts the temporary credentials
+#[tokio::test]
+async fn test_assume_role_session_policy() {
+    let (client, server) = test_server().await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+        }
+    });
+    let credentials = assume_role(&server, Some(policy.to_string())).await;
+    let session_client =
+        aws_sdk_secretsmanager::Client::new(&server.sdk_config_with_credentials(credentials));
+
+    session_client
+        .get_secret_value()
+        .secret_id("ci/test")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        error_code(
+            session_client
+                .get_secret_value()
+                .secret_id("prod/test")
+                .send()
+                .await
+        ),
+        "AccessDeniedException"
+    );
+}
+
+/// Tests that temporary credentials are rejected without the matching
+/// session token
+#[tokio::test]
+async fn test_session_token_invalid() {
+    let (_client, server) = test_server().await;
+
+    let credentials = assume_role(&server, None).await;
+
+    // Missing session token
+    let client = server
+        .client_with_credentials(credentials.access_key_id(), credentials.secret_access_key());
+    assert_eq!(
+        error_code(client.list_secrets().send().await),
+        "InvalidClientTokenId"
+    );
+
+    // Session token for another session
+    let other = assume_role(&server, None).await;
+    let credentials = Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        other.session_token().map(str::to_string),
+        None,
+        "test",
+    );
+    let client =
+        aws_sdk_secretsmanager::Client::new(&server.sdk_config_with_credentials(credentials));
+    assert_eq!(
+        error_code(client.list_secrets().send().await),
+        "InvalidClientTokenId"
+    );
+}
+
+/// Tests that expired temporary credentials are rejected
+#[tokio::test]
+async fn test_session_token_expired() {
+    let (_client, server) = test_server().await;
+
+    create_session_credential(
+        &server.db,
+        CreateSessionCredential {
+            access_key_id: "ASIAEXPIRED".to_string(),
+            access_key_secret: "expired".to_string(),
+            session_token: "expired-token".to_string(),
+            role_arn: TEST_ROLE_ARN.to_string(),
+            session_name: "expired".to_string(),
+            source_access_key_id: "test".to_string(),
+            policy: None,
+            source_policies: None,
+            expires_at: Utc::now() - Duration::minutes(1),
+        },
+    )
+    .await
+    .unwrap();
+
+    let credentials = Credentials::new(
+        "ASIAEXPIRED",
+        "expired",
+        Some("expired-token".to_string()),
+        None,
+        "test",
+    );
+    let client =
+        aws_sdk_secretsmanager::Client::new(&server.sdk_config_with_credentials(credentials));
+
+    assert_eq!(
+        error_code(client.list_secrets().send().await),
+        "ExpiredTokenException"
+    );
+}
+
+/// Tests that invalid assume role requests are rejected
+#[tokio::test]
+async fn test_assume_role_invalid() {
+    let (_client, server) = test_server().await;
+    let client = aws_sdk_sts::Client::new(server.sdk_config());
+
+    // Duration too short
+    let result = client
+        .assume_role()
+        .role_arn(TEST_ROLE_ARN)
+        .role_session_name("session")
+        .duration_seconds(60)
+        .send()
+        .await;
+    assert_eq!(error_code(result), "ValidationError");
+
+    // Not a role ARN
+    let result = client
+        .assume_role()
+        .role_arn("arn:aws:iam::123456789012:user/test")
+        .role_session_name("session")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "ValidationError");
+
+    // Session policies can't specify a principal
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": "*",
+            "Resource": "*"
+        }
+    });
+    let result = client
+        .assume_role()
+        .role_arn(TEST_ROLE_ARN)
+        .role_session_name("session")
+        .policy(policy.to_string())
+        .send()
+        .await;
+    assert_eq!(error_code(result), "MalformedPolicyDocument");
+}
+
+/// Tests that identity policies restrict which roles can be assumed
+#[tokio::test]
+async fn test_assume_role_identity_policy() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Action": "sts:AssumeRole",
+            "Resource": "arn:aws:iam::*:role/service/*"
+        }
+    });
+
+    let (_client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![AwsCredential {
+            name: "ci".to_string(),
+            access_key_id: "ci".to_string(),
+            access_key_secret: "ci".to_string(),
+            principal_arn: None,
+            expires_at: None,
+            enabled: true,
+            policy: Some(policy),
+            source_policies: Vec::new(),
+        }],
+        ..Default::default()
+    })
+    .await;
+
+    let client = sts_client(&server, Credentials::new("ci", "ci", None, None, "test"));
+
+    client
+        .assume_role()
+        .role_arn(TEST_ROLE_ARN)
+        .role_session_name("session")
+        .send()
+        .await
+        .unwrap();
+
+    let result = client
+        .assume_role()
+        .role_arn("arn:aws:iam::123456789012:role/admin")
+        .role_session_name("session")
+        .send()
+        .await;
+    assert_eq!(error_code(result), "AccessDenied");
+}
+
+/// Tests that temporary credentials stay restricted by the identity policy
+/// of the caller that assumed the role, including for chained sessions
+#[tokio::test]
+async fn test_assume_role_keeps_identity_policy() {
+    let policy = json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Effect": "Allow",
+                "Action": "sts:AssumeRole",
+                "Resource": "*"
+            },
+            {
+                "Effect": "Allow",
+                "Action": "secretsmanager:GetSecretValue",
+                "Resource": "arn:aws:secretsmanager:*:*:secret:ci/*"
+            }
+        ]
+    });
+
+    let (client, server) = test_server_with_options(TestServerOptions {
+        credentials: vec![AwsCredential {
+            name: "ci".to_string(),
+            access_key_id: "ci".to_string(),
+            access_key_secret: "ci".to_string(),
+            principal_arn: None,
+            expires_at: None,
+            enabled: true,
+            policy: Some(policy),
+            source_policies: Vec::new(),
+        }],
+        ..Default::default()
+    })
+    .await;
+
+    for name in ["ci/test", "prod/test"] {
+        client
+            .create_secret()
+            .name(name)
+            .secret_string("test")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    // Session policy allowing more than the identity policy of the caller
+    let session_policy = json!({
+        "Version": "2012-10-17",
+        "Statement": {
+            "Effect": "Allow",
+            "Action": ["secretsmanager:*", "sts:AssumeRole"],
+            "Resource": "*"
+        }
+    });
+
+    let mut credentials = Credentials::new("ci", "ci", None, None, "test");
+    for policy in [Some(session_policy.to_string()), None] {
+        let response = sts_client(&server, credentials)
+            .assume_role()
+            .role_arn(TEST_ROLE_ARN)
+            .role_session_name("session")
+            .set_policy(policy)
+            .send()
+            .await
+            .unwrap();
+
+        let session = response.credentials().unwrap();
+        credentials = Credentials::new(
+            session.access_key_id(),
+            session.secret_access_key(),
+            Some(session.session_token().to_string()),
+            None,
+            "test",
+        );
+
+        let session_client = aws_sdk_secretsmanager::Client::new(
+            &server.sdk_config_with_credentials(credentials.clone()),
+        );
+
+        session_client
+            .get_secret_value()
+            .secret_id("ci/test")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            error_code(
+                session_client
+                    .get_secret_value()
+                    .secret_id("prod/test")
+                    .send()
+                    .await
+            ),
+            "AccessDeniedException"
+        );
+        assert_eq!(
+            error_code(session_client.list_secrets().send().await),
+            "AccessDeniedException"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use aws_sdk_secretsmanager::{
+    error::SdkError, operation::validate_resource_policy::ValidateResourcePolicyError,
+    types::error::ResourceNotFoundException,
+};
+
+use crate::common::test_server;
+
+mod common;
+
+const TEST_POLICY: &str = r#"{
+    "Version": "2012-10-17",
+    "Statement": [
+        {
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*"
+        }
+    ]
+}"#;
+
+/// Tests that a valid policy passes validation
+#[tokio::test]
+async fn test_validate_resource_policy_success() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    assert!(validate_response.policy_validation_passed());
+    assert!(validate_response.validation_errors().is_empty());
+}
+
+/// Tests that a policy can be validated against an existing secret
+#[tokio::test]
+async fn test_validate_resource_policy_secret_success() {
+    let (client, _server) = test_server().await;
+
+    client
+        .create_secret()
+        .name("test")
+        .secret_string("test")
+        .send()
+        .await
+        .unwrap();
+
+    let validate_response = client
+        .validate_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap();
+
+    assert!(validate_response.policy_validation_passed());
+}
+
+/// Tests that an invalid policy provides the validation errors
+#[tokio::test]
+async fn test_validate_resource_policy_errors() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    assert!(!validate_response.policy_validation_passed());
+
+    let errors = validate_response.validation_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].check_name(), Some("SYNTAX_CHECKING"));
+    assert_eq!(
+        errors[0].error_message(),
+        Some("Statement 0 must specify either Principal or NotPrincipal")
+    );
+}
+
+/// Tests that a policy that is not JSON fails validation
+#[tokio::test]
+async fn test_validate_resource_policy_invalid_json() {
+    let (client, _server) = test_server().await;
+
+    let validate_response = client
+        .validate_resource_policy()
+        .resource_policy("not json")
+        .send()
+        .await
+        .unwrap();
+
+    assert!(!validate_response.policy_validation_passed());
+
+    let errors = validate_response.validation_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].check_name(), Some("JSON_SYNTAX_CHECKING"));
+}
+
+/// Tests that validating against an unknown secret will fail
+#[tokio::test]
+async fn test_validate_resource_policy_unknown_error() {
+    let (client, _server) = test_server().await;
+
+    let validate_err = client
+        .validate_resource_policy()
+        .secret_id("test")
+        .resource_policy(TEST_POLICY)
+        .send()
+        .await
+        .unwrap_err();
+
+    let validate_err = match validate_err {
+        SdkError::ServiceError(error) => error,
+        error => panic!("expected SdkError::ServiceError got {error:?}"),
+    };
+
+    let _exception: ResourceNotFoundException = match validate_err.into_err() {
+        ValidateResourcePolicyError::ResourceNotFoundException(error) => error,
+        error => {
+            panic!("expected ValidateResourcePolicyError::ResourceNotFoundException got {error:?}")
+        }
+    };
+}
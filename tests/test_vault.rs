@@ -0,0 +1,323 @@
+use axum::http::{Method, Request, StatusCode};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use loker::handlers::vault::VaultOptions;
+use serde_json::{Value, json};
+
+use crate::common::{TestServer, TestServerOptions, test_server_with_options};
+
+mod common;
+
+const TEST_TOKEN: &str = "test-vault-token";
+
+/// Create a test server with the Vault endpoint mounted at `secret`
+async fn vault_server() -> (aws_sdk_secretsmanager::Client, TestServer) {
+    test_server_with_options(TestServerOptions {
+        vault: Some(VaultOptions {
+            token: TEST_TOKEN.to_string(),
+            region: "us-east-1".to_string(),
+            account_id: "123456789012".to_string(),
+            mount: "secret".to_string(),
+        }),
+        ..Default::default()
+    })
+    .await
+}
+
+/// Send a request to the Vault endpoint using the test token
+async fn vault_request(
+    server: &TestServer,
+    method: &str,
+    path: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    vault_request_with_token(server, method, path, body, Some(TEST_TOKEN)).await
+}
+
+/// Send a request to the Vault endpoint using the provided `token`
+async fn vault_request_with_token(
+    server: &TestServer,
+    method: &str,
+    path: &str,
+    body: Option<Value>,
+    token: Option<&str>,
+) -> (StatusCode, Value) {
+    let url = format!("{}{path}", server.vault_url.as_ref().unwrap());
+
+    let mut request = Request::builder()
+        .method(Method::from_bytes(method.as_bytes()).unwrap())
+        .uri(url);
+    if let Some(token) = token {
+        request = request.header("X-Vault-Token", token);
+    }
+
+    let body = body
+        .map(|body| Bytes::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap_or_default();
+    let request = request.body(Full::new(body)).unwrap();
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let response = client.request(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    (status, body)
+}
+
+/// Tests writing KV data and reading it back through Vault and Secrets Manager
+#[tokio::test]
+async fn test_write_read_secret() {
+    let (client, server) = vault_server().await;
+
+    let (status, body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/data/app/db",
+        Some(json!({ "data": { "username": "admin", "password": "hunter2" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["version"], 1);
+
+    let (status, body) = vault_request(&server, "GET", "/v1/secret/data/app/db", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["data"]["data"],
+        json!({ "username": "admin", "password": "hunter2" })
+    );
+    assert_eq!(body["data"]["metadata"]["version"], 1);
+    assert_eq!(body["data"]["metadata"]["deletion_time"], "");
+
+    // The KV data is stored as the JSON secret string
+    let response = client
+        .get_secret_value()
+        .secret_id("app/db")
+        .send()
+        .await
+        .unwrap();
+    let value: Value = serde_json::from_str(response.secret_string().unwrap()).unwrap();
+    assert_eq!(value, json!({ "username": "admin", "password": "hunter2" }));
+}
+
+/// Tests that writes create new versions that can be read individually
+#[tokio::test]
+async fn test_read_secret_versions() {
+    let (_client, server) = vault_server().await;
+
+    for value in ["first", "second", "third"] {
+        let (status, _body) = vault_request(
+            &server,
+            "PUT",
+            "/v1/secret/data/test",
+            Some(json!({ "data": { "value": value } })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    for (version, value) in [(1, "first"), (2, "second"), (3, "third"), (0, "third")] {
+        let (status, body) = vault_request(
+            &server,
+            "GET",
+            &format!("/v1/secret/data/test?version={version}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["data"]["value"], value);
+    }
+
+    let (status, _body) =
+        vault_request(&server, "GET", "/v1/secret/data/test?version=4", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Tests that check-and-set writes only succeed against the current version
+#[tokio::test]
+async fn test_write_secret_cas() {
+    let (_client, server) = vault_server().await;
+
+    let (status, _body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/data/test",
+        Some(json!({ "data": { "value": "first" }, "options": { "cas": 0 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/data/test",
+        Some(json!({ "data": { "value": "second" }, "options": { "cas": 0 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["errors"][0],
+        "check-and-set parameter did not match the current version"
+    );
+
+    let (status, body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/data/test",
+        Some(json!({ "data": { "value": "second" }, "options": { "cas": 1 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["version"], 2);
+}
+
+/// Tests soft deleting and undeleting versions
+#[tokio::test]
+async fn test_delete_undelete_versions() {
+    let (_client, server) = vault_server().await;
+
+    for value in ["first", "second"] {
+        vault_request(
+            &server,
+            "POST",
+            "/v1/secret/data/test",
+            Some(json!({ "data": { "value": value } })),
+        )
+        .await;
+    }
+
+    // Deleting the latest version
+    let (status, _body) = vault_request(&server, "DELETE", "/v1/secret/data/test", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = vault_request(&server, "GET", "/v1/secret/data/test", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["data"]["data"], Value::Null);
+    assert_ne!(body["data"]["metadata"]["deletion_time"], "");
+
+    // Deleting specific versions
+    let (status, _body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/delete/test",
+        Some(json!({ "versions": [1] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) =
+        vault_request(&server, "GET", "/v1/secret/data/test?version=1", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = vault_request(
+        &server,
+        "POST",
+        "/v1/secret/undelete/test",
+        Some(json!({ "versions": [1, 2] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    for (version, value) in [(1, "first"), (2, "second")] {
+        let (status, body) = vault_request(
+            &server,
+            "GET",
+            &format!("/v1/secret/data/test?version={version}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["data"]["value"], value);
+    }
+}
+
+/// Tests reading the metadata of a secret
+#[tokio::test]
+async fn test_read_metadata() {
+    let (_client, server) = vault_server().await;
+
+    for value in ["first", "second"] {
+        vault_request(
+            &server,
+            "POST",
+            "/v1/secret/data/test",
+            Some(json!({ "data": { "value": value } })),
+        )
+        .await;
+    }
+
+    let (status, body) = vault_request(&server, "GET", "/v1/secret/metadata/test", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["current_version"], 2);
+    assert_eq!(body["data"]["oldest_version"], 1);
+    assert_eq!(body["data"]["versions"].as_object().unwrap().len(), 2);
+    assert_eq!(body["data"]["versions"]["1"]["destroyed"], false);
+
+    let (status, _body) = vault_request(&server, "GET", "/v1/secret/metadata/missing", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Tests listing the secrets and folders at a path
+#[tokio::test]
+async fn test_list_secrets() {
+    let (_client, server) = vault_server().await;
+
+    for path in ["app/db", "app/api/key", "app/cache", "other"] {
+        vault_request(
+            &server,
+            "POST",
+            &format!("/v1/secret/data/{path}"),
+            Some(json!({ "data": { "value": "test" } })),
+        )
+        .await;
+    }
+
+    let (status, body) = vault_request(&server, "LIST", "/v1/secret/metadata/app", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["keys"], json!(["api/", "cache", "db"]));
+
+    let (status, body) =
+        vault_request(&server, "GET", "/v1/secret/metadata/?list=true", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["keys"], json!(["app/", "other"]));
+
+    let (status, _body) = vault_request(&server, "LIST", "/v1/secret/metadata/missing", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Tests deleting the metadata and all versions of a secret
+#[tokio::test]
+async fn test_delete_metadata() {
+    let (client, server) = vault_server().await;
+
+    vault_request(
+        &server,
+        "POST",
+        "/v1/secret/data/test",
+        Some(json!({ "data": { "value": "test" } })),
+    )
+    .await;
+
+    let (status, _body) = vault_request(&server, "DELETE", "/v1/secret/metadata/test", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) = vault_request(&server, "GET", "/v1/secret/data/test", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let result = client.describe_secret().secret_id("test").send().await;
+    assert!(result.is_err());
+}
+
+/// Tests that requests without the expected token are rejected
+#[tokio::test]
+async fn test_invalid_token() {
+    let (_client, server) = vault_server().await;
+
+    for token in [None, Some("other-token")] {
+        let (status, body) =
+            vault_request_with_token(&server, "GET", "/v1/secret/data/test", None, token).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["errors"][0], "permission denied");
+    }
+}